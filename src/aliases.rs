@@ -0,0 +1,151 @@
+//! Custom commands defined in the config, such as `today = "list --due today"`
+//!
+//! Aliases live under `aliases` in the config file and are expanded before
+//! the command line is parsed, so anything a command line can say an alias
+//! can too. An alias may start with another alias, but not lead back to
+//! itself, and built-in commands can't be redefined.
+
+use crate::cli::Cli;
+use crate::config::config_path;
+use crate::error::TodoError;
+use anyhow::Result;
+use clap::CommandFactory;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+
+/// Loads just the aliases from the configuration file
+///
+/// Like [`crate::config::load_priority_scale`], falls back to none when
+/// there is no file or it doesn't parse, leaving `Config::load` to report
+/// the error.
+pub fn load_aliases() -> BTreeMap<String, String> {
+    #[derive(Deserialize)]
+    struct Aliases {
+        #[serde(default)]
+        aliases: BTreeMap<String, String>,
+    }
+
+    fs::read_to_string(config_path())
+        .ok()
+        .and_then(|content| serde_json::from_str::<Aliases>(&content).ok())
+        .map(|a| a.aliases)
+        .unwrap_or_default()
+}
+
+/// Replaces the aliases in the configuration file, leaving its other settings alone
+pub fn save_aliases(aliases: &BTreeMap<String, String>) -> Result<()> {
+    let path = config_path();
+    let mut config = match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|source| TodoError::Parse { path: path.clone(), source })?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_json::json!({}),
+        Err(e) => return Err(TodoError::storage("read", &path, e).into()),
+    };
+    let Some(settings) = config.as_object_mut() else {
+        return Err(TodoError::Validation(format!("{} doesn't hold a JSON object", path.display())).into());
+    };
+    if aliases.is_empty() {
+        settings.remove("aliases");
+    } else {
+        settings.insert("aliases".to_string(), serde_json::to_value(aliases)?);
+    }
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| TodoError::storage("create", dir, e))?;
+    }
+    let content = serde_json::to_string_pretty(&config)?;
+    fs::write(&path, content).map_err(|e| TodoError::storage("write to", &path, e))?;
+    Ok(())
+}
+
+/// Returns true if `name` is a built-in command, which aliases can't replace
+pub fn is_builtin(name: &str) -> bool {
+    name == "help" || Cli::command().get_subcommands().any(|command| command.get_name() == name)
+}
+
+/// Splits an alias into words as a shell would, honoring single and double quotes
+pub fn split_words(expansion: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    for c in expansion.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("Unclosed quote in '{}'", expansion));
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Returns the words `name` stands for, following aliases that start with other aliases
+///
+/// Fails if the chain leads back to an alias already expanded, or an
+/// alias is empty or can't be split into words.
+pub fn resolve(name: &str, aliases: &BTreeMap<String, String>) -> Result<Vec<String>, String> {
+    let mut chain = vec![name.to_string()];
+    let mut words = vec![name.to_string()];
+    while let Some(expansion) = aliases.get(&words[0]).filter(|_| !is_builtin(&words[0])) {
+        let mut expanded = split_words(expansion)?;
+        if expanded.is_empty() {
+            return Err(format!("Alias '{}' is empty", words[0]));
+        }
+        expanded.extend(words.drain(1..));
+        words = expanded;
+        if chain.contains(&words[0]) {
+            chain.push(words[0].clone());
+            return Err(format!("Alias cycle: {}", chain.join(" -> ")));
+        }
+        chain.push(words[0].clone());
+    }
+    Ok(words)
+}
+
+/// Expands an alias in place of the command in `args`, which starts with the program name
+///
+/// The command is the first argument that isn't a global option or the
+/// value of one; arguments after it are kept after the expansion.
+pub fn expand(args: Vec<OsString>, aliases: &BTreeMap<String, String>) -> Result<Vec<OsString>, String> {
+    if aliases.is_empty() {
+        return Ok(args);
+    }
+
+    let cli = Cli::command();
+    let takes_value = |arg: &str| {
+        cli.get_arguments().filter(|a| a.get_action().takes_values()).any(|a| {
+            a.get_long().is_some_and(|long| arg.strip_prefix("--") == Some(long))
+                || a.get_short().is_some_and(|short| arg.strip_prefix('-') == Some(short.to_string().as_str()))
+        })
+    };
+
+    let mut position = 1;
+    while let Some(arg) = args.get(position).and_then(|arg| arg.to_str()) {
+        if !arg.starts_with('-') || arg == "-" {
+            break;
+        }
+        position += if takes_value(arg) { 2 } else { 1 };
+    }
+    let Some(name) = args.get(position).and_then(|arg| arg.to_str()) else {
+        return Ok(args);
+    };
+    if !aliases.contains_key(name) || is_builtin(name) {
+        return Ok(args);
+    }
+
+    let words = resolve(name, aliases)?;
+    let mut expanded = args[..position].to_vec();
+    expanded.extend(words.into_iter().map(OsString::from));
+    expanded.extend(args[position + 1..].iter().cloned());
+    Ok(expanded)
+}
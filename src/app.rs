@@ -0,0 +1,499 @@
+//! Dispatching parsed command lines to the command handlers
+
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use crate::{
+    cli::{
+        AliasAction, CheckAction, Cli, Commands, DaemonAction, FilterArgs, FocusAction, ReportKind, TemplateAction,
+        TrashAction,
+    },
+    commands::{
+        add_alias, add_batch, add_comment, add_todo, apply_escalation, attach, backup_todos,
+        bench_storage, block_todo, capture_todo, change_status, check_add, check_done,
+        check_pending, clear_todos, clipboard_builder, compact_todos, convert_todos, count_todos,
+        daemon_status, diff_snapshots, duplicate_todo, edit_todo, empty_trash, escalate_todos,
+        export_todos, focus_add, focus_remove, focus_show, hide_todo, import_todos, list_aliases,
+        list_interactive, list_templates, list_todos, list_trash, mark_done, mark_done_where,
+        merge_file, next_todos, open_todo, pin_todo, prompt_segment, recover_pending, remove_alias,
+        remove_todo, remove_where, reorder_todo, report_done, report_time, restore_backup,
+        restore_trash, review_todos, run_daemon, run_doctor, run_nag, run_notify, run_pomodoro,
+        save_template, send_digest, set_priority, set_progress, show_agenda, show_board,
+        show_history, show_reminders, show_stale, show_stats, show_summary, show_timeline,
+        show_todo, show_workload, start_daemon, start_timer, stop_daemon, stop_timer, sync_todos,
+        transfer_todo, unblock_todo, unhide_todo, unpin_todo, watch_todos, ListOutput, Placement,
+        TodoEdit, Transfer,
+    },
+    archive::{ArchivingStore, ARCHIVE_LIST},
+    config::{config_path, Config},
+    display::{
+        find_theme, set_assignee_column, set_color_mode, set_icon_mode, set_stale_after, set_theme, set_truncation,
+        set_urgency_column,
+    },
+    error::TodoError,
+    events::{EventSink, EventStore},
+    filter::Filter,
+    hooks::{hooks_dir, HookStore},
+    calendar::set_week_start,
+    i18n::{set_locale, Locale},
+    models::{set_smart_sort, DateInput, Status, TodoBuilder},
+    output::{info, set_verbosity, verbosity, Verbosity},
+    plugins::run_plugin,
+    staging::StagedStore,
+    storage::{list_path, JsonFileStore, TodoStore},
+    templates::{find_template, Template},
+    terminal::read_clipboard,
+    urgency::set_coefficients,
+    validate::set_max_title_length,
+};
+#[cfg(feature = "email")]
+use crate::commands::email_reminders;
+#[cfg(feature = "feeds")]
+use crate::{
+    cli::FeedAction,
+    commands::{feed_add, feed_list, feed_remove, feed_sync},
+};
+#[cfg(feature = "github")]
+use crate::{
+    cli::GithubAction,
+    commands::{github_close, github_pull},
+};
+#[cfg(feature = "jira")]
+use crate::{
+    cli::JiraAction,
+    commands::{jira_done, jira_pull},
+};
+#[cfg(feature = "share")]
+use crate::commands::{import_from_url, share_todos};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+/// Runs a parsed command line against the database it selects
+pub fn run(cli: Cli) -> Result<()> {
+    set_color_mode(cli.color);
+    set_verbosity(match (cli.quiet, cli.verbose) {
+        (true, _) => Verbosity::Quiet,
+        (_, true) => Verbosity::Verbose,
+        _ => Verbosity::Normal,
+    });
+    let config_path = config_path();
+    if config_path.exists() {
+        info(format_args!("Config: {}", config_path.display()));
+    } else {
+        info(format_args!("Config: defaults ({} not found)", config_path.display()));
+    }
+    let config = Config::load()?;
+    set_locale(config.locale.unwrap_or_else(Locale::from_env));
+    set_week_start(config.week_start);
+    set_truncation(config.display.truncate.clone());
+    set_icon_mode(config.display.icons);
+    set_assignee_column(config.display.assignee);
+    set_stale_after(config.list.stale_after);
+    set_smart_sort(config.list.smart_sort);
+    set_coefficients(config.urgency.clone());
+    set_max_title_length(config.validation.max_title_length);
+    let theme = cli.theme.as_deref().or(config.display.theme.as_deref()).unwrap_or("default");
+    set_theme(find_theme(theme, &config.display.themes)?);
+    let path = match &cli.list {
+        Some(name) => list_path(cli.file.parent().unwrap_or(Path::new("")), name)?,
+        None => cli.file.clone(),
+    };
+    info(format_args!("Database: {}", path.display()));
+    // Plugins open the database themselves, if at all
+    if let Commands::Plugin(args) = &cli.command {
+        return run_plugin(args, &cli, &path, cli.read_only || config.read_only);
+    }
+    let mut store: Box<dyn TodoStore> = Box::new(
+        JsonFileStore::new(path)
+            .read_only(cli.read_only || config.read_only)
+            .patches(config.storage.patch_above, config.storage.compact_after)
+            .format(config.storage.format)
+            .actor(config.me()),
+    );
+    // Innermost, so hooks and events see saves as the command made them
+    if let Some(after) = config.archive.completed_after {
+        if cli.list.as_deref() != Some(ARCHIVE_LIST) {
+            store = Box::new(ArchivingStore::new(store, after));
+        }
+    }
+    let sink = cli.events_fd.map(EventSink::fd).or(config.events.log.map(EventSink::file));
+    if let Some(sink) = sink {
+        store = Box::new(EventStore::new(store, sink));
+    }
+    #[cfg(feature = "webhooks")]
+    if !config.webhooks.is_empty() {
+        store = Box::new(EventStore::new(store, EventSink::webhooks(config.webhooks.clone())));
+    }
+    // Outermost, so a pre-save hook that refuses a change stops it before anyone hears of it
+    let hooks = hooks_dir();
+    if hooks.is_dir() {
+        info(format_args!("Hooks: {}", hooks.display()));
+        store = Box::new(HookStore::new(store, hooks));
+    }
+    // Read-only commands meant for prompts and status bars must never stop to ask, and
+    // read-only lists can't replay or drop an interrupted save anyway
+    let asks = !matches!(
+        cli.command,
+        Commands::Recover { .. } | Commands::Summary | Commands::PromptSegment { .. } | Commands::Bench { .. }
+    );
+    if asks && !store.is_read_only() {
+        check_pending(store.as_mut())?;
+    }
+    if cli.command.dry_run() {
+        let mut staged = StagedStore::new(store.as_mut());
+        dispatch(&mut staged, cli.command)?;
+        return staged.report();
+    }
+    dispatch(store.as_mut(), cli.command)
+}
+
+/// Builds the filter for a default view
+///
+/// Hidden and scheduled todos are left out unless `hidden` or `all` is
+/// set, and old completions unless `all` is. `--assignee me` is taken as
+/// the user's own name.
+fn default_filter(args: &FilterArgs, all: bool, hidden: bool, config: &Config) -> Filter {
+    let mut filter = args.to_filter();
+    filter.assignee = args.assignee.as_deref().map(|name| config.assignee(name));
+    filter.visible_only = !(all || hidden);
+    // Old completions are hidden by default so history doesn't crowd the table
+    if !all {
+        if let Some(age) = config.list.hide_completed_after {
+            filter.completed_since = Local::now().checked_sub_signed(age.duration());
+        }
+    }
+    filter
+}
+
+/// Runs a single command against the given store
+pub fn dispatch(store: &mut dyn TodoStore, command: Commands) -> Result<()> {
+    match command {
+        Commands::Add {
+            title, stdin, from_file, from_clipboard, from_template, due, start, scheduled, priority, tags, notes,
+            reminders, estimate, project, assignee, icon, title_color,
+        } => {
+            // Options given on the command line win over the template's
+            let template = match &from_template {
+                Some(name) => find_template(name)?,
+                None => Template::default(),
+            };
+            let mut builder = template.builder(title);
+            if from_clipboard {
+                builder = clipboard_builder(&read_clipboard()?, builder)?;
+            }
+            builder = builder
+                .start_date(start.map(DateInput::to_date))
+                .due_date(due.map(DateInput::to_date))
+                .scheduled(scheduled.map(DateInput::to_date))
+                .tags(tags)
+                .reminders(reminders);
+            if let Some(priority) = priority {
+                builder = builder.priority(priority);
+            }
+            if estimate.is_some() {
+                builder = builder.estimate(estimate);
+            }
+            if let Some(project) = project {
+                builder = builder.project(Some(project));
+            }
+            if let Some(assignee) = assignee {
+                builder = builder.assignee(Some(Config::load()?.assignee(&assignee)));
+            }
+            if let Some(notes) = notes {
+                builder = builder.notes(notes);
+            }
+            if icon.is_some() {
+                builder = builder.icon(icon);
+            }
+            if title_color.is_some() {
+                builder = builder.color(title_color);
+            }
+
+            if stdin {
+                let mut input = String::new();
+                std::io::stdin().read_to_string(&mut input)?;
+                add_batch(store, &input, builder)
+            } else if let Some(path) = from_file {
+                let input = fs::read_to_string(&path).map_err(|e| TodoError::storage("read", &path, e))?;
+                add_batch(store, &input, builder)
+            } else {
+                add_todo(store, builder)
+            }
+        }
+        Commands::Capture { text } => {
+            capture_todo(store, &text.join(" "), TodoBuilder::default())
+        }
+        Commands::Show { id, json } => {
+            show_todo(store, &id, json)
+        }
+        Commands::History { id, field } => {
+            show_history(store, &id, field.as_deref())
+        }
+        Commands::Comment { id, text } => {
+            add_comment(store, &id, &text)
+        }
+        Commands::Attach { id, target } => {
+            attach(store, &id, &target)
+        }
+        Commands::Open { id, number } => {
+            open_todo(store, &id, number)
+        }
+        Commands::Duplicate { id, due } => {
+            duplicate_todo(store, &id, due.map(DateInput::to_date))
+        }
+        Commands::Template { action } => match action {
+            TemplateAction::Save { id, name } => save_template(store, &id, &name),
+            TemplateAction::List => list_templates(),
+        },
+        Commands::List {
+            sort, reverse, filter, all, hidden, interactive, out, width, wide, truncate, watch, count, limit,
+            offset, no_pager, group_by,
+        } => {
+            let sort = if reverse { sort.reversed() } else { sort };
+            set_urgency_column(verbosity() == Verbosity::Verbose);
+            let config = Config::load()?;
+            if config.escalation.on_list {
+                apply_escalation(store, false)?;
+            }
+
+            let filter = default_filter(&filter, all, hidden, &config);
+            let output = ListOutput {
+                out, width, wide, truncate, limit, offset, pager: !no_pager, group_by, archived: all,
+            };
+            if count {
+                count_todos(store, &filter)
+            } else if interactive {
+                list_interactive(store, sort, &filter)
+            } else if let Some(seconds) = watch {
+                watch_todos(store, sort, &filter, &output, Duration::from_secs(seconds.max(1)))
+            } else {
+                list_todos(store, sort, &filter, &output)
+            }
+        }
+        Commands::Board { filter, all } => {
+            show_board(store, &default_filter(&filter, all, false, &Config::load()?))
+        }
+        Commands::Alias { action } => match action {
+            AliasAction::Add { name, expansion } => add_alias(&name, &expansion),
+            AliasAction::List => list_aliases(),
+            AliasAction::Remove { name } => remove_alias(&name),
+        },
+        Commands::Check { action } => match action {
+            CheckAction::Add { id, text } => check_add(store, &id, &text),
+            CheckAction::Done { id, number } => check_done(store, &id, number),
+        },
+        Commands::Focus { action } => match action {
+            FocusAction::Add { ids } => focus_add(store, &ids),
+            FocusAction::Remove { ids } => focus_remove(store, &ids),
+            FocusAction::Show => focus_show(store),
+        },
+        Commands::Done { id, query, .. } => match (id, query) {
+            (_, Some(query)) => mark_done_where(store, &query),
+            (Some(id), None) => mark_done(store, &id),
+            (None, None) => unreachable!("clap requires an ID or --where"),
+        },
+        Commands::Remove { id, query, confirm, dry_run } => {
+            let ask = !dry_run && confirm.should_ask(Config::load()?.confirm.remove);
+            match (id, query) {
+                (_, Some(query)) => remove_where(store, &query, ask, dry_run),
+                (Some(id), None) => remove_todo(store, &id, ask, dry_run),
+                (None, None) => unreachable!("clap requires an ID or --where"),
+            }
+        }
+        Commands::Clear { confirm, dry_run } => {
+            let ask = !dry_run && confirm.should_ask(Config::load()?.confirm.clear);
+            clear_todos(store, ask)
+        }
+        Commands::Backup { to, include } => {
+            backup_todos(store, to.as_deref(), &include)
+        }
+        Commands::Restore { from, include, confirm } => {
+            restore_backup(store, from.as_deref(), confirm.should_ask(Config::load()?.confirm.restore), &include)
+        }
+        Commands::Diff { old, new } => {
+            diff_snapshots(store, &old, new.as_deref())
+        }
+        Commands::Merge { other, interactive, include, .. } => {
+            merge_file(store, &other, interactive, &include)
+        }
+        Commands::Compact => compact_todos(store),
+        Commands::Convert { to } => convert_todos(store, to),
+        Commands::Bench { todos, format, runs } => bench_storage(todos, format, runs),
+        Commands::Trash { action } => match action {
+            TrashAction::List => list_trash(store),
+            TrashAction::Restore { id } => restore_trash(store, &id),
+            TrashAction::Empty { older_than, confirm } => {
+                empty_trash(store, older_than, confirm.should_ask(Config::load()?.confirm.trash_empty))
+            }
+        },
+        Commands::Cancel { id } => {
+            change_status(store, &id, Status::Cancelled)
+        }
+        Commands::Edit { id, title, due, no_due, priority, notes, project, assignee, estimate, icon, title_color } => {
+            let due_date = match (due, no_due) {
+                (Some(due), _) => Some(Some(due.to_date())),
+                (None, true) => Some(None),
+                (None, false) => None,
+            };
+            let assignee = match assignee {
+                Some(name) => Some(Config::load()?.assignee(&name)),
+                None => None,
+            };
+            let edit = TodoEdit { title, due_date, priority, estimate, notes, project, assignee, icon, color: title_color };
+            edit_todo(store, &id, &edit)
+        }
+        Commands::Status { id, status } => {
+            change_status(store, &id, status)
+        }
+        Commands::Priority { id, priority } => {
+            set_priority(store, &id, priority)
+        }
+        Commands::Progress { id, percent } => {
+            set_progress(store, &id, percent)
+        }
+        Commands::Escalate { dry_run } => {
+            escalate_todos(store, dry_run)
+        }
+        Commands::Agenda { weeks } => {
+            show_agenda(store, weeks)
+        }
+        Commands::Timeline { weeks } => {
+            show_timeline(store, weeks)
+        }
+        Commands::Stats { weeks, live_only } => {
+            show_stats(store, weeks, live_only)
+        }
+        Commands::Stale { days } => {
+            show_stale(store, days)
+        }
+        Commands::Review { interval, all } => {
+            review_todos(store, interval, all, &mut std::io::stdin().lock())
+        }
+        Commands::Summary => {
+            show_summary(store)
+        }
+        Commands::PromptSegment { shell } => {
+            prompt_segment(store, shell)
+        }
+        Commands::Sync { remote, strategy } => {
+            sync_todos(store, remote, strategy)
+        }
+        Commands::Nag => {
+            run_nag(store)
+        }
+        Commands::Remind {
+            #[cfg(feature = "email")]
+            email,
+        } => {
+            #[cfg(feature = "email")]
+            if email {
+                return email_reminders(store);
+            }
+            show_reminders(store)
+        }
+        Commands::Digest { channel, print } => {
+            send_digest(store, channel, print)
+        }
+        #[cfg(feature = "github")]
+        Commands::Github { action } => match action {
+            GithubAction::Pull { repo } => github_pull(store, repo.as_deref()),
+            GithubAction::Close { id } => github_close(store, &id),
+        },
+        #[cfg(feature = "feeds")]
+        Commands::Feed { action } => match action {
+            FeedAction::Add { url, title, every } => feed_add(&url, title.as_deref(), every),
+            FeedAction::List => feed_list(),
+            FeedAction::Remove { feed } => feed_remove(&feed),
+            FeedAction::Sync { force } => feed_sync(store, force),
+        },
+        #[cfg(feature = "jira")]
+        Commands::Jira { action } => match action {
+            JiraAction::Pull => jira_pull(store),
+            JiraAction::Done { id } => jira_done(store, &id),
+        },
+        #[cfg(feature = "share")]
+        Commands::Share { ids, to, yes } => share_todos(store, &ids, to, yes),
+        Commands::Notify { since } => {
+            run_notify(store, since)
+        }
+        Commands::Daemon { action, foreground } => match action {
+            Some(DaemonAction::Stop) => stop_daemon(),
+            Some(DaemonAction::Status) => daemon_status(),
+            None if foreground => run_daemon(store),
+            None => start_daemon(),
+        },
+        Commands::Doctor { ids, fix, .. } => {
+            run_doctor(store, ids, fix)
+        }
+        Commands::Hide { id, until } => {
+            hide_todo(store, &id, until.to_date())
+        }
+        Commands::Unhide { id } => {
+            unhide_todo(store, &id)
+        }
+        Commands::Pin { id } => {
+            pin_todo(store, &id)
+        }
+        Commands::Unpin { id } => {
+            unpin_todo(store, &id)
+        }
+        Commands::Move { id, to, before, after } => match (to, before, after) {
+            (Some(to), _, _) => transfer_todo(store, &id, &to, Transfer::Move),
+            (None, Some(anchor), _) => reorder_todo(store, &id, &anchor, Placement::Before),
+            (None, None, Some(anchor)) => reorder_todo(store, &id, &anchor, Placement::After),
+            (None, None, None) => unreachable!("clap requires --to, --before, or --after"),
+        },
+        Commands::Copy { id, to } => {
+            transfer_todo(store, &id, &to, Transfer::Copy)
+        }
+        Commands::Export { format, group_by, filter, out, include } => {
+            let mut filter = filter.to_filter();
+            if let Some(name) = &filter.assignee {
+                filter.assignee = Some(Config::load()?.assignee(name));
+            }
+            export_todos(store, format, group_by, &filter, out.as_deref(), &include)
+        }
+        #[cfg(feature = "share")]
+        Commands::Import { from_url: Some(url), format, title, replace, .. } => {
+            import_from_url(store, &url, format, title.as_deref(), replace)
+        }
+        Commands::Import { path, format, title, replace, .. } => {
+            let path = path.ok_or_else(|| anyhow!("Nothing to import: pass a file, or - for stdin"))?;
+            import_todos(store, &path, format, title.as_deref(), replace)
+        }
+        Commands::Block { id, on } => {
+            block_todo(store, &id, &on)
+        }
+        Commands::Unblock { id, on } => {
+            unblock_todo(store, &id, on.as_deref())
+        }
+        Commands::Start { id } => {
+            start_timer(store, &id)
+        }
+        Commands::Stop => {
+            stop_timer(store)
+        }
+        Commands::Next { count, spread, all } => {
+            next_todos(store, count, spread, all)
+        }
+        Commands::Pomodoro { id, cycles } => {
+            run_pomodoro(store, &id, cycles)
+        }
+        Commands::Workload { days, capacity } => {
+            show_workload(store, days, capacity)
+        }
+        Commands::Report { report: ReportKind::Time { by, since } } => {
+            report_time(store, by, since.map(DateInput::to_date))
+        }
+        Commands::Report { report: ReportKind::Done { by, since, until } } => {
+            report_done(store, by, since, until)
+        }
+        Commands::Recover { discard } => {
+            recover_pending(store, discard)
+        }
+        Commands::Plugin(args) => {
+            Err(anyhow!("The {} plugin can only be run from the command line", args.first().map_or("", String::as_str)))
+        }
+    }
+}
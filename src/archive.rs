@@ -0,0 +1,192 @@
+//! Todos put away without being deleted
+//!
+//! Archiving moves todos into the list named `archive` next to the
+//! database (`todos-archive.json`), keeping their UUIDs, where `--list
+//! archive` shows them. As with `move`, dependencies only survive between
+//! todos that end up in the same list.
+//!
+//! With `archive.completed_after` in the config, [`ArchivingStore`] does
+//! this on every save for todos completed longer ago than that.
+
+use crate::duration::HumanDuration;
+use crate::history::FieldChange;
+use crate::journal::{JournalEntry, Operation};
+use crate::models::{Todo, TrashedTodo};
+use crate::output::info;
+use crate::storage::{StorageFormat, TodoStore};
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Name of the list archived todos go to
+pub const ARCHIVE_LIST: &str = "archive";
+
+/// Moves the todos in `todos` that `archived` picks into the archive list, saving both
+///
+/// The archive is saved first, so a failure can't lose a todo, and
+/// `todos` is left as it was when nothing could be archived. Archived
+/// todos get the next free IDs in the archive. Returns them as archived.
+pub fn archive_todos(
+    store: &mut dyn TodoStore,
+    todos: &mut Vec<Todo>,
+    archived: impl Fn(&Todo) -> bool,
+) -> Result<Vec<Todo>> {
+    let mut moving: Vec<Todo> = todos.iter().filter(|t| archived(t)).cloned().collect();
+    if moving.is_empty() {
+        return Ok(moving);
+    }
+
+    let mut archive = store.open_list(ARCHIVE_LIST)?;
+    let mut archive_todos = archive.load()?;
+    let uuids: HashSet<String> = moving.iter().map(|t| t.uuid.clone()).collect();
+    // Also what stops the archive being archived into itself
+    if let Some(todo) = archive_todos.iter().find(|t| uuids.contains(&t.uuid)) {
+        return Err(anyhow!("The archive already has todo {}", todo.uuid));
+    }
+
+    let mut next_id = archive_todos.iter().map(|t| t.id).max().unwrap_or(0);
+    let archive_uuids: HashSet<String> = archive_todos.iter().map(|t| t.uuid.clone()).chain(uuids.clone()).collect();
+    for todo in &mut moving {
+        next_id += 1;
+        todo.id = next_id;
+        todo.sort_key = None;
+        todo.depends_on.retain(|uuid| archive_uuids.contains(uuid));
+    }
+
+    archive_todos.extend(moving.iter().cloned());
+    archive.save(&archive_todos)?;
+    for todo in &moving {
+        archive.record(Operation::Added, todo)?;
+    }
+    todos.retain(|t| !uuids.contains(&t.uuid));
+    for todo in todos.iter_mut() {
+        todo.depends_on.retain(|uuid| !uuids.contains(uuid));
+    }
+    store.save(todos)?;
+    for todo in &moving {
+        store.record(Operation::Archived, todo)?;
+    }
+    Ok(moving)
+}
+
+/// Loads the archived todos kept alongside `store`
+pub fn load_archive(store: &dyn TodoStore) -> Result<Vec<Todo>> {
+    store.open_list(ARCHIVE_LIST)?.load()
+}
+
+/// Opens the archive kept alongside `store`, unless it has none or is the archive
+pub fn other_archive(store: &dyn TodoStore) -> Option<Box<dyn TodoStore>> {
+    store
+        .open_list(ARCHIVE_LIST)
+        .ok()
+        .filter(|archive| archive.backup_location() != store.backup_location())
+}
+
+/// Wraps another store, archiving todos completed longer ago than a set age whenever it saves
+///
+/// Meant to sit right around the file store, so hooks and the event log
+/// see the save as asked for; the archive's own journal records the move.
+pub struct ArchivingStore {
+    inner: Box<dyn TodoStore>,
+    after: HumanDuration,
+}
+
+impl ArchivingStore {
+    /// Archives todos completed longer ago than `after` on saves to `inner`
+    pub fn new(inner: Box<dyn TodoStore>, after: HumanDuration) -> Self {
+        Self { inner, after }
+    }
+}
+
+impl TodoStore for ArchivingStore {
+    fn load(&mut self) -> Result<Vec<Todo>> {
+        self.inner.load()
+    }
+
+    fn for_each_todo(&mut self, visit: &mut dyn FnMut(Todo)) -> Result<()> {
+        self.inner.for_each_todo(visit)
+    }
+
+    /// Saves `todos` less the ones due for the archive, which go there first
+    fn save(&mut self, todos: &[Todo]) -> Result<()> {
+        // An age reaching back past the earliest time there is archives nothing
+        let cutoff = Local::now().checked_sub_signed(self.after.duration());
+        let mut todos = todos.to_vec();
+        let old = |todo: &Todo| {
+            todo.completed && todo.completed_at.zip(cutoff).is_some_and(|(at, cutoff)| at < cutoff)
+        };
+        let archived = archive_todos(self.inner.as_mut(), &mut todos, old)?;
+        if archived.is_empty() {
+            return self.inner.save(&todos);
+        }
+        info(format_args!("Archived {} todos completed over {} ago", archived.len(), self.after));
+        Ok(())
+    }
+
+    fn append_journal(&mut self, entry: &JournalEntry) -> Result<()> {
+        self.inner.append_journal(entry)
+    }
+
+    fn load_journal(&self) -> Result<Vec<JournalEntry>> {
+        self.inner.load_journal()
+    }
+
+    fn load_history(&self) -> Result<Vec<FieldChange>> {
+        self.inner.load_history()
+    }
+
+    fn actor(&self) -> Option<&str> {
+        self.inner.actor()
+    }
+
+    fn last_modified(&self) -> Option<SystemTime> {
+        self.inner.last_modified()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.inner.is_read_only()
+    }
+
+    fn backup_location(&self) -> Option<(PathBuf, String)> {
+        self.inner.backup_location()
+    }
+
+    fn pending(&self) -> Result<Option<Vec<Todo>>> {
+        self.inner.pending()
+    }
+
+    fn discard_pending(&mut self) -> Result<()> {
+        self.inner.discard_pending()
+    }
+
+    /// Opens the other list as it is, without archiving
+    fn open_list(&self, name: &str) -> Result<Box<dyn TodoStore>> {
+        self.inner.open_list(name)
+    }
+
+    fn load_trash(&self) -> Result<Vec<TrashedTodo>> {
+        self.inner.load_trash()
+    }
+
+    fn save_trash(&mut self, trash: &[TrashedTodo]) -> Result<()> {
+        self.inner.save_trash(trash)
+    }
+
+    fn load_cache(&self, name: &str) -> Option<String> {
+        self.inner.load_cache(name)
+    }
+
+    fn save_cache(&mut self, name: &str, content: &str) -> Result<()> {
+        self.inner.save_cache(name, content)
+    }
+
+    fn convert(&mut self, format: StorageFormat) -> Result<bool> {
+        self.inner.convert(format)
+    }
+
+    fn compact(&mut self) -> Result<usize> {
+        self.inner.compact()
+    }
+}
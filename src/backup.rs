@@ -0,0 +1,250 @@
+//! Timestamped snapshots of the database
+//!
+//! A backup is a copy of the todos in the database's own format, named
+//! after the database and the time it was taken, e.g.
+//! `todos.backups/todos-20251102-091403.json`. Each one can be opened
+//! with `--file` like any other database. Only the newest `backup.keep`
+//! snapshots in a directory are kept.
+//!
+//! With `--include`, the archive and trash are saved next to the todos,
+//! in `todos-20251102-091403.archive.json` and `...trash.json`, where
+//! `restore` and `merge` find them again.
+
+use crate::archive::{load_archive, ARCHIVE_LIST};
+use crate::config::{BackupConfig, Config};
+use crate::error::TodoError;
+use crate::models::{Todo, TrashedTodo};
+use crate::output;
+use crate::storage::{JsonFileStore, TodoStore};
+use crate::t;
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use clap::ValueEnum;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Stores kept alongside the todos that can be taken along with them
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtraStore {
+    /// The archived todos, `--list archive`
+    Archive,
+    /// The removed todos in the trash
+    Trash,
+}
+
+impl ExtraStore {
+    fn name(self) -> &'static str {
+        match self {
+            ExtraStore::Archive => "archive",
+            ExtraStore::Trash => "trash",
+        }
+    }
+
+    /// Returns where this store is kept next to the snapshot at `snapshot`
+    pub fn path(self, snapshot: &Path) -> PathBuf {
+        let stem = snapshot.file_stem().and_then(|s| s.to_str()).unwrap_or("todos");
+        snapshot.with_file_name(format!("{}.{}.json", stem, self.name()))
+    }
+}
+
+/// The archive and trash taken along with the todos, for the stores asked for
+#[derive(Debug, Clone, Default)]
+pub struct Extras {
+    pub archive: Option<Vec<Todo>>,
+    pub trash: Option<Vec<TrashedTodo>>,
+}
+
+impl Extras {
+    /// Loads the stores in `include` kept alongside `store`
+    pub fn load(store: &dyn TodoStore, include: &[ExtraStore]) -> Result<Self> {
+        let mut extras = Self::default();
+        if include.contains(&ExtraStore::Archive) {
+            extras.archive = Some(load_archive(store)?);
+        }
+        if include.contains(&ExtraStore::Trash) {
+            extras.trash = Some(store.load_trash()?);
+        }
+        Ok(extras)
+    }
+
+    /// Reads the stores in `include` saved next to the snapshot at `snapshot`
+    pub fn read(snapshot: &Path, include: &[ExtraStore]) -> Result<Self> {
+        let mut extras = Self::default();
+        for &extra in include {
+            let path = extra.path(snapshot);
+            if !path.exists() {
+                let (name, snapshot) = (extra.name(), snapshot.display());
+                return Err(anyhow!("No {} saved with {}; expected it at {}", name, snapshot, path.display()));
+            }
+            match extra {
+                ExtraStore::Archive => extras.archive = Some(JsonFileStore::new(&path).read_only(true).load()?),
+                ExtraStore::Trash => {
+                    let content = fs::read_to_string(&path).map_err(|e| TodoError::storage("read", &path, e))?;
+                    let trash = serde_json::from_str(&content).map_err(|source| TodoError::Parse { path, source })?;
+                    extras.trash = Some(trash);
+                }
+            }
+        }
+        Ok(extras)
+    }
+
+    /// Writes the stores next to the snapshot at `snapshot`
+    fn write(&self, snapshot: &Path) -> Result<()> {
+        let files = [
+            (ExtraStore::Archive, self.archive.as_ref().map(serde_json::to_string_pretty)),
+            (ExtraStore::Trash, self.trash.as_ref().map(serde_json::to_string_pretty)),
+        ];
+        for (extra, content) in files {
+            if let Some(content) = content {
+                let path = extra.path(snapshot);
+                let content = content.with_context(|| format!("Failed to serialize the {}", extra.name()))?;
+                fs::write(&path, content).map_err(|e| TodoError::storage("write to", &path, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the stores kept alongside `store` with these
+    pub fn save(&self, store: &mut dyn TodoStore) -> Result<()> {
+        if let Some(archive) = &self.archive {
+            store.open_list(ARCHIVE_LIST)?.save(archive)?;
+        }
+        if let Some(trash) = &self.trash {
+            store.save_trash(trash)?;
+        }
+        Ok(())
+    }
+
+    /// Prints how many todos each store holds, one line per store
+    pub fn print_counts(&self) {
+        if let Some(archive) = &self.archive {
+            println!("{}", t!("include.archive", count = archive.len()));
+        }
+        if let Some(trash) = &self.trash {
+            println!("{}", t!("include.trash", count = trash.len()));
+        }
+    }
+}
+/// A snapshot that was written
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub todos: usize,
+    /// The archive and trash saved with the todos, if asked for
+    pub extras: Extras,
+    /// Older snapshots deleted to stay within `backup.keep`
+    pub rotated: Vec<PathBuf>,
+}
+
+/// Returns the directory a store's backups go to, `backup.dir` or its own, and their name
+///
+/// `None` for stores that aren't backed up, such as the one behind `--dry-run`.
+pub fn backup_location(store: &dyn TodoStore, config: &BackupConfig) -> Option<(PathBuf, String)> {
+    let (dir, name) = store.backup_location()?;
+    Some((config.dir.clone().unwrap_or(dir), name))
+}
+
+/// Lists the snapshots in `dir` named `prefix-<time>.json`, oldest first
+pub fn list_backups(dir: &Path, prefix: &str) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(dir).map_err(|e| TodoError::storage("read", dir, e))?;
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let stamp = name.strip_prefix(prefix).and_then(|n| n.strip_prefix('-')).unwrap_or("");
+            // Not the archive and trash saved next to a snapshot
+            let stamp = stamp.strip_suffix(".json").unwrap_or("");
+            stamp.starts_with(|c: char| c.is_ascii_digit()) && stamp.chars().all(|c| c.is_ascii_digit() || c == '-')
+        })
+        .collect();
+    backups.sort_by_cached_key(|path| taken(path, prefix));
+    Ok(backups)
+}
+
+/// Orders a snapshot by the time in its name, then by the number telling same-second ones apart
+///
+/// `todos-20251102-091403.json` comes first, then `-2`, ..., `-10`.
+fn taken(path: &Path, prefix: &str) -> (String, u64) {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let stamp = stem.strip_prefix(prefix).and_then(|s| s.strip_prefix('-')).unwrap_or(stem);
+    let (time, n) = match stamp.get(15..) {
+        Some(rest) => (&stamp[..15], rest.strip_prefix('-').and_then(|n| n.parse().ok()).unwrap_or(1)),
+        None => (stamp, 1),
+    };
+    (time.to_string(), n)
+}
+
+/// Writes the store's todos to a new snapshot `prefix-<time>.json` in `dir`,
+/// with the stores in `include` next to it
+///
+/// Then drops all but the newest `keep` snapshots there; 0 keeps them all.
+pub fn write_backup(
+    store: &mut dyn TodoStore,
+    dir: &Path,
+    prefix: &str,
+    keep: usize,
+    include: &[ExtraStore],
+) -> Result<Backup> {
+    let todos = store.load()?;
+    let extras = Extras::load(store, include)?;
+    fs::create_dir_all(dir).map_err(|e| TodoError::storage("create", dir, e))?;
+
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    let mut path = dir.join(format!("{}-{}.json", prefix, stamp));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}-{}.json", prefix, stamp, n));
+        n += 1;
+    }
+    let content = serde_json::to_string_pretty(&todos).with_context(|| "Failed to serialize todos")?;
+    fs::write(&path, content).map_err(|e| TodoError::storage("write to", &path, e))?;
+    extras.write(&path)?;
+
+    let mut rotated = Vec::new();
+    let backups = list_backups(dir, prefix)?;
+    if keep > 0 && backups.len() > keep {
+        for old in &backups[..backups.len() - keep] {
+            fs::remove_file(old).map_err(|e| TodoError::storage("remove", old, e))?;
+            for extra in [ExtraStore::Archive, ExtraStore::Trash] {
+                let _ = fs::remove_file(extra.path(old));
+            }
+            rotated.push(old.clone());
+        }
+    }
+
+    Ok(Backup { path, todos: todos.len(), extras, rotated })
+}
+
+/// Backs the store up before a destructive change, if `backup.before_destructive` is on
+///
+/// Stores that aren't backed up are skipped, and so are read-only ones,
+/// which refuse the change anyway. The stores in `include`, which the
+/// change replaces too, are backed up with the todos. Prints where the
+/// snapshot went.
+pub fn backup_before_change(store: &mut dyn TodoStore, include: &[ExtraStore]) -> Result<()> {
+    let config = Config::load()?.backup;
+    if !config.before_destructive || store.is_read_only() {
+        return Ok(());
+    }
+    let Some((dir, prefix)) = backup_location(store, &config) else {
+        return Ok(());
+    };
+
+    let backup = write_backup(store, &dir, &prefix, config.keep, include)?;
+    println!("{}", t!("backup.done", count = backup.todos, path = backup.path.display()));
+    backup.extras.print_counts();
+    Ok(())
+}
+
+/// Loads the todos in a snapshot, or any other database file, without changing it
+pub fn load_snapshot(path: &Path) -> Result<Vec<Todo>> {
+    if !path.exists() {
+        return Err(anyhow!("No backup at {}", path.display()));
+    }
+    output::info(format_args!("Reading {}", path.display()));
+    JsonFileStore::new(path).read_only(true).load()
+}
@@ -0,0 +1,76 @@
+//! Days and weeks as the user counts them
+//!
+//! Weeks start on Monday unless `week_start` in the config says Sunday.
+//! The `next week` and `end of week` dates, the `this-week` filter, the
+//! agenda, and everything else that splits time into weeks go by it. Days
+//! end at local midnight, which is when a todo due that day turns overdue.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// The day weeks start on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+impl WeekStart {
+    /// Returns the first weekday of the week
+    pub fn weekday(self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+static WEEK_START: OnceLock<WeekStart> = OnceLock::new();
+
+/// Sets the day weeks start on for the rest of the process
+///
+/// Only the first call has an effect.
+pub fn set_week_start(start: WeekStart) {
+    let _ = WEEK_START.set(start);
+}
+
+/// Returns the day weeks start on, Monday unless set otherwise
+pub fn week_start() -> WeekStart {
+    WEEK_START.get().copied().unwrap_or_default()
+}
+
+/// Returns the moment the local day `date` ends, i.e. the first moment of the next one
+///
+/// On a day whose midnight is skipped by a DST change, that is the first
+/// moment after the gap.
+pub fn end_of_day(date: NaiveDate) -> DateTime<Local> {
+    let midnight = (date + Duration::days(1)).and_time(NaiveTime::MIN);
+    // Gaps last an hour or less in practice
+    (0..=8)
+        .map(|quarter| midnight + Duration::minutes(15 * quarter))
+        .find_map(|time| Local.from_local_datetime(&time).earliest())
+        .unwrap_or_else(|| Local.from_utc_datetime(&midnight))
+}
+
+/// Returns the first day of the week `date` falls in
+pub fn start_of_week(date: NaiveDate) -> NaiveDate {
+    date.week(week_start().weekday()).first_day()
+}
+
+/// Returns the last day of the week `date` falls in
+pub fn end_of_week(date: NaiveDate) -> NaiveDate {
+    date.week(week_start().weekday()).last_day()
+}
+
+/// Returns the first day of the week after the one `date` falls in
+pub fn next_week(date: NaiveDate) -> NaiveDate {
+    start_of_week(date) + Duration::weeks(1)
+}
+
+/// Returns how many weeks the week of `date` lies after that of `today`, negative for earlier ones
+pub fn weeks_between(today: NaiveDate, date: NaiveDate) -> i64 {
+    (start_of_week(date) - start_of_week(today)).num_weeks()
+}
@@ -1,12 +1,55 @@
-use clap::{Parser, Subcommand, ValueEnum};
-use crate::models::{DateInput, Priority};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use crate::backup::ExtraStore;
+use crate::display::{ColorMode, Grouping};
+use crate::duration::HumanDuration;
+use crate::filter::{DueWindow, Filter};
+use crate::formats::{Format, GroupBy};
+#[cfg(feature = "share")]
+use crate::integrations::share::ShareService;
+use crate::models::{DateInput, Priority, Status};
+use crate::query::Query;
+use crate::sync::MergeStrategy;
 use anyhow::Result;
-use chrono::NaiveDate;
+use crate::storage::{StorageFormat, TODO_FILE};
+use chrono::{Datelike, NaiveDate};
+use std::path::PathBuf;
 
 /// Command line interface for the todo application
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
+    /// Path of the todo database
+    #[arg(short, long, global = true, env = "TODO_FILE", default_value = TODO_FILE)]
+    pub file: PathBuf,
+    
+    /// Use the named list (todos-NAME.json next to the database) instead
+    #[arg(long, global = true, value_name = "NAME")]
+    pub list: Option<String>,
+    
+    /// When to use colors
+    #[arg(long, global = true, value_enum, default_value_t = ColorMode::Auto)]
+    pub color: ColorMode,
+    
+    /// Theme for colors and styles: default, solarized, monochrome, high-contrast, or one from the config
+    #[arg(long, global = true, value_name = "NAME")]
+    pub theme: Option<String>,
+    
+    /// Don't show the list after changing it
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+    
+    /// Report which files are read and written, on stderr
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+    
+    /// Refuse any change to the todos, e.g. for a shared list you shouldn't edit
+    #[arg(long, global = true)]
+    pub read_only: bool,
+    
+    /// Write an event for every change to a todo to this file descriptor, as JSON lines
+    #[arg(long, global = true, value_name = "FD")]
+    pub events_fd: Option<u32>,
+    
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -16,88 +59,1380 @@ pub struct Cli {
 pub enum Commands {
     /// Add a new todo
     Add {
-        /// The title of the todo
-        title: String,
+        /// The title of the todo (defaults to the template's with --from-template)
+        #[arg(required_unless_present_any = ["from_template", "stdin", "from_file", "from_clipboard"])]
+        title: Option<String>,
+        
+        /// Add one todo per line read from stdin, allowing the tokens `capture` takes
+        #[arg(long, conflicts_with_all = ["title", "from_file"])]
+        stdin: bool,
+        
+        /// Add one todo per line of a file, like --stdin (`--file` is the database)
+        #[arg(long, value_name = "PATH", conflicts_with = "title")]
+        from_file: Option<PathBuf>,
         
-        /// Due date (today, tomorrow, or YYYY-MM-DD)
+        /// Take the title from the first line of the clipboard, the notes from
+        /// the rest, and attach any links in it
+        #[arg(long, conflicts_with_all = ["title", "stdin", "from_file"])]
+        from_clipboard: bool,
+        
+        /// Start from a template saved with `template save`
+        #[arg(long, value_name = "NAME")]
+        from_template: Option<String>,
+        
+        /// Due date (today, tomorrow, a weekday, next week, end of week, or YYYY-MM-DD)
         #[arg(short, long, value_parser = parse_date_input)]
         due: Option<DateInput>,
         
-        /// Priority level
-        #[arg(short, long, value_enum, default_value_t = Priority::Normal)]
-        priority: Priority,
+        /// Planned start date, shown by `timeline` (same forms as --due)
+        #[arg(long, value_parser = parse_date_input)]
+        start: Option<DateInput>,
+        
+        /// Keep the todo out of `list` and `next` until this date (same forms as --due)
+        #[arg(long, value_parser = parse_date_input)]
+        scheduled: Option<DateInput>,
+        
+        /// Priority level (defaults to normal, or the template's)
+        #[arg(short, long)]
+        priority: Option<Priority>,
+        
+        /// Tag to attach (repeatable)
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        
+        /// Free-form notes
+        #[arg(short, long)]
+        notes: Option<String>,
+        
+        /// Remind this long before the due time, e.g. 1d or 2h (repeatable)
+        #[arg(short, long = "remind")]
+        reminders: Vec<HumanDuration>,
+        
+        /// How long the todo should take, e.g. 2h or 1h30m, counted by `workload`
+        #[arg(short, long)]
+        estimate: Option<HumanDuration>,
+        
+        /// Project the todo belongs to
+        #[arg(long)]
+        project: Option<String>,
+        
+        /// Who the todo is for; `me` is your own name
+        #[arg(long)]
+        assignee: Option<String>,
+        
+        /// Emoji or symbol shown before the title, e.g. 🏠
+        #[arg(long)]
+        icon: Option<String>,
+        
+        /// Title color, e.g. green or "bright blue bold"
+        #[arg(long)]
+        title_color: Option<String>,
+    },
+    
+    /// Add a todo from one line, e.g. "Submit report !high @work #finance due:friday"
+    ///
+    /// `!` marks the priority, `@` the project, `#` a tag, and `due:` the due
+    /// date; the remaining words are the title.
+    Capture {
+        /// The title with its tokens; several words are joined with spaces
+        #[arg(required = true, value_name = "TEXT")]
+        text: Vec<String>,
+    },
+    
+    /// Change the title, due date, priority, notes, project, assignee, icon, or color of a todo
+    Edit {
+        /// ID, UUID prefix, or title of the todo to edit
+        id: String,
+        
+        /// New title
+        #[arg(short, long)]
+        title: Option<String>,
+        
+        /// New due date (same forms as `add --due`)
+        #[arg(short, long, value_parser = parse_date_input)]
+        due: Option<DateInput>,
+        
+        /// Remove the due date
+        #[arg(long, conflicts_with = "due")]
+        no_due: bool,
+        
+        /// New priority level
+        #[arg(short, long)]
+        priority: Option<Priority>,
+        
+        /// New notes; an empty string removes them
+        #[arg(short, long)]
+        notes: Option<String>,
+        
+        /// New project; an empty string removes it
+        #[arg(long)]
+        project: Option<String>,
+        
+        /// New assignee, `me` for yourself; an empty string removes it
+        #[arg(long)]
+        assignee: Option<String>,
+        
+        /// New estimate, e.g. 2h; 0m removes it
+        #[arg(short, long)]
+        estimate: Option<HumanDuration>,
+        
+        /// Emoji or symbol shown before the title, e.g. 🏠; an empty string removes it
+        #[arg(long)]
+        icon: Option<String>,
+        
+        /// Title color, e.g. green or "bright blue bold"; an empty string removes it
+        #[arg(long)]
+        title_color: Option<String>,
     },
     
     /// List todos
+    ///
+    /// With --verbose the table has an Urgency column.
     List {
-        /// Sort order
-        #[arg(short, long, value_enum, default_value_t = SortOrder::Smart)]
+        /// Sort keys separated by commas, each optionally `:asc` or `:desc`, e.g. `priority,due:desc`
+        ///
+        /// Keys: smart, status, due, priority, created, modified, title,
+        /// urgency, and manual. Without a direction, priority and urgency go
+        /// highest first, modified most recent first, and the others ascending.
+        #[arg(short, long, value_name = "KEYS", default_value = "smart")]
         sort: SortOrder,
         
-        /// Show only active (incomplete) todos
+        /// Reverse the direction of every sort key
+        #[arg(long)]
+        reverse: bool,
+        
+        #[command(flatten)]
+        filter: FilterArgs,
+        
+        /// Include todos completed long ago (see list.hide_completed_after in the config) and hidden
+        /// ones, followed by the archived ones
+        #[arg(long)]
+        all: bool,
+        
+        /// Include todos hidden with `hide` or scheduled for a later day
+        #[arg(long)]
+        hidden: bool,
+        
+        /// Move through the list with the arrow keys, toggling and deleting todos
+        #[arg(short, long, conflicts_with = "out")]
+        interactive: bool,
+        
+        /// Write the table to a file, without colors
         #[arg(short, long)]
-        active: bool,
+        out: Option<PathBuf>,
         
-        /// Filter by minimum priority
-        #[arg(short, long, value_enum)]
-        priority: Option<Priority>,
+        /// Table width in characters (defaults to the terminal width)
+        #[arg(short, long)]
+        width: Option<usize>,
+        
+        /// Show full titles, widening the table as needed
+        #[arg(long, conflicts_with = "truncate")]
+        wide: bool,
+        
+        /// Cut long titles to fit the table width, even when output is piped
+        #[arg(long)]
+        truncate: bool,
+        
+        /// Keep the list on screen, redrawing when the database changes or every SECONDS (up to a day)
+        #[arg(
+            long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "5",
+            value_parser = clap::value_parser!(u64).range(1..=86_400), conflicts_with_all = ["out", "interactive"]
+        )]
+        watch: Option<u64>,
+        
+        /// Print only the number of matching todos
+        #[arg(long, conflicts_with_all = ["out", "interactive", "watch"])]
+        count: bool,
+        
+        /// Show at most N todos
+        #[arg(long, value_name = "N", conflicts_with_all = ["interactive", "count"])]
+        limit: Option<usize>,
+        
+        /// Skip the first N todos, e.g. with --limit to page through the list
+        #[arg(long, value_name = "N", default_value_t = 0, conflicts_with_all = ["interactive", "count"])]
+        offset: usize,
+        
+        /// Print long lists directly instead of through $PAGER
+        #[arg(long)]
+        no_pager: bool,
+        
+        /// Split the table into sections with counts
+        #[arg(short, long, value_enum, conflicts_with_all = ["interactive", "count"])]
+        group_by: Option<Grouping>,
+    },
+    
+    /// Show todos as a board with Backlog, In Progress, Waiting, and Done columns
+    Board {
+        #[command(flatten)]
+        filter: FilterArgs,
+        
+        /// Include todos completed long ago (see list.hide_completed_after in the config) and hidden ones
+        #[arg(long)]
+        all: bool,
+    },
+    
+    /// Show every field of a todo, with its notes and attachments
+    Show {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// Print the todo as stored, as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Show when a todo's fields changed, and from what
+    History {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// Only show changes to this field, as it is stored (e.g. due_date)
+        #[arg(long)]
+        field: Option<String>,
+    },
+    
+    /// Add a timestamped comment to a todo, shown by `show`
+    Comment {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// What to say, e.g. progress or a decision made
+        text: String,
+    },
+    
+    /// Attach a URL or file path to a todo
+    Attach {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// URL (https://..., mailto:...) or path of a file
+        target: String,
+    },
+    
+    /// Open the page a todo came from, or one of its attachments, with the system's default application
+    Open {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// Which attachment to open, as numbered by `show`; without one, the
+        /// todo's source (an issue or card) or else its first attachment
+        number: Option<usize>,
+    },
+    
+    /// Add an open copy of a todo
+    Duplicate {
+        /// ID, UUID prefix, or title of the todo to copy
+        id: String,
+        
+        /// Due date for the copy (same forms as `add --due`)
+        #[arg(short, long, value_parser = parse_date_input)]
+        due: Option<DateInput>,
+    },
+    
+    /// Save and list reusable todo shapes for `add --from-template`
+    Template {
+        #[command(subcommand)]
+        action: TemplateAction,
+    },
+    
+    /// Define, list, and remove custom commands (see aliases in the config)
+    Alias {
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+    
+    /// Keep a checklist of small steps on a todo, shown by `show` and counted in `list`
+    Check {
+        #[command(subcommand)]
+        action: CheckAction,
+    },
+    
+    /// Keep a short hand-picked list of what to work on today, whatever is due
+    Focus {
+        #[command(subcommand)]
+        action: FocusAction,
     },
     
     /// Mark a todo as done
     Done {
-        /// ID of the todo to mark as done
-        id: u64,
+        /// ID, UUID prefix, or title of the todo to mark as done
+        #[arg(required_unless_present = "query", conflicts_with = "query")]
+        id: Option<String>,
+        
+        /// Mark every todo matching a query as done
+        #[arg(long = "where", value_name = "EXPR")]
+        query: Option<Query>,
+        
+        /// Print what would change without saving anything
+        #[arg(long)]
+        dry_run: bool,
     },
     
-    /// Remove a todo
+    /// Move a todo to the trash
     Remove {
-        /// ID of the todo to remove
-        id: u64,
+        /// ID, UUID prefix, or title of the todo to remove
+        #[arg(required_unless_present = "query", conflicts_with = "query")]
+        id: Option<String>,
+        
+        /// Remove every todo matching a query
+        #[arg(long = "where", value_name = "EXPR")]
+        query: Option<Query>,
+        
+        #[command(flatten)]
+        confirm: ConfirmArgs,
+        
+        /// Print what would change without saving anything
+        #[arg(long)]
+        dry_run: bool,
     },
     
     /// Clear all todos
-    Clear,
+    Clear {
+        #[command(flatten)]
+        confirm: ConfirmArgs,
+        
+        /// Print what would change without saving anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    
+    /// Save a timestamped snapshot of the database (see backup in the config)
+    Backup {
+        /// Directory to write it to instead of the backup directory
+        #[arg(long)]
+        to: Option<PathBuf>,
+        
+        /// Stores to back up with the todos, e.g. archive,trash
+        #[arg(long, value_enum, value_delimiter = ',')]
+        include: Vec<ExtraStore>,
+    },
+    
+    /// Replace the todos with a snapshot, after showing what would change
+    Restore {
+        /// Snapshot to restore; defaults to the newest backup
+        #[arg(long)]
+        from: Option<PathBuf>,
+        
+        /// Stores to restore with the todos, from a backup taken with the same --include
+        #[arg(long, value_enum, value_delimiter = ',')]
+        include: Vec<ExtraStore>,
+        
+        #[command(flatten)]
+        confirm: ConfirmArgs,
+    },
+    
+    /// Show the todos added, removed, and changed between two snapshots
+    Diff {
+        /// Backup or database file to compare from
+        #[arg(value_name = "SNAPSHOT")]
+        old: PathBuf,
+        
+        /// Backup or database file to compare to; defaults to the database
+        #[arg(value_name = "SNAPSHOT")]
+        new: Option<PathBuf>,
+    },
+    
+    /// Merge another copy of the database, such as a sync conflict copy, into this one
+    Merge {
+        /// Database file to merge in
+        other: PathBuf,
+        
+        /// Ask which value to keep for each conflicting field instead of taking the newer
+        #[arg(short, long)]
+        interactive: bool,
+        
+        /// Stores to merge too, saved next to the other copy as `backup --include` does
+        #[arg(long, value_enum, value_delimiter = ',')]
+        include: Vec<ExtraStore>,
+        
+        /// Print what would change without saving anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    
+    /// Rewrite the database file with the changes appended to its patch log
+    Compact,
+    
+    /// Rewrite the database file in another format (see storage.format in the config)
+    Convert {
+        /// Format to store the todos in
+        #[arg(long, value_enum)]
+        to: StorageFormat,
+    },
+    
+    /// Time loading, sorting, and saving a scratch database of synthetic todos
+    #[command(hide = true)]
+    Bench {
+        /// Number of todos to generate
+        #[arg(long, default_value_t = 10_000)]
+        todos: usize,
+        /// Format to store them in
+        #[arg(long, value_enum, default_value_t = StorageFormat::Json)]
+        format: StorageFormat,
+        /// Times to run each step, keeping the quickest
+        #[arg(long, default_value_t = 3)]
+        runs: usize,
+    },
+    
+    /// List, restore, or permanently delete removed todos
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+    
+    /// Mark a todo as cancelled, dropping it without finishing it
+    Cancel {
+        /// ID, UUID prefix, or title of the todo to cancel
+        id: String,
+    },
+    
+    /// Set the status of a todo
+    Status {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// New status
+        #[arg(value_enum)]
+        status: Status,
+    },
     
     /// Set priority of a todo
     Priority {
-        /// ID of the todo
-        id: u64,
+        /// ID, UUID prefix, or title of the todo
+        id: String,
         
         /// New priority level
         priority: Priority,
     },
+    
+    /// Set how far along a todo is, shown as a bar in `list` and `show`
+    Progress {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// Percent done, from 0 to 100
+        percent: u8,
+    },
+    
+    /// Raise priorities of todos nearing or past their due date, per the config rules
+    Escalate {
+        /// Show what would change without saving
+        #[arg(long)]
+        dry_run: bool,
+    },
+    
+    /// Show open todos day by day, with overdue ones first and undated ones last
+    Agenda {
+        /// Number of calendar weeks to show, starting with the rest of this one (at most 520)
+        #[arg(short, long, default_value_t = 2, value_parser = clap::value_parser!(u32).range(1..=520))]
+        weeks: u32,
+    },
+    
+    /// Show open todos as bars from their start to their due date
+    Timeline {
+        /// Number of weeks to show, starting this week (at most 520)
+        #[arg(short, long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..=520))]
+        weeks: u32,
+    },
+    
+    /// Show weekly statistics of added, completed, and removed todos
+    Stats {
+        /// Number of weeks to show
+        #[arg(short, long, default_value_t = 4)]
+        weeks: u32,
+        
+        /// Only use live todos, ignoring the operation journal
+        #[arg(long)]
+        live_only: bool,
+    },
+    
+    /// List open todos that haven't changed for a while, least recently changed first
+    Stale {
+        /// How many days without a change make a todo stale
+        #[arg(short, long, default_value_t = 30)]
+        days: u32,
+    },
+    
+    /// Go through the open todos one at a time, keeping, rescheduling, or putting away each
+    ///
+    /// For each todo answer k (keep), r (reschedule), p (priority), t (tag),
+    /// a (archive), d (delete), s (skip), or q (quit). Reviewed todos are
+    /// left out of the next review until the interval has passed.
+    Review {
+        /// Leave out todos reviewed within this long, e.g. 7d or 2w
+        #[arg(long, default_value = "7d")]
+        interval: HumanDuration,
+        
+        /// Review every open todo, however recently it was reviewed
+        #[arg(long)]
+        all: bool,
+    },
+    
+    /// Print one line of counts, e.g. "12 active, 3 due today, 2 overdue, 1 urgent", for status bars
+    Summary,
+    
+    /// Print a compact count string such as "✔3 ⚑1 ⏰2" for a shell prompt (see prompt.format in the config)
+    PromptSegment {
+        /// Wrap color codes so the shell doesn't count them toward the prompt width
+        #[arg(long, value_enum, default_value_t = PromptShell::Plain)]
+        shell: PromptShell,
+    },
+    
+    /// Sync todos with a git remote
+    Sync {
+        /// Git remote URL (overrides sync.remote in the config)
+        #[arg(short, long)]
+        remote: Option<String>,
+        
+        /// Conflict resolution strategy (overrides sync.strategy in the config)
+        #[arg(short, long, value_enum)]
+        strategy: Option<MergeStrategy>,
+    },
+    
+    /// Upload todos as a Markdown checklist and print a link to them
+    #[cfg(feature = "share")]
+    Share {
+        /// IDs, UUID prefixes, or titles of the todos to share, e.g. 1,2,3 (defaults to every open todo)
+        #[arg(long, value_delimiter = ',')]
+        ids: Vec<String>,
+        
+        /// Where to upload them (defaults to share.to in the config)
+        #[arg(long, value_enum)]
+        to: Option<ShareService>,
+        
+        /// Upload without showing the checklist and asking first
+        #[arg(short, long)]
+        yes: bool,
+    },
+    
+    /// Turn GitHub issues into todos and close them when done
+    #[cfg(feature = "github")]
+    Github {
+        #[command(subcommand)]
+        action: GithubAction,
+    },
+    
+    /// Keep todos in step with calendar and JSON feeds, such as course deadlines
+    #[cfg(feature = "feeds")]
+    Feed {
+        #[command(subcommand)]
+        action: FeedAction,
+    },
+    
+    /// Turn Jira issues assigned to you into todos and resolve them when done
+    #[cfg(feature = "jira")]
+    Jira {
+        #[command(subcommand)]
+        action: JiraAction,
+    },
+    
+    /// Fire reminders that came due since the last run
+    Notify {
+        /// Look back this far instead of to the last run, e.g. 1h
+        #[arg(long)]
+        since: Option<HumanDuration>,
+    },
+    
+    /// Print a short nag if the inbox is backing up or urgent todos are overdue
+    Nag,
+    
+    /// Print the todos due today or overdue, grouped by priority
+    Remind {
+        /// Email them instead (see email in the config); sends nothing when none are due
+        #[cfg(feature = "email")]
+        #[arg(long)]
+        email: bool,
+    },
+    
+    /// Post today's due and overdue todos to Slack or Discord (see digest in the config)
+    Digest {
+        /// Where to post
+        #[arg(short, long, value_enum, default_value_t = DigestChannel::Slack)]
+        channel: DigestChannel,
+        
+        /// Print the message instead of posting it
+        #[arg(long)]
+        print: bool,
+    },
+    
+    /// Fire reminders from a background process
+    Daemon {
+        #[command(subcommand)]
+        action: Option<DaemonAction>,
+        
+        /// Run in the foreground instead of starting a background process
+        #[arg(long)]
+        foreground: bool,
+    },
+    
+    /// Check the database for consistency problems
+    Doctor {
+        /// Check for duplicate or missing IDs and UUIDs
+        #[arg(long)]
+        ids: bool,
+        
+        /// Repair the problems found
+        #[arg(long)]
+        fix: bool,
+        
+        /// With --fix, print the repairs without saving them
+        #[arg(long, requires = "fix")]
+        dry_run: bool,
+    },
+    
+    /// Hide a todo from default views until a date
+    Hide {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// When the todo resurfaces (e.g. next-week, next-month, +3d, or YYYY-MM-DD)
+        #[arg(short, long, value_parser = parse_date_input)]
+        until: DateInput,
+    },
+    
+    /// Show a hidden todo again
+    Unhide {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+    },
+    
+    /// Keep a todo at the top of every list, whatever the sort
+    Pin {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+    },
+    
+    /// Let a pinned todo take its place in the sort order again
+    Unpin {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+    },
+    
+    /// Move a todo to another list, keeping its UUID, or to another place in the manual order
+    Move {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// Name of the destination list
+        #[arg(long, required_unless_present_any = ["before", "after"], conflicts_with_all = ["before", "after"])]
+        to: Option<String>,
+        
+        /// Put the todo just before this one in the manual order (`list --sort manual`)
+        #[arg(long, value_name = "ID", conflicts_with = "after")]
+        before: Option<String>,
+        
+        /// Put the todo just after this one in the manual order
+        #[arg(long, value_name = "ID")]
+        after: Option<String>,
+    },
+    
+    /// Copy a todo to another list under a new UUID
+    Copy {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// Name of the destination list
+        #[arg(long)]
+        to: String,
+    },
+    
+    /// Write todos in another format, e.g. a Markdown checklist
+    Export {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+        
+        /// Group todos under headings
+        #[arg(short, long, value_enum, default_value_t = GroupBy::Project)]
+        group_by: GroupBy,
+        
+        #[command(flatten)]
+        filter: FilterArgs,
+        
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+        
+        /// Stores to export after the todos, each under its own heading, e.g. archive,trash
+        #[arg(long, value_enum, value_delimiter = ',')]
+        include: Vec<ExtraStore>,
+    },
+    
+    /// Add todos from another format, e.g. a Markdown checklist
+    Import {
+        /// File to read, or - for stdin
+        #[cfg_attr(not(feature = "share"), arg(required = true))]
+        #[cfg_attr(feature = "share", arg(required_unless_present = "from_url", conflicts_with = "from_url"))]
+        path: Option<PathBuf>,
+        
+        /// Download the checklist from a link made by `share`, or a calendar feed, instead
+        #[cfg(feature = "share")]
+        #[arg(long, value_name = "URL")]
+        from_url: Option<String>,
+        
+        /// Input format
+        #[arg(long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+        
+        /// Title for calendar entries, with {summary}, {location}, {categories},
+        /// and {calendar} filled in, e.g. "{calendar}: {summary}"
+        #[arg(long, value_name = "TEMPLATE")]
+        title: Option<String>,
+        
+        /// Replace every todo with the imported ones, after a backup
+        #[arg(long)]
+        replace: bool,
+        
+        /// Print what would change without saving anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    
+    /// Make a todo depend on another
+    Block {
+        /// ID, UUID prefix, or title of the todo that has to wait
+        id: String,
+        
+        /// ID, UUID prefix, or title of the todo it waits on
+        #[arg(long)]
+        on: String,
+    },
+    
+    /// Remove a todo's dependencies
+    Unblock {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// Only remove the dependency on this todo
+        #[arg(long)]
+        on: Option<String>,
+    },
+    
+    /// Start tracking time on a todo and mark it in progress, stopping any running timer
+    Start {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+    },
+    
+    /// Stop the running timer
+    Stop,
+    
+    /// Show the todos to work on next
+    Next {
+        /// How many todos to show
+        #[arg(short = 'n', long, default_value_t = 1)]
+        count: usize,
+        
+        /// Rotate through projects instead of following the ranking strictly
+        #[arg(long, value_enum)]
+        spread: Option<Spread>,
+        
+        /// Include todos hidden with `hide` or scheduled for a later day
+        #[arg(long)]
+        all: bool,
+    },
+    
+    /// Work on a todo in timed work/break intervals
+    Pomodoro {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// Stop after this many work intervals (runs until Ctrl-C otherwise)
+        #[arg(short, long)]
+        cycles: Option<u32>,
+    },
+    
+    /// Sum the estimates of open todos per day, by due date, flagging overcommitted days
+    Workload {
+        /// How many days to show, starting today (at most 366)
+        #[arg(short, long, default_value_t = 14, value_parser = clap::value_parser!(u32).range(1..=366))]
+        days: u32,
+        
+        /// Work that fits in a day (defaults to workload.capacity in the config, or 8h)
+        #[arg(short, long)]
+        capacity: Option<HumanDuration>,
+    },
+    
+    /// Summarize tracked data
+    Report {
+        #[command(subcommand)]
+        report: ReportKind,
+    },
+    
+    /// Replay changes left by an interrupted save
+    Recover {
+        /// Drop the pending changes instead
+        #[arg(long)]
+        discard: bool,
+    },
+    
+    /// Any other command runs the todo-NAME plugin on PATH with the arguments after it
+    #[command(external_subcommand)]
+    Plugin(Vec<String>),
 }
 
-/// Available sort orders for listing todos
-#[derive(ValueEnum, Clone, Debug)]
-pub enum SortOrder {
+impl Commands {
+    /// Returns true if the command was given `--dry-run`, so its saves are staged and reported
+    pub fn dry_run(&self) -> bool {
+        match self {
+            Commands::Done { dry_run, .. }
+            | Commands::Remove { dry_run, .. }
+            | Commands::Clear { dry_run, .. }
+            | Commands::Merge { dry_run, .. }
+            | Commands::Doctor { dry_run, .. }
+            | Commands::Import { dry_run, .. } => *dry_run,
+            _ => false,
+        }
+    }
+}
+
+/// Fields todos can be sorted by
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortField {
     /// Smart sorting (incomplete first, then by priority, due date, and creation time)
     Smart,
     
-    /// Sort by due date (earliest first)
+    /// Sort by status (in progress, to do, waiting, done, then cancelled)
+    Status,
+    
+    /// Sort by due date (earliest first, undated todos last either way)
     Due,
     
-    /// Sort by priority (highest first)
+    /// Sort by priority (lowest first when ascending)
     Priority,
     
     /// Sort by creation time (oldest first)
     Created,
+    
+    /// Sort by when a save last changed the todo (most recent first)
+    Modified,
+    
+    /// Sort by title, ignoring case
+    Title,
+    
+    /// Sort by urgency score (most urgent first)
+    Urgency,
+    
+    /// Sort in the order set with `move --before` and `--after`
+    Manual,
+}
+
+/// One field of a sort order and its direction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+/// Sort keys applied in turn, each breaking ties left by the ones before
+///
+/// Parsed from specs like `priority,due:desc`. Todos equal on every key
+/// keep the smart order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortOrder {
+    pub keys: Vec<SortKey>,
+}
+
+impl SortOrder {
+    /// Sorts by one field in its usual direction
+    pub fn by(field: SortField) -> Self {
+        let descending = matches!(field, SortField::Priority | SortField::Modified | SortField::Urgency);
+        Self { keys: vec![SortKey { field, descending }] }
+    }
+
+    /// Flips the direction of every key
+    pub fn reversed(mut self) -> Self {
+        for key in &mut self.keys {
+            key.descending = !key.descending;
+        }
+        self
+    }
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        Self::by(SortField::Smart)
+    }
+}
+
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut keys = Vec::new();
+        for spec in s.split(',').map(str::trim) {
+            let (name, direction) = match spec.split_once(':') {
+                Some((name, direction)) => (name, Some(direction)),
+                None => (spec, None),
+            };
+            let field = <SortField as ValueEnum>::from_str(name, true).map_err(|_| {
+                format!("Unknown sort key '{}'. Use smart, status, due, priority, created, modified, title, urgency, or manual", name)
+            })?;
+            let mut key = Self::by(field).keys[0];
+            match direction.map(str::to_lowercase).as_deref() {
+                None => {}
+                Some("asc") => key.descending = false,
+                Some("desc") => key.descending = true,
+                Some(other) => return Err(format!("Unknown sort direction '{}'. Use asc or desc", other)),
+            }
+            keys.push(key);
+        }
+        Ok(Self { keys })
+    }
+}
+
+/// Ways `next` can spread its picks
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Spread {
+    /// Take the top todo of each project in turn
+    Projects,
+}
+
+/// Shells `prompt-segment` can format color codes for
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PromptShell {
+    /// Leave color codes as they are, e.g. for starship
+    Plain,
+    
+    /// Wrap color codes in `\[` and `\]`
+    Bash,
+    
+    /// Wrap color codes in `%{` and `%}`
+    Zsh,
+}
+
+/// Chat services `digest` can post to
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DigestChannel {
+    /// A Slack incoming webhook, from digest.slack_url
+    Slack,
+    
+    /// A Discord webhook, from digest.discord_url
+    Discord,
+}
+
+impl DigestChannel {
+    /// Returns the service's name
+    pub fn name(self) -> &'static str {
+        match self {
+            DigestChannel::Slack => "Slack",
+            DigestChannel::Discord => "Discord",
+        }
+    }
+
+    /// Marks `text` up as bold in the service's message format
+    pub fn bold(self, text: &str) -> String {
+        match self {
+            DigestChannel::Slack => format!("*{}*", text),
+            DigestChannel::Discord => format!("**{}**", text),
+        }
+    }
+}
+
+/// What to do with the trash
+#[derive(Subcommand)]
+pub enum TrashAction {
+    /// List removed todos
+    List,
+    
+    /// Put a removed todo back on the list
+    Restore {
+        /// ID the todo had, or a prefix of its UUID
+        id: String,
+    },
+    
+    /// Permanently delete removed todos
+    Empty {
+        /// Only delete todos removed longer ago than this, e.g. 30d
+        #[arg(long)]
+        older_than: Option<HumanDuration>,
+        
+        #[command(flatten)]
+        confirm: ConfirmArgs,
+    },
+}
+
+/// GitHub issue commands
+#[cfg(feature = "github")]
+#[derive(Subcommand)]
+pub enum GithubAction {
+    /// Add todos for open issues assigned to you
+    Pull {
+        /// Repository as owner/name (defaults to github.repo in the config)
+        #[arg(short, long)]
+        repo: Option<String>,
+    },
+    
+    /// Close the issue linked to a todo, marking the todo done
+    Close {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+    },
+}
+
+/// Feed subscription commands
+#[cfg(feature = "feeds")]
+#[derive(Subcommand)]
+pub enum FeedAction {
+    /// Subscribe to an iCalendar or JSON Feed URL
+    Add {
+        /// Address of the feed (https://, http://, or webcal://)
+        url: String,
+        
+        /// Title for calendar entries, with {summary}, {location}, {categories},
+        /// and {calendar} filled in, e.g. "{calendar}: {summary}"
+        #[arg(long, value_name = "TEMPLATE")]
+        title: Option<String>,
+        
+        /// Sync the feed at most this often, e.g. 30m or 1d (1h by default)
+        #[arg(long)]
+        every: Option<HumanDuration>,
+    },
+    
+    /// List the feeds subscribed to
+    List,
+    
+    /// Unsubscribe from a feed, keeping its todos
+    Remove {
+        /// URL of the feed, or its number in `feed list`
+        feed: String,
+    },
+    
+    /// Add todos for new feed items, update changed ones, and close those that are gone
+    Sync {
+        /// Sync every feed, even those synced recently
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Jira issue commands
+#[cfg(feature = "jira")]
+#[derive(Subcommand)]
+pub enum JiraAction {
+    /// Add todos for unresolved issues assigned to you, refreshing those already added
+    Pull,
+    
+    /// Move the issue linked to a todo to a done status, marking the todo done
+    Done {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+    },
+}
+
+/// Template commands
+#[derive(Subcommand)]
+pub enum TemplateAction {
+    /// Save a todo's priority, tags, notes, project, and reminders under a name
+    Save {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// Name to save the template as
+        name: String,
+    },
+    
+    /// List saved templates
+    List,
+}
+
+/// Checklist commands
+#[derive(Subcommand)]
+pub enum CheckAction {
+    /// Add an item to the end of a todo's checklist
+    Add {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// What the item says, e.g. "buy tickets"
+        text: String,
+    },
+    
+    /// Tick off an item on a todo's checklist
+    Done {
+        /// ID, UUID prefix, or title of the todo
+        id: String,
+        
+        /// Which item to tick off, as numbered by `show`
+        number: usize,
+    },
+}
+
+/// Focus list commands
+#[derive(Subcommand)]
+pub enum FocusAction {
+    /// Put todos on the focus list
+    Add {
+        /// IDs, UUID prefixes, or titles of the todos
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    
+    /// Take todos off the focus list
+    Remove {
+        /// IDs, UUID prefixes, or titles of the todos
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    
+    /// Show the open todos on the focus list
+    Show,
+}
+
+/// Alias commands
+#[derive(Subcommand)]
+pub enum AliasAction {
+    /// Define a command, replacing any alias with that name
+    Add {
+        /// Name to run it by, e.g. today
+        name: String,
+        
+        /// What it stands for, e.g. "list --due today --sort priority"
+        expansion: String,
+    },
+    
+    /// List the aliases and what they stand for
+    List,
+    
+    /// Remove an alias
+    Remove {
+        /// Name of the alias
+        name: String,
+    },
+}
+
+/// Daemon controls besides starting it
+#[derive(Subcommand)]
+pub enum DaemonAction {
+    /// Stop the running daemon
+    Stop,
+    
+    /// Show whether the daemon is running
+    Status,
+}
+
+/// Available reports
+#[derive(Subcommand)]
+pub enum ReportKind {
+    /// Time tracked per todo, by day or week
+    Time {
+        /// Group entries by day or by week
+        #[arg(short, long, value_enum, default_value_t = ReportPeriod::Day)]
+        by: ReportPeriod,
+        
+        /// Include entries started on or after this date (defaults to the last 7 days or 4 weeks)
+        #[arg(short, long, value_parser = parse_date_input)]
+        since: Option<DateInput>,
+    },
+    
+    /// Todos completed in a date range, for standups and weekly reviews
+    Done {
+        /// Group todos by the day or week they were completed, or by project
+        #[arg(short, long, value_enum, default_value_t = DoneGrouping::Day)]
+        by: DoneGrouping,
+        
+        /// First day to include: a date, today, yesterday, a weekday (the last one), or -3d / -2w
+        #[arg(short, long, value_parser = parse_past_date, default_value = "-6d", allow_hyphen_values = true)]
+        since: NaiveDate,
+        
+        /// Last day to include, given the same way (defaults to today)
+        #[arg(short, long, value_parser = parse_past_date, allow_hyphen_values = true)]
+        until: Option<NaiveDate>,
+    },
+}
+
+/// Periods a report can be grouped by
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ReportPeriod {
+    /// One group per day
+    Day,
+    
+    /// One group per week, starting on the configured first day
+    Week,
+}
+
+/// Ways to group the todos in a completion report
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DoneGrouping {
+    /// One group per day
+    Day,
+    
+    /// One group per week, starting on the configured first day
+    Week,
+    
+    /// One group per project, todos without one last
+    Project,
+}
+
+/// Whether to ask before a destructive command goes ahead
+///
+/// Either flag overrides the command's setting under `confirm` in the
+/// config.
+#[derive(Args, Debug, Clone, Copy, Default)]
+pub struct ConfirmArgs {
+    /// Go ahead without asking
+    #[arg(short, long, conflicts_with = "confirm")]
+    pub yes: bool,
+    
+    /// Ask before going ahead, even if the config says not to
+    #[arg(long)]
+    pub confirm: bool,
+}
+
+impl ConfirmArgs {
+    /// Returns whether to ask, given the configured default for the command
+    pub fn should_ask(self, configured: bool) -> bool {
+        !self.yes && (self.confirm || configured)
+    }
+}
+
+/// Options for selecting todos, shared by commands that filter
+#[derive(Args, Debug, Clone, Default)]
+pub struct FilterArgs {
+    /// Show only active (incomplete) todos
+    #[arg(short, long)]
+    pub active: bool,
+    
+    /// Filter by minimum priority
+    #[arg(short, long)]
+    pub priority: Option<Priority>,
+    
+    /// Show only todos due today, within the next 7 days, or this calendar week
+    #[arg(long, value_enum)]
+    pub due: Option<DueWindow>,
+    
+    /// Show only incomplete todos past their due date
+    #[arg(long)]
+    pub overdue: bool,
+    
+    /// Show only todos due before this date (today, tomorrow, or YYYY-MM-DD)
+    #[arg(long, value_parser = parse_date_input)]
+    pub due_before: Option<DateInput>,
+    
+    /// Show only todos due after this date (today, tomorrow, or YYYY-MM-DD)
+    #[arg(long, value_parser = parse_date_input)]
+    pub due_after: Option<DateInput>,
+    
+    /// Show only todos matching a query, e.g. "priority>=high and due<=+3d and tag:work"
+    #[arg(long = "where", value_name = "EXPR")]
+    pub query: Option<Query>,
+    
+    /// Show only todos in this project
+    #[arg(long)]
+    pub project: Option<String>,
+    
+    /// Show only todos assigned to this person, or `me`
+    #[arg(long)]
+    pub assignee: Option<String>,
+    
+    /// Show only todos in these statuses, e.g. `--status in-progress,waiting`
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub status: Vec<Status>,
+    
+    /// Show only todos on the focus list
+    #[arg(long)]
+    pub focus: bool,
+}
+
+impl FilterArgs {
+    /// Converts the options into a filter
+    pub fn to_filter(&self) -> Filter {
+        Filter {
+            active_only: self.active,
+            min_priority: self.priority,
+            due: self.due,
+            overdue: self.overdue,
+            due_before: self.due_before.map(DateInput::to_date),
+            due_after: self.due_after.map(DateInput::to_date),
+            query: self.query.clone(),
+            completed_since: None,
+            visible_only: false,
+            project: self.project.clone(),
+            assignee: self.assignee.clone(),
+            statuses: self.status.clone(),
+            focused: self.focus,
+        }
+    }
 }
 
 /// Parse a date string into a DateInput enum
+///
+/// Besides `today`, `tomorrow`, and `YYYY-MM-DD`, accepts `next week` (the
+/// first day of it), `end of week`, `next-month`, offsets such as `+3d` or
+/// `+2w`, and weekday names for the next such day (today if it is one).
+/// Words may be joined by spaces or dashes.
 pub fn parse_date_input(s: &str) -> Result<DateInput, String> {
-    match s.to_lowercase().as_str() {
+    let lower = s.trim().to_lowercase().split_whitespace().collect::<Vec<_>>().join("-");
+    match lower.as_str() {
         "today" => Ok(DateInput::Today),
         "tomorrow" => Ok(DateInput::Tomorrow),
+        "next-week" => Ok(DateInput::NextWeek),
+        "end-of-week" | "eow" => Ok(DateInput::EndOfWeek),
+        "next-month" => Ok(DateInput::NextMonth),
         _ => {
+            if let Some(offset) = lower.strip_prefix('+') {
+                let days = match offset.strip_suffix('w') {
+                    Some(weeks) => weeks.parse::<i64>().ok().and_then(|w| w.checked_mul(7)),
+                    None => offset.strip_suffix('d').unwrap_or(offset).parse::<i64>().ok(),
+                };
+                let today = chrono::Local::now().date_naive();
+                return days
+                    .filter(|&days| chrono::Duration::try_days(days).and_then(|d| today.checked_add_signed(d)).is_some())
+                    .map(DateInput::InDays)
+                    .ok_or_else(|| format!("Invalid date offset '{}'. Use e.g. '+3d' or '+2w'", s));
+            }
+            
+            if let Ok(weekday) = lower.parse::<chrono::Weekday>() {
+                let today = chrono::Local::now().date_naive().weekday();
+                let ahead = (7 + weekday.num_days_from_monday() - today.num_days_from_monday()) % 7;
+                return Ok(DateInput::InDays(ahead as i64));
+            }
+            
             NaiveDate::parse_from_str(s, "%Y-%m-%d")
                 .map(DateInput::Date)
-                .map_err(|_| format!("Invalid date format. Use 'today', 'tomorrow', or 'YYYY-MM-DD'"))
+                .map_err(|_| "Invalid date format. Use 'today', 'tomorrow', a weekday like 'friday', 'next week', 'end of week', 'next-month', '+3d', or 'YYYY-MM-DD'".to_string())
         }
     }
 }
 
+/// Parses a day at or before today, for the start or end of a report
+///
+/// Accepts a YYYY-MM-DD date, `today`, `yesterday`, a weekday name for the
+/// last such day (today if it is one), or an offset back like `-3d` or `-2w`.
+pub fn parse_past_date(s: &str) -> Result<NaiveDate, String> {
+    let today = chrono::Local::now().date_naive();
+    let lower = s.to_lowercase();
+    match lower.as_str() {
+        "today" => return Ok(today),
+        "yesterday" => return Ok(today - chrono::Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(weekday) = lower.parse::<chrono::Weekday>() {
+        let back = (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        return Ok(today - chrono::Duration::days(back as i64));
+    }
+    if let Some(offset) = lower.strip_prefix('-') {
+        let days = match offset.strip_suffix('w') {
+            Some(weeks) => weeks.parse::<i64>().ok().and_then(|w| w.checked_mul(7)),
+            None => offset.strip_suffix('d').unwrap_or(offset).parse::<i64>().ok(),
+        };
+        return days
+            .and_then(chrono::Duration::try_days)
+            .and_then(|days| today.checked_sub_signed(days))
+            .ok_or_else(|| format!("Invalid date offset '{}'. Use e.g. '-3d' or '-2w'", s));
+    }
+
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| "Invalid date. Use 'today', 'yesterday', a weekday like 'monday', '-3d', or 'YYYY-MM-DD'".to_string())
+}
+
 /// Parse command line arguments
+///
+/// Sets the configured priority levels first, since arguments may name
+/// them, and expands any alias in place of the command.
 pub fn parse() -> Cli {
-    Cli::parse()
+    crate::models::set_priority_scale(crate::config::load_priority_scale());
+    let args = crate::aliases::expand(std::env::args_os().collect(), &crate::aliases::load_aliases())
+        .unwrap_or_else(|message| Cli::command().error(clap::error::ErrorKind::InvalidValue, message).exit());
+    Cli::parse_from(args)
 }
@@ -1,45 +1,115 @@
 use crate::{
-    models::{DateInput, Todo},
-    storage::{load_todos, save_todos},
-    display::display_todos,
+    error::TodoError,
+    i18n::format_date,
+    journal::Operation,
+    models::{Attachment, TodoBuilder},
+    storage::TodoStore,
+    display::display_updated,
+    tokens,
+    t,
 };
 use anyhow::Result;
-use chrono::{Local, Duration};
 
-/// Adds a new todo with the given title, due date, and priority
-pub fn add_todo(title: String, due: Option<DateInput>, priority: crate::models::Priority) -> Result<()> {
-    let mut todos = load_todos()?;
+/// Adds a new todo built from the given builder
+pub fn add_todo(store: &mut dyn TodoStore, builder: TodoBuilder) -> Result<()> {
+    let mut todos = store.load()?;
     
     // Generate a new ID (max ID + 1)
     let new_id = todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
     
-    // Convert DateInput to NaiveDate if needed
-    let due_date = due.and_then(|d| match d {
-        DateInput::Today => Some(Local::now().date_naive()),
-        DateInput::Tomorrow => Some(Local::now().date_naive() + Duration::days(1)),
-        DateInput::Date(date) => Some(date),
-    });
-    
     // Create the todo with all fields
-    let todo = Todo::new(new_id, title, due_date, priority);
+    let todo = builder.build(new_id)?;
+    let due_date = todo.due_date;
     
     // Clone values needed for the success message before moving todo
     let title_clone = todo.title.clone();
     let priority_clone = todo.priority;
+    let uuid = todo.uuid.clone();
     
     todos.push(todo);
-    save_todos(&todos)?;
+    store.save(&todos)?;
+    if let Some(added) = todos.last() {
+        store.record(Operation::Added, added)?;
+    }
     
     // Display success message with appropriate formatting
     match due_date {
-        Some(date) => println!("Added todo #{} '{}' (Priority: {}, Due: {})", 
-            new_id, title_clone, priority_clone, date.format("%Y-%m-%d")),
-        None => println!("Added todo #{} '{}' (Priority: {})", 
-            new_id, title_clone, priority_clone),
+        Some(date) => println!("{}", t!("add.added_due",
+            id = new_id, title = title_clone, priority = priority_clone, due = format_date(date))),
+        None => println!("{}", t!("add.added",
+            id = new_id, title = title_clone, priority = priority_clone)),
+    }
+    println!("{}", t!("add.uuid", uuid = uuid));
+    
+    // Show the updated list
+    display_updated(&todos);
+    
+    Ok(())
+}
+
+/// Fills in `builder` from copied text, e.g. an email or chat message
+///
+/// The first non-blank line becomes the title and the rest the notes. Web
+/// links anywhere in the text are attached.
+pub fn clipboard_builder(text: &str, builder: TodoBuilder) -> Result<TodoBuilder> {
+    let text = text.trim();
+    let (title, notes) = text.split_once('\n').unwrap_or((text, ""));
+    if title.trim().is_empty() {
+        return Err(TodoError::Validation("The clipboard is empty".to_string()).into());
+    }
+
+    let mut builder = builder.title(title.trim()).attachments(Attachment::links_in(text));
+    if !notes.trim().is_empty() {
+        builder = builder.notes(notes.trim());
+    }
+    Ok(builder)
+}
+
+/// Adds a todo from one line of free text, such as `Submit report !high @work #finance due:friday`
+///
+/// The priority, project, tags, and due date are taken from the tokens
+/// described in [`crate::tokens`] and override those in `base`.
+pub fn capture_todo(store: &mut dyn TodoStore, text: &str, base: TodoBuilder) -> Result<()> {
+    let tokens = tokens::parse(text).map_err(TodoError::Validation)?;
+    add_todo(store, tokens.apply(base))
+}
+
+/// Adds one todo per line of `input`, all in a single save
+///
+/// Each line is a title with optional tokens, as for [`capture_todo`],
+/// which override the defaults in `base`. Blank lines and lines
+/// starting with `#` are skipped. Nothing is added if any line is invalid.
+pub fn add_batch(store: &mut dyn TodoStore, input: &str, base: TodoBuilder) -> Result<()> {
+    let mut todos = store.load()?;
+    let existing = todos.len();
+    let mut next_id = todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+
+    for (number, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let todo = tokens::parse(line)
+            .map_err(anyhow::Error::msg)
+            .and_then(|tokens| tokens.apply(base.clone()).build(next_id))
+            .map_err(|e| TodoError::Validation(format!("Line {}: {}", number + 1, e)))?;
+        todos.push(todo);
+        next_id += 1;
+    }
+
+    if todos.len() == existing {
+        println!("{}", t!("add.nothing"));
+        return Ok(());
+    }
+
+    store.save(&todos)?;
+    for todo in &todos[existing..] {
+        store.record(Operation::Added, todo)?;
+        println!("{}", t!("add.added", id = todo.id, title = todo.title, priority = todo.priority));
     }
     
     // Show the updated list
-    display_todos(&todos);
+    display_updated(&todos);
     
     Ok(())
 }
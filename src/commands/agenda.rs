@@ -0,0 +1,25 @@
+use crate::{
+    calendar::end_of_week,
+    cli::SortOrder,
+    deps::blocked_uuids,
+    storage::TodoStore,
+    display::render_agenda,
+};
+use chrono::{Duration, Local};
+use super::{list::sort_todos, CommandResult};
+
+/// Shows open todos day by day through the end of the `weeks`th calendar week, with overdue ones first
+///
+/// The first week is the current one, from today to its last day.
+pub fn show_agenda(store: &mut dyn TodoStore, weeks: u32) -> CommandResult {
+    let mut todos = store.load()?;
+    let blocked = blocked_uuids(&todos);
+    let today = Local::now().date_naive();
+    todos.retain(|t| !t.is_hidden_on(today));
+    sort_todos(&mut todos, &SortOrder::default(), &blocked);
+
+    let end = end_of_week(today) + Duration::weeks(i64::from(weeks.max(1)) - 1);
+    print!("{}", render_agenda(&todos, today, (end - today).num_days() + 1, &blocked));
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use crate::{
+    aliases::{is_builtin, load_aliases, resolve, save_aliases, split_words},
+    config::config_path,
+    error::TodoError,
+    t,
+};
+use super::CommandResult;
+
+/// Defines the alias `name`, replacing any with that name
+///
+/// Refuses names of built-in commands and names that aren't single
+/// words, and aliases that would lead back to themselves.
+pub fn add_alias(name: &str, expansion: &str) -> CommandResult {
+    let name = name.trim();
+    if name.is_empty() || name.starts_with('-') || name.contains(char::is_whitespace) {
+        return Err(TodoError::Validation(format!("Invalid alias name '{}': use a single word", name)).into());
+    }
+    if is_builtin(name) {
+        return Err(TodoError::Validation(format!("'{}' is a built-in command and can't be an alias", name)).into());
+    }
+    let words = split_words(expansion).map_err(TodoError::Validation)?;
+    if words.is_empty() {
+        return Err(TodoError::Validation("An alias has to stand for a command".to_string()).into());
+    }
+
+    let mut aliases = load_aliases();
+    let replaced = aliases.insert(name.to_string(), expansion.trim().to_string()).is_some();
+    resolve(name, &aliases).map_err(TodoError::Validation)?;
+    save_aliases(&aliases)?;
+
+    let expansion = expansion.trim();
+    if replaced {
+        println!("{}", t!("alias.updated", name = name, expansion = expansion));
+    } else {
+        println!("{}", t!("alias.added", name = name, expansion = expansion));
+    }
+    Ok(())
+}
+
+/// Lists the aliases and what they stand for
+pub fn list_aliases() -> CommandResult {
+    let aliases = load_aliases();
+    if aliases.is_empty() {
+        println!("{}", t!("alias.none", path = config_path().display()));
+        return Ok(());
+    }
+
+    for (name, expansion) in &aliases {
+        println!("{:<16} {}", name, expansion);
+    }
+    Ok(())
+}
+
+/// Removes the alias `name`
+pub fn remove_alias(name: &str) -> CommandResult {
+    let mut aliases = load_aliases();
+    let Some(expansion) = aliases.remove(name) else {
+        return Err(TodoError::Validation(format!("No alias named '{}'", name)).into());
+    };
+    save_aliases(&aliases)?;
+
+    println!("{}", t!("alias.removed", name = name, expansion = expansion));
+    Ok(())
+}
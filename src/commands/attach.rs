@@ -0,0 +1,121 @@
+use crate::{
+    error::TodoError,
+    models::{is_web_url, Attachment, AttachmentKind},
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use anyhow::{anyhow, Context};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use super::{resolve::resolve_index, CommandResult};
+
+/// Attaches a URL or file path to a todo
+///
+/// The kind is detected from `target`. Relative paths are made absolute so
+/// the attachment still opens from another directory; the file doesn't
+/// have to exist yet.
+pub fn attach(store: &mut dyn TodoStore, target: &str, link: &str) -> CommandResult {
+    let mut attachment = Attachment::detect(link);
+    if attachment.target.is_empty() {
+        return Err(TodoError::Validation("Nothing to attach".to_string()).into());
+    }
+    if attachment.kind == AttachmentKind::File {
+        let path = Path::new(&attachment.target);
+        if path.is_relative() {
+            attachment.target = std::env::current_dir()?.join(path).display().to_string();
+        }
+    }
+
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    let id = todo.id;
+    if todo.attachments.contains(&attachment) {
+        println!("{}", t!("attach.already", id = id, target = attachment.target));
+        return Ok(());
+    }
+
+    println!("{}", t!("attach.attached", kind = attachment.kind, target = attachment.target, id = id, title = todo.title));
+    todo.attachments.push(attachment);
+    store.save(&todos)?;
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
+
+/// Opens `target` with the desktop's handler for it
+///
+/// Uses `open` on macOS, the URL handler through `rundll32` on Windows,
+/// which unlike `cmd /C start` doesn't read `&` and the like as commands,
+/// and `xdg-open` elsewhere.
+fn open_with_system(target: &str) -> CommandResult {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = command
+        .arg(target)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .with_context(|| "Failed to run the system opener; is xdg-open installed?")?;
+    if !status.success() {
+        return Err(anyhow!("The system opener could not open {}", target));
+    }
+    Ok(())
+}
+
+/// Opens attachment `number` (counting from 1) of a todo, or without a
+/// number the page of the item it came from in another system
+///
+/// Todos with no such page open their first attachment.
+pub fn open_todo(store: &mut dyn TodoStore, target: &str, number: Option<usize>) -> CommandResult {
+    let todos = store.load()?;
+    let todo = &todos[resolve_index(&todos, target)?];
+    if let (None, Some(external)) = (number, &todo.external_ref) {
+        match &external.url {
+            Some(url) if !is_web_url(url) => {
+                return Err(TodoError::Validation(format!(
+                    "Todo #{} came from {}, which isn't an http or https page; not opening it", todo.id, url
+                )).into());
+            }
+            Some(url) => {
+                open_with_system(url)?;
+                println!("{}", t!("open.source", kind = external.kind, id = external.id));
+                return Ok(());
+            }
+            None if todo.attachments.is_empty() => {
+                return Err(TodoError::Validation(format!(
+                    "Todo #{} came from {} {}, which has no page to open", todo.id, external.kind, external.id
+                )).into());
+            }
+            None => {}
+        }
+    }
+    let number = number.unwrap_or(1);
+    if todo.attachments.is_empty() {
+        return Err(TodoError::Validation(format!("Todo #{} has no attachments; add one with `attach`", todo.id)).into());
+    }
+    let attachment = number
+        .checked_sub(1)
+        .and_then(|i| todo.attachments.get(i))
+        .ok_or_else(|| {
+            TodoError::Validation(format!("Todo #{} has {} attachments", todo.id, todo.attachments.len()))
+        })?;
+    if attachment.kind == AttachmentKind::File && !Path::new(&attachment.target).exists() {
+        let source = std::io::Error::from(std::io::ErrorKind::NotFound);
+        return Err(TodoError::storage("open", &attachment.target, source).into());
+    }
+
+    open_with_system(&attachment.target)?;
+    println!("{}", t!("attach.opened", target = attachment.target));
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+use crate::{
+    backup::{backup_before_change, backup_location, list_backups, load_snapshot, write_backup, ExtraStore, Extras},
+    config::Config,
+    error::TodoError,
+    events::{diff, Event, EventKind},
+    journal::Operation,
+    storage::TodoStore,
+    display::display_updated,
+    terminal::confirm,
+    t,
+};
+use anyhow::anyhow;
+use chrono::Local;
+use super::CommandResult;
+
+/// Writes a timestamped snapshot of the todos to `to`, or the backup directory,
+/// with the stores in `include` next to it
+///
+/// Older snapshots beyond `backup.keep` are deleted from the same directory.
+pub fn backup_todos(store: &mut dyn TodoStore, to: Option<&Path>, include: &[ExtraStore]) -> CommandResult {
+    let config = Config::load()?.backup;
+    let location = backup_location(store, &config);
+    let prefix = location.as_ref().map_or("todos".to_string(), |(_, prefix)| prefix.clone());
+    let dir = match (to, location) {
+        (Some(dir), _) => dir.to_path_buf(),
+        (None, Some((dir, _))) => dir,
+        (None, None) => return Err(anyhow!("This database has no backup directory; pass --to")),
+    };
+
+    let backup = write_backup(store, &dir, &prefix, config.keep, include)?;
+    println!("{}", t!("backup.done", count = backup.todos, path = backup.path.display()));
+    backup.extras.print_counts();
+    if !backup.rotated.is_empty() {
+        println!("{}", t!("backup.rotated", count = backup.rotated.len(), keep = config.keep));
+    }
+
+    Ok(())
+}
+
+/// Returns the newest snapshot in the store's backup directory
+fn latest_backup(store: &dyn TodoStore) -> anyhow::Result<PathBuf> {
+    let config = Config::load()?.backup;
+    let (dir, prefix) = backup_location(store, &config)
+        .ok_or_else(|| anyhow!("This database has no backup directory; pass --from"))?;
+    list_backups(&dir, &prefix)?.pop().ok_or_else(|| {
+        TodoError::Validation(format!("No backups in {}; take one with `backup`", dir.display())).into()
+    })
+}
+
+/// Journals the additions, completions, and removals among `changes`
+pub(super) fn record_changes(store: &mut dyn TodoStore, changes: &[Event]) -> anyhow::Result<()> {
+    for event in changes {
+        let (operation, todo) = match (event.kind, &event.after, &event.before) {
+            (EventKind::Added, Some(todo), _) => (Operation::Added, todo),
+            (EventKind::Completed, Some(todo), _) => (Operation::Completed, todo),
+            (EventKind::Removed, _, Some(todo)) => (Operation::Removed, todo),
+            _ => continue,
+        };
+        store.record(operation, todo)?;
+    }
+    Ok(())
+}
+
+/// Replaces the todos with those in the snapshot at `from`, or the newest one
+///
+/// Shows what would change first, and asks before going ahead if `ask` is
+/// set. The current todos are backed up before they're replaced, unless
+/// `backup.before_destructive` is off. The stores in `include` are
+/// replaced with the ones saved next to the snapshot, and backed up first
+/// too. Refused up front for read-only lists, before anything is written.
+pub fn restore_backup(store: &mut dyn TodoStore, from: Option<&Path>, ask: bool, include: &[ExtraStore]) -> CommandResult {
+    if store.is_read_only() {
+        return Err(TodoError::Validation("Can't restore a backup into a list opened read-only".to_string()).into());
+    }
+    let path = match from {
+        Some(path) => path.to_path_buf(),
+        None => latest_backup(store)?,
+    };
+    let snapshot = load_snapshot(&path)?;
+    let extras = Extras::read(&path, include)?;
+    let current = store.load()?;
+    let changes = diff(&current, &snapshot, Local::now());
+    if changes.is_empty() && include.is_empty() {
+        println!("{}", t!("restore.same", path = path.display()));
+        return Ok(());
+    }
+
+    if !changes.is_empty() {
+        println!("{}", t!("restore.preview", path = path.display()));
+    }
+    for event in &changes {
+        println!("  {}", event.describe());
+    }
+    if ask && !confirm(&t!("restore.confirm"))? {
+        println!("{}", t!("common.cancelled"));
+        return Ok(());
+    }
+
+    backup_before_change(store, include)?;
+    store.save(&snapshot)?;
+    record_changes(store, &changes)?;
+    extras.save(store)?;
+    println!("{}", t!("restore.done", count = snapshot.len(), path = path.display()));
+    extras.print_counts();
+
+    // Show the updated list
+    display_updated(&snapshot);
+
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::fs;
+use std::time::{Duration, Instant};
+use crate::{
+    cli::SortOrder,
+    config::Config,
+    deps::blocked_uuids,
+    error::TodoError,
+    models::{priority_scale, Priority, Status, Todo, TodoBuilder},
+    storage::{JsonFileStore, StorageFormat, TodoStore},
+    t,
+};
+use anyhow::Result;
+use chrono::{Duration as Days, Local};
+use super::{list::sort_todos, CommandResult};
+
+const TAGS: [&str; 6] = ["work", "home", "errand", "reading", "health", "money"];
+
+/// Builds `count` todos that look like a well-used list: mixed priorities, tags, due dates, and notes
+fn synthetic_todos(count: usize) -> Result<Vec<Todo>> {
+    let priorities: Vec<Priority> = priority_scale().priorities().collect();
+    let today = Local::now().date_naive();
+    (1..=count)
+        .map(|n| {
+            let mut builder = TodoBuilder::new(format!("Synthetic task {} with a few more words", n))
+                .priority(priorities[n % priorities.len()])
+                .tags(TAGS.iter().copied().skip(n % TAGS.len()).take(n % 3));
+            if n % 4 == 0 {
+                builder = builder.due(today + Days::days((n % 60) as i64 - 20));
+            }
+            if n % 5 == 0 {
+                builder = builder.notes("Some notes about the task, long enough to be realistic.");
+            }
+            let mut todo = builder.build(n as u64)?;
+            if n % 3 == 0 {
+                todo.set_status(Status::Done);
+            }
+            Ok(todo)
+        })
+        .collect()
+}
+
+/// Runs `step` `runs` times and returns the quickest run, with the last result
+fn quickest<T>(runs: usize, mut step: impl FnMut() -> Result<T>) -> Result<(Duration, T)> {
+    let mut best = (Duration::MAX, None);
+    for _ in 0..runs.max(1) {
+        let start = Instant::now();
+        let result = step()?;
+        best = (best.0.min(start.elapsed()), Some(result));
+    }
+    Ok((best.0, best.1.expect("ran at least once")))
+}
+
+/// Times loading, sorting, and saving `count` synthetic todos in a scratch database
+///
+/// The database goes in a temporary directory that is removed afterwards,
+/// so the real one is never read or written. Patch settings come from the
+/// config, as they decide how a single change is saved. Each step reports
+/// its quickest of `runs` runs.
+pub fn bench_storage(count: usize, format: StorageFormat, runs: usize) -> CommandResult {
+    let storage = Config::load()?.storage;
+    let dir = std::env::temp_dir().join(format!("todo-cli-bench-{}", std::process::id()));
+    fs::create_dir_all(&dir).map_err(|e| TodoError::storage("create", &dir, e))?;
+    let path = dir.join("todos.json");
+    let open = || {
+        JsonFileStore::new(&path).patches(storage.patch_above, storage.compact_after).format(format)
+    };
+
+    let result = (|| {
+        let (generate, todos) = quickest(runs, || synthetic_todos(count))?;
+        let (save, ()) = quickest(runs, || open().save(&todos))?;
+        let size = fs::metadata(&path).map_err(|e| TodoError::storage("read", &path, e))?.len();
+        let (load, loaded) = quickest(runs, || open().load())?;
+        let (stream, ()) = quickest(runs, || open().for_each_todo(&mut |_| {}))?;
+        // Sorting starts from the loaded order every time, not the last run's sorted one
+        let blocked = blocked_uuids(&loaded);
+        let mut sort = Duration::MAX;
+        for _ in 0..runs.max(1) {
+            let mut todos = loaded.clone();
+            let start = Instant::now();
+            sort_todos(&mut todos, &SortOrder::default(), &blocked);
+            sort = sort.min(start.elapsed());
+        }
+        // Each run starts from a full save, so every one measures the same kind of write
+        let mut change = Duration::MAX;
+        for run in 0..runs.max(1) {
+            open().save(&todos)?;
+            let mut store = open();
+            let mut todos = store.load()?;
+            let start = Instant::now();
+            if let Some(todo) = todos.get_mut(run % count.max(1)) {
+                todo.set_status(if todo.completed { Status::Todo } else { Status::Done });
+            }
+            store.save(&todos)?;
+            change = change.min(start.elapsed());
+        }
+
+        println!(
+            "{}",
+            t!(
+                "bench.header",
+                count = count,
+                format = format.name(),
+                size = format!("{:.1}", size as f64 / 1_000_000.0),
+                runs = runs.max(1)
+            )
+        );
+        let steps = [
+            ("generate", generate),
+            ("save", save),
+            ("load", load),
+            ("stream", stream),
+            ("sort", sort),
+            ("save one change", change),
+        ];
+        for (step, took) in steps {
+            println!("  {:<16}{:>10.1} ms", step, took.as_secs_f64() * 1000.0);
+        }
+        Ok(())
+    })();
+
+    fs::remove_dir_all(&dir).map_err(|e| TodoError::storage("remove", &dir, e))?;
+    result
+}
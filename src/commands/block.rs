@@ -0,0 +1,71 @@
+use crate::{
+    deps::depends_transitively,
+    error::TodoError,
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use super::{resolve::resolve_index, CommandResult};
+
+/// Makes a todo depend on another, rejecting dependency cycles
+pub fn block_todo(store: &mut dyn TodoStore, target: &str, on: &str) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let dependency = resolve_index(&todos, on)?;
+    let (id, uuid) = (todos[index].id, todos[index].uuid.clone());
+    let (dep_id, dep_uuid) = (todos[dependency].id, todos[dependency].uuid.clone());
+    
+    if index == dependency {
+        return Err(TodoError::Validation(format!("Todo #{} cannot depend on itself", id)).into());
+    }
+    if todos[index].depends_on.contains(&dep_uuid) {
+        println!("{}", t!("block.already", id = id, dependency = dep_id));
+        return Ok(());
+    }
+    if depends_transitively(&todos, &dep_uuid, &uuid) {
+        return Err(TodoError::Validation(format!(
+            "Todo #{} already depends on #{} (directly or through other todos), so #{} cannot depend on #{}",
+            dep_id, id, id, dep_id
+        )).into());
+    }
+    
+    todos[index].depends_on.push(dep_uuid);
+    store.save(&todos)?;
+    println!("{}", t!("block.blocked", id = id, dependency = dep_id, title = todos[dependency].title));
+    
+    // Show the updated list
+    display_updated(&todos);
+    
+    Ok(())
+}
+
+/// Removes a todo's dependency on another, or all of them when `on` is `None`
+pub fn unblock_todo(store: &mut dyn TodoStore, target: &str, on: Option<&str>) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let id = todos[index].id;
+    
+    match on {
+        Some(on) => {
+            let dependency = resolve_index(&todos, on)?;
+            let (dep_id, dep_uuid) = (todos[dependency].id, todos[dependency].uuid.clone());
+            let deps = &mut todos[index].depends_on;
+            if !deps.contains(&dep_uuid) {
+                println!("{}", t!("unblock.not_blocked", id = id, dependency = dep_id));
+                return Ok(());
+            }
+            deps.retain(|d| *d != dep_uuid);
+            println!("{}", t!("unblock.unblocked", id = id, dependency = dep_id));
+        }
+        None => {
+            let count = std::mem::take(&mut todos[index].depends_on).len();
+            println!("{}", t!("unblock.all", count = count, id = id));
+        }
+    }
+    store.save(&todos)?;
+    
+    // Show the updated list
+    display_updated(&todos);
+    
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use crate::{
+    cli::SortOrder,
+    deps::blocked_uuids,
+    filter::Filter,
+    storage::TodoStore,
+    terminal,
+    display::render_board,
+};
+use super::{list::sort_todos, CommandResult};
+
+/// Shows the todos matching `filter` as a board with a column per status
+pub fn show_board(store: &mut dyn TodoStore, filter: &Filter) -> CommandResult {
+    let mut todos = store.load()?;
+    let blocked = blocked_uuids(&todos);
+    filter.apply(&mut todos);
+    sort_todos(&mut todos, &SortOrder::default(), &blocked);
+
+    print!("{}", render_board(&todos, terminal::width().unwrap_or(80), &blocked));
+
+    Ok(())
+}
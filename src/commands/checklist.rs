@@ -0,0 +1,53 @@
+use crate::{
+    error::TodoError,
+    models::ChecklistItem,
+    storage::TodoStore,
+    display::display_updated,
+    t,
+    validate,
+};
+use super::{resolve::resolve_index, CommandResult};
+
+/// Adds an item to the end of a todo's checklist
+pub fn check_add(store: &mut dyn TodoStore, target: &str, text: &str) -> CommandResult {
+    let text = validate::text(text).ok_or_else(|| TodoError::Validation("Checklist item is empty".to_string()))?;
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    todo.checklist.push(ChecklistItem { text, done: false });
+    println!("{}", t!("check.added", number = todo.checklist.len(), id = todo.id, title = todo.title));
+    store.save(&todos)?;
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
+
+/// Ticks off item `number`, counting from 1, on a todo's checklist
+pub fn check_done(store: &mut dyn TodoStore, target: &str, number: usize) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    if todo.checklist.is_empty() {
+        return Err(TodoError::Validation(format!("Todo #{} has no checklist; add to it with `check add`", todo.id)).into());
+    }
+    let (id, count) = (todo.id, todo.checklist.len());
+    let item = number
+        .checked_sub(1)
+        .and_then(|i| todo.checklist.get_mut(i))
+        .ok_or_else(|| TodoError::Validation(format!("Todo #{} has {} checklist items", id, count)))?;
+
+    if item.done {
+        println!("{}", t!("check.already", number = number, id = id));
+        return Ok(());
+    }
+    item.done = true;
+    println!("{}", t!("check.done", number = number, id = id, text = item.text));
+    store.save(&todos)?;
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
@@ -1,28 +1,32 @@
-use std::io::{self, Write};
 use crate::{
-    storage::save_todos,
-    display::display_todos,
+    backup::backup_before_change,
+    journal::Operation,
+    storage::TodoStore,
+    display::display_updated,
+    terminal::confirm,
+    t,
 };
 use super::CommandResult;
 
-/// Clears all todos after confirmation
-pub fn clear_todos() -> CommandResult {
-    // Ask for confirmation
-    print!("Are you sure you want to clear all todos? (y/N): ");
-    io::stdout().flush()?;
-    
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    
-    if input.trim().eq_ignore_ascii_case("y") {
-        save_todos(&[])?;
-        println!("All todos have been cleared.");
+/// Moves all todos to the trash, first asking if `ask` is set
+///
+/// Backs the list up first unless `backup.before_destructive` is off.
+pub fn clear_todos(store: &mut dyn TodoStore, ask: bool) -> CommandResult {
+    if !ask || confirm(&t!("clear.confirm"))? {
+        backup_before_change(store, &[])?;
+        let removed = store.load()?;
+        store.save(&[])?;
+        store.trash(&removed)?;
+        for todo in &removed {
+            store.record(Operation::Removed, todo)?;
+        }
+        println!("{}", t!("clear.done"));
     } else {
-        println!("Operation cancelled.");
+        println!("{}", t!("common.cancelled"));
     }
     
     // Show empty list
-    display_todos(&[]);
+    display_updated(&[]);
     
     Ok(())
 }
@@ -0,0 +1,29 @@
+use crate::{
+    error::TodoError,
+    models::Comment,
+    storage::TodoStore,
+    display::display_updated,
+    t,
+    validate,
+};
+use chrono::Local;
+use super::{resolve::resolve_index, CommandResult};
+
+/// Appends a comment to a todo, timestamped now
+///
+/// Comments may span several lines; control characters are stripped as
+/// they are from notes.
+pub fn add_comment(store: &mut dyn TodoStore, target: &str, text: &str) -> CommandResult {
+    let text = validate::notes(text).ok_or_else(|| TodoError::Validation("Comment is empty".to_string()))?;
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    todo.comments.push(Comment { at: Local::now(), text });
+    println!("{}", t!("comment.added", id = todo.id, title = todo.title));
+    store.save(&todos)?;
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use crate::storage::TodoStore;
+use crate::t;
+use super::CommandResult;
+
+/// Folds the changes appended since the last full save back into the database file
+pub fn compact_todos(store: &mut dyn TodoStore) -> CommandResult {
+    match store.compact()? {
+        0 => println!("{}", t!("compact.nothing")),
+        1 => println!("{}", t!("compact.one")),
+        patches => println!("{}", t!("compact.many", count = patches)),
+    }
+    Ok(())
+}
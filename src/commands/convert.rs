@@ -0,0 +1,16 @@
+use crate::storage::{StorageFormat, TodoStore};
+use crate::t;
+use super::CommandResult;
+
+/// Rewrites the database file in `format`
+///
+/// Later saves keep the new format, whatever `storage.format` says; that
+/// setting only picks the format of new databases.
+pub fn convert_todos(store: &mut dyn TodoStore, format: StorageFormat) -> CommandResult {
+    if store.convert(format)? {
+        println!("{}", t!("convert.done", format = format.name()));
+    } else {
+        println!("{}", t!("convert.already", format = format.name()));
+    }
+    Ok(())
+}
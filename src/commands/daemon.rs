@@ -0,0 +1,144 @@
+use std::fs::{self, OpenOptions};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::SystemTime;
+use crate::{
+    config::{config_dir, Config},
+    models::Todo,
+    notify::reminders_between,
+    storage::TodoStore,
+    t,
+};
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, Local};
+use super::{notify::{fire, last_run, set_last_run}, CommandResult};
+
+const PID_FILE: &str = "daemon.pid";
+const LOG_FILE: &str = "daemon.log";
+
+/// Returns the PID of the running daemon, if any
+fn running_pid() -> Option<u32> {
+    let pid: u32 = fs::read_to_string(config_dir().join(PID_FILE)).ok()?.trim().parse().ok()?;
+    let alive = Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    alive.then_some(pid)
+}
+
+/// Starts the daemon in the background by re-running this command with `--foreground`
+///
+/// Output goes to `daemon.log` in the config directory.
+pub fn start_daemon() -> CommandResult {
+    if let Some(pid) = running_pid() {
+        println!("{}", t!("daemon.already_running", pid = pid));
+        return Ok(());
+    }
+
+    let dir = config_dir();
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let log_path = dir.join(LOG_FILE);
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .with_context(|| format!("Failed to open {}", log_path.display()))?;
+
+    let exe = std::env::current_exe().with_context(|| "Failed to locate the todo-cli executable")?;
+    let child = Command::new(exe)
+        .args(std::env::args_os().skip(1))
+        .arg("--foreground")
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .spawn()
+        .with_context(|| "Failed to start the daemon")?;
+
+    println!("{}", t!("daemon.started", pid = child.id(), path = log_path.display()));
+    Ok(())
+}
+
+/// Stops the running daemon
+pub fn stop_daemon() -> CommandResult {
+    let Some(pid) = running_pid() else {
+        println!("{}", t!("daemon.not_running"));
+        return Ok(());
+    };
+
+    let stopped = Command::new("kill")
+        .arg(pid.to_string())
+        .status()
+        .is_ok_and(|status| status.success());
+    if !stopped {
+        return Err(anyhow!("Failed to stop the daemon (pid {})", pid));
+    }
+
+    let _ = fs::remove_file(config_dir().join(PID_FILE));
+    println!("{}", t!("daemon.stopped", pid = pid));
+    Ok(())
+}
+
+/// Reports whether the daemon is running
+pub fn daemon_status() -> CommandResult {
+    match running_pid() {
+        Some(pid) => println!("{}", t!("daemon.running", pid = pid)),
+        None => println!("{}", t!("daemon.not_running")),
+    }
+    Ok(())
+}
+
+/// Runs the reminder loop in the foreground until killed
+///
+/// Sleeps until the next reminder or `notify.poll_interval`, whichever comes
+/// first, reloading the todos whenever the database changes. Shares the
+/// last-run time with `notify`, so the two never fire the same reminder.
+/// With the `feeds` feature, feeds due for a sync are synced on each pass.
+pub fn run_daemon(store: &mut dyn TodoStore) -> CommandResult {
+    if let Some(pid) = running_pid().filter(|&pid| pid != std::process::id()) {
+        return Err(anyhow!("Daemon is already running (pid {})", pid));
+    }
+    let pid_path = config_dir().join(PID_FILE);
+    if let Some(dir) = pid_path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    fs::write(&pid_path, std::process::id().to_string())
+        .with_context(|| format!("Failed to write to {}", pid_path.display()))?;
+
+    let config = Config::load()?;
+    let poll = config.notify.poll_interval.duration().max(Duration::seconds(1));
+    let mut todos = store.load()?;
+    let mut modified = store.last_modified();
+    let mut from = last_run().unwrap_or_else(Local::now);
+    println!("{}", t!("daemon.watching", time = Local::now().format("%F %T"), count = todos.len()));
+
+    loop {
+        #[cfg(feature = "feeds")]
+        super::feed::sync_due_feeds(store);
+        reload_if_changed(store, &mut todos, &mut modified)?;
+
+        let now = Local::now();
+        fire(&todos, &config, from, now);
+        set_last_run(now)?;
+        from = now;
+
+        let next = reminders_between(&todos, &config.notify, now, now + poll).first().map(|r| r.at);
+        let wait = next.map_or(poll, |at| at - now).max(Duration::seconds(1));
+        thread::sleep(wait.to_std().unwrap_or_default());
+    }
+}
+
+/// Reloads the todos if the database changed since the last look
+fn reload_if_changed(
+    store: &mut dyn TodoStore,
+    todos: &mut Vec<Todo>,
+    modified: &mut Option<SystemTime>,
+) -> Result<()> {
+    let current = store.last_modified();
+    if current != *modified {
+        *todos = store.load()?;
+        *modified = current;
+        println!("{}", t!("daemon.reloaded", time = Local::now().format("%F %T"), count = todos.len()));
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use std::path::Path;
+use crate::{
+    backup::load_snapshot,
+    events::{diff, Event, EventKind},
+    history::field_changes,
+    models::Todo,
+    storage::TodoStore,
+    t,
+};
+use anyhow::Result;
+use chrono::Local;
+use colored::Colorize;
+use serde_json::Value;
+use super::CommandResult;
+
+/// Writes a field's value briefly: text without quotes, `none` for null
+pub(super) fn brief(value: &Value) -> String {
+    match value {
+        Value::Null => "none".to_string(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Lists the fields that differ between two versions of a todo, as `name: old → new`
+///
+/// Fields are named as they are stored. The modification time and author
+/// are left out, since they change with everything else.
+pub fn changed_fields(before: &Todo, after: &Todo) -> Result<Vec<String>> {
+    Ok(field_changes(before, after)?
+        .into_iter()
+        .map(|(name, was, is)| format!("{}: {} → {}", name, brief(&was), brief(&is)))
+        .collect())
+}
+
+/// Prints each change under a heading for its kind, with changed fields under each edit
+fn print_changes(changes: &[Event]) -> Result<()> {
+    let sections = [
+        (t!("diff.added"), "+", vec![EventKind::Added]),
+        (t!("diff.removed"), "-", vec![EventKind::Removed]),
+        (t!("diff.changed"), "~", vec![EventKind::Completed, EventKind::Edited]),
+    ];
+    for (heading, mark, kinds) in sections {
+        let events: Vec<&Event> = changes.iter().filter(|e| kinds.contains(&e.kind)).collect();
+        if events.is_empty() {
+            continue;
+        }
+        println!("{}", heading.bold());
+        for event in events {
+            let title = event.after.as_ref().or(event.before.as_ref()).map_or("", |t| t.title.as_str());
+            let line = format!("  {} #{:<4} {}", mark, event.id, title);
+            println!("{}", match event.kind {
+                EventKind::Added => line.green(),
+                EventKind::Removed => line.red(),
+                _ => line.yellow(),
+            });
+            if let (Some(before), Some(after)) = (&event.before, &event.after) {
+                for field in changed_fields(before, after)? {
+                    println!("      {}", field);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Shows how the todos in snapshot `a` differ from those in `b`, or the database
+///
+/// Todos are matched by UUID, so renumbered todos show as changed rather
+/// than removed and added again.
+pub fn diff_snapshots(store: &mut dyn TodoStore, a: &Path, b: Option<&Path>) -> CommandResult {
+    let before = load_snapshot(a)?;
+    let (after, against) = match b {
+        Some(b) => (load_snapshot(b)?, b.display().to_string()),
+        None => (store.load()?, t!("diff.database")),
+    };
+    let changes = diff(&before, &after, Local::now());
+    if changes.is_empty() {
+        println!("{}", t!("diff.none", old = a.display(), new = against));
+        return Ok(());
+    }
+
+    println!("{}", t!("diff.header", old = a.display(), new = against));
+    print_changes(&changes)?;
+    let count = |kinds: &[EventKind]| changes.iter().filter(|e| kinds.contains(&e.kind)).count();
+    println!(
+        "{}",
+        t!(
+            "diff.summary",
+            added = count(&[EventKind::Added]),
+            removed = count(&[EventKind::Removed]),
+            changed = count(&[EventKind::Completed, EventKind::Edited])
+        )
+    );
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use crate::{
+    cli::{DigestChannel, SortOrder},
+    config::{Config, DigestConfig},
+    deps::blocked_uuids,
+    error::TodoError,
+    i18n::format_day,
+    models::Todo,
+    notify::post_json,
+    storage::TodoStore,
+    t,
+};
+use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveDate};
+use serde_json::json;
+use super::{list::sort_todos, CommandResult};
+
+/// Replaces each `{name}` in `template` with its value from `values`
+///
+/// Unknown or unclosed placeholders are validation errors naming `setting`.
+fn fill(template: &str, setting: &str, values: &[(&str, String)]) -> Result<String> {
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let end = rest[start..].find('}').map(|i| start + i).ok_or_else(|| {
+            TodoError::Validation(format!("Unclosed '{{' in {} '{}'", setting, template))
+        })?;
+        let name = &rest[start + 1..end];
+        let (_, value) = values.iter().find(|(n, _)| *n == name).ok_or_else(|| {
+            let names: Vec<String> = values.iter().map(|(n, _)| format!("{{{}}}", n)).collect();
+            TodoError::Validation(format!("Unknown placeholder '{{{}}}' in {}; use {}", name, setting, names.join(", ")))
+        })?;
+        text.push_str(value);
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+    Ok(text)
+}
+
+/// Formats the digest for `today`, or returns `None` when there's nothing to send
+///
+/// Open todos that are overdue or due today are listed under a heading
+/// each, in the default sort order. With neither, the message is
+/// `digest.empty`, unless that is blank.
+pub fn digest_message(
+    todos: &[Todo],
+    config: &DigestConfig,
+    channel: DigestChannel,
+    today: NaiveDate,
+) -> Result<Option<String>> {
+    let blocked = blocked_uuids(todos);
+    let pick = |due: &dyn Fn(NaiveDate) -> bool| {
+        let mut picked: Vec<Todo> = todos
+            .iter()
+            .filter(|t| !t.completed && t.due_date.is_some_and(due))
+            .cloned()
+            .collect();
+        sort_todos(&mut picked, &SortOrder::default(), &blocked);
+        picked
+    };
+    let overdue = pick(&|d| d < today);
+    let due_today = pick(&|d| d == today);
+
+    if overdue.is_empty() && due_today.is_empty() {
+        return Ok(Some(config.empty.clone()).filter(|m| !m.trim().is_empty()));
+    }
+
+    let header = [
+        ("date", format_day(today)),
+        ("overdue", overdue.len().to_string()),
+        ("due_today", due_today.len().to_string()),
+    ];
+    let mut lines = vec![fill(&config.header, "digest.header", &header)?];
+    for (heading, todos) in [(t!("digest.overdue"), &overdue), (t!("digest.due_today"), &due_today)] {
+        if todos.is_empty() {
+            continue;
+        }
+        lines.push(String::new());
+        lines.push(channel.bold(&heading));
+        for todo in todos {
+            let item = [
+                ("id", todo.id.to_string()),
+                ("title", todo.title.clone()),
+                ("priority", todo.priority.to_string()),
+                ("due", todo.due_date.map(|d| d.to_string()).unwrap_or_default()),
+                ("project", todo.project.clone().unwrap_or_default()),
+            ];
+            lines.push(fill(&config.item, "digest.item", &item)?);
+        }
+    }
+    Ok(Some(lines.join("\n")))
+}
+
+/// Posts today's due and overdue todos to a chat webhook, or prints the message
+///
+/// The webhook URL comes from `digest.slack_url` or `digest.discord_url`.
+/// Meant for a morning cron job.
+pub fn send_digest(store: &mut dyn TodoStore, channel: DigestChannel, print: bool) -> CommandResult {
+    let config = Config::load()?.digest;
+    let todos = store.load()?;
+    let Some(message) = digest_message(&todos, &config, channel, Local::now().date_naive())? else {
+        println!("{}", t!("digest.nothing"));
+        return Ok(());
+    };
+    if print {
+        println!("{}", message);
+        return Ok(());
+    }
+
+    let url = match channel {
+        DigestChannel::Slack => config.slack_url.as_deref(),
+        DigestChannel::Discord => config.discord_url.as_deref(),
+    }
+    .ok_or_else(|| {
+        let name = channel.name();
+        anyhow!("No webhook URL for {}: set digest.{}_url in the config file", name, name.to_lowercase())
+    })?;
+    let body = match channel {
+        DigestChannel::Slack => json!({ "text": message }),
+        DigestChannel::Discord => json!({ "content": message }),
+    };
+    post_json(url, &body.to_string())?;
+    println!("{}", t!("digest.sent", channel = channel.name()));
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use std::collections::{HashMap, HashSet};
+use crate::{
+    archive::other_archive,
+    models::{generate_uuid, Todo},
+    storage::TodoStore,
+    t,
+};
+use super::CommandResult;
+
+/// One of the stores `doctor` checks
+struct Checked<'a> {
+    name: &'static str,
+    todos: &'a mut [Todo],
+    /// Whether IDs must be unique; the trash keeps the IDs todos had when removed
+    unique_ids: bool,
+}
+
+/// Finds duplicate IDs within each store and duplicate UUIDs across all
+/// of them, optionally reassigning them
+///
+/// The first todo holding an ID or UUID keeps it, in the order the stores
+/// are given; later ones get a fresh value. Dependencies only hold within
+/// a list, so when the UUID was first held in another store, the
+/// dependencies on it in this one move to the new UUID. Returns the
+/// number of problems found.
+fn check_ids(stores: &mut [Checked], fix: bool) -> usize {
+    let mut problems = 0;
+    let mut holders: HashMap<String, usize> = HashMap::new();
+
+    for (index, Checked { name: store, todos, unique_ids }) in stores.iter_mut().enumerate() {
+        let store = *store;
+        let mut seen_ids = HashSet::new();
+        let mut next_id = todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+        let mut moved: HashMap<String, String> = HashMap::new();
+
+        for todo in todos.iter_mut() {
+            if *unique_ids && !seen_ids.insert(todo.id) {
+                problems += 1;
+                if fix {
+                    println!("{}", t!("doctor.duplicate_id_fixed", store = store, id = todo.id, title = todo.title, new_id = next_id));
+                    todo.id = next_id;
+                    next_id += 1;
+                } else {
+                    println!("{}", t!("doctor.duplicate_id", store = store, id = todo.id, title = todo.title));
+                }
+            }
+
+            let holder = holders.get(&todo.uuid).copied();
+            if todo.uuid.is_empty() || holder.is_some() {
+                problems += 1;
+                let missing = todo.uuid.is_empty();
+                if fix {
+                    let uuid = generate_uuid();
+                    let message = match missing {
+                        true => t!("doctor.missing_uuid_fixed", store = store, id = todo.id, title = todo.title, uuid = uuid),
+                        false => t!("doctor.duplicate_uuid_fixed", store = store, id = todo.id, title = todo.title, uuid = uuid),
+                    };
+                    println!("{}", message);
+                    if holder.is_some_and(|holder| holder != index) {
+                        moved.entry(todo.uuid.clone()).or_insert_with(|| uuid.clone());
+                    }
+                    todo.uuid = uuid;
+                } else {
+                    let message = match missing {
+                        true => t!("doctor.missing_uuid", store = store, uuid = todo.uuid, id = todo.id, title = todo.title),
+                        false => t!("doctor.duplicate_uuid", store = store, uuid = todo.uuid, id = todo.id, title = todo.title),
+                    };
+                    println!("{}", message);
+                }
+            }
+            holders.entry(todo.uuid.clone()).or_insert(index);
+        }
+
+        for (old, uuid) in &moved {
+            let mut count = 0;
+            for todo in todos.iter_mut() {
+                for dependency in todo.depends_on.iter_mut().filter(|d| *d == old) {
+                    *dependency = uuid.clone();
+                    count += 1;
+                }
+            }
+            if count > 0 {
+                println!("{}", t!("doctor.references_fixed", store = store, count = count, old = old, uuid = uuid));
+            }
+        }
+    }
+
+    problems
+}
+
+/// Checks the todo database, its archive, and its trash for consistency problems
+///
+/// Each flag selects a check; with none selected every check runs. With
+/// `fix`, problems are repaired and the stores are saved. The archive is
+/// skipped when it is the list being checked, or when the store has none.
+pub fn run_doctor(store: &mut dyn TodoStore, ids: bool, fix: bool) -> CommandResult {
+    let check_all = !ids;
+    let mut todos = store.load()?;
+    let mut archive = other_archive(store);
+    let mut archived = match &mut archive {
+        Some(archive) => archive.load()?,
+        None => Vec::new(),
+    };
+    let mut trash = store.load_trash()?;
+    let mut trashed: Vec<Todo> = trash.iter().map(|t| t.todo.clone()).collect();
+    let mut problems = 0;
+
+    if ids || check_all {
+        let mut stores = vec![Checked { name: "active", todos: &mut todos, unique_ids: true }];
+        if archive.is_some() {
+            stores.push(Checked { name: "archive", todos: &mut archived, unique_ids: true });
+        }
+        stores.push(Checked { name: "trash", todos: &mut trashed, unique_ids: false });
+        problems += check_ids(&mut stores, fix);
+    }
+
+    if problems == 0 {
+        println!("{}", t!("doctor.ok"));
+    } else if fix {
+        store.save(&todos)?;
+        if let Some(archive) = &mut archive {
+            archive.save(&archived)?;
+        }
+        if !trash.is_empty() {
+            for (entry, todo) in trash.iter_mut().zip(trashed) {
+                entry.todo = todo;
+            }
+            store.save_trash(&trash)?;
+        }
+        println!("{}", t!("doctor.fixed", count = problems));
+    } else {
+        println!("{}", t!("doctor.found", count = problems));
+    }
+
+    Ok(())
+}
@@ -1,40 +1,93 @@
 use crate::{
-    storage::{load_todos, save_todos},
-    display::display_todos,
+    deps::unfinished_dependencies,
+    journal::Operation,
+    models::{Status, Todo},
+    query::Query,
+    storage::TodoStore,
+    display::display_updated,
+    t,
 };
-use super::CommandResult;
+use super::{resolve::resolve_index, CommandResult};
 
-/// Marks a todo as done by its ID
-pub fn mark_done(id: u64) -> CommandResult {
-    let mut todos = load_todos()?;
-    let mut found = false;
-    let mut todo_title = String::new();
-    
-    // First pass: find and update the todo
-    for todo in &mut todos {
-        if todo.id == id {
-            if todo.completed {
-                println!("Todo #{} is already marked as done.", id);
-                return Ok(());
-            } else {
-                todo.completed = true;
-                todo_title = todo.title.clone();
-                found = true;
-                break;
-            }
-        }
+/// Marks a single todo as done, returning false if it already was
+///
+/// A timer running on the todo is stopped.
+pub(super) fn complete(todo: &mut Todo) -> bool {
+    if todo.status == Status::Done {
+        return false;
     }
-    
-    if !found {
-        return Err(anyhow::anyhow!("Todo #{} not found", id));
+    todo.set_status(Status::Done);
+    let now = todo.completed_at;
+    if let Some(entry) = todo.running_entry() {
+        entry.end = now;
     }
+    true
+}
+
+/// Warns about dependencies of a just-completed todo that are still open
+fn warn_unfinished(todos: &[Todo], todo: &Todo) {
+    for dependency in unfinished_dependencies(todos, todo) {
+        println!(
+            "{}",
+            t!("done.unfinished_dependency", id = todo.id, dependency = dependency.id, title = dependency.title)
+        );
+    }
+}
+
+/// Marks a todo as done by its ID or UUID prefix
+pub fn mark_done(store: &mut dyn TodoStore, target: &str) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+
+    if !complete(todo) {
+        println!("{}", t!("done.already", id = todo.id));
+        return Ok(());
+    }
+
+    let id = todo.id;
+    let todo_title = todo.title.clone();
     
     // Save the updated todos
-    save_todos(&todos)?;
-    println!("Marked todo #{} as done: {}", id, todo_title);
+    store.save(&todos)?;
+    store.record(Operation::Completed, &todos[index])?;
+    println!("{}", t!("done.done", id = id, title = todo_title));
+    warn_unfinished(&todos, &todos[index]);
     
     // Show the updated list
-    display_todos(&todos);
+    display_updated(&todos);
     
     Ok(())
 }
+
+/// Marks every incomplete todo matching the query as done
+pub fn mark_done_where(store: &mut dyn TodoStore, query: &Query) -> CommandResult {
+    let mut todos = store.load()?;
+    let mut completed = Vec::new();
+
+    for todo in todos.iter_mut().filter(|t| query.matches(t)) {
+        if complete(todo) {
+            completed.push(todo.clone());
+        }
+    }
+
+    if completed.is_empty() {
+        println!("{}", t!("done.no_match"));
+        return Ok(());
+    }
+
+    store.save(&todos)?;
+    for todo in &completed {
+        store.record(Operation::Completed, todo)?;
+        println!("{}", t!("done.done", id = todo.id, title = todo.title));
+    }
+    // Dependencies completed in the same batch don't count
+    for todo in &completed {
+        warn_unfinished(&todos, todo);
+    }
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use crate::{
+    journal::Operation,
+    models::{generate_uuid, Status},
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use chrono::{Local, NaiveDate};
+use super::{resolve::resolve_index, CommandResult};
+
+/// Adds an open copy of a todo, optionally due on `due` instead
+///
+/// The copy keeps the title, priority, tags, notes, project, reminders,
+/// and dependencies. Completion, tracked time, pomodoros, hiding, and the
+/// linked issue stay with the original.
+pub fn duplicate_todo(store: &mut dyn TodoStore, target: &str, due: Option<NaiveDate>) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+
+    let mut copy = todos[index].clone();
+    copy.id = todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+    copy.uuid = generate_uuid();
+    copy.set_status(Status::Todo);
+    copy.created_at = Local::now();
+    copy.modified_at = None;
+    copy.modified_by = None;
+    copy.hidden_until = None;
+    copy.time_entries.clear();
+    copy.pomodoros_completed = 0;
+    copy.external_ref = None;
+    if let Some(due) = due {
+        copy.due_date = Some(due);
+        copy.start_date = copy.start_date.filter(|&start| start <= due);
+    }
+
+    let (old_id, new_id) = (todos[index].id, copy.id);
+    todos.push(copy);
+    store.save(&todos)?;
+    if let Some(added) = todos.last() {
+        store.record(Operation::Added, added)?;
+        println!("{}", t!("duplicate.done", id = old_id, new_id = new_id, title = added.title));
+    }
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
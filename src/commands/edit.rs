@@ -0,0 +1,114 @@
+use crate::{
+    duration::HumanDuration,
+    error::TodoError,
+    models::Priority,
+    storage::TodoStore,
+    validate,
+    display::{check_style, display_updated},
+    t,
+};
+use chrono::NaiveDate;
+use super::{resolve::resolve_index, CommandResult};
+
+/// Changes made by `edit`; fields left as `None` keep their value
+///
+/// An empty string clears the notes, project, assignee, icon, or color, a zero
+/// estimate clears the estimate, and `Some(None)` clears the due date.
+#[derive(Debug, Clone, Default)]
+pub struct TodoEdit {
+    pub title: Option<String>,
+    pub due_date: Option<Option<NaiveDate>>,
+    pub priority: Option<Priority>,
+    pub estimate: Option<HumanDuration>,
+    pub notes: Option<String>,
+    pub project: Option<String>,
+    pub assignee: Option<String>,
+    pub icon: Option<String>,
+    pub color: Option<String>,
+}
+
+/// Sets an optional text field; returns whether it changed
+fn set_text(field: &mut Option<String>, value: Option<String>) -> bool {
+    if *field == value {
+        return false;
+    }
+    *field = value;
+    true
+}
+
+/// Changes the fields of a todo given in `edit`
+///
+/// Rejects an empty or overlong title, a due date before the start date,
+/// and colors that aren't known styles, leaving the todo untouched. Text is
+/// cleaned up as described in [`crate::validate`]. Saves only when
+/// something changed.
+pub fn edit_todo(store: &mut dyn TodoStore, target: &str, edit: &TodoEdit) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    let id = todo.id;
+
+    let title = edit.title.as_deref().map(validate::title).transpose()?;
+    if let (Some(start), Some(Some(due))) = (todo.start_date, edit.due_date) {
+        if start > due {
+            let message = format!("Start date {} is after the due date {}", start, due);
+            return Err(TodoError::Validation(message).into());
+        }
+    }
+    if let Some(color) = edit.color.as_deref().and_then(validate::text) {
+        check_style(&color).map_err(TodoError::Validation)?;
+    }
+
+    let mut changed = Vec::new();
+    if let Some(title) = title {
+        if todo.title != title {
+            todo.title = title;
+            changed.push("title");
+        }
+    }
+    if let Some(due) = edit.due_date {
+        if todo.due_date != due {
+            todo.due_date = due;
+            changed.push("due date");
+        }
+    }
+    if let Some(priority) = edit.priority {
+        if todo.priority != priority {
+            todo.priority = priority;
+            changed.push("priority");
+        }
+    }
+    if let Some(estimate) = edit.estimate {
+        let estimate = Some(estimate).filter(|e| e.duration() > chrono::Duration::zero());
+        if todo.estimate != estimate {
+            todo.estimate = estimate;
+            changed.push("estimate");
+        }
+    }
+    let texts = [
+        ("notes", edit.notes.as_deref().map(validate::notes), &mut todo.notes),
+        ("project", edit.project.as_deref().map(validate::text), &mut todo.project),
+        ("assignee", edit.assignee.as_deref().map(validate::text), &mut todo.assignee),
+        ("icon", edit.icon.as_deref().map(validate::text), &mut todo.icon),
+        ("color", edit.color.as_deref().map(validate::text), &mut todo.color),
+    ];
+    for (name, value, field) in texts {
+        if let Some(value) = value {
+            if set_text(field, value) {
+                changed.push(name);
+            }
+        }
+    }
+
+    if changed.is_empty() {
+        println!("{}", t!("edit.unchanged", id = id));
+        return Ok(());
+    }
+    store.save(&todos)?;
+    println!("{}", t!("edit.updated", fields = changed.join(", "), id = id, title = todos[index].title));
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use crate::{
+    config::Config,
+    escalate::escalate,
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use anyhow::Result;
+use chrono::Local;
+use super::CommandResult;
+
+/// Applies the configured escalation rules, printing each change
+///
+/// Saves only when something changed, unless `dry_run` is set, in which
+/// case nothing is saved at all. Returns how many todos were escalated.
+pub fn apply_escalation(store: &mut dyn TodoStore, dry_run: bool) -> Result<usize> {
+    let rules = Config::load()?.escalation.rules;
+    let mut todos = store.load()?;
+    let changes = escalate(&mut todos, &rules, Local::now().date_naive());
+
+    for change in &changes {
+        let todo = &todos[change.index];
+        println!("{}", t!("escalate.escalated", id = todo.id, from = change.from, to = change.to, title = todo.title));
+    }
+    if !changes.is_empty() && !dry_run {
+        store.save(&todos)?;
+    }
+
+    Ok(changes.len())
+}
+
+/// Bumps priorities of todos nearing or past their due date
+pub fn escalate_todos(store: &mut dyn TodoStore, dry_run: bool) -> CommandResult {
+    let count = apply_escalation(store, dry_run)?;
+    if count == 0 {
+        println!("{}", t!("escalate.nothing"));
+        return Ok(());
+    }
+    if dry_run {
+        println!("{}", t!("escalate.dry_run", count = count));
+        return Ok(());
+    }
+
+    // Show the updated list
+    display_updated(&store.load()?);
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use crate::{
+    backup::{backup_before_change, ExtraStore, Extras},
+    cli::SortOrder,
+    deps::blocked_uuids,
+    filter::Filter,
+    error::TodoError,
+    formats::{from_ics, from_markdown, from_mstodo, from_trello, to_markdown, Format, GroupBy},
+    journal::Operation,
+    models::{Status, Todo},
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use anyhow::Context;
+use super::{list::sort_todos, CommandResult};
+
+/// Writes the todos matching `filter` in `format`, to `out` or stdout
+///
+/// The matching todos from the stores in `include` follow, each under a
+/// `# Archive` or `# Trash` heading.
+pub fn export_todos(
+    store: &mut dyn TodoStore,
+    format: Format,
+    group_by: GroupBy,
+    filter: &Filter,
+    out: Option<&Path>,
+    include: &[ExtraStore],
+) -> CommandResult {
+    let mut todos = store.load()?;
+    let blocked = blocked_uuids(&todos);
+    filter.apply(&mut todos);
+    sort_todos(&mut todos, &SortOrder::default(), &blocked);
+
+    let mut extras = Extras::load(store, include)?;
+    let mut sections = Vec::new();
+    if let Some(archive) = &mut extras.archive {
+        let blocked = blocked_uuids(archive);
+        filter.apply(archive);
+        sort_todos(archive, &SortOrder::default(), &blocked);
+        sections.push(("Archive", archive.clone()));
+    }
+    if let Some(trash) = &mut extras.trash {
+        trash.retain(|t| filter.matches(&t.todo));
+        sections.push(("Trash", trash.iter().map(|t| t.todo.clone()).collect()));
+    }
+
+    let render = |todos: &[Todo]| -> anyhow::Result<String> {
+        let message = match format {
+            Format::Markdown => return Ok(to_markdown(todos, group_by)),
+            #[cfg(feature = "macos-integration")]
+            Format::Reminders => return Ok(crate::integrations::reminders::to_reminders_script(todos)),
+            Format::Mstodo => "Microsoft To Do exports can only be imported, not written",
+            Format::Trello => "Trello boards can only be imported, not written",
+            Format::Ics => "Calendars can only be imported, not written",
+        };
+        Err(TodoError::Validation(message.to_string()).into())
+    };
+    let mut content = render(&todos)?;
+    for (heading, todos) in &sections {
+        content.push_str(&format!("\n# {}\n\n{}", heading, render(todos)?));
+    }
+
+    match out {
+        Some(path) => {
+            fs::write(path, content)
+                .with_context(|| format!("Failed to write to {}", path.display()))?;
+            println!("{}", t!("export.done", count = todos.len(), path = path.display()));
+            extras.print_counts();
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
+
+/// Adds the todos read from `path` (or stdin for `-`) in `format`
+///
+/// Items whose title matches an existing todo are skipped, so importing
+/// the same checklist twice doesn't duplicate it. With `replace`, the
+/// imported items take the place of every existing todo instead, after
+/// a backup. `title` is a template for the titles of calendar entries.
+pub fn import_todos(
+    store: &mut dyn TodoStore,
+    path: &Path,
+    format: Format,
+    title: Option<&str>,
+    replace: bool,
+) -> CommandResult {
+    let mut input = String::new();
+    if path == Path::new("-") {
+        std::io::stdin().read_to_string(&mut input)?;
+    } else {
+        input = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+    }
+    import_text(store, &input, format, title, replace)
+}
+
+/// Adds the todos in `input`, read in `format`, as [`import_todos`] does
+pub(super) fn import_text(
+    store: &mut dyn TodoStore,
+    input: &str,
+    format: Format,
+    title: Option<&str>,
+    replace: bool,
+) -> CommandResult {
+    if title.is_some() && format != Format::Ics {
+        return Err(TodoError::Validation("--title only applies to --format ics".to_string()).into());
+    }
+    let items = match format {
+        Format::Markdown => from_markdown(input),
+        #[cfg(feature = "macos-integration")]
+        Format::Reminders => {
+            let message = "Reminders scripts can only be exported, not imported".to_string();
+            return Err(TodoError::Validation(message).into());
+        }
+        Format::Mstodo => from_mstodo(input)?,
+        Format::Trello => from_trello(input)?,
+        Format::Ics => from_ics(input, title)?,
+    };
+
+    let mut todos = store.load()?;
+    let mut replaced = Vec::new();
+    if replace {
+        backup_before_change(store, &[])?;
+        replaced = std::mem::take(&mut todos);
+    }
+    let existing = todos.len();
+    let mut next_id = todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+    let mut skipped = 0;
+    for item in items {
+        // Compare the cleaned-up title, as it would be stored
+        let mut todo = item.builder.build(next_id)?;
+        if todos.iter().any(|t| t.title == todo.title) {
+            skipped += 1;
+            continue;
+        }
+
+        if item.status != Status::Todo {
+            todo.set_status(item.status);
+        }
+        todos.push(todo);
+        next_id += 1;
+    }
+
+    store.save(&todos)?;
+    for todo in &replaced {
+        store.record(Operation::Removed, todo)?;
+    }
+    for todo in &todos[existing..] {
+        store.record(Operation::Added, todo)?;
+    }
+
+    if replace {
+        println!("{}", t!("import.replaced", count = replaced.len(), imported = todos.len()));
+    } else {
+        println!("{}", t!("import.done", count = todos.len() - existing, skipped = skipped));
+    }
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use std::collections::{BTreeMap, HashSet};
+use crate::{
+    duration::HumanDuration,
+    error::TodoError,
+    formats::ImportedTodo,
+    integrations::feeds::{load_feeds, save_feeds, Feed},
+    journal::Operation,
+    models::{ExternalKind, ExternalRef, Todo},
+    output::info,
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use super::{done::complete, CommandResult};
+
+/// Cache of when each feed was last synced, by URL
+const CACHE: &str = "feeds";
+
+/// What syncing a feed changed, as indices into the todos
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FeedChanges {
+    /// Todos added for new items
+    pub added: Vec<usize>,
+    /// Todos whose item was renamed or rescheduled
+    pub updated: Vec<usize>,
+    /// Open todos whose item left the feed or was cancelled, now closed
+    pub closed: Vec<usize>,
+}
+
+impl FeedChanges {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.closed.is_empty()
+    }
+}
+
+/// Brings the todos from the feed at `url` in line with its current `items`
+///
+/// Todos are matched to items by the item's ID (a calendar entry's `UID`,
+/// or the title of an entry without one). New items are added, unless
+/// they're already closed; known ones take the item's title and due date;
+/// open todos whose item is gone or cancelled are closed. Todos completed
+/// by hand are left alone. A feed with no items at all is more likely
+/// broken than finished, so it closes nothing.
+pub fn apply_feed(todos: &mut Vec<Todo>, url: &str, items: Vec<ImportedTodo>) -> Result<FeedChanges> {
+    let mut changes = FeedChanges::default();
+    let mut seen = HashSet::new();
+    let mut next_id = todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+    for item in items {
+        let mut todo = item.builder.build(next_id)?;
+        let mut external = todo
+            .external_ref
+            .take()
+            .unwrap_or_else(|| ExternalRef::new(ExternalKind::Feed, &todo.title, None));
+        external.feed = Some(url.to_string());
+        if !seen.insert((external.kind, external.id.clone())) {
+            continue;
+        }
+
+        let existing = todos.iter().position(|t| {
+            let same = |r: &ExternalRef| r.feed == external.feed && r.kind == external.kind && r.id == external.id;
+            t.external_ref.as_ref().is_some_and(same)
+        });
+        match existing {
+            Some(index) => {
+                let known = &mut todos[index];
+                if known.title != todo.title || known.due_date != todo.due_date {
+                    known.title = todo.title;
+                    known.due_date = todo.due_date;
+                    changes.updated.push(index);
+                }
+                if item.status.is_closed() && !known.completed {
+                    known.set_status(item.status);
+                    changes.closed.push(index);
+                }
+            }
+            None if item.status.is_closed() => {}
+            None => {
+                todo.external_ref = Some(external);
+                todos.push(todo);
+                changes.added.push(todos.len() - 1);
+                next_id += 1;
+            }
+        }
+    }
+
+    if seen.is_empty() {
+        return Ok(changes);
+    }
+    for (index, todo) in todos.iter_mut().enumerate() {
+        let from_feed = todo.external_ref.as_ref().filter(|r| r.feed.as_deref() == Some(url));
+        let gone = from_feed.is_some_and(|r| !seen.contains(&(r.kind, r.id.clone())));
+        if gone && !todo.completed && complete(todo) {
+            changes.closed.push(index);
+        }
+    }
+    changes.updated.retain(|index| !changes.closed.contains(index));
+    Ok(changes)
+}
+
+/// Subscribes to the feed at `url`, replacing any subscription to it
+pub fn feed_add(url: &str, title: Option<&str>, every: Option<HumanDuration>) -> CommandResult {
+    let feed = Feed::new(url, title.map(str::to_string), every)?;
+    let mut feeds = load_feeds()?;
+    let replaced = feeds.iter().position(|f| f.url == feed.url).map(|i| feeds.remove(i)).is_some();
+    if replaced {
+        println!("{}", t!("feed.updated", url = feed.url, every = feed.every));
+    } else {
+        println!("{}", t!("feed.added", url = feed.url, every = feed.every));
+    }
+    feeds.push(feed);
+    save_feeds(&feeds)?;
+    Ok(())
+}
+
+/// Lists the subscriptions, numbered for `feed remove`
+pub fn feed_list() -> CommandResult {
+    let feeds = load_feeds()?;
+    if feeds.is_empty() {
+        println!("{}", t!("feed.none"));
+    }
+    for (i, feed) in feeds.iter().enumerate() {
+        println!("{:>3}. {} ({})", i + 1, feed.url, t!("feed.every", every = feed.every));
+    }
+    Ok(())
+}
+
+/// Unsubscribes from a feed, by URL or number in `feed list`
+///
+/// Todos from the feed stay as they are.
+pub fn feed_remove(target: &str) -> CommandResult {
+    let mut feeds = load_feeds()?;
+    let index = match target.parse::<usize>() {
+        Ok(number) => number.checked_sub(1).filter(|&i| i < feeds.len()),
+        Err(_) => feeds.iter().position(|f| f.url == target.trim()),
+    };
+    let index = index.ok_or_else(|| TodoError::Validation(format!("No feed {}; see `feed list`", target)))?;
+    let feed = feeds.remove(index);
+    save_feeds(&feeds)?;
+    println!("{}", t!("feed.removed", url = feed.url));
+    Ok(())
+}
+
+/// Syncs the feeds not synced within their interval, or all of them with `force`
+///
+/// A feed that can't be downloaded or read is skipped with a warning, and
+/// tried again next time. Meant to be run periodically, e.g. from cron;
+/// the daemon runs it too.
+pub fn feed_sync(store: &mut dyn TodoStore, force: bool) -> CommandResult {
+    let feeds = load_feeds()?;
+    if feeds.is_empty() {
+        println!("{}", t!("feed.none"));
+        return Ok(());
+    }
+    let mut synced: BTreeMap<String, DateTime<Local>> =
+        store.load_cache(CACHE).and_then(|content| serde_json::from_str(&content).ok()).unwrap_or_default();
+
+    let now = Local::now();
+    let due: Vec<&Feed> = feeds
+        .iter()
+        .filter(|feed| force || synced.get(&feed.url).is_none_or(|&at| now - at >= feed.every.duration()))
+        .collect();
+    if due.is_empty() {
+        info("Every feed was synced within its interval; pass --force to sync anyway");
+        return Ok(());
+    }
+
+    let mut todos = store.load()?;
+    let mut changes = FeedChanges::default();
+    for feed in due {
+        let items = match feed.fetch() {
+            Ok(items) => items,
+            Err(err) => {
+                eprintln!("{}", t!("feed.failed", url = feed.url, error = format!("{:#}", err)));
+                continue;
+            }
+        };
+        if items.is_empty() {
+            eprintln!("{}", t!("feed.empty", url = feed.url));
+        }
+        let changed = apply_feed(&mut todos, &feed.url, items)?;
+        println!("{}", t!(
+            "feed.synced",
+            url = feed.url, added = changed.added.len(), updated = changed.updated.len(), closed = changed.closed.len()
+        ));
+        synced.insert(feed.url.clone(), now);
+        changes.added.extend(changed.added);
+        changes.updated.extend(changed.updated);
+        changes.closed.extend(changed.closed);
+    }
+
+    if !changes.is_empty() {
+        store.save(&todos)?;
+        for &index in &changes.added {
+            store.record(Operation::Added, &todos[index])?;
+        }
+        for &index in &changes.updated {
+            store.record(Operation::Updated, &todos[index])?;
+        }
+        for &index in &changes.closed {
+            store.record(Operation::Completed, &todos[index])?;
+        }
+    }
+    let _ = store.save_cache(CACHE, &serde_json::to_string(&synced)?);
+
+    if !changes.is_empty() {
+        // Show the updated list
+        display_updated(&todos);
+    }
+    Ok(())
+}
+
+/// Syncs the feeds that are due for it, warning instead of failing, for the daemon
+pub(super) fn sync_due_feeds(store: &mut dyn TodoStore) {
+    if load_feeds().is_ok_and(|feeds| feeds.is_empty()) {
+        return;
+    }
+    if let Err(err) = feed_sync(store, false) {
+        eprintln!("{}", t!("feed.sync_failed", error = format!("{:#}", err)));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{
+    cli::SortOrder,
+    deps::blocked_uuids,
+    error::TodoError,
+    storage::TodoStore,
+    display::{display_updated, render_table, TableLayout},
+    t,
+};
+use super::{list::sort_todos, resolve::resolve_index, CommandResult};
+
+/// Puts todos on the focus list
+///
+/// Todos already on it are left as they are. Nothing changes if any of
+/// the todos is completed.
+pub fn focus_add(store: &mut dyn TodoStore, targets: &[String]) -> CommandResult {
+    let mut todos = store.load()?;
+    let indices = targets.iter().map(|target| resolve_index(&todos, target)).collect::<Result<Vec<_>, _>>()?;
+    if let Some(todo) = indices.iter().map(|&index| &todos[index]).find(|t| t.completed) {
+        return Err(TodoError::Validation(format!("Todo #{} is already completed", todo.id)).into());
+    }
+
+    let mut changed = false;
+    for index in indices {
+        let todo = &mut todos[index];
+        if todo.focused {
+            println!("{}", t!("focus.already", id = todo.id));
+            continue;
+        }
+        todo.focused = true;
+        changed = true;
+        println!("{}", t!("focus.added", id = todo.id, title = todo.title));
+    }
+
+    if changed {
+        store.save(&todos)?;
+        // Show the updated list
+        display_updated(&todos);
+    }
+    Ok(())
+}
+
+/// Takes todos off the focus list
+pub fn focus_remove(store: &mut dyn TodoStore, targets: &[String]) -> CommandResult {
+    let mut todos = store.load()?;
+    let indices = targets.iter().map(|target| resolve_index(&todos, target)).collect::<Result<Vec<_>, _>>()?;
+
+    let mut changed = false;
+    for index in indices {
+        let todo = &mut todos[index];
+        if !todo.focused {
+            println!("{}", t!("focus.not_focused", id = todo.id));
+            continue;
+        }
+        todo.focused = false;
+        changed = true;
+        println!("{}", t!("focus.removed", id = todo.id, title = todo.title));
+    }
+
+    if changed {
+        store.save(&todos)?;
+        // Show the updated list
+        display_updated(&todos);
+    }
+    Ok(())
+}
+
+/// Shows the open todos on the focus list, in the smart order
+pub fn focus_show(store: &mut dyn TodoStore) -> CommandResult {
+    let todos = store.load()?;
+    let blocked = blocked_uuids(&todos);
+    let mut focused: Vec<_> = todos.into_iter().filter(|t| t.focused && !t.completed).collect();
+    if focused.is_empty() {
+        println!("{}", t!("focus.empty"));
+        return Ok(());
+    }
+
+    sort_todos(&mut focused, &SortOrder::default(), &blocked);
+    print!("{}", render_table(&focused, &TableLayout::auto(&focused), &blocked));
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use crate::{
+    config::Config,
+    integrations::github::GithubClient,
+    journal::Operation,
+    models::{ExternalKind, ExternalRef, TodoBuilder},
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use anyhow::anyhow;
+use super::{done::complete, resolve::resolve_index, CommandResult};
+
+/// Adds a todo for each open issue in `repo` assigned to the token's owner
+///
+/// Falls back to `github.repo` from the config. Issues already linked to a
+/// todo are skipped. Labels become tags and the repository the project.
+pub fn github_pull(store: &mut dyn TodoStore, repo: Option<&str>) -> CommandResult {
+    let config = Config::load()?.github;
+    let repo = repo
+        .map(str::to_string)
+        .or(config.repo.clone())
+        .ok_or_else(|| anyhow!("No repository given: pass --repo owner/name or set github.repo in the config file"))?;
+    let issues = GithubClient::from_config(&config)?.assigned_issues(&repo)?;
+
+    let mut todos = store.load()?;
+    let existing = todos.len();
+    let mut next_id = todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+    for issue in issues {
+        let id = format!("{}#{}", repo, issue.number);
+        if todos.iter().any(|t| t.external_id(ExternalKind::Github) == Some(id.as_str())) {
+            continue;
+        }
+
+        let tags = issue.labels.iter().map(|label| label.split_whitespace().collect::<Vec<_>>().join("-"));
+        let todo = TodoBuilder::new(format!("{} (#{})", issue.title, issue.number))
+            .tags(tags)
+            .project(Some(repo.clone()))
+            .external_ref(ExternalRef::new(ExternalKind::Github, id, Some(issue.url)))
+            .build(next_id)?;
+        todos.push(todo);
+        next_id += 1;
+    }
+
+    let added = todos.len() - existing;
+    if added > 0 {
+        store.save(&todos)?;
+        for todo in &todos[existing..] {
+            store.record(Operation::Added, todo)?;
+        }
+    }
+    println!("{}", t!("github.pulled", count = added, repo = repo));
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
+
+/// Closes the issue linked to a todo, marking the todo done if it isn't yet
+pub fn github_close(store: &mut dyn TodoStore, target: &str) -> CommandResult {
+    let config = Config::load()?.github;
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    let url = todo
+        .external_ref
+        .as_ref()
+        .filter(|external| external.kind == ExternalKind::Github)
+        .and_then(|external| external.url.clone())
+        .ok_or_else(|| anyhow!("Todo #{} is not linked to an issue", todo.id))?;
+
+    GithubClient::from_config(&config)?.close_issue(&url)?;
+    println!("{}", t!("github.closed", url = url));
+
+    if complete(todo) {
+        let id = todo.id;
+        store.save(&todos)?;
+        store.record(Operation::Completed, &todos[index])?;
+        println!("{}", t!("github.done", id = id));
+    }
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use crate::{
+    error::TodoError,
+    storage::TodoStore,
+    display::display_updated,
+    i18n::format_day,
+    t,
+};
+use chrono::{Local, NaiveDate};
+use super::{resolve::resolve_index, CommandResult};
+
+/// Hides a todo from default views until `until`
+pub fn hide_todo(store: &mut dyn TodoStore, target: &str, until: NaiveDate) -> CommandResult {
+    if until <= Local::now().date_naive() {
+        return Err(TodoError::Validation(format!("Hide date {} is not in the future", until)).into());
+    }
+    
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    todo.hidden_until = Some(until);
+    let id = todo.id;
+    let title = todo.title.clone();
+    
+    store.save(&todos)?;
+    println!("{}", t!("hide.hidden", id = id, until = format_day(until), title = title));
+    
+    // Show the updated list
+    display_updated(&todos);
+    
+    Ok(())
+}
+
+/// Shows a hidden todo again
+pub fn unhide_todo(store: &mut dyn TodoStore, target: &str) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    let id = todo.id;
+    
+    if !todo.is_hidden_on(Local::now().date_naive()) {
+        println!("{}", t!("unhide.not_hidden", id = id));
+        return Ok(());
+    }
+    
+    todo.hidden_until = None;
+    store.save(&todos)?;
+    println!("{}", t!("unhide.done", id = id));
+    
+    // Show the updated list
+    display_updated(&todos);
+    
+    Ok(())
+}
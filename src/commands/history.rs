@@ -0,0 +1,33 @@
+use crate::storage::TodoStore;
+use crate::t;
+use colored::Colorize;
+use super::{diff::brief, resolve::resolve_index, CommandResult};
+
+/// Prints every recorded change to a todo's fields, oldest first, or just those to `field`
+pub fn show_history(store: &mut dyn TodoStore, target: &str, field: Option<&str>) -> CommandResult {
+    let todos = store.load()?;
+    let todo = &todos[resolve_index(&todos, target)?];
+    let mut changes = store.load_history()?;
+    changes.retain(|c| c.uuid == todo.uuid && field.is_none_or(|f| c.field == f));
+    if changes.is_empty() {
+        match field {
+            Some(field) => println!("{}", t!("history.none_field", field = field, id = todo.id, title = todo.title)),
+            None => println!("{}", t!("history.none", id = todo.id, title = todo.title)),
+        }
+        return Ok(());
+    }
+
+    println!("{}", t!("history.header", id = todo.id, title = todo.title.bold()));
+    for change in &changes {
+        let by = change.by.as_ref().map(|by| format!(" ({})", by)).unwrap_or_default();
+        println!(
+            "  {}  {}: {} → {}{}",
+            change.timestamp.format("%Y-%m-%d %H:%M").to_string().dimmed(),
+            change.field,
+            brief(&change.old),
+            brief(&change.new),
+            by.dimmed()
+        );
+    }
+    Ok(())
+}
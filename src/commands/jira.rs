@@ -0,0 +1,116 @@
+use crate::{
+    config::Config,
+    error::TodoError,
+    integrations::jira::{Issue, JiraClient},
+    journal::Operation,
+    models::{ExternalKind, ExternalRef, Priority, Todo, TodoBuilder},
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use super::{done::complete, resolve::resolve_index, CommandResult};
+
+/// Maps a Jira priority name to a level of the scale
+///
+/// `Highest` and `Lowest` take the ends of the scale; other names are
+/// used as they are when the scale has them, as `High` and `Low` do by
+/// default, and anything else gets the default priority.
+fn priority(name: Option<&str>) -> Priority {
+    match name.map(str::trim) {
+        Some(name) if name.eq_ignore_ascii_case("highest") => Priority::highest(),
+        Some(name) if name.eq_ignore_ascii_case("lowest") => Priority::lowest(),
+        Some(name) => name.parse().unwrap_or_default(),
+        None => Priority::default(),
+    }
+}
+
+/// Returns the title of the todo for an issue, ending in its key
+fn title(issue: &Issue) -> String {
+    format!("{} ({})", issue.summary, issue.key)
+}
+
+/// Brings a linked todo's title, priority, and due date in line with its issue, returning whether any changed
+fn refresh(todo: &mut Todo, issue: &Issue) -> bool {
+    let (title, priority) = (title(issue), priority(issue.priority.as_deref()));
+    let changed = todo.title != title || todo.priority != priority || todo.due_date != issue.due_date;
+    if changed {
+        todo.title = title;
+        todo.priority = priority;
+        todo.due_date = issue.due_date;
+    }
+    changed
+}
+
+/// Adds a todo for each unresolved Jira issue assigned to the token's owner
+///
+/// Todos already linked to an issue are refreshed instead: their title,
+/// priority, and due date follow the issue's. New todos get the issue's
+/// labels as tags and its project key as their project.
+pub fn jira_pull(store: &mut dyn TodoStore) -> CommandResult {
+    let config = Config::load()?.jira;
+    let issues = JiraClient::from_config(&config)?.assigned_issues()?;
+
+    let mut todos = store.load()?;
+    let existing = todos.len();
+    let mut refreshed = 0;
+    let mut next_id = todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+    for issue in issues {
+        if let Some(todo) = todos[..existing].iter_mut().find(|t| t.external_id(ExternalKind::Jira) == Some(issue.key.as_str())) {
+            refreshed += usize::from(refresh(todo, &issue));
+            continue;
+        }
+
+        let project = issue.key.split_once('-').map(|(project, _)| project.to_string());
+        let tags = issue.labels.iter().map(|label| label.split_whitespace().collect::<Vec<_>>().join("-"));
+        let todo = TodoBuilder::new(title(&issue))
+            .priority(priority(issue.priority.as_deref()))
+            .due_date(issue.due_date)
+            .tags(tags)
+            .project(project)
+            .external_ref(ExternalRef::new(ExternalKind::Jira, issue.key, Some(issue.url)))
+            .build(next_id)?;
+        todos.push(todo);
+        next_id += 1;
+    }
+
+    let added = todos.len() - existing;
+    if added > 0 || refreshed > 0 {
+        store.save(&todos)?;
+        for todo in &todos[existing..] {
+            store.record(Operation::Added, todo)?;
+        }
+    }
+    println!("{}", t!("jira.pulled", added = added, refreshed = refreshed));
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
+
+/// Moves the Jira issue linked to a todo into a done status, marking the todo done if it isn't yet
+pub fn jira_done(store: &mut dyn TodoStore, target: &str) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    let key = todo
+        .external_id(ExternalKind::Jira)
+        .map(str::to_string)
+        .ok_or_else(|| TodoError::Validation(format!("Todo #{} is not linked to a Jira issue", todo.id)))?;
+
+    let config = Config::load()?.jira;
+    let status = JiraClient::from_config(&config)?.transition_to_done(&key)?;
+    println!("{}", t!("jira.moved", key = key, status = status));
+
+    if complete(todo) {
+        let id = todo.id;
+        store.save(&todos)?;
+        store.record(Operation::Completed, &todos[index])?;
+        println!("{}", t!("jira.done", id = id));
+    }
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
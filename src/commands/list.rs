@@ -1,52 +1,357 @@
 use crate::{
-    models::Priority,
-    storage::load_todos,
-    display::display_todos,
+    archive::load_archive,
+    cli::{SortField, SortKey, SortOrder},
+    deps::blocked_uuids,
+    filter::Filter,
+    journal::Operation,
+    models::{smart_sort, Status, Todo},
+    storage::TodoStore,
+    display::{
+        display_updated, render_grouped_table, render_interactive, render_section, render_table, without_color,
+        Grouping, TableLayout,
+    },
+    terminal::{self, Key, RawMode},
+    t,
+    urgency::urgency,
 };
+use anyhow::Context;
+use chrono::{Local, NaiveDate};
+use colored::*;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use super::CommandResult;
 
+/// Compares two todos on one sort key as of `today`
+fn compare_key(a: &Todo, b: &Todo, key: SortKey, blocked: &HashSet<String>, today: NaiveDate) -> Ordering {
+    let ordering = match key.field {
+        // The configured strategy handles everything except blocking
+        SortField::Smart => a.completed.cmp(&b.completed)
+            .then_with(|| blocked.contains(&a.uuid).cmp(&blocked.contains(&b.uuid)))
+            .then_with(|| smart_sort().compare(a, b, today)),
+        SortField::Status => a.status.rank().cmp(&b.status.rank()),
+        SortField::Due => match (a.due_date, b.due_date) {
+            (Some(a_date), Some(b_date)) => a_date.cmp(&b_date),
+            // Undated todos go last in either direction
+            (Some(_), None) => return Ordering::Less,
+            (None, Some(_)) => return Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        },
+        SortField::Priority => a.priority.cmp(&b.priority),
+        SortField::Created => a.created_at.cmp(&b.created_at),
+        SortField::Modified => a.modified().cmp(&b.modified()),
+        SortField::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+        SortField::Urgency => {
+            let now = Local::now();
+            let a_score = urgency(a, now, blocked.contains(&a.uuid));
+            a_score.total_cmp(&urgency(b, now, blocked.contains(&b.uuid)))
+        }
+        SortField::Manual => a.manual_key().total_cmp(&b.manual_key()).then(a.id.cmp(&b.id)),
+    };
+    if key.descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+/// Sorts todos in the given order
+///
+/// Smart sorting goes by status (in progress, to do, waiting, done, then
+/// cancelled) and puts todos in `blocked` after the other incomplete ones;
+/// within a status the [`smart_sort`] strategy decides. Ties left by every
+/// key fall back to the todos' own order. Pinned todos come before the
+/// rest whatever the order.
+pub fn sort_todos(todos: &mut [Todo], sort_order: &SortOrder, blocked: &HashSet<String>) {
+    let today = Local::now().date_naive();
+    todos.sort_by(|a, b| {
+        b.pinned.cmp(&a.pinned).then_with(|| {
+            sort_order
+                .keys
+                .iter()
+                .map(|&key| compare_key(a, b, key, blocked, today))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| a.cmp(b))
+        })
+    });
+}
+
+/// Where and how wide to render a listing
+#[derive(Debug, Clone, Default)]
+pub struct ListOutput {
+    /// Write the table to this file (without colors) instead of stdout
+    pub out: Option<PathBuf>,
+    /// Table width in characters
+    pub width: Option<usize>,
+    /// Never truncate titles
+    pub wide: bool,
+    /// Always truncate titles to the table width
+    pub truncate: bool,
+    /// Show at most this many todos
+    pub limit: Option<usize>,
+    /// Skip this many todos first
+    pub offset: usize,
+    /// Page tables taller than the terminal through `$PAGER`
+    pub pager: bool,
+    /// Split the table into sections
+    pub group_by: Option<Grouping>,
+    /// Follow the table with the archived todos that match the filter
+    pub archived: bool,
+}
+
+/// Returns the first `keep` matching todos in sort order, and the blocked ones among all
+///
+/// Streams the database twice, first for which todos are blocked, then
+/// keeping only the best todos seen so far, so listing the top few of a
+/// large database never holds all of it. Ties keep the order of the file,
+/// as a full sort would.
+fn top_todos(
+    store: &mut dyn TodoStore,
+    sort_order: &SortOrder,
+    filter: &Filter,
+    keep: usize,
+) -> anyhow::Result<(Vec<Todo>, HashSet<String>)> {
+    let mut unfinished = HashSet::new();
+    let mut waiting = Vec::new();
+    store.for_each_todo(&mut |todo| {
+        if !todo.completed {
+            if !todo.depends_on.is_empty() {
+                waiting.push((todo.uuid.clone(), todo.depends_on));
+            }
+            unfinished.insert(todo.uuid);
+        }
+    })?;
+    let blocked: HashSet<String> = waiting
+        .into_iter()
+        .filter(|(_, depends_on)| depends_on.iter().any(|d| unfinished.contains(d)))
+        .map(|(uuid, _)| uuid)
+        .collect();
+    drop(unfinished);
+
+    let today = Local::now().date_naive();
+    let mut top = Vec::new();
+    store.for_each_todo(&mut |todo| {
+        if filter.matches_on(&todo, today) {
+            top.push(todo);
+            if top.len() >= keep.max(64) * 2 {
+                sort_todos(&mut top, sort_order, &blocked);
+                top.truncate(keep);
+            }
+        }
+    })?;
+    sort_todos(&mut top, sort_order, &blocked);
+    top.truncate(keep);
+    Ok((top, blocked))
+}
+
+/// Loads the archived todos matching `filter`, sorted, with those waiting on a dependency
+///
+/// None when `store` is the archive itself, so it isn't listed twice.
+fn archived_todos(
+    store: &mut dyn TodoStore,
+    sort_order: &SortOrder,
+    filter: &Filter,
+) -> anyhow::Result<(Vec<Todo>, HashSet<String>)> {
+    let mut archived = load_archive(store)?;
+    let uuids: HashSet<String> = archived.iter().map(|t| t.uuid.clone()).collect();
+    let mut is_archive = false;
+    store.for_each_todo(&mut |todo| is_archive |= uuids.contains(&todo.uuid))?;
+    if is_archive {
+        return Ok((Vec::new(), HashSet::new()));
+    }
+
+    let blocked = blocked_uuids(&archived);
+    filter.apply(&mut archived);
+    sort_todos(&mut archived, sort_order, &blocked);
+    Ok((archived, blocked))
+}
+
 /// Lists todos with optional filtering and sorting
-pub fn list_todos(sort_order: crate::cli::SortOrder, active_only: bool, min_priority: Option<Priority>) -> CommandResult {
-    let mut todos = load_todos()?;
+///
+/// With a limit only that page of todos is kept in memory; see [`top_todos`].
+pub fn list_todos(
+    store: &mut dyn TodoStore,
+    sort_order: SortOrder,
+    filter: &Filter,
+    output: &ListOutput,
+) -> CommandResult {
+    let (todos, blocked) = match output.limit {
+        Some(limit) => top_todos(store, &sort_order, filter, output.offset.saturating_add(limit))?,
+        None => {
+            let mut todos = store.load()?;
+            
+            // Blocked is decided against every todo, not just the ones shown
+            let blocked = blocked_uuids(&todos);
+            
+            // Apply filters
+            filter.apply(&mut todos);
+            
+            // Apply sorting
+            sort_todos(&mut todos, &sort_order, &blocked);
+            (todos, blocked)
+        }
+    };
     
-    // Apply filters
-    if active_only {
-        todos.retain(|t| !t.completed);
-    }
+    // Take one page of the sorted list, e.g. for scripts walking through it
+    let todos: Vec<Todo> = todos
+        .into_iter()
+        .skip(output.offset)
+        .take(output.limit.unwrap_or(usize::MAX))
+        .collect();
+    let (archived, archived_blocked) = if output.archived {
+        archived_todos(store, &sort_order, filter)?
+    } else {
+        (Vec::new(), HashSet::new())
+    };
+    
+    // Tables with a known width are truncated to fit; piped output shows full titles
+    let width = output.width.or_else(|| output.out.is_none().then(terminal::width).flatten());
+    let truncate = !output.wide && (output.truncate || width.is_some() || output.out.is_some());
+    let width = width.unwrap_or(80);
+    let layout = if truncate {
+        TableLayout::for_width(width)
+    } else {
+        TableLayout::wide(&[todos.as_slice(), &archived].concat(), width)
+    };
+    
+    let render = || {
+        let mut out = match output.group_by {
+            Some(grouping) => render_grouped_table(&todos, &layout, &blocked, grouping, Local::now().date_naive()),
+            None => render_table(&todos, &layout, &blocked),
+        };
+        if !archived.is_empty() {
+            out.push_str(&render_section(&t!("list.archived"), &archived, &layout, &archived_blocked));
+        }
+        out
+    };
     
-    if let Some(min_prio) = min_priority {
-        todos.retain(|t| t.priority >= min_prio);
+    // Display the todos, or write them to the requested file
+    match &output.out {
+        Some(path) => {
+            fs::write(path, without_color(render))
+                .with_context(|| format!("Failed to write to {}", path.display()))?;
+            println!("{}", t!("list.written", count = todos.len(), path = path.display()));
+        }
+        None if output.pager => terminal::page(&render())?,
+        None => print!("{}", render()),
     }
     
-    // Apply sorting
-    match sort_order {
-        crate::cli::SortOrder::Smart => {
-            // Already implemented via the Ord trait
-            todos.sort();
+    Ok(())
+}
+
+/// Prints how many todos match the filter, without drawing the table or holding every todo
+pub fn count_todos(store: &mut dyn TodoStore, filter: &Filter) -> CommandResult {
+    let today = Local::now().date_naive();
+    let mut count = 0;
+    store.for_each_todo(&mut |todo| count += usize::from(filter.matches_on(&todo, today)))?;
+    println!("{}", count);
+    Ok(())
+}
+
+/// Redraws the list whenever the database changes, and at least every `interval`
+///
+/// Runs until interrupted. Changes are noticed by polling the store's
+/// modification time. A load that fails, e.g. while the file is being
+/// edited by hand, shows the error and keeps watching. The list is never
+/// paged, since the pager would stop the redraws.
+pub fn watch_todos(
+    store: &mut dyn TodoStore,
+    sort_order: SortOrder,
+    filter: &Filter,
+    output: &ListOutput,
+    interval: Duration,
+) -> CommandResult {
+    const POLL: Duration = Duration::from_millis(250);
+    let output = &ListOutput { pager: false, ..output.clone() };
+    loop {
+        let seen = store.last_modified();
+        terminal::clear_screen();
+        println!(
+            "{}",
+            t!("list.watching", seconds = interval.as_secs(), time = Local::now().format("%H:%M:%S")).dimmed()
+        );
+        if let Err(err) = list_todos(store, sort_order.clone(), filter, output) {
+            println!("{}", t!("common.error", error = format!("{:?}", err)));
         }
-        crate::cli::SortOrder::Due => {
-            todos.sort_by(|a, b| {
-                match (a.due_date, b.due_date) {
-                    (Some(a_date), Some(b_date)) => a_date.cmp(&b_date),
-                    (Some(_), None) => std::cmp::Ordering::Less,
-                    (None, Some(_)) => std::cmp::Ordering::Greater,
-                    (None, None) => a.cmp(b),
-                }
-            });
+        std::io::Write::flush(&mut std::io::stdout())?;
+
+        let redraw_at = Instant::now() + interval;
+        while Instant::now() < redraw_at && store.last_modified() == seen {
+            std::thread::sleep(POLL);
         }
-        crate::cli::SortOrder::Priority => {
-            todos.sort_by(|a, b| {
-                b.priority.cmp(&a.priority)
-                    .then_with(|| a.cmp(b))
-            });
+    }
+}
+
+/// Shows the filtered list with a movable cursor for toggling and deleting todos
+///
+/// Changes are kept in memory and saved when quitting with `q`; Ctrl-C
+/// discards them.
+pub fn list_interactive(store: &mut dyn TodoStore, sort_order: SortOrder, filter: &Filter) -> CommandResult {
+    let mut todos = store.load()?;
+    let blocked = blocked_uuids(&todos);
+    let mut view: Vec<Todo> = todos.iter().filter(|t| filter.matches(t)).cloned().collect();
+    sort_todos(&mut view, &sort_order, &blocked);
+    
+    let mut cursor = 0;
+    let mut completed = Vec::new();
+    let mut removed = Vec::new();
+    let save = {
+        let _raw = RawMode::enable()?;
+        loop {
+            terminal::clear_screen();
+            print!("{}", render_interactive(&view, cursor, &blocked));
+            std::io::Write::flush(&mut std::io::stdout())?;
+            
+            match terminal::read_key()? {
+                Key::Up | Key::Char('k') => cursor = cursor.saturating_sub(1),
+                Key::Down | Key::Char('j') if cursor + 1 < view.len() => cursor += 1,
+                Key::Char(' ') if !view.is_empty() => {
+                    let todo = &mut view[cursor];
+                    todo.set_status(if todo.completed { Status::Todo } else { Status::Done });
+                }
+                Key::Char('d') if !view.is_empty() => {
+                    removed.push(view.remove(cursor));
+                    cursor = cursor.min(view.len().saturating_sub(1));
+                }
+                Key::Char('q') => break true,
+                Key::CtrlC => break false,
+                _ => {}
+            }
         }
-        crate::cli::SortOrder::Created => {
-            todos.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    };
+    terminal::clear_screen();
+    
+    if !save {
+        println!("{}", t!("list.discarded"));
+        return Ok(());
+    }
+    
+    // Write the edited view back into the full list
+    todos.retain(|t| !removed.iter().any(|r| r.uuid == t.uuid));
+    for edited in &view {
+        if let Some(todo) = todos.iter_mut().find(|t| t.uuid == edited.uuid) {
+            if edited.completed && !todo.completed {
+                completed.push(edited.clone());
+            }
+            todo.completed = edited.completed;
+            todo.completed_at = edited.completed_at;
+            todo.status = edited.status;
         }
     }
     
-    // Display the todos
-    display_todos(&todos);
+    store.save(&todos)?;
+    store.trash(&removed)?;
+    for todo in &completed {
+        store.record(Operation::Completed, todo)?;
+    }
+    for todo in &removed {
+        store.record(Operation::Removed, todo)?;
+    }
+    
+    println!("{}", t!("list.saved", completed = completed.len(), removed = removed.len()));
+    display_updated(&view);
     
     Ok(())
 }
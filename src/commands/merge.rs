@@ -0,0 +1,114 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::Path;
+use crate::{
+    archive::load_archive,
+    backup::{backup_before_change, load_snapshot, ExtraStore, Extras},
+    events::diff,
+    storage::TodoStore,
+    sync::{merge_copies, summarize, FieldConflict, Side},
+    display::display_updated,
+    t,
+};
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use super::{backup::record_changes, diff::brief, CommandResult};
+
+/// Asks which copy's value to keep for a conflicting field
+fn ask_side(conflict: &FieldConflict) -> Result<Side> {
+    let newer = conflict.newer();
+    let mark = |side: Side| if side == newer { " (newer)" } else { "" };
+    println!("{}", t!("merge.conflict", id = conflict.id, title = conflict.title, field = conflict.field));
+    println!("{}{}", t!("merge.here", value = brief(&conflict.ours)), mark(Side::Ours));
+    println!("{}{}", t!("merge.other", value = brief(&conflict.theirs)), mark(Side::Theirs));
+    print!("Keep which? (1/2, Enter for the newer): ");
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        println!();
+        return Err(anyhow!("No answer on stdin; leave out --interactive to keep the newer values"));
+    }
+    Ok(match input.trim() {
+        "1" => Side::Ours,
+        "2" => Side::Theirs,
+        _ => newer,
+    })
+}
+
+/// Merges the todos in `other`, such as a sync conflict copy, into the database
+///
+/// Todos in both copies are merged field by field, keeping the value from
+/// whichever was modified last, or asking for each field if `interactive`
+/// is set. The database is backed up first, as for other destructive
+/// changes.
+///
+/// The stores in `include` are merged from the files saved next to
+/// `other` by `backup --include`: the archive like the todos, and the
+/// trash by adding the entries it doesn't have yet.
+pub fn merge_file(store: &mut dyn TodoStore, other: &Path, interactive: bool, include: &[ExtraStore]) -> CommandResult {
+    if !other.exists() {
+        return Err(anyhow!("No database at {}", other.display()));
+    }
+    let theirs = load_snapshot(other)?;
+    let their_extras = Extras::read(other, include)?;
+    let ours = store.load()?;
+
+    let mut conflicts = 0;
+    let mut pick = |conflict: &FieldConflict| {
+        conflicts += 1;
+        if interactive { ask_side(conflict) } else { Ok(conflict.newer()) }
+    };
+    let merged = merge_copies(ours.clone(), theirs, &mut pick)?;
+    let summary = summarize(&ours, &merged);
+
+    let mut extras = Extras::default();
+    let mut archive_summary = None;
+    if let Some(theirs) = their_extras.archive {
+        let ours = load_archive(store)?;
+        let archive = merge_copies(ours.clone(), theirs, &mut pick)?;
+        archive_summary = Some(summarize(&ours, &archive));
+        extras.archive = Some(archive);
+    }
+    let mut trash_added = None;
+    if let Some(theirs) = their_extras.trash {
+        let mut trash = store.load_trash()?;
+        let known: HashSet<String> = trash.iter().map(|t| t.todo.uuid.clone()).collect();
+        let before = trash.len();
+        trash.extend(theirs.into_iter().filter(|t| !known.contains(&t.todo.uuid)));
+        trash_added = Some(trash.len() - before);
+        extras.trash = Some(trash);
+    }
+
+    let extras_changed = archive_summary.is_some_and(|s| !s.is_empty()) || trash_added.is_some_and(|n| n > 0);
+    if summary.is_empty() && !extras_changed {
+        println!("{}", t!("merge.nothing", path = other.display()));
+        return Ok(());
+    }
+
+    backup_before_change(store, include)?;
+    store.save(&merged)?;
+    record_changes(store, &diff(&ours, &merged, Local::now()))?;
+    extras.save(store)?;
+    println!(
+        "{}",
+        t!(
+            "merge.done",
+            path = other.display(),
+            added = summary.added,
+            updated = summary.updated,
+            conflicts = conflicts
+        )
+    );
+    if let Some(summary) = archive_summary {
+        println!("{}", t!("include.archive_merged", added = summary.added, updated = summary.updated));
+    }
+    if let Some(added) = trash_added {
+        println!("{}", t!("include.trash_merged", added = added));
+    }
+
+    // Show the updated list
+    display_updated(&merged);
+
+    Ok(())
+}
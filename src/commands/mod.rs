@@ -1,18 +1,132 @@
 //! Command handlers for the todo application
 
 mod add;
+mod alias;
+mod agenda;
+mod attach;
+mod backup;
+mod bench;
+mod block;
+mod board;
+mod checklist;
 mod clear;
+mod comment;
+mod compact;
+mod convert;
+mod daemon;
+mod diff;
+mod digest;
+mod doctor;
+mod escalate;
+mod export;
+#[cfg(feature = "feeds")]
+mod feed;
+mod focus;
+#[cfg(feature = "github")]
+mod github;
+#[cfg(feature = "jira")]
+mod jira;
+mod hide;
+mod history;
 mod done;
+mod duplicate;
+mod edit;
 mod list;
+mod merge;
+mod nag;
+mod next;
+mod notify;
+mod pin;
+mod pomodoro;
 mod priority;
+mod progress;
+mod prompt;
+mod recover;
+mod remind;
 mod remove;
+mod reorder;
+mod report;
+mod resolve;
+mod review;
+#[cfg(feature = "share")]
+mod share;
+mod show;
+mod stale;
+mod stats;
+mod status;
+mod summary;
+mod sync;
+mod template;
+mod timeline;
+mod track;
+mod transfer;
+mod trash;
+mod workload;
 
-pub use add::add_todo;
+pub use add::{add_batch, add_todo, capture_todo, clipboard_builder};
+pub use alias::{add_alias, list_aliases, remove_alias};
+pub use agenda::show_agenda;
+pub use attach::{attach, open_todo};
+pub use backup::{backup_todos, restore_backup};
+pub use bench::bench_storage;
+pub use block::{block_todo, unblock_todo};
+pub use board::show_board;
+pub use checklist::{check_add, check_done};
 pub use clear::clear_todos;
-pub use done::mark_done;
-pub use list::list_todos;
+pub use comment::add_comment;
+pub use compact::compact_todos;
+pub use convert::convert_todos;
+pub use daemon::{daemon_status, run_daemon, start_daemon, stop_daemon};
+pub use diff::{changed_fields, diff_snapshots};
+pub use digest::{digest_message, send_digest};
+pub use doctor::run_doctor;
+pub use escalate::{apply_escalation, escalate_todos};
+pub use export::{export_todos, import_todos};
+#[cfg(feature = "feeds")]
+pub use feed::{apply_feed, feed_add, feed_list, feed_remove, feed_sync, FeedChanges};
+pub use focus::{focus_add, focus_remove, focus_show};
+#[cfg(feature = "github")]
+pub use github::{github_close, github_pull};
+#[cfg(feature = "jira")]
+pub use jira::{jira_done, jira_pull};
+pub use hide::{hide_todo, unhide_todo};
+pub use history::show_history;
+pub use duplicate::duplicate_todo;
+pub use done::{mark_done, mark_done_where};
+pub use edit::{edit_todo, TodoEdit};
+pub use list::{count_todos, list_interactive, list_todos, sort_todos, watch_todos, ListOutput};
+pub use merge::merge_file;
+pub use nag::run_nag;
+pub use next::{actionable_todos, next_todos};
+pub use notify::run_notify;
+pub use pin::{pin_todo, unpin_todo};
+pub use pomodoro::run_pomodoro;
 pub use priority::set_priority;
-pub use remove::remove_todo;
+pub use progress::set_progress;
+pub use prompt::{prompt_segment, render_segment};
+pub use recover::{check_pending, recover_pending};
+#[cfg(feature = "email")]
+pub use remind::email_reminders;
+pub use remind::{due_reminders, reminder_text, show_reminders};
+pub use remove::{remove_todo, remove_where};
+pub use reorder::{reorder_todo, Placement};
+pub use report::{report_done, report_time};
+pub use resolve::resolve_index;
+pub use review::review_todos;
+#[cfg(feature = "share")]
+pub use share::{import_from_url, share_todos};
+pub use show::show_todo;
+pub use stale::{show_stale, stale_todos};
+pub use stats::show_stats;
+pub use status::change_status;
+pub use summary::{cached_summary, show_summary, Summary};
+pub use sync::sync_todos;
+pub use template::{list_templates, save_template};
+pub use timeline::show_timeline;
+pub use track::{start_timer, stop_timer};
+pub use transfer::{transfer_todo, Transfer};
+pub use trash::{empty_trash, list_trash, restore_trash};
+pub use workload::{daily_workload, show_workload, DayLoad};
 
 use anyhow::Result;
 
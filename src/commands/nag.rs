@@ -0,0 +1,73 @@
+use crate::{
+    config::Config,
+    models::Priority,
+    notify::send_desktop,
+    storage::TodoStore,
+    t,
+};
+use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveTime};
+use super::CommandResult;
+
+/// Returns true if `now` falls in a `HH:MM-HH:MM` range, which may wrap past midnight
+fn in_quiet_hours(spec: &str, now: NaiveTime) -> Result<bool> {
+    let parse = |s: &str| NaiveTime::parse_from_str(s.trim(), "%H:%M");
+    let (start, end) = spec
+        .split_once('-')
+        .and_then(|(start, end)| Some((parse(start).ok()?, parse(end).ok()?)))
+        .ok_or_else(|| anyhow!("Invalid nag.quiet_hours '{}'. Use HH:MM-HH:MM, e.g. 22:00-08:00", spec))?;
+
+    Ok(if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    })
+}
+
+/// Prints a one-line nag if the inbox is backing up or urgent todos are overdue
+///
+/// The inbox is open todos without a project; they count once they are
+/// older than `nag.inbox_age`. Prints nothing when all is well or during
+/// `nag.quiet_hours`, so it can run from a shell rc file or cron.
+pub fn run_nag(store: &mut dyn TodoStore) -> CommandResult {
+    let config = Config::load()?.nag;
+    let now = Local::now();
+    if let Some(spec) = &config.quiet_hours {
+        if in_quiet_hours(spec, now.time())? {
+            return Ok(());
+        }
+    }
+
+    let todos = store.load()?;
+    let today = now.date_naive();
+    let open: Vec<_> = todos.iter().filter(|t| !t.completed && !t.is_hidden_on(today)).collect();
+    let stale = open
+        .iter()
+        .filter(|t| t.project.is_none() && now - t.created_at > config.inbox_age.duration())
+        .count();
+    let overdue: Vec<_> = open
+        .iter()
+        .filter(|t| t.priority == Priority::highest() && t.is_overdue_on(today))
+        .collect();
+
+    let mut parts = Vec::new();
+    if stale > 0 {
+        parts.push(t!("nag.inbox", count = stale, age = config.inbox_age));
+    }
+    match overdue.as_slice() {
+        [] => {}
+        [todo] => parts.push(t!("nag.overdue_one", id = todo.id, title = todo.title)),
+        todos => parts.push(t!("nag.overdue_many", count = todos.len())),
+    }
+    if parts.is_empty() {
+        return Ok(());
+    }
+
+    let message = parts.join("; ");
+    println!("{}", t!("nag.nag", message = message));
+    if config.desktop {
+        send_desktop(&t!("nag.title"), &message);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+use crate::{
+    cli::{SortOrder, Spread},
+    deps::blocked_uuids,
+    display::{format_due_date, format_priority, icon_title, paint_todo},
+    models::Todo,
+    storage::TodoStore,
+    t,
+    width::pad,
+};
+use chrono::{Local, NaiveDate};
+use colored::Colorize;
+use super::{list::sort_todos, CommandResult};
+
+/// Takes the first todo of each project in turn, projects ordered by their best todo
+fn round_robin(todos: Vec<Todo>, count: usize) -> Vec<Todo> {
+    let mut groups: Vec<(Option<String>, VecDeque<Todo>)> = Vec::new();
+    for todo in todos {
+        match groups.iter_mut().find(|(project, _)| *project == todo.project) {
+            Some((_, group)) => group.push_back(todo),
+            None => groups.push((todo.project.clone(), VecDeque::from([todo]))),
+        }
+    }
+
+    let mut picked = Vec::new();
+    while picked.len() < count && groups.iter().any(|(_, group)| !group.is_empty()) {
+        for (_, group) in groups.iter_mut() {
+            if picked.len() == count {
+                break;
+            }
+            if let Some(todo) = group.pop_front() {
+                picked.push(todo);
+            }
+        }
+    }
+    picked
+}
+
+/// Returns the todos that can be worked on `today`, most important first
+///
+/// These are open todos that aren't blocked or due to start later, ranked
+/// like the smart sort of `list`. Hidden and scheduled todos count only
+/// with `include_hidden`.
+pub fn actionable_todos(mut todos: Vec<Todo>, today: NaiveDate, include_hidden: bool) -> Vec<Todo> {
+    let blocked = blocked_uuids(&todos);
+    todos.retain(|t| {
+        !t.completed
+            && (include_hidden || !t.is_hidden_on(today))
+            && !blocked.contains(&t.uuid)
+            && t.start_date.is_none_or(|start| start <= today)
+    });
+    sort_todos(&mut todos, &SortOrder::default(), &blocked);
+    todos
+}
+
+/// Shows the `count` todos to work on next, the first one highlighted
+///
+/// With `Spread::Projects` the picks rotate through the projects instead
+/// of following the ranking of [`actionable_todos`] strictly. `all` brings
+/// in hidden and scheduled todos.
+pub fn next_todos(store: &mut dyn TodoStore, count: usize, spread: Option<Spread>, all: bool) -> CommandResult {
+    let todos = actionable_todos(store.load()?, Local::now().date_naive(), all);
+
+    let picked = match spread {
+        Some(Spread::Projects) => round_robin(todos, count),
+        None => todos.into_iter().take(count).collect(),
+    };
+
+    if picked.is_empty() {
+        println!("{}", t!("next.nothing"));
+        return Ok(());
+    }
+
+    for (i, todo) in picked.iter().enumerate() {
+        let title = paint_todo(&icon_title(todo), todo);
+        let (marker, title) = match i {
+            0 => ("→".bold().to_string(), title.bold().to_string()),
+            _ => (" ".to_string(), title),
+        };
+        let mut line = format!("{} #{:<4} {} {}", marker, todo.id, pad(&format_priority(todo.priority), 8), title);
+        if let Some(project) = &todo.project {
+            line.push_str(&format!(" [{}]", project));
+        }
+        if todo.due_date.is_some() {
+            line.push_str(&format!(" ({})", format_due_date(todo.due_date)));
+        }
+        println!("{}", line);
+    }
+
+    Ok(())
+}
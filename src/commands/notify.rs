@@ -0,0 +1,83 @@
+use std::fs;
+use crate::{
+    config::{config_dir, Config},
+    duration::HumanDuration,
+    i18n::format_day,
+    models::Todo,
+    notify::{reminders_between, resurfacing_between, send_desktop},
+    storage::TodoStore,
+    t,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local};
+use super::CommandResult;
+
+const LAST_RUN_FILE: &str = "notify-last-run";
+
+/// Returns when reminders were last fired, by `notify` or the daemon
+pub(super) fn last_run() -> Option<DateTime<Local>> {
+    fs::read_to_string(config_dir().join(LAST_RUN_FILE))
+        .ok()
+        .and_then(|s| DateTime::parse_from_rfc3339(s.trim()).ok())
+        .map(|t| t.with_timezone(&Local))
+}
+
+/// Records that reminders up to `at` have been fired
+pub(super) fn set_last_run(at: DateTime<Local>) -> Result<()> {
+    let state_file = config_dir().join(LAST_RUN_FILE);
+    if let Some(dir) = state_file.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    fs::write(&state_file, at.to_rfc3339())
+        .with_context(|| format!("Failed to write to {}", state_file.display()))
+}
+
+/// Prints and sends the reminders and resurfacing todos in `(from, to]`
+///
+/// With the `webhooks` feature, todos that became overdue in the window
+/// are posted too.
+pub(super) fn fire(todos: &[Todo], config: &Config, from: DateTime<Local>, to: DateTime<Local>) {
+    #[cfg(feature = "webhooks")]
+    crate::integrations::webhooks::deliver_overdue(&config.webhooks, todos, from, to);
+    let config = &config.notify;
+    for reminder in reminders_between(todos, config, from, to) {
+        let when = format!("{} {}", format_day(reminder.due.date_naive()), reminder.due.format("%H:%M"));
+        let body = t!("notify.due", when = when, offset = reminder.offset);
+        println!("{}", t!("notify.reminder", id = reminder.id, title = reminder.title, body = body));
+        if config.desktop {
+            send_desktop(&t!("notify.title", title = reminder.title), &body);
+        }
+    }
+
+    if config.resurface {
+        for todo in resurfacing_between(todos, config, from, to) {
+            println!("{}", t!("notify.resurfaced", id = todo.id, title = todo.title));
+            if config.desktop {
+                send_desktop(&t!("notify.resurfaced_title"), &todo.title);
+            }
+        }
+    }
+}
+
+/// Fires the reminders that came due since the last run (or within `since`)
+///
+/// Hidden todos whose hide date arrived in the same window are announced
+/// too, unless `notify.resurface` is off.
+///
+/// Meant to be run periodically, e.g. from cron. The first run looks back
+/// one hour.
+pub fn run_notify(store: &mut dyn TodoStore, since: Option<HumanDuration>) -> CommandResult {
+    let config = Config::load()?;
+    let now = Local::now();
+
+    let from = match since {
+        Some(window) => now - window.duration(),
+        None => last_run().unwrap_or_else(|| now - Duration::hours(1)),
+    };
+
+    let todos = store.load()?;
+    fire(&todos, &config, from, now);
+    set_last_run(now)?;
+
+    Ok(())
+}
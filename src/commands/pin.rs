@@ -0,0 +1,51 @@
+use crate::{
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use super::{resolve::resolve_index, CommandResult};
+
+/// Pins a todo to the top of every list
+pub fn pin_todo(store: &mut dyn TodoStore, target: &str) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    let id = todo.id;
+
+    if todo.pinned {
+        println!("{}", t!("pin.already", id = id));
+        return Ok(());
+    }
+
+    todo.pinned = true;
+    let title = todo.title.clone();
+    store.save(&todos)?;
+    println!("{}", t!("pin.pinned", id = id, title = title));
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
+
+/// Lets a pinned todo take its place in the sort order again
+pub fn unpin_todo(store: &mut dyn TodoStore, target: &str) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    let id = todo.id;
+
+    if !todo.pinned {
+        println!("{}", t!("unpin.not_pinned", id = id));
+        return Ok(());
+    }
+
+    todo.pinned = false;
+    store.save(&todos)?;
+    println!("{}", t!("unpin.done", id = id));
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
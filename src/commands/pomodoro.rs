@@ -0,0 +1,96 @@
+use std::io::{self, IsTerminal, Write};
+use std::thread;
+use std::time::Duration as StdDuration;
+use crate::{
+    config::Config,
+    duration::HumanDuration,
+    models::TimeEntry,
+    notify::send_desktop,
+    storage::TodoStore,
+    t,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use super::{resolve::resolve_index, CommandResult};
+
+/// Counts down `length`, redrawing a single status line once a second on terminals
+fn countdown(label: &str, title: &str, length: HumanDuration) -> Result<()> {
+    let end = Local::now() + length.duration();
+    let live = io::stdout().is_terminal();
+    if !live {
+        println!("{} ({}): {}", label, length, title);
+    }
+
+    loop {
+        let remaining = (end - Local::now()).num_milliseconds().max(0);
+        let left = (remaining + 999) / 1000;
+        if live {
+            print!("\r\x1b[K{} {:02}:{:02}  {}", label, left / 60, left % 60, title);
+            io::stdout().flush()?;
+        }
+        if left == 0 {
+            break;
+        }
+        thread::sleep(StdDuration::from_millis(remaining.min(1000) as u64));
+    }
+
+    if live {
+        println!();
+    }
+    Ok(())
+}
+
+/// Counts a finished work interval on the todo and logs it as tracked time
+fn record_pomodoro(store: &mut dyn TodoStore, uuid: &str, start: DateTime<Local>) -> Result<u32> {
+    let mut todos = store.load()?;
+    let todo = todos
+        .iter_mut()
+        .find(|t| t.uuid == uuid)
+        .ok_or_else(|| anyhow!("The todo was removed during the pomodoro"))?;
+    todo.pomodoros_completed += 1;
+    todo.time_entries.push(TimeEntry { start, end: Some(Local::now()) });
+    let count = todo.pomodoros_completed;
+    store.save(&todos)?;
+    Ok(count)
+}
+
+/// Runs work/break intervals on a todo until `cycles` work intervals are done
+///
+/// Interval lengths come from the `pomodoro` config section. Each finished
+/// work interval is saved straight away, so stopping with Ctrl-C only loses
+/// the interval in progress.
+pub fn run_pomodoro(store: &mut dyn TodoStore, target: &str, cycles: Option<u32>) -> CommandResult {
+    let config = Config::load()?.pomodoro;
+    let todos = store.load()?;
+    let todo = &todos[resolve_index(&todos, target)?];
+    if todo.completed {
+        return Err(anyhow!("Todo #{} is already done", todo.id));
+    }
+    let (id, uuid, title) = (todo.id, todo.uuid.clone(), todo.title.clone());
+    println!("{}", t!("pomodoro.started", id = id, title = title, work = config.work, rest = config.short_break));
+
+    let mut done = 0;
+    loop {
+        let start = Local::now();
+        countdown(&t!("pomodoro.work"), &title, config.work)?;
+        let count = record_pomodoro(store, &uuid, start)?;
+        done += 1;
+        println!("{}", t!("pomodoro.finished", done = done, id = id, count = count));
+        if config.notify {
+            send_desktop(&t!("pomodoro.finished_title"), &t!("pomodoro.take_break", title = title));
+        }
+
+        if cycles.is_some_and(|cycles| done >= cycles) {
+            break;
+        }
+
+        let long = config.long_break_every > 0 && done % config.long_break_every == 0;
+        let length = if long { config.long_break } else { config.short_break };
+        countdown(&t!("pomodoro.break"), &title, length)?;
+        if config.notify {
+            send_desktop(&t!("pomodoro.break_over"), &t!("pomodoro.back_to", title = title));
+        }
+    }
+
+    Ok(())
+}
@@ -1,29 +1,28 @@
 use crate::{
     models::Priority,
-    storage::{load_todos, save_todos},
-    display::display_todos,
+    storage::TodoStore,
+    display::display_updated,
+    t,
 };
-use super::CommandResult;
+use super::{resolve::resolve_index, CommandResult};
 
 /// Updates the priority of a todo
-pub fn set_priority(id: u64, new_priority: Priority) -> CommandResult {
-    let mut todos = load_todos()?;
+pub fn set_priority(store: &mut dyn TodoStore, target: &str, new_priority: Priority) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    let id = todo.id;
     
-    if let Some(todo) = todos.iter_mut().find(|t| t.id == id) {
-        if todo.priority == new_priority {
-            println!("Todo #{} already has priority: {}", id, new_priority);
-        } else {
-            let old_priority = std::mem::replace(&mut todo.priority, new_priority);
-            save_todos(&todos)?;
-            println!("Updated priority of todo #{} from {} to {}", 
-                     id, old_priority, new_priority);
-        }
+    if todo.priority == new_priority {
+        println!("{}", t!("priority.same", id = id, priority = new_priority));
     } else {
-        return Err(anyhow::anyhow!("Todo #{} not found", id));
+        let old_priority = std::mem::replace(&mut todo.priority, new_priority);
+        store.save(&todos)?;
+        println!("{}", t!("priority.updated", id = id, from = old_priority, to = new_priority));
     }
     
     // Show the updated list
-    display_todos(&todos);
+    display_updated(&todos);
     
     Ok(())
 }
@@ -0,0 +1,38 @@
+use crate::{
+    error::TodoError,
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use super::{resolve::resolve_index, CommandResult};
+
+/// Sets how far along a todo is, in percent
+///
+/// Todos with a checklist take their progress from it instead, so they
+/// can't be set by hand.
+pub fn set_progress(store: &mut dyn TodoStore, target: &str, percent: u8) -> CommandResult {
+    if percent > 100 {
+        return Err(TodoError::Validation(format!("Progress {}% is over 100%", percent)).into());
+    }
+
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    if !todo.checklist.is_empty() {
+        return Err(TodoError::Validation(format!(
+            "Todo #{} takes its progress from its checklist; tick items off with `check done`",
+            todo.id
+        ))
+        .into());
+    }
+
+    todo.progress = Some(percent);
+    let id = todo.id;
+    store.save(&todos)?;
+    println!("{}", t!("progress.set", id = id, percent = percent));
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
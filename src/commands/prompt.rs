@@ -0,0 +1,101 @@
+use crate::{
+    cli::PromptShell,
+    config::{Config, PromptConfig},
+    display::{check_style, paint},
+    error::TodoError,
+    storage::TodoStore,
+};
+use anyhow::Result;
+use chrono::Local;
+use super::{summary::{cached_summary, Summary}, CommandResult};
+
+/// Returns the count a `{name}` placeholder stands for
+fn count(summary: &Summary, name: &str) -> Option<usize> {
+    match name {
+        "active" => Some(summary.active),
+        "due_today" => Some(summary.due_today),
+        "overdue" => Some(summary.overdue),
+        "urgent" => Some(summary.urgent),
+        "done_today" => Some(summary.done_today),
+        _ => None,
+    }
+}
+
+/// Fills in the prompt format, leaving out parts whose counts are all zero
+///
+/// Each part is painted with the style of the first count it shows.
+/// Unknown or unclosed placeholders are validation errors.
+pub fn render_segment(config: &PromptConfig, summary: &Summary) -> Result<String> {
+    let mut parts = Vec::new();
+    for part in config.format.split_whitespace() {
+        let mut text = String::new();
+        let mut rest = part;
+        let mut first = None;
+        let mut any_counts = false;
+        let mut nonzero = false;
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+            let end = rest[start..].find('}').map(|i| start + i).ok_or_else(|| {
+                TodoError::Validation(format!("Unclosed '{{' in prompt.format part '{}'", part))
+            })?;
+            let name = &rest[start + 1..end];
+            let value = count(summary, name).ok_or_else(|| {
+                TodoError::Validation(format!(
+                    "Unknown count '{{{}}}' in prompt.format; use active, due_today, overdue, urgent, or done_today",
+                    name
+                ))
+            })?;
+            first.get_or_insert(name);
+            any_counts = true;
+            nonzero |= value > 0;
+            text.push_str(&value.to_string());
+            rest = &rest[end + 1..];
+        }
+        text.push_str(rest);
+
+        if any_counts && !nonzero {
+            continue;
+        }
+        parts.push(match first.and_then(|name| config.styles.get(name)) {
+            Some(style) => paint(&text, style),
+            None => text,
+        });
+    }
+    Ok(parts.join(" "))
+}
+
+/// Wraps each color code so the shell knows it takes up no room
+fn wrap_escapes(text: &str, shell: PromptShell) -> String {
+    let (open, close) = match shell {
+        PromptShell::Plain => return text.to_string(),
+        PromptShell::Bash => ("\\[", "\\]"),
+        PromptShell::Zsh => ("%{", "%}"),
+    };
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('\x1b') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..].find('m').map_or(rest.len(), |i| start + i + 1);
+        out.push_str(open);
+        out.push_str(&rest[start..end]);
+        out.push_str(close);
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Prints the counts for a shell prompt as set by `prompt.format`, or an
+/// empty line when every count is zero
+///
+/// Counts come from the summary cache, so drawing a prompt doesn't parse
+/// the database unless it changed.
+pub fn prompt_segment(store: &mut dyn TodoStore, shell: PromptShell) -> CommandResult {
+    let config = Config::load()?.prompt;
+    for style in config.styles.values() {
+        check_style(style).map_err(TodoError::Validation)?;
+    }
+    let summary = cached_summary(store, Local::now().date_naive())?;
+    println!("{}", wrap_escapes(&render_segment(&config, &summary)?, shell));
+    Ok(())
+}
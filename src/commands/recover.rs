@@ -0,0 +1,113 @@
+use std::io::{self, Write};
+use crate::{
+    error::TodoError,
+    models::Todo,
+    storage::TodoStore,
+    display::display_updated,
+    sync::{summarize, MergeSummary},
+    t,
+    terminal,
+};
+use anyhow::{anyhow, Result};
+use super::CommandResult;
+
+/// Returns true if the pending changes were found but couldn't be read, e.g. torn by a crash
+fn unreadable(err: &anyhow::Error) -> bool {
+    matches!(err.downcast_ref::<TodoError>(), Some(TodoError::Parse { .. }))
+}
+
+fn describe(summary: &MergeSummary) -> String {
+    t!("recover.summary", added = summary.added, removed = summary.removed, updated = summary.updated)
+}
+
+fn replay(store: &mut dyn TodoStore, pending: &[Todo], summary: &MergeSummary) -> CommandResult {
+    store.save(pending)?;
+    println!("{}", t!("recover.replayed", summary = describe(summary)));
+    display_updated(pending);
+    Ok(())
+}
+
+/// Replays or discards changes left by an interrupted save
+///
+/// Changes too damaged to read can only be discarded.
+pub fn recover_pending(store: &mut dyn TodoStore, discard: bool) -> CommandResult {
+    let pending = match store.pending() {
+        Ok(pending) => pending,
+        Err(err) if unreadable(&err) && discard => {
+            store.discard_pending()?;
+            println!("{}", t!("recover.discarded_unreadable"));
+            return Ok(());
+        }
+        Err(err) if unreadable(&err) => {
+            return Err(err.context("The changes left by an interrupted save can't be read; run `todo-cli recover --discard` to drop them"));
+        }
+        Err(err) => return Err(err),
+    };
+    let Some(pending) = pending else {
+        println!("{}", t!("recover.none"));
+        return Ok(());
+    };
+
+    let summary = summarize(&store.load()?, &pending);
+    if discard {
+        store.discard_pending()?;
+        println!("{}", t!("recover.discarded", summary = describe(&summary)));
+        Ok(())
+    } else {
+        replay(store, &pending, &summary)
+    }
+}
+
+/// Returns the pending changes, warning about and skipping any that can't be read
+fn readable_pending(store: &dyn TodoStore) -> Result<Option<Vec<Todo>>> {
+    match store.pending() {
+        Err(err) if unreadable(&err) => {
+            eprintln!("{}", t!("recover.unreadable", error = err));
+            Ok(None)
+        }
+        pending => pending,
+    }
+}
+
+/// Checks for changes left by an interrupted save before running a command
+///
+/// Changes that already reached the database are cleaned up silently.
+/// Otherwise the user is asked to replay or discard them; without a
+/// terminal to ask on, this fails so a later save can't overwrite them.
+/// Changes too damaged to read are only warned about, since nothing can
+/// be replayed from them.
+pub fn check_pending(store: &mut dyn TodoStore) -> CommandResult {
+    let Some(pending) = readable_pending(store)? else {
+        return Ok(());
+    };
+
+    // The save completed but the process died before cleaning up
+    let summary = summarize(&store.load()?, &pending);
+    if summary.is_empty() {
+        return store.discard_pending();
+    }
+
+    if !terminal::is_interactive() {
+        return Err(anyhow!(
+            "An interrupted save left unsaved changes ({}); run `todo-cli recover` to replay them or `todo-cli recover --discard` to drop them",
+            describe(&summary)
+        ));
+    }
+
+    println!("{}", t!("recover.found", summary = describe(&summary)));
+    print!("{}", t!("recover.ask"));
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    match input.trim().to_lowercase().as_str() {
+        "r" | "replay" => replay(store, &pending, &summary),
+        "d" | "discard" => {
+            store.discard_pending()?;
+            println!("{}", t!("recover.discarded_all"));
+            Ok(())
+        }
+        _ => Err(anyhow!("Pending changes left in place; run `todo-cli recover` to deal with them")),
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    display::{group_todos, Group, Grouping},
+    i18n::{format_date, format_day},
+    models::Todo,
+    storage::TodoStore,
+    t,
+};
+use chrono::{Local, NaiveDate};
+use super::CommandResult;
+
+/// Returns the open todos that are overdue or due on `today`, earliest due first
+pub fn due_reminders(todos: &[Todo], today: NaiveDate) -> Vec<Todo> {
+    let mut due: Vec<Todo> = todos
+        .iter()
+        .filter(|t| !t.completed && t.due_date.is_some_and(|d| d <= today))
+        .cloned()
+        .collect();
+    due.sort_by_key(|t| (t.due_date, t.id));
+    due
+}
+
+/// Returns the line summing up `due`, e.g. "Todos for Tue Oct 14: 2 overdue, 1 due today"
+fn summary_line(due: &[Todo], today: NaiveDate) -> String {
+    let overdue = due.iter().filter(|t| t.is_overdue_on(today)).count();
+    t!("remind.summary", day = format_day(today), overdue = overdue, due = due.len() - overdue)
+}
+
+/// Describes when a todo was due, as seen from `today`
+fn when(todo: &Todo, today: NaiveDate) -> String {
+    match todo.due_date {
+        Some(due) if due < today => t!("remind.overdue", date = format_date(due)),
+        _ => t!("remind.due_today"),
+    }
+}
+
+/// Formats `due` as plain text, grouped by priority, highest first
+pub fn reminder_text(due: &[Todo], today: NaiveDate) -> String {
+    let mut lines = vec![summary_line(due, today)];
+    for Group { heading, todos } in group_todos(due, Grouping::Priority, today) {
+        lines.push(String::new());
+        lines.push(heading);
+        for todo in todos {
+            lines.push(format!("  #{} {} ({})", todo.id, todo.title, when(todo, today)));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Prints the todos that are due today or overdue, grouped by priority
+pub fn show_reminders(store: &mut dyn TodoStore) -> CommandResult {
+    let today = Local::now().date_naive();
+    let due = due_reminders(&store.load()?, today);
+    if due.is_empty() {
+        println!("{}", t!("remind.nothing"));
+        return Ok(());
+    }
+    println!("{}", reminder_text(&due, today));
+    Ok(())
+}
+
+/// Formats `due` as an HTML email body, grouped by priority, highest first
+#[cfg(feature = "email")]
+fn reminder_html(due: &[Todo], today: NaiveDate) -> String {
+    use crate::integrations::email::escape_html;
+
+    let mut html = format!("<h2>{}</h2>\n", escape_html(&summary_line(due, today)));
+    for Group { heading, todos } in group_todos(due, Grouping::Priority, today) {
+        html.push_str(&format!("<h3>{}</h3>\n<ul>\n", escape_html(&heading)));
+        for todo in todos {
+            let style = if todo.is_overdue_on(today) { " style=\"color: #c0392b\"" } else { "" };
+            html.push_str(&format!(
+                "  <li>#{} {} <em{}>({})</em></li>\n",
+                todo.id,
+                escape_html(&todo.title),
+                style,
+                when(todo, today)
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+    html
+}
+
+/// Emails the todos that are due today or overdue, grouped by priority
+///
+/// Sends nothing when no todo is due, so it can run from cron every
+/// morning. See `email` in the config for the server and recipient.
+#[cfg(feature = "email")]
+pub fn email_reminders(store: &mut dyn TodoStore) -> CommandResult {
+    use crate::{config::Config, integrations::email::{send, Email}};
+
+    let config = Config::load()?.email;
+    let today = Local::now().date_naive();
+    let due = due_reminders(&store.load()?, today);
+    if due.is_empty() {
+        println!("{}", t!("remind.no_email"));
+        return Ok(());
+    }
+
+    let email = Email {
+        subject: summary_line(&due, today),
+        text: reminder_text(&due, today),
+        html: reminder_html(&due, today),
+    };
+    send(&config, &email)?;
+    println!("{}", t!("remind.emailed", count = due.len(), to = config.to.as_deref().unwrap_or_default()));
+
+    Ok(())
+}
@@ -1,24 +1,73 @@
 use crate::{
-    storage::{load_todos, save_todos},
-    display::display_todos,
+    journal::Operation,
+    query::Query,
+    storage::TodoStore,
+    display::display_updated,
+    t,
+    terminal::confirm,
 };
-use super::CommandResult;
+use super::{resolve::resolve_index, CommandResult};
 
-/// Removes a todo by its ID
-pub fn remove_todo(id: u64) -> CommandResult {
-    let mut todos = load_todos()?;
-    let original_len = todos.len();
-    
-    todos.retain(|t| t.id != id);
+/// Moves a todo to the trash by its ID or UUID prefix, first asking if `ask` is set
+///
+/// With `dry_run` the store is only staging, so the todo is reported as
+/// one that would be removed.
+pub fn remove_todo(store: &mut dyn TodoStore, target: &str, ask: bool, dry_run: bool) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    if ask && !confirm(&t!("remove.confirm", id = todos[index].id, title = todos[index].title))? {
+        println!("{}", t!("common.cancelled"));
+        return Ok(());
+    }
     
-    if todos.len() < original_len {
-        save_todos(&todos)?;
-        println!("Removed todo #{}", id);
-        
-        // Show the updated list
-        display_todos(&todos);
-        Ok(())
+    let removed = todos.remove(index);
+    store.save(&todos)?;
+    store.trash(std::slice::from_ref(&removed))?;
+    store.record(Operation::Removed, &removed)?;
+    if dry_run {
+        println!("{}", t!("remove.would_remove", id = removed.id));
     } else {
-        Err(anyhow::anyhow!("Todo #{} not found", id))
+        println!("{}", t!("remove.removed", id = removed.id));
     }
+    
+    // Show the updated list
+    display_updated(&todos);
+    Ok(())
+}
+
+/// Moves every todo matching the query to the trash, first listing them
+/// and asking if `ask` is set; `dry_run` is as for [`remove_todo`]
+pub fn remove_where(store: &mut dyn TodoStore, query: &Query, ask: bool, dry_run: bool) -> CommandResult {
+    let (removed, todos): (Vec<_>, Vec<_>) = store.load()?
+        .into_iter()
+        .partition(|t| query.matches(t));
+    
+    if removed.is_empty() {
+        println!("{}", t!("remove.no_match"));
+        return Ok(());
+    }
+    if ask {
+        for todo in &removed {
+            println!("  #{} {}", todo.id, todo.title);
+        }
+        if !confirm(&t!("remove.confirm_many", count = removed.len()))? {
+            println!("{}", t!("common.cancelled"));
+            return Ok(());
+        }
+    }
+    
+    store.save(&todos)?;
+    store.trash(&removed)?;
+    for todo in &removed {
+        store.record(Operation::Removed, todo)?;
+        if dry_run {
+            println!("{}", t!("remove.would_remove_title", id = todo.id, title = todo.title));
+        } else {
+            println!("{}", t!("remove.removed_title", id = todo.id, title = todo.title));
+        }
+    }
+    
+    // Show the updated list
+    display_updated(&todos);
+    Ok(())
 }
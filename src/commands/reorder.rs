@@ -0,0 +1,70 @@
+use crate::{
+    error::TodoError,
+    models::Todo,
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use super::{resolve::resolve_index, CommandResult};
+
+/// Which side of the other todo a todo is moved to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    Before,
+    After,
+}
+
+/// Compares todos by their place in the manual order
+fn manual_order(a: &Todo, b: &Todo) -> std::cmp::Ordering {
+    a.manual_key().total_cmp(&b.manual_key()).then(a.id.cmp(&b.id))
+}
+
+/// Moves a todo just before or after `anchor` in the manual order
+///
+/// Only the moved todo's `sort_key` changes, to halfway between its new
+/// neighbors. When they are too close together for that, every todo is
+/// numbered afresh in its current order.
+pub fn reorder_todo(store: &mut dyn TodoStore, target: &str, anchor: &str, placement: Placement) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let anchor = resolve_index(&todos, anchor)?;
+    if index == anchor {
+        let id = todos[index].id;
+        return Err(TodoError::Validation(format!("Todo #{} cannot be moved next to itself", id)).into());
+    }
+
+    // The others in manual order, and where the todo goes among them
+    let mut order: Vec<usize> = (0..todos.len()).filter(|&i| i != index).collect();
+    order.sort_by(|&a, &b| manual_order(&todos[a], &todos[b]));
+    let at = order.iter().position(|&i| i == anchor).unwrap_or(0) + usize::from(placement == Placement::After);
+
+    let before = at.checked_sub(1).map(|i| todos[order[i]].manual_key());
+    let after = order.get(at).map(|&i| todos[i].manual_key());
+    let key = match (before, after) {
+        (Some(before), Some(after)) => (before + after) / 2.0,
+        (Some(before), None) => before + 1.0,
+        (None, Some(after)) => after - 1.0,
+        (None, None) => 0.0,
+    };
+    if before.is_some_and(|before| key <= before) || after.is_some_and(|after| key >= after) {
+        order.insert(at, index);
+        for (place, &i) in order.iter().enumerate() {
+            todos[i].sort_key = Some(place as f64 + 1.0);
+        }
+    } else {
+        todos[index].sort_key = Some(key);
+    }
+
+    store.save(&todos)?;
+    let (id, title, other) = (todos[index].id, todos[index].title.clone(), todos[anchor].id);
+    let message = match placement {
+        Placement::Before => t!("reorder.before", id = id, title = title, other = other),
+        Placement::After => t!("reorder.after", id = id, title = title, other = other),
+    };
+    println!("{}", message);
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
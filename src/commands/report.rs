@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use crate::{
+    cli::{DoneGrouping, ReportPeriod},
+    i18n::{format_date, format_day, format_full_day},
+    models::{Status, Todo},
+    storage::TodoStore,
+    t,
+    width::{pad, truncate},
+};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use super::{stats::week_start, track::format_tracked, CommandResult};
+
+/// Prints the time tracked per todo for each day or week since `since`
+///
+/// Entries count toward the period they started in; a running timer counts
+/// up to now. Without `since`, the last 7 days or 4 weeks are shown.
+pub fn report_time(store: &mut dyn TodoStore, period: ReportPeriod, since: Option<NaiveDate>) -> CommandResult {
+    let todos = store.load()?;
+    let now = Local::now();
+    let since = since.unwrap_or_else(|| match period {
+        ReportPeriod::Day => now.date_naive() - Duration::days(6),
+        ReportPeriod::Week => week_start(&now) - Duration::weeks(3),
+    });
+    
+    // Period start -> todo position -> time spent
+    let mut by_period: BTreeMap<NaiveDate, BTreeMap<usize, Duration>> = BTreeMap::new();
+    for (index, todo) in todos.iter().enumerate() {
+        for entry in todo.time_entries.iter().filter(|e| e.start.date_naive() >= since) {
+            let key = match period {
+                ReportPeriod::Day => entry.start.date_naive(),
+                ReportPeriod::Week => week_start(&entry.start),
+            };
+            *by_period.entry(key).or_default().entry(index).or_insert_with(Duration::zero) += entry.duration_at(now);
+        }
+    }
+    
+    if by_period.is_empty() {
+        println!("{}", t!("report.nothing_tracked", date = format_date(since)));
+        return Ok(());
+    }
+    
+    let mut total = Duration::zero();
+    for (start, spent) in &by_period {
+        let period_total = spent.values().fold(Duration::zero(), |acc, d| acc + *d);
+        total += period_total;
+        let label = match period {
+            ReportPeriod::Day => format_full_day(*start),
+            ReportPeriod::Week => t!("report.week_of", date = format_date(*start)),
+        };
+        println!("{:<42} {:>8} {:>7}", label, format_tracked(period_total), hours(period_total));
+        
+        for (&index, &duration) in spent {
+            let todo = &todos[index];
+            let title = pad(&truncate(&todo.title, 33), 33);
+            println!("  #{:<5} {} {:>8} {:>7}", todo.id, title, format_tracked(duration), hours(duration));
+        }
+        println!();
+    }
+    
+    println!("{:<42} {:>8} {:>7}", t!("report.total"), format_tracked(total), hours(total));
+    
+    Ok(())
+}
+
+/// Prints the todos completed from `since` through `until`, grouped by day, week, or project
+///
+/// Only todos marked done count, not cancelled ones, and each goes by the
+/// local day it was completed on. `until` defaults to today.
+pub fn report_done(store: &mut dyn TodoStore, by: DoneGrouping, since: NaiveDate, until: Option<NaiveDate>) -> CommandResult {
+    let todos = store.load()?;
+    let until = until.unwrap_or_else(|| Local::now().date_naive());
+    let mut done: Vec<(DateTime<Local>, &Todo)> = todos
+        .iter()
+        .filter(|t| t.status == Status::Done)
+        .filter_map(|t| Some((t.completed_at?, t)))
+        .filter(|(completed, _)| (since..=until).contains(&completed.date_naive()))
+        .collect();
+    done.sort_by_key(|(completed, _)| *completed);
+
+    let range = t!("report.range", since = format_full_day(since), until = format_full_day(until));
+    if done.is_empty() {
+        println!("{}", t!("report.nothing_done", range = range));
+        return Ok(());
+    }
+    println!("{}", t!("report.done", range = range, count = done.len()));
+
+    // (day or week, no project, project) -> todos completed, oldest first; todos without a project go last
+    let mut groups: BTreeMap<(NaiveDate, bool, &str), Vec<&Todo>> = BTreeMap::new();
+    for (completed, todo) in done {
+        let key = match by {
+            DoneGrouping::Day => (completed.date_naive(), false, ""),
+            DoneGrouping::Week => (week_start(&completed), false, ""),
+            DoneGrouping::Project => (since, todo.project.is_none(), todo.project.as_deref().unwrap_or("")),
+        };
+        groups.entry(key).or_default().push(todo);
+    }
+
+    for ((start, no_project, project), todos) in groups {
+        let heading = match by {
+            DoneGrouping::Day => format_full_day(start),
+            DoneGrouping::Week => t!("report.week_of", date = format_date(start)),
+            DoneGrouping::Project if no_project => t!("common.no_project"),
+            DoneGrouping::Project => project.to_string(),
+        };
+        println!();
+        println!("{} ({})", heading, todos.len());
+        for todo in todos {
+            let title = pad(&truncate(&todo.title, 40), 40);
+            let detail = match (by, todo.completed_at) {
+                (DoneGrouping::Project, Some(completed)) => format_day(completed.date_naive()),
+                _ => todo.project.clone().unwrap_or_default(),
+            };
+            println!("{}", format!("  #{:<5} {} {}", todo.id, title, detail).trim_end());
+        }
+    }
+
+    Ok(())
+}
+
+/// Formats a duration as decimal hours, e.g. "1.25h"
+pub(super) fn hours(duration: Duration) -> String {
+    format!("{:.2}h", duration.num_minutes() as f64 / 60.0)
+}
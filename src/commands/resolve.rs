@@ -0,0 +1,117 @@
+use crate::error::TodoError;
+use crate::models::Todo;
+use crate::t;
+use crate::terminal;
+use anyhow::Result;
+use std::io::{self, Write};
+
+/// Digits a UUID prefix needs when it has no letters, so a mistyped ID
+/// doesn't land on whichever UUID happens to start with it
+const MIN_DIGIT_PREFIX: usize = 4;
+
+/// Finds the position of the todo referenced by `target`
+///
+/// The target may be a numeric ID, a (case-insensitive) prefix of the
+/// todo's UUID, or part of its title. Numeric IDs take precedence, so a
+/// UUID prefix made only of digits is tried after no todo with that ID
+/// exists, and only if it's at least four digits long; titles are
+/// searched last. Numbers are never looked up as
+/// titles, so `done 12` can't finish "Buy 1 apple and 2 pears". When
+/// several titles match, the user picks one from a menu, or the lookup
+/// fails if not on a terminal.
+pub fn resolve_index(todos: &[Todo], target: &str) -> Result<usize> {
+    let target = target.trim();
+
+    if let Ok(id) = target.parse::<u64>() {
+        if let Some(index) = todos.iter().position(|t| t.id == id) {
+            return Ok(index);
+        }
+    }
+
+    let prefix = target.to_lowercase();
+    let digits_only = prefix.chars().all(|c| c.is_ascii_digit());
+    let long_enough = !prefix.is_empty() && (!digits_only || prefix.len() >= MIN_DIGIT_PREFIX);
+    if long_enough && prefix.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+        let matches: Vec<usize> = todos
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.uuid.starts_with(&prefix))
+            .map(|(i, _)| i)
+            .collect();
+
+        match matches.len() {
+            0 => {}
+            1 => return Ok(matches[0]),
+            matches => {
+                return Err(TodoError::Ambiguous { prefix: target.to_string(), matches }.into());
+            }
+        }
+    }
+
+    if target.chars().all(|c| c.is_ascii_digit()) {
+        return Err(TodoError::NotFound { id: target.to_string() }.into());
+    }
+    let matches = title_matches(todos, target);
+    match matches.as_slice() {
+        [] => Err(TodoError::NotFound { id: target.to_string() }.into()),
+        [index] => Ok(*index),
+        _ if terminal::is_interactive() => choose(todos, target, &matches),
+        _ => Err(ambiguous_title(todos, target, &matches)),
+    }
+}
+
+/// Finds todos whose title matches `query`, best matches only
+///
+/// An exact title (ignoring case) beats a title containing the query, which
+/// beats one containing its characters in order ("dntst" finds "Dentist").
+/// Open todos are preferred over completed ones.
+fn title_matches(todos: &[Todo], query: &str) -> Vec<usize> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let titles: Vec<String> = todos.iter().map(|t| t.title.to_lowercase()).collect();
+    let tiers: [&dyn Fn(&str) -> bool; 3] = [
+        &|title| title == query,
+        &|title| title.contains(&query),
+        &|title| is_subsequence(&query, title),
+    ];
+    for matches_tier in tiers {
+        let matches: Vec<usize> = (0..todos.len()).filter(|&i| matches_tier(&titles[i])).collect();
+        if matches.is_empty() {
+            continue;
+        }
+        let open: Vec<usize> = matches.iter().copied().filter(|&i| !todos[i].completed).collect();
+        return if open.is_empty() { matches } else { open };
+    }
+    Vec::new()
+}
+
+/// Returns true if the characters of `needle` appear in `haystack` in order
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().filter(|c| !c.is_whitespace()).all(|c| haystack.any(|h| h == c))
+}
+
+fn ambiguous_title(todos: &[Todo], query: &str, matches: &[usize]) -> anyhow::Error {
+    let ids = matches.iter().map(|&i| todos[i].id).collect();
+    TodoError::AmbiguousTitle { query: query.to_string(), ids }.into()
+}
+
+/// Asks which of several matching todos was meant
+fn choose(todos: &[Todo], query: &str, matches: &[usize]) -> Result<usize> {
+    println!("{}", t!("resolve.several", query = query));
+    for (n, &index) in matches.iter().enumerate() {
+        println!("  {}) #{} {}", n + 1, todos[index].id, todos[index].title);
+    }
+    print!("{}", t!("resolve.which", count = matches.len()));
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    match input.trim().parse::<usize>() {
+        Ok(n) if (1..=matches.len()).contains(&n) => Ok(matches[n - 1]),
+        _ => Err(ambiguous_title(todos, query, matches)),
+    }
+}
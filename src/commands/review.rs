@@ -0,0 +1,200 @@
+use std::io::{self, BufRead, Write};
+use crate::{
+    archive::archive_todos,
+    cli::{parse_date_input, SortOrder},
+    deps::blocked_uuids,
+    display::{display_updated, TableLayout, TodoDisplay},
+    duration::HumanDuration,
+    journal::Operation,
+    models::{Priority, Todo},
+    storage::TodoStore,
+    t,
+    validate,
+};
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use super::{list::sort_todos, CommandResult};
+
+/// What to do with the todo under review
+enum Action {
+    Keep,
+    Reschedule,
+    Priority,
+    Tag,
+    Archive,
+    Delete,
+    Skip,
+    Quit,
+}
+
+/// Prints `question` and reads one line of the answer, or `None` once input ends
+fn ask(input: &mut dyn BufRead, question: &str) -> Result<Option<String>> {
+    print!("{} ", question);
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        println!();
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+/// Asks what to do with the todo until the answer names an action
+fn ask_action(input: &mut dyn BufRead) -> Result<Action> {
+    loop {
+        let Some(answer) = ask(input, &t!("review.prompt"))? else {
+            return Ok(Action::Quit);
+        };
+        return Ok(match answer.to_lowercase().as_str() {
+            "" | "k" | "keep" => Action::Keep,
+            "r" | "reschedule" => Action::Reschedule,
+            "p" | "priority" => Action::Priority,
+            "t" | "tag" => Action::Tag,
+            "a" | "archive" => Action::Archive,
+            "d" | "delete" => Action::Delete,
+            "s" | "skip" => Action::Skip,
+            "q" | "quit" => Action::Quit,
+            _ => {
+                println!("{}", t!("review.help"));
+                continue;
+            }
+        });
+    }
+}
+
+/// Returns true if `todo` is open, not hidden, and, unless `all` is set,
+/// wasn't reviewed within `interval` of `today`
+fn needs_review(todo: &Todo, today: NaiveDate, interval: HumanDuration, all: bool) -> bool {
+    let recent = todo.last_reviewed.is_some_and(|reviewed| today - reviewed < interval.duration());
+    !todo.completed && !todo.is_hidden_on(today) && (all || !recent)
+}
+
+/// Changes the todo under review as `action` asks, returning false if the answer was left out
+fn change(todo: &mut Todo, action: &Action, input: &mut dyn BufRead) -> Result<bool> {
+    match action {
+        Action::Reschedule => {
+            let Some(answer) = ask(input, &t!("review.ask_due"))? else { return Ok(false) };
+            if answer.is_empty() {
+                todo.due_date = None;
+            } else {
+                match parse_date_input(&answer) {
+                    Ok(date) => todo.due_date = Some(date.to_date()),
+                    Err(e) => {
+                        println!("{}", e);
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+        Action::Priority => {
+            let Some(answer) = ask(input, &t!("review.ask_priority"))? else { return Ok(false) };
+            match answer.parse::<Priority>() {
+                Ok(priority) => todo.priority = priority,
+                Err(e) => {
+                    println!("{}", e);
+                    return Ok(false);
+                }
+            }
+        }
+        Action::Tag => {
+            let Some(answer) = ask(input, &t!("review.ask_tag"))? else { return Ok(false) };
+            match validate::tag(&answer) {
+                Ok(tag) if !todo.tags.contains(&tag) => todo.tags.push(tag),
+                Ok(_) => {}
+                Err(e) => {
+                    println!("{}", e);
+                    return Ok(false);
+                }
+            }
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Steps through the open todos not reviewed within `interval`, or all of them, asking what to do with each
+///
+/// Keeping a todo, or rescheduling it, changing its priority, or tagging
+/// it, marks it reviewed today; the last three ask again so several
+/// changes can be made before moving on. Archived todos go to the archive
+/// list and deleted ones to the trash. Every answer is saved right away,
+/// so quitting part way keeps what was done.
+pub fn review_todos(
+    store: &mut dyn TodoStore,
+    interval: HumanDuration,
+    all: bool,
+    input: &mut dyn BufRead,
+) -> CommandResult {
+    let today = Local::now().date_naive();
+    let mut todos = store.load()?;
+    let blocked = blocked_uuids(&todos);
+    let mut queue: Vec<Todo> = todos.iter().filter(|t| needs_review(t, today, interval, all)).cloned().collect();
+    sort_todos(&mut queue, &SortOrder::default(), &blocked);
+    if queue.is_empty() {
+        println!("{}", t!("review.nothing"));
+        return Ok(());
+    }
+
+    let layout = TableLayout::auto(&queue);
+    let total = queue.len();
+    let mut reviewed = 0;
+    'todos: for (n, uuid) in queue.iter().map(|t| t.uuid.clone()).enumerate() {
+        let mut changed = false;
+        loop {
+            let Some(index) = todos.iter().position(|t| t.uuid == uuid) else { continue 'todos };
+            println!("\n{}", t!("review.progress", n = n + 1, total = total));
+            println!("{}", todos[index].display_row(&layout, blocked.contains(&uuid)));
+
+            let action = ask_action(input)?;
+            match action {
+                Action::Quit => {
+                    reviewed += usize::from(changed);
+                    break 'todos;
+                }
+                Action::Skip => {
+                    reviewed += usize::from(changed);
+                    continue 'todos;
+                }
+                Action::Keep => {
+                    todos[index].last_reviewed = Some(today);
+                    store.save(&todos)?;
+                    reviewed += 1;
+                    continue 'todos;
+                }
+                Action::Archive => {
+                    let archived = archive_todos(store, &mut todos, |t| t.uuid == uuid)?;
+                    for todo in &archived {
+                        println!("{}", t!("review.archived", title = todo.title));
+                    }
+                    reviewed += 1;
+                    continue 'todos;
+                }
+                Action::Delete => {
+                    let removed = todos.remove(index);
+                    store.save(&todos)?;
+                    store.trash(std::slice::from_ref(&removed))?;
+                    store.record(Operation::Removed, &removed)?;
+                    println!("{}", t!("remove.removed", id = removed.id));
+                    reviewed += 1;
+                    continue 'todos;
+                }
+                Action::Reschedule | Action::Priority | Action::Tag => {
+                    let mut todo = todos[index].clone();
+                    if change(&mut todo, &action, input)? {
+                        todo.last_reviewed = Some(today);
+                        todos[index] = todo;
+                        store.save(&todos)?;
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+
+    println!("{}", t!("review.done", reviewed = reviewed, total = total));
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
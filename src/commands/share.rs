@@ -0,0 +1,81 @@
+use crate::{
+    config::Config,
+    formats::{to_markdown, Format, GroupBy},
+    integrations::{
+        github::GithubClient,
+        share::{fetch, upload_paste, ShareService},
+    },
+    models::Todo,
+    storage::TodoStore,
+    t,
+    terminal::confirm,
+};
+use super::{export::import_text, resolve::resolve_index, CommandResult};
+
+const FILENAME: &str = "todos.md";
+
+/// Uploads the todos `ids` name, or every open one, as a Markdown checklist and prints the link
+///
+/// Shows exactly what will be uploaded and asks first unless `yes` is set,
+/// since anyone with the link can read it. Goes to `to`, or `share.to`.
+pub fn share_todos(store: &mut dyn TodoStore, ids: &[String], to: Option<ShareService>, yes: bool) -> CommandResult {
+    let config = Config::load()?;
+    let todos = store.load()?;
+    let shared: Vec<Todo> = if ids.is_empty() {
+        todos.iter().filter(|t| !t.completed).cloned().collect()
+    } else {
+        let mut picked = Vec::new();
+        for id in ids {
+            let todo = &todos[resolve_index(&todos, id)?];
+            if !picked.iter().any(|t: &Todo| t.uuid == todo.uuid) {
+                picked.push(todo.clone());
+            }
+        }
+        picked
+    };
+    if shared.is_empty() {
+        println!("{}", t!("share.nothing"));
+        return Ok(());
+    }
+
+    let to = to.unwrap_or(config.share.to);
+    let content = to_markdown(&shared, GroupBy::Project);
+    let place = match to {
+        ShareService::Gist => t!("share.gist"),
+        ShareService::Paste => config.share.paste_url.clone(),
+    };
+    println!("{}", t!("share.preview", count = shared.len(), place = place));
+    print!("{}", content);
+    println!();
+    if !yes && !confirm(&t!("share.confirm"))? {
+        println!("{}", t!("common.cancelled"));
+        return Ok(());
+    }
+
+    let (url, raw_url) = match to {
+        ShareService::Gist => {
+            let description = format!("{} todos shared from todo-cli", shared.len());
+            GithubClient::from_config(&config.github)?.create_gist(&description, FILENAME, &content)?
+        }
+        ShareService::Paste => {
+            let url = upload_paste(&config.share.paste_url, FILENAME, &content)?;
+            (url.clone(), url)
+        }
+    };
+    println!("{}", t!("share.shared", count = shared.len(), url = url));
+    println!("{}", t!("share.import", url = raw_url));
+
+    Ok(())
+}
+
+/// Downloads a checklist or calendar from `url` and imports it as `import` does a file
+pub fn import_from_url(
+    store: &mut dyn TodoStore,
+    url: &str,
+    format: Format,
+    title: Option<&str>,
+    replace: bool,
+) -> CommandResult {
+    let input = fetch(url)?;
+    import_text(store, &input, format, title, replace)
+}
@@ -0,0 +1,32 @@
+use crate::{
+    deps::blocked_uuids,
+    storage::TodoStore,
+    display::render_details,
+};
+use super::{resolve::resolve_index, CommandResult};
+
+/// Prints every field of a todo, including its notes and attachments
+///
+/// Who last changed the todo, and who closed it, are shown when known; the
+/// latter comes from the last close in its history. With `json`, prints the
+/// todo as it is stored instead, for scripts.
+pub fn show_todo(store: &mut dyn TodoStore, target: &str, json: bool) -> CommandResult {
+    let todos = store.load()?;
+    let todo = &todos[resolve_index(&todos, target)?];
+    if json {
+        println!("{}", serde_json::to_string_pretty(todo)?);
+        return Ok(());
+    }
+    let blocked = blocked_uuids(&todos).contains(&todo.uuid);
+    let closed_by = match todo.completed_at {
+        Some(_) => store
+            .load_history()?
+            .into_iter()
+            .rev()
+            .find(|c| c.uuid == todo.uuid && c.field == "completed_at" && !c.new.is_null())
+            .and_then(|c| c.by),
+        None => None,
+    };
+    print!("{}", render_details(todo, &todos, blocked, closed_by.as_deref()));
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::{
+    display::{format_priority, format_relative_time},
+    models::Todo,
+    storage::TodoStore,
+    t,
+    width::pad,
+};
+use chrono::{DateTime, Duration, Local};
+use super::CommandResult;
+
+/// Returns the open todos unchanged for `age` as of `now`, least recently changed first
+pub fn stale_todos(todos: &[Todo], now: DateTime<Local>, age: Duration) -> Vec<&Todo> {
+    let mut stale: Vec<&Todo> = todos.iter().filter(|t| t.is_stale(now, age)).collect();
+    stale.sort_by_key(|t| t.modified());
+    stale
+}
+
+/// Lists the open todos that haven't changed in `days` days
+///
+/// Any save that changes a todo counts, from editing it to tracking time
+/// on it.
+pub fn show_stale(store: &mut dyn TodoStore, days: u32) -> CommandResult {
+    let todos = store.load()?;
+    let stale = stale_todos(&todos, Local::now(), Duration::days(i64::from(days)));
+    if stale.is_empty() {
+        println!("{}", t!("stale.none", days = days));
+        return Ok(());
+    }
+
+    for todo in &stale {
+        let changed = t!("stale.changed", when = format_relative_time(&todo.modified()));
+        println!("#{:<4} {} {} {}", todo.id, pad(&format_priority(todo.priority), 8), pad(&changed, 22), todo.title);
+    }
+    println!();
+    println!("{}", t!("stale.summary", count = stale.len(), days = days));
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use std::collections::{BTreeMap, HashSet};
+use crate::{
+    archive::other_archive,
+    calendar::start_of_week,
+    i18n::format_date,
+    journal::Operation,
+    models::Status,
+    storage::TodoStore,
+    t,
+};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use super::CommandResult;
+
+/// Per-week counts of added, completed, and removed todos
+#[derive(Debug, Default, Clone, Copy)]
+struct WeekStats {
+    added: usize,
+    completed: usize,
+    removed: usize,
+}
+
+/// Returns the first day of the week containing `dt`
+pub(super) fn week_start(dt: &DateTime<Local>) -> NaiveDate {
+    start_of_week(dt.date_naive())
+}
+
+/// Prints weekly activity statistics
+///
+/// Counts are derived from live and archived todos plus the operation
+/// journal, so history stays accurate after todos are removed or archived.
+/// Every removal in the journal counts, even of a todo later restored from
+/// the trash. With `live_only` the journal and archive are ignored and
+/// removals cannot be counted.
+pub fn show_stats(store: &mut dyn TodoStore, weeks: u32, live_only: bool) -> CommandResult {
+    let todos = store.load()?;
+    let journal = if live_only { Vec::new() } else { store.load_journal()? };
+    let archived = match other_archive(store) {
+        Some(mut archive) if !live_only => archive.load()?,
+        _ => Vec::new(),
+    };
+
+    let mut by_week: BTreeMap<NaiveDate, WeekStats> = BTreeMap::new();
+    let known: HashSet<&str> = todos.iter().chain(&archived).map(|t| t.uuid.as_str()).collect();
+
+    for todo in todos.iter().chain(&archived) {
+        by_week.entry(week_start(&todo.created_at)).or_default().added += 1;
+        if let (Status::Done, Some(completed_at)) = (todo.status, todo.completed_at) {
+            by_week.entry(week_start(&completed_at)).or_default().completed += 1;
+        }
+    }
+
+    // Live and archived todos are already counted as added and completed;
+    // the journal fills in the ones that are gone, and every removal
+    for entry in &journal {
+        let gone = !known.contains(entry.uuid.as_str());
+        let stats = by_week.entry(week_start(&entry.timestamp)).or_default();
+        match entry.operation {
+            Operation::Added if gone => stats.added += 1,
+            Operation::Completed if gone => stats.completed += 1,
+            Operation::Removed => stats.removed += 1,
+            Operation::Added | Operation::Completed | Operation::Updated | Operation::Archived => {}
+        }
+    }
+
+    let active = todos.iter().filter(|t| !t.completed).count();
+    let cancelled = todos.iter().filter(|t| t.status == Status::Cancelled).count();
+    print!("{}", t!("stats.totals", count = todos.len(), active = active, completed = todos.len() - active - cancelled));
+    if cancelled > 0 {
+        print!("{}", t!("stats.cancelled", count = cancelled));
+    }
+    println!();
+    println!();
+    println!(
+        "{:<12} {:>6} {:>10} {:>8}",
+        t!("stats.week_of"),
+        t!("stats.added"),
+        t!("stats.completed"),
+        t!("stats.removed")
+    );
+    println!("{}", "-".repeat(39));
+
+    let this_week = week_start(&Local::now());
+    for offset in (0..weeks as i64).rev() {
+        let start = this_week - Duration::weeks(offset);
+        let stats = by_week.get(&start).copied().unwrap_or_default();
+        let removed = if live_only { "-".to_string() } else { stats.removed.to_string() };
+        println!(
+            "{:<12} {:>6} {:>10} {:>8}",
+            format_date(start), stats.added, stats.completed, removed
+        );
+    }
+
+    Ok(())
+}
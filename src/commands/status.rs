@@ -0,0 +1,43 @@
+use crate::{
+    models::Status,
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use chrono::Local;
+use super::{done::mark_done, resolve::resolve_index, CommandResult};
+
+/// Moves a todo to a new status
+///
+/// Marking it done goes through `done`, so dependencies are checked and the
+/// completion is journaled. Closing a todo stops a timer running on it.
+pub fn change_status(store: &mut dyn TodoStore, target: &str, status: Status) -> CommandResult {
+    if status == Status::Done {
+        return mark_done(store, target);
+    }
+
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let todo = &mut todos[index];
+    let id = todo.id;
+
+    if todo.status == status {
+        println!("{}", t!("status.same", id = id, status = status));
+        return Ok(());
+    }
+
+    let old_status = todo.status;
+    todo.set_status(status);
+    if status.is_closed() {
+        if let Some(entry) = todo.running_entry() {
+            entry.end = Some(Local::now());
+        }
+    }
+    store.save(&todos)?;
+    println!("{}", t!("status.moved", id = id, from = old_status, to = status, title = todos[index].title));
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
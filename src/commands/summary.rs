@@ -0,0 +1,109 @@
+use crate::{
+    models::{Priority, Status, Todo},
+    storage::TodoStore,
+};
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::SystemTime;
+use super::CommandResult;
+
+const CACHE: &str = "summary";
+
+/// Counts of open todos, as shown by `summary`
+///
+/// Hidden todos are left out, as in the default `list`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Summary {
+    /// Todos not done or cancelled
+    pub active: usize,
+    /// Active todos due on the day counted
+    pub due_today: usize,
+    /// Active todos due before the day counted
+    pub overdue: usize,
+    /// Active todos at the highest priority
+    pub urgent: usize,
+    /// Todos marked done on the day counted
+    pub done_today: usize,
+}
+
+impl Summary {
+    /// Counts the open todos as of `today`
+    pub fn of(todos: &[Todo], today: NaiveDate) -> Self {
+        let highest = Priority::highest();
+        let mut summary = Self {
+            done_today: todos
+                .iter()
+                .filter(|t| t.status == Status::Done && t.completed_at.is_some_and(|at| at.date_naive() == today))
+                .count(),
+            ..Self::default()
+        };
+        for todo in todos.iter().filter(|t| !t.completed && !t.is_hidden_on(today)) {
+            summary.active += 1;
+            if todo.due_date == Some(today) {
+                summary.due_today += 1;
+            } else if todo.is_overdue_on(today) {
+                summary.overdue += 1;
+            }
+            if todo.priority == highest {
+                summary.urgent += 1;
+            }
+        }
+        summary
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} active, {} due today, {} overdue, {} {}",
+            self.active,
+            self.due_today,
+            self.overdue,
+            self.urgent,
+            Priority::highest().name().to_lowercase()
+        )
+    }
+}
+
+/// Counts saved next to the database, valid while it and the day are unchanged
+#[derive(Serialize, Deserialize)]
+struct SummaryCache {
+    modified: SystemTime,
+    day: NaiveDate,
+    /// Name of the highest priority, which may change with the config
+    highest: String,
+    summary: Summary,
+}
+
+/// Counts the open todos as of `today`, reusing the store's cache when the
+/// database hasn't changed since it was written
+///
+/// This spares status bars and prompts from parsing a large database every
+/// time they're drawn. Failing to write the cache is not an error.
+pub fn cached_summary(store: &mut dyn TodoStore, today: NaiveDate) -> Result<Summary> {
+    let modified = store.last_modified();
+    let highest = Priority::highest().name().to_string();
+    if let (Some(modified), Some(content)) = (modified, store.load_cache(CACHE)) {
+        if let Ok(cache) = serde_json::from_str::<SummaryCache>(&content) {
+            if cache.modified == modified && cache.day == today && cache.highest == highest {
+                return Ok(cache.summary);
+            }
+        }
+    }
+
+    let summary = Summary::of(&store.load()?, today);
+    if let Some(modified) = modified {
+        let cache = SummaryCache { modified, day: today, highest, summary };
+        let _ = store.save_cache(CACHE, &serde_json::to_string(&cache)?);
+    }
+    Ok(summary)
+}
+
+/// Prints the counts of open todos on one line, e.g. for a status bar
+pub fn show_summary(store: &mut dyn TodoStore) -> CommandResult {
+    println!("{}", cached_summary(store, Local::now().date_naive())?);
+    Ok(())
+}
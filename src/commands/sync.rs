@@ -0,0 +1,87 @@
+use std::fs;
+use std::time::UNIX_EPOCH;
+use crate::{
+    config::{config_dir, Config},
+    error::TodoError,
+    storage::TodoStore,
+    sync::{merge_per_item, summarize, MergeStrategy, SyncRepo},
+    t,
+};
+use super::CommandResult;
+
+/// Commits the database to the sync repository and exchanges it with the remote
+///
+/// Refused for read-only lists, since it pushes whatever is local.
+pub fn sync_todos(store: &mut dyn TodoStore, remote: Option<String>, strategy: Option<MergeStrategy>) -> CommandResult {
+    if store.is_read_only() {
+        let message = "Can't sync a list opened read-only; sync pushes local changes".to_string();
+        return Err(TodoError::Validation(message).into());
+    }
+    let config = Config::load()?.sync;
+    let remote = remote.or(config.remote);
+    let strategy = strategy.unwrap_or(config.strategy);
+    let repo_dir = config.repo_dir.unwrap_or_else(|| config_dir().join("sync"));
+
+    let name = store.backup_location().map(|(_, name)| name).unwrap_or_else(|| "todos".to_string());
+    let repo = SyncRepo::open(&repo_dir, &name)?;
+    if let Some(url) = &remote {
+        repo.set_remote(url)?;
+    }
+
+    let local = store.load()?;
+    let local_time = store
+        .last_modified()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let base = repo.committed_todos()?;
+    let fetched = match &remote {
+        Some(_) => repo.fetch(&config.branch)?,
+        None => None,
+    };
+    let merged_remote = fetched.is_some();
+
+    let merged = match fetched {
+        None => local,
+        Some((theirs, remote_time)) => {
+            let merged = match strategy {
+                MergeStrategy::PerItem => merge_per_item(&base, local.clone(), theirs),
+                MergeStrategy::LastWriteWins if remote_time > local_time => theirs,
+                MergeStrategy::LastWriteWins => local.clone(),
+            };
+
+            let summary = summarize(&local, &merged);
+            if summary.is_empty() {
+                println!("{}", t!("sync.up_to_date"));
+            } else {
+                store.save(&merged)?;
+                println!(
+                    "{}",
+                    t!("sync.pulled", added = summary.added, removed = summary.removed, updated = summary.updated)
+                );
+            }
+            merged
+        }
+    };
+
+    let host = std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .unwrap_or_else(|| "unknown host".to_string());
+
+    if repo.commit(&merged, &format!("Sync from {}", host), merged_remote)? {
+        println!("{}", t!("sync.committed", count = merged.len(), path = repo_dir.display()));
+    }
+
+    match remote {
+        Some(_) => {
+            repo.push(&config.branch)?;
+            println!("{}", t!("sync.pushed", branch = config.branch));
+        }
+        None => println!("{}", t!("sync.no_remote")),
+    }
+
+    Ok(())
+}
+
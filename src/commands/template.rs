@@ -0,0 +1,46 @@
+use crate::{
+    error::TodoError,
+    storage::TodoStore,
+    t,
+    templates::{load_templates, save_templates, templates_path, Template},
+};
+use super::{resolve::resolve_index, CommandResult};
+
+/// Saves the shape of a todo as the template `name`, replacing any with that name
+pub fn save_template(store: &mut dyn TodoStore, target: &str, name: &str) -> CommandResult {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(TodoError::Validation("Template name cannot be empty".to_string()).into());
+    }
+
+    let todos = store.load()?;
+    let todo = &todos[resolve_index(&todos, target)?];
+    let mut templates = load_templates()?;
+    let replaced = templates.insert(name.to_string(), Template::from_todo(todo)).is_some();
+    save_templates(&templates)?;
+
+    let message = match replaced {
+        true => t!("template.updated", name = name, id = todo.id, title = todo.title),
+        false => t!("template.saved", name = name, id = todo.id, title = todo.title),
+    };
+    println!("{}", message);
+    Ok(())
+}
+
+/// Lists the saved templates
+pub fn list_templates() -> CommandResult {
+    let templates = load_templates()?;
+    if templates.is_empty() {
+        println!("{}", t!("template.none", path = templates_path().display()));
+        return Ok(());
+    }
+
+    for (name, template) in &templates {
+        let mut line = format!("{:<16} {} ({})", name, template.title, template.priority);
+        for tag in &template.tags {
+            line.push_str(&format!(" #{}", tag));
+        }
+        println!("{}", line);
+    }
+    Ok(())
+}
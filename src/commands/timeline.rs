@@ -0,0 +1,21 @@
+use crate::{
+    storage::TodoStore,
+    terminal,
+    timeline::render_timeline,
+};
+use chrono::Local;
+use super::{stats::week_start, CommandResult};
+
+/// Shows open todos as bars from start to due date over `weeks` weeks from the start of this one
+pub fn show_timeline(store: &mut dyn TodoStore, weeks: u32) -> CommandResult {
+    let mut todos = store.load()?;
+    let now = Local::now();
+    let today = now.date_naive();
+    todos.retain(|t| !t.completed && !t.is_hidden_on(today));
+
+    let width = terminal::width().unwrap_or(80);
+    let days = weeks.max(1) as usize * 7;
+    print!("{}", render_timeline(&todos, week_start(&now), days, today, width));
+
+    Ok(())
+}
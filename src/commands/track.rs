@@ -0,0 +1,74 @@
+use crate::{
+    duration::HumanDuration,
+    models::{Status, TimeEntry, Todo},
+    storage::TodoStore,
+    t,
+};
+use anyhow::anyhow;
+use chrono::{DateTime, Duration, Local};
+use super::{resolve::resolve_index, CommandResult};
+
+/// Formats a tracked duration to the minute, e.g. "1h25m"
+pub(super) fn format_tracked(duration: Duration) -> String {
+    HumanDuration(Duration::minutes(duration.num_minutes())).to_string()
+}
+
+/// Stops the running timer on any todo, returning the stopped todo's position
+fn stop_running(todos: &mut [Todo], now: DateTime<Local>) -> Option<usize> {
+    let index = todos.iter().position(|t| t.time_entries.iter().any(|e| e.end.is_none()))?;
+    for entry in todos[index].time_entries.iter_mut().filter(|e| e.end.is_none()) {
+        entry.end = Some(now);
+    }
+    Some(index)
+}
+
+fn print_stopped(todo: &Todo) {
+    if let Some(entry) = todo.time_entries.last() {
+        println!(
+            "{}",
+            t!("track.stopped", id = todo.id, title = todo.title, duration = format_tracked(entry.duration_at(Local::now())))
+        );
+    }
+}
+
+/// Starts the timer on a todo, stopping any timer already running
+pub fn start_timer(store: &mut dyn TodoStore, target: &str) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    
+    if todos[index].running_entry().is_some() {
+        println!("{}", t!("track.already", id = todos[index].id));
+        return Ok(());
+    }
+    if todos[index].completed {
+        return Err(anyhow!("Todo #{} is already done", todos[index].id));
+    }
+    
+    let now = Local::now();
+    let stopped = stop_running(&mut todos, now);
+    todos[index].time_entries.push(TimeEntry { start: now, end: None });
+    todos[index].set_status(Status::InProgress);
+    store.save(&todos)?;
+    
+    if let Some(stopped) = stopped {
+        print_stopped(&todos[stopped]);
+    }
+    println!("{}", t!("track.started", id = todos[index].id, title = todos[index].title));
+    
+    Ok(())
+}
+
+/// Stops the running timer
+pub fn stop_timer(store: &mut dyn TodoStore) -> CommandResult {
+    let mut todos = store.load()?;
+    
+    match stop_running(&mut todos, Local::now()) {
+        Some(index) => {
+            store.save(&todos)?;
+            print_stopped(&todos[index]);
+        }
+        None => println!("{}", t!("track.none")),
+    }
+    
+    Ok(())
+}
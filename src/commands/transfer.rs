@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+use crate::{
+    journal::Operation,
+    models::generate_uuid,
+    storage::TodoStore,
+    display::display_updated,
+    t,
+};
+use anyhow::anyhow;
+use super::{resolve::resolve_index, CommandResult};
+
+/// Whether a transfer leaves the original behind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transfer {
+    /// Remove the todo from the source list, keeping its UUID
+    Move,
+    /// Keep the original and give the copy a new UUID
+    Copy,
+}
+
+/// Moves or copies a todo into the list named `to`
+///
+/// The todo gets the next free ID in the destination. Dependencies are
+/// only kept where both todos end up in the same list: the transferred
+/// todo drops dependencies on todos the destination doesn't have, and on
+/// a move, todos left behind stop depending on it.
+pub fn transfer_todo(store: &mut dyn TodoStore, target: &str, to: &str, mode: Transfer) -> CommandResult {
+    let mut todos = store.load()?;
+    let index = resolve_index(&todos, target)?;
+    let mut dest = store.open_list(to)?;
+    let mut dest_todos = dest.load()?;
+
+    let mut todo = match mode {
+        Transfer::Move => todos.remove(index),
+        Transfer::Copy => todos[index].clone(),
+    };
+    let old_id = todo.id;
+    if mode == Transfer::Copy {
+        todo.uuid = generate_uuid();
+        // Tracked time stays with the original so it isn't counted twice
+        todo.time_entries.clear();
+    }
+    if dest_todos.iter().any(|t| t.uuid == todo.uuid) {
+        return Err(anyhow!("List '{}' already has todo {}", to, todo.uuid));
+    }
+
+    todo.id = dest_todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+    // A place in the manual order means nothing in another list
+    todo.sort_key = None;
+    let dest_uuids: HashSet<&str> = dest_todos.iter().map(|t| t.uuid.as_str()).collect();
+    let before = todo.depends_on.len();
+    todo.depends_on.retain(|uuid| dest_uuids.contains(uuid.as_str()));
+    let mut dropped = before - todo.depends_on.len();
+
+    if mode == Transfer::Move {
+        for other in todos.iter_mut() {
+            let before = other.depends_on.len();
+            other.depends_on.retain(|uuid| *uuid != todo.uuid);
+            dropped += before - other.depends_on.len();
+        }
+    }
+
+    let new_id = todo.id;
+    let title = todo.title.clone();
+    dest_todos.push(todo);
+
+    // Add to the destination first, so a failure can't lose the todo
+    dest.save(&dest_todos)?;
+    if let Some(added) = dest_todos.last() {
+        dest.record(Operation::Added, added)?;
+    }
+    if mode == Transfer::Move {
+        store.save(&todos)?;
+        if let Some(moved) = dest_todos.last() {
+            store.record(Operation::Removed, moved)?;
+        }
+    }
+
+    let message = match mode {
+        Transfer::Move => t!("transfer.moved", id = old_id, list = to, new_id = new_id, title = title),
+        Transfer::Copy => t!("transfer.copied", id = old_id, list = to, new_id = new_id, title = title),
+    };
+    println!("{}", message);
+    if dropped > 0 {
+        println!("{}", t!("transfer.dropped", count = dropped));
+    }
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use crate::{
+    duration::HumanDuration,
+    journal::Operation,
+    models::Todo,
+    storage::TodoStore,
+    display::{display_updated, format_relative_time},
+    t,
+    terminal::confirm,
+};
+use chrono::Local;
+use super::{resolve::resolve_index, CommandResult};
+
+/// Lists the todos in the trash, most recently removed first
+pub fn list_trash(store: &mut dyn TodoStore) -> CommandResult {
+    let mut trash = store.load_trash()?;
+    if trash.is_empty() {
+        println!("{}", t!("trash.empty"));
+        return Ok(());
+    }
+
+    trash.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+    println!("{:<5} {:<14} {}", "ID", t!("trash.removed"), t!("common.title"));
+    for entry in &trash {
+        println!("{:<5} {:<14} {}", entry.todo.id, format_relative_time(&entry.deleted_at), entry.todo.title);
+    }
+
+    Ok(())
+}
+
+/// Puts a todo from the trash back on the list
+///
+/// `target` is the ID the todo had, or a prefix of its UUID. When several
+/// removed todos had the ID, the one removed last is restored, as it's
+/// listed first. The todo gets a new ID if its old one has been reused in
+/// the meantime.
+pub fn restore_trash(store: &mut dyn TodoStore, target: &str) -> CommandResult {
+    let mut trash = store.load_trash()?;
+    trash.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+    let trashed: Vec<Todo> = trash.iter().map(|entry| entry.todo.clone()).collect();
+    let index = resolve_index(&trashed, target)?;
+
+    let mut todos = store.load()?;
+    let mut todo = trash.remove(index).todo;
+    if todos.iter().any(|t| t.uuid == todo.uuid) {
+        store.save_trash(&trash)?;
+        println!("{}", t!("trash.already", id = todo.id));
+        return Ok(());
+    }
+
+    let old_id = todo.id;
+    if todos.iter().any(|t| t.id == todo.id) {
+        todo.id = todos.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+    }
+    todos.push(todo);
+
+    // Put it back before dropping it from the trash, so a failure can't lose it
+    store.save(&todos)?;
+    store.save_trash(&trash)?;
+    if let Some(restored) = todos.last() {
+        store.record(Operation::Added, restored)?;
+        if restored.id == old_id {
+            println!("{}", t!("trash.restored", id = restored.id, title = restored.title));
+        } else {
+            println!("{}", t!("trash.restored_as", id = old_id, new_id = restored.id, title = restored.title));
+        }
+    }
+
+    // Show the updated list
+    display_updated(&todos);
+
+    Ok(())
+}
+
+/// Permanently deletes trashed todos, or only those removed longer than `older_than` ago
+///
+/// With `ask`, says how many would go and asks first.
+pub fn empty_trash(store: &mut dyn TodoStore, older_than: Option<HumanDuration>, ask: bool) -> CommandResult {
+    let mut trash = store.load_trash()?;
+    let before = trash.len();
+    match older_than {
+        // An age reaching back past the earliest time there is keeps everything
+        Some(age) => {
+            if let Some(cutoff) = Local::now().checked_sub_signed(age.duration()) {
+                trash.retain(|entry| entry.deleted_at > cutoff);
+            }
+        }
+        None => trash.clear(),
+    }
+
+    let purged = before - trash.len();
+    if ask && purged > 0 && !confirm(&t!("trash.confirm", count = purged))? {
+        println!("{}", t!("common.cancelled"));
+        return Ok(());
+    }
+    if purged > 0 {
+        store.save_trash(&trash)?;
+    }
+    println!("{}", t!("trash.deleted", count = purged, left = trash.len()));
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use crate::{
+    config::Config,
+    duration::HumanDuration,
+    i18n::format_full_day,
+    models::Todo,
+    storage::TodoStore,
+    t,
+};
+use chrono::{Duration, Local, NaiveDate};
+use colored::Colorize;
+use super::{report::hours, CommandResult};
+
+/// Width of the bar for a day holding exactly the capacity
+const BAR_WIDTH: i64 = 20;
+
+/// Estimated work due on one day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayLoad {
+    pub date: NaiveDate,
+    pub estimated: Duration,
+    /// IDs of the todos counted, in list order
+    pub todos: Vec<u64>,
+}
+
+/// Sums the estimates of open todos due on each of `days` days from `today`
+///
+/// Overdue todos count toward today, since their work is still ahead.
+/// Todos without an estimate or a due date are left out.
+pub fn daily_workload(todos: &[Todo], today: NaiveDate, days: u32) -> Vec<DayLoad> {
+    let mut loads: Vec<DayLoad> = (0..days)
+        .map(|offset| DayLoad {
+            date: today + Duration::days(i64::from(offset)),
+            estimated: Duration::zero(),
+            todos: Vec::new(),
+        })
+        .collect();
+
+    for todo in todos.iter().filter(|t| !t.completed) {
+        let (Some(due), Some(estimate)) = (todo.due_date, todo.estimate) else {
+            continue;
+        };
+        let offset = (due - today).num_days().max(0);
+        if let Some(load) = usize::try_from(offset).ok().and_then(|i| loads.get_mut(i)) {
+            load.estimated += estimate.duration();
+            load.todos.push(todo.id);
+        }
+    }
+    loads
+}
+
+/// Draws a day's load as a bar, the part over `capacity` in red
+fn bar(estimated: Duration, capacity: Duration) -> String {
+    let capacity = capacity.num_minutes().max(1);
+    let filled = (estimated.num_minutes() * BAR_WIDTH / capacity).min(BAR_WIDTH * 2);
+    let within = filled.min(BAR_WIDTH) as usize;
+    let over = (filled - BAR_WIDTH).max(0) as usize;
+    let free = BAR_WIDTH as usize - within;
+    format!("{}{}{}", "█".repeat(within), "░".repeat(free).dimmed(), "█".repeat(over).red())
+}
+
+/// Prints the estimated work due on each of the next `days` days
+///
+/// Days holding more than `capacity` (or `workload.capacity` from the
+/// config) are flagged. Open todos due in the range without an estimate
+/// are counted, since they make the totals too low.
+pub fn show_workload(store: &mut dyn TodoStore, days: u32, capacity: Option<HumanDuration>) -> CommandResult {
+    let capacity = match capacity {
+        Some(capacity) => capacity,
+        None => Config::load()?.workload.capacity,
+    };
+    let todos = store.load()?;
+    let today = Local::now().date_naive();
+    let loads = daily_workload(&todos, today, days);
+
+    println!("{}", t!("workload.header", capacity = capacity));
+    let mut overcommitted = 0;
+    for load in &loads {
+        let mut line = format!(
+            "{}  {} {:>7}",
+            format_full_day(load.date),
+            bar(load.estimated, capacity.duration()),
+            hours(load.estimated)
+        );
+        match load.todos.len() {
+            0 => {}
+            1 => line.push_str(&format!("  #{}", load.todos[0])),
+            n => line.push_str(&format!("  {}", t!("workload.todos", count = n))),
+        }
+        if load.estimated > capacity.duration() {
+            overcommitted += 1;
+            let over = format!("  {}", t!("workload.over", hours = hours(load.estimated - capacity.duration())));
+            line.push_str(&over.red().bold().to_string());
+        }
+        println!("{}", line);
+    }
+
+    let last = today + Duration::days(i64::from(days.max(1)) - 1);
+    let unestimated = todos
+        .iter()
+        .filter(|t| !t.completed && t.estimate.is_none() && t.due_date.is_some_and(|due| due <= last))
+        .count();
+    println!();
+    match overcommitted {
+        0 => println!("{}", t!("workload.ok")),
+        1 => println!("{}", t!("workload.over_one")),
+        n => println!("{}", t!("workload.over_many", count = n)),
+    }
+    match unestimated {
+        0 => {}
+        1 => println!("{}", t!("workload.unestimated_one")),
+        n => println!("{}", t!("workload.unestimated_many", count = n)),
+    }
+
+    Ok(())
+}
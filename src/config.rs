@@ -0,0 +1,504 @@
+//! User configuration loaded from a JSON file
+
+use crate::display::{IconMode, Theme};
+use crate::duration::HumanDuration;
+use crate::error::TodoError;
+use crate::escalate::{default_rules, EscalationRule};
+use crate::models::PriorityScale;
+use crate::storage::StorageFormat;
+use crate::sync::MergeStrategy;
+use crate::width::Truncation;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+const CONFIG_FILE: &str = "config.json";
+
+/// Settings for `sync`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// Git remote URL to push to and pull from
+    pub remote: Option<String>,
+    /// Branch used on the remote
+    pub branch: String,
+    /// How to resolve differences between machines
+    pub strategy: MergeStrategy,
+    /// Local git repository used for syncing (defaults to `<config dir>/sync`)
+    pub repo_dir: Option<PathBuf>,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            remote: None,
+            branch: "main".to_string(),
+            strategy: MergeStrategy::PerItem,
+            repo_dir: None,
+        }
+    }
+}
+
+/// Settings for reminders and `notify`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Time of day (HH:MM) a todo is considered due on its due date
+    pub due_time: String,
+    /// Offsets before the due time used when nothing more specific applies
+    pub default_offsets: Vec<HumanDuration>,
+    /// Offsets by priority name, e.g. `{"urgent": ["1d", "1h"]}`
+    pub by_priority: HashMap<String, Vec<HumanDuration>>,
+    /// Offsets by tag, e.g. `{"meeting": ["15m"]}`
+    pub by_tag: HashMap<String, Vec<HumanDuration>>,
+    /// Whether to send desktop notifications in addition to printing
+    pub desktop: bool,
+    /// Whether to notify when a hidden todo resurfaces
+    pub resurface: bool,
+    /// Longest the daemon sleeps before checking the database again
+    pub poll_interval: HumanDuration,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            due_time: "09:00".to_string(),
+            default_offsets: vec![HumanDuration(chrono::Duration::days(1))],
+            by_priority: HashMap::new(),
+            by_tag: HashMap::new(),
+            desktop: true,
+            resurface: true,
+            poll_interval: HumanDuration(chrono::Duration::minutes(1)),
+        }
+    }
+}
+
+/// Settings for `list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListConfig {
+    /// Hide todos completed longer ago than this unless `--all` is given;
+    /// `null` shows every completed todo
+    pub hide_completed_after: Option<HumanDuration>,
+    /// Dim the titles of open todos that haven't changed for this long,
+    /// e.g. `"30d"`; `null` (the default) marks none
+    pub stale_after: Option<HumanDuration>,
+    /// How the smart sort ranks todos of the same status: `"overdue_first"`
+    /// (the default) or `"priority"`
+    pub smart_sort: crate::models::SmartSort,
+}
+
+impl Default for ListConfig {
+    fn default() -> Self {
+        Self {
+            hide_completed_after: Some(HumanDuration(chrono::Duration::days(7))),
+            stale_after: None,
+            smart_sort: crate::models::SmartSort::default(),
+        }
+    }
+}
+
+/// Settings for `pomodoro`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PomodoroConfig {
+    /// Length of a work interval
+    pub work: HumanDuration,
+    /// Length of the break after most work intervals
+    pub short_break: HumanDuration,
+    /// Length of the break after every `long_break_every` work intervals
+    pub long_break: HumanDuration,
+    /// How many work intervals make up a cycle ending in a long break
+    pub long_break_every: u32,
+    /// Whether to send a desktop notification when an interval ends
+    pub notify: bool,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work: HumanDuration(chrono::Duration::minutes(25)),
+            short_break: HumanDuration(chrono::Duration::minutes(5)),
+            long_break: HumanDuration(chrono::Duration::minutes(15)),
+            long_break_every: 4,
+            notify: true,
+        }
+    }
+}
+
+/// Settings for `nag`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NagConfig {
+    /// Nag about inbox todos (open, without a project) older than this
+    pub inbox_age: HumanDuration,
+    /// Stay silent between these times of day, e.g. `"22:00-08:00"`
+    pub quiet_hours: Option<String>,
+    /// Whether to send a desktop notification in addition to printing
+    pub desktop: bool,
+}
+
+impl Default for NagConfig {
+    fn default() -> Self {
+        Self {
+            inbox_age: HumanDuration(chrono::Duration::hours(24)),
+            quiet_hours: None,
+            desktop: false,
+        }
+    }
+}
+
+/// Settings for `digest`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    /// Incoming webhook URL for `--channel slack`
+    pub slack_url: Option<String>,
+    /// Webhook URL for `--channel discord`
+    pub discord_url: Option<String>,
+    /// First line, with `{date}`, `{due_today}`, and `{overdue}` filled in
+    pub header: String,
+    /// One line per todo, with `{id}`, `{title}`, `{priority}`, `{due}`, and `{project}` filled in
+    pub item: String,
+    /// Sent instead when nothing is due or overdue; leave empty to send nothing
+    pub empty: String,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            slack_url: None,
+            discord_url: None,
+            header: "Todos for {date}: {overdue} overdue, {due_today} due today".to_string(),
+            item: "• #{id} {title} ({priority})".to_string(),
+            empty: "Nothing due today.".to_string(),
+        }
+    }
+}
+
+/// Which destructive commands ask before going ahead, unless given `--yes` or `--confirm`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfirmConfig {
+    /// Ask before `clear`
+    pub clear: bool,
+    /// Ask before `remove`
+    pub remove: bool,
+    /// Ask before `trash empty`
+    pub trash_empty: bool,
+    /// Ask before `restore`, after showing what it would change
+    pub restore: bool,
+}
+
+impl Default for ConfirmConfig {
+    fn default() -> Self {
+        Self {
+            clear: true,
+            remove: false,
+            trash_empty: false,
+            restore: true,
+        }
+    }
+}
+
+/// How the database file is written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Format of new databases; `convert` switches an existing one
+    pub format: StorageFormat,
+    /// Fewest todos for small changes to be appended to the patch log; null always rewrites the file
+    pub patch_above: Option<usize>,
+    /// Patches appended before the next save rewrites the file
+    pub compact_after: usize,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            format: StorageFormat::Json,
+            patch_above: Some(1000),
+            compact_after: 100,
+        }
+    }
+}
+
+/// Limits on what todos may contain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationConfig {
+    /// Longest title accepted, in characters; 0 means no limit
+    pub max_title_length: usize,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self { max_title_length: crate::validate::DEFAULT_MAX_TITLE_LENGTH }
+    }
+}
+
+/// Settings for `backup` and the backups taken before destructive commands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// Directory for backups; defaults to `todos.backups` next to the database
+    pub dir: Option<PathBuf>,
+    /// How many backups to keep in the directory, newest first; 0 keeps all
+    pub keep: usize,
+    /// Back up before `clear`, `import --replace`, and `restore`
+    pub before_destructive: bool,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            keep: 10,
+            before_destructive: true,
+        }
+    }
+}
+
+/// Settings for archiving todos automatically
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    /// Move todos completed longer ago than this to the archive list
+    /// whenever the todos are saved, e.g. `"30d"`; `null` (the default)
+    /// archives none
+    pub completed_after: Option<HumanDuration>,
+}
+
+/// Settings for `workload`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkloadConfig {
+    /// How much estimated work fits in a day before it is overcommitted
+    pub capacity: HumanDuration,
+}
+
+impl Default for WorkloadConfig {
+    fn default() -> Self {
+        Self {
+            capacity: HumanDuration(chrono::Duration::hours(8)),
+        }
+    }
+}
+
+/// Settings for `prompt-segment`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PromptConfig {
+    /// Parts separated by spaces, with `{active}`, `{due_today}`, `{overdue}`,
+    /// `{urgent}`, and `{done_today}` replaced by counts; parts whose counts
+    /// are all zero are left out
+    pub format: String,
+    /// Style of each part by the first count it shows, e.g. `{"overdue": "red"}`
+    pub styles: BTreeMap<String, String>,
+}
+
+impl Default for PromptConfig {
+    fn default() -> Self {
+        let styles = [("done_today", "green"), ("urgent", "yellow"), ("overdue", "red")];
+        Self {
+            format: "✔{done_today} ⚑{urgent} ⏰{overdue}".to_string(),
+            styles: styles.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+}
+
+/// Settings for `escalate`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EscalationConfig {
+    /// Rules such as `{"from": "normal", "to": "high", "before": "2d"}`;
+    /// leaving out `before` escalates once the todo is overdue
+    pub rules: Vec<EscalationRule>,
+    /// Whether `list` applies the rules before showing the table
+    pub on_list: bool,
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        Self {
+            rules: default_rules(),
+            on_list: false,
+        }
+    }
+}
+
+/// Settings for how tables are drawn
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    /// How long titles are cut, e.g. `{"style": "middle", "ellipsis": "..."}`
+    pub truncate: Truncation,
+    /// Whether to show todo icons: `"auto"` (when the locale is UTF-8), `"always"`, or `"never"`
+    pub icons: IconMode,
+    /// Theme used unless `--theme` is given: a built-in one (default,
+    /// solarized, monochrome, high-contrast) or one from `themes`
+    pub theme: Option<String>,
+    /// Custom themes by name, e.g. `{"mine": {"overdue": "magenta bold", "header": "cyan"}}`
+    pub themes: BTreeMap<String, Theme>,
+    /// Whether tables have an Assignee column
+    pub assignee: bool,
+}
+
+/// Settings for change events
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    /// Append an event for every change to a todo to this file, as JSON lines
+    pub log: Option<PathBuf>,
+}
+
+/// Top-level configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Refuse any change to the todos, as if `--read-only` were always given
+    pub read_only: bool,
+    /// Language of messages and dates, `"en"` or `"de"`; defaults to the one
+    /// `LC_ALL`, `LC_MESSAGES`, or `LANG` names
+    pub locale: Option<crate::i18n::Locale>,
+    /// Day weeks start on, `"monday"` (the default) or `"sunday"`
+    pub week_start: crate::calendar::WeekStart,
+    /// Your name, which `--assignee me` stands for and changes are credited
+    /// to; defaults to `$USER`
+    pub me: Option<String>,
+    pub sync: SyncConfig,
+    pub notify: NotifyConfig,
+    pub list: ListConfig,
+    pub display: DisplayConfig,
+    pub pomodoro: PomodoroConfig,
+    pub nag: NagConfig,
+    pub digest: DigestConfig,
+    pub escalation: EscalationConfig,
+    /// Weights of the urgency score, e.g. `{"due": 12.0, "tags": 0.0}`
+    pub urgency: crate::urgency::Coefficients,
+    pub events: EventsConfig,
+    pub prompt: PromptConfig,
+    pub workload: WorkloadConfig,
+    pub archive: ArchiveConfig,
+    pub backup: BackupConfig,
+    pub validation: ValidationConfig,
+    pub storage: StorageConfig,
+    /// Which commands ask for confirmation, e.g. `{"clear": false, "remove": true}`
+    pub confirm: ConfirmConfig,
+    /// Custom commands by name, e.g. `{"today": "list --due today --sort priority"}`;
+    /// managed with `alias add` and `alias remove`
+    pub aliases: BTreeMap<String, String>,
+    /// Priority levels, lowest first, e.g.
+    /// `{"levels": [{"name": "someday"}, {"name": "soon"}, {"name": "now", "color": "red"}], "default": "soon"}`
+    pub priorities: PriorityScale,
+    #[cfg(feature = "email")]
+    pub email: crate::integrations::email::EmailConfig,
+    /// Feeds kept in step by `feed sync`, managed with `feed add` and `feed remove`
+    #[cfg(feature = "feeds")]
+    pub feeds: Vec<crate::integrations::feeds::Feed>,
+    #[cfg(feature = "github")]
+    pub github: crate::integrations::github::GithubConfig,
+    #[cfg(feature = "jira")]
+    pub jira: crate::integrations::jira::JiraConfig,
+    #[cfg(feature = "share")]
+    pub share: crate::integrations::share::ShareConfig,
+    /// URLs told about added, completed, and overdue todos, e.g.
+    /// `[{"url": "https://example.com/hook", "events": ["added", "overdue"]}]`
+    #[cfg(feature = "webhooks")]
+    pub webhooks: Vec<crate::integrations::webhooks::Webhook>,
+}
+
+impl Config {
+    /// Loads the configuration, falling back to defaults when no file exists
+    pub fn load() -> Result<Self> {
+        let path = config_path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Returns the user's own name: `me`, or else `$USER`
+    pub fn me(&self) -> Option<String> {
+        self.me.clone().or_else(|| std::env::var("USER").ok().filter(|user| !user.is_empty()))
+    }
+
+    /// Returns the assignee `name` stands for: `me` is the user's own name, anything else itself
+    pub fn assignee(&self, name: &str) -> String {
+        match self.me() {
+            Some(me) if name.eq_ignore_ascii_case("me") => me,
+            _ => name.to_string(),
+        }
+    }
+}
+
+/// Loads just the priority levels from the configuration file
+///
+/// They have to be known before anything else is parsed, the escalation
+/// rules of the same file included. Falls back to the default levels when
+/// there is no file or it doesn't parse; `Config::load` reports the error.
+pub fn load_priority_scale() -> PriorityScale {
+    #[derive(Deserialize)]
+    struct Priorities {
+        #[serde(default)]
+        priorities: PriorityScale,
+    }
+
+    fs::read_to_string(config_path())
+        .ok()
+        .and_then(|content| serde_json::from_str::<Priorities>(&content).ok())
+        .map(|p| p.priorities)
+        .unwrap_or_default()
+}
+
+/// Returns the directory holding the configuration and other per-user files
+///
+/// Uses `$XDG_CONFIG_HOME/todo-cli`, falling back to `~/.config/todo-cli`.
+pub fn config_dir() -> PathBuf {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    base.join("todo-cli")
+}
+
+/// Sets one top-level setting in the configuration file, or removes it
+/// for `None`, leaving the others as they are
+pub fn save_setting(name: &str, value: Option<serde_json::Value>) -> Result<()> {
+    let path = config_path();
+    let mut config = match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|source| TodoError::Parse { path: path.clone(), source })?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_json::json!({}),
+        Err(e) => return Err(TodoError::storage("read", &path, e).into()),
+    };
+    let Some(settings) = config.as_object_mut() else {
+        return Err(TodoError::Validation(format!("{} doesn't hold a JSON object", path.display())).into());
+    };
+    match value {
+        Some(value) => settings.insert(name.to_string(), value),
+        None => settings.remove(name),
+    };
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| TodoError::storage("create", dir, e))?;
+    }
+    let content = serde_json::to_string_pretty(&config)?;
+    fs::write(&path, content).map_err(|e| TodoError::storage("write to", &path, e))?;
+    Ok(())
+}
+
+/// Returns the configuration file path, overridable with `TODO_CONFIG`
+pub fn config_path() -> PathBuf {
+    std::env::var_os("TODO_CONFIG")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| config_dir().join(CONFIG_FILE))
+}
@@ -0,0 +1,52 @@
+//! Dependencies between todos
+//!
+//! A todo depends on others by UUID. It is blocked while any of them exists
+//! and is unfinished; dependencies on removed todos are ignored.
+
+use crate::models::Todo;
+use std::collections::{HashMap, HashSet};
+
+/// Returns the incomplete todos `todo` depends on
+pub fn unfinished_dependencies<'a>(todos: &'a [Todo], todo: &Todo) -> Vec<&'a Todo> {
+    todos
+        .iter()
+        .filter(|t| !t.completed && todo.depends_on.contains(&t.uuid))
+        .collect()
+}
+
+/// Returns the UUIDs of incomplete todos waiting on an unfinished dependency
+pub fn blocked_uuids(todos: &[Todo]) -> HashSet<String> {
+    let unfinished: HashSet<&str> = todos
+        .iter()
+        .filter(|t| !t.completed)
+        .map(|t| t.uuid.as_str())
+        .collect();
+
+    todos
+        .iter()
+        .filter(|t| !t.completed && t.depends_on.iter().any(|d| unfinished.contains(d.as_str())))
+        .map(|t| t.uuid.clone())
+        .collect()
+}
+
+/// Returns true if `from` depends on `to`, directly or through other todos
+pub fn depends_transitively(todos: &[Todo], from: &str, to: &str) -> bool {
+    let edges: HashMap<&str, &[String]> = todos
+        .iter()
+        .map(|t| (t.uuid.as_str(), t.depends_on.as_slice()))
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut stack = vec![from];
+    while let Some(uuid) = stack.pop() {
+        if uuid == to {
+            return true;
+        }
+        if seen.insert(uuid) {
+            if let Some(deps) = edges.get(uuid) {
+                stack.extend(deps.iter().map(String::as_str));
+            }
+        }
+    }
+    false
+}
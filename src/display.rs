@@ -1,8 +1,327 @@
+use crate::calendar::{start_of_week, weeks_between};
+use crate::deps::blocked_uuids;
+use crate::duration::HumanDuration;
+use crate::error::TodoError;
+use crate::i18n::{format_date, format_day, format_short_date};
 use crate::models::*;
+use crate::output::{self, Verbosity};
+use crate::t;
+use crate::terminal;
+use crate::urgency::urgency;
+use crate::width::{pad, text_width, truncate, truncate_with, Truncation};
 use chrono::{DateTime, Local, NaiveDate};
+use clap::ValueEnum;
 use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::io::IsTerminal;
+use std::sync::OnceLock;
 
-const DATE_FORMAT: &str = "%b %-d";
+static TRUNCATION: OnceLock<Truncation> = OnceLock::new();
+
+/// Sets how tables cut long titles for the rest of the process
+///
+/// Only the first call has an effect.
+pub fn set_truncation(truncation: Truncation) {
+    let _ = TRUNCATION.set(truncation);
+}
+
+static STALE_AFTER: OnceLock<Option<HumanDuration>> = OnceLock::new();
+
+/// Sets how long an open todo goes unchanged before tables dim it
+///
+/// Only the first call has an effect.
+pub fn set_stale_after(age: Option<HumanDuration>) {
+    let _ = STALE_AFTER.set(age);
+}
+
+static ASSIGNEE_COLUMN: OnceLock<bool> = OnceLock::new();
+
+/// Sets whether tables have an Assignee column for the rest of the process
+///
+/// Only the first call has an effect.
+pub fn set_assignee_column(shown: bool) {
+    let _ = ASSIGNEE_COLUMN.set(shown);
+}
+
+/// Whether tables have an Assignee column
+fn assignee_column() -> bool {
+    ASSIGNEE_COLUMN.get().copied().unwrap_or(false)
+}
+
+/// Formats the assignee cell, empty when the column is off
+fn assignee_cell(todo: &Todo) -> String {
+    if !assignee_column() {
+        return String::new();
+    }
+    let name = todo.assignee.as_deref().unwrap_or("-");
+    format!("{} ", pad(&truncate(name, TableLayout::ASSIGNEE_WIDTH), TableLayout::ASSIGNEE_WIDTH))
+}
+
+static URGENCY_COLUMN: OnceLock<bool> = OnceLock::new();
+
+/// Sets whether tables have an Urgency column for the rest of the process
+///
+/// Only the first call has an effect.
+pub fn set_urgency_column(shown: bool) {
+    let _ = URGENCY_COLUMN.set(shown);
+}
+
+/// Whether tables have an Urgency column
+fn urgency_column() -> bool {
+    URGENCY_COLUMN.get().copied().unwrap_or(false)
+}
+
+/// Formats the urgency cell, empty when the column is off
+fn urgency_cell(todo: &Todo, blocked: bool) -> String {
+    if !urgency_column() {
+        return String::new();
+    }
+    format!("{:>width$.1} ", urgency(todo, Local::now(), blocked), width = TableLayout::URGENCY_WIDTH)
+}
+
+static ICONS: OnceLock<bool> = OnceLock::new();
+
+/// When to show the icons set with `edit --icon`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconMode {
+    /// Show icons unless the locale says the terminal isn't UTF-8
+    #[default]
+    Auto,
+    /// Always show icons
+    Always,
+    /// Never show icons
+    Never,
+}
+
+/// Sets whether todo icons are shown for the rest of the process
+///
+/// Only the first call has an effect.
+pub fn set_icon_mode(mode: IconMode) {
+    let enabled = match mode {
+        IconMode::Always => true,
+        IconMode::Never => false,
+        IconMode::Auto => utf8_locale(),
+    };
+    let _ = ICONS.set(enabled);
+}
+
+/// Whether the locale allows UTF-8, going by the first of `LC_ALL`,
+/// `LC_CTYPE`, and `LANG` that is set; an unset locale counts as UTF-8
+fn utf8_locale() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()));
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        }
+        None => true,
+    }
+}
+
+/// Returns the title with the todo's icon in front, when icons are shown,
+/// `»` (or `>>` without UTF-8) in front of that for open todos in focus,
+/// and `★` (or `*`) in front of everything for pinned todos
+pub fn icon_title(todo: &Todo) -> String {
+    let icons = *ICONS.get_or_init(utf8_locale);
+    let mut title = match &todo.icon {
+        Some(icon) if icons => format!("{} {}", icon, todo.title),
+        _ => todo.title.clone(),
+    };
+    if todo.focused && !todo.completed {
+        title = format!("{} {}", if icons { "»" } else { ">>" }, title);
+    }
+    if todo.pinned {
+        title = format!("{} {}", if icons { "★" } else { "*" }, title);
+    }
+    title
+}
+
+/// Draws `percent` as a bar `width` cells wide
+fn progress_bar(percent: u8, width: usize) -> String {
+    let (full, empty) = if *ICONS.get_or_init(utf8_locale) { ("█", "░") } else { ("#", "-") };
+    let filled = (usize::from(percent.min(100)) * width + 50) / 100;
+    format!("{}{}", full.repeat(filled), empty.repeat(width - filled))
+}
+
+/// Formats how far along the todo is, after the title in table rows: a
+/// bar, then how much of the checklist is done, e.g. ` ██░░░ 2/5`; empty
+/// for todos without progress
+fn progress_cell(todo: &Todo) -> String {
+    let Some(percent) = todo.progress_percent() else {
+        return String::new();
+    };
+    match todo.checklist_progress() {
+        Some((done, total)) => format!(" {} {}/{}", progress_bar(percent, 5), done, total),
+        None => format!(" {}", progress_bar(percent, 5)),
+    }
+}
+
+/// Colors text with the todo's own color, if it has one, and the theme's
+/// focus style for open todos in focus
+pub fn paint_todo(text: &str, todo: &Todo) -> String {
+    let text = match &todo.color {
+        Some(style) => paint(text, style),
+        None => text.to_string(),
+    };
+    if todo.focused && !todo.completed {
+        paint(&text, &theme().focused)
+    } else {
+        text
+    }
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Names of the built-in themes
+pub const THEMES: [&str; 4] = ["default", "solarized", "monochrome", "high-contrast"];
+
+/// Styles for the parts of the output, each as taken by [`paint`]
+///
+/// Fields missing from a theme in the config keep their default styles.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Styles by priority name in place of the levels' own colors; levels
+    /// not listed are unstyled. `None` keeps the levels' colors.
+    pub priorities: Option<BTreeMap<String, String>>,
+    /// Due dates that have passed, and the agenda's overdue heading
+    pub overdue: String,
+    /// Rows of done and cancelled todos, except the status marker
+    pub completed: String,
+    /// The status marker of done todos
+    pub done: String,
+    /// Table headers and section headings
+    pub header: String,
+    /// Titles of todos in focus
+    pub focused: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            priorities: None,
+            overdue: "red".to_string(),
+            completed: String::new(),
+            done: "green".to_string(),
+            header: "bold".to_string(),
+            focused: "bold".to_string(),
+        }
+    }
+}
+
+impl Theme {
+    /// Returns the built-in theme called `name`
+    pub fn builtin(name: &str) -> Option<Self> {
+        let priorities = |styles: [&str; 4]| {
+            let names = ["low", "normal", "high", "urgent"];
+            Some(names.iter().zip(styles).map(|(n, s)| (n.to_string(), s.to_string())).collect())
+        };
+        let theme = match name {
+            "default" => Self::default(),
+            "solarized" => Self {
+                priorities: priorities(["bright black", "bright cyan", "yellow", "bright red bold"]),
+                overdue: "magenta".to_string(),
+                completed: "bright black".to_string(),
+                done: "cyan".to_string(),
+                header: "blue bold".to_string(),
+                focused: "bright yellow".to_string(),
+            },
+            "monochrome" => Self {
+                priorities: priorities(["dimmed", "", "bold", "bold underline"]),
+                overdue: "bold".to_string(),
+                completed: "dimmed".to_string(),
+                done: String::new(),
+                header: "bold underline".to_string(),
+                focused: "bold".to_string(),
+            },
+            "high-contrast" => Self {
+                priorities: priorities(["white", "bright white", "bright yellow bold", "bright red bold"]),
+                overdue: "bright red bold".to_string(),
+                completed: "bright green".to_string(),
+                done: "bright green bold".to_string(),
+                header: "bright white bold underline".to_string(),
+                focused: "bright cyan bold".to_string(),
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
+
+    /// Checks every style in the theme
+    pub fn check(&self) -> Result<(), String> {
+        let priorities = self.priorities.iter().flat_map(|p| p.values());
+        for style in priorities.chain([&self.overdue, &self.completed, &self.done, &self.header, &self.focused]) {
+            check_style(style)?;
+        }
+        Ok(())
+    }
+
+    /// Styles text shown at `priority`
+    fn priority_style(&self, priority: Priority) -> Option<&str> {
+        match &self.priorities {
+            Some(styles) => styles
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(priority.name()))
+                .map(|(_, style)| style.as_str()),
+            None => priority.level().color.as_deref(),
+        }
+    }
+}
+
+/// Finds the theme called `name`, defined in `custom` or built in
+///
+/// Custom themes take precedence over built-in ones of the same name.
+pub fn find_theme(name: &str, custom: &BTreeMap<String, Theme>) -> anyhow::Result<Theme> {
+    let theme = custom.get(name).cloned().or_else(|| Theme::builtin(name)).ok_or_else(|| {
+        let mut names: Vec<&str> = THEMES.to_vec();
+        names.extend(custom.keys().map(String::as_str).filter(|n| !THEMES.contains(n)));
+        TodoError::Validation(format!("Unknown theme '{}'; choose from {}", name, names.join(", ")))
+    })?;
+    theme
+        .check()
+        .map_err(|e| TodoError::Validation(format!("Theme '{}': {}", name, e)))?;
+    Ok(theme)
+}
+
+/// Sets the theme for the rest of the process
+///
+/// Only the first call has an effect.
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+/// When to use colors in output
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Color when writing to a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    /// Always color
+    Always,
+    /// Never color
+    Never,
+}
+
+/// Applies the color mode to all subsequent output
+pub fn set_color_mode(mode: ColorMode) {
+    let enabled = match mode {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => {
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+            !no_color && std::io::stdout().is_terminal()
+        }
+    };
+    colored::control::set_override(enabled);
+}
 
 /// Formats a datetime as a relative time string (e.g., "2h ago")
 pub fn format_relative_time(dt: &DateTime<Local>) -> String {
@@ -10,89 +329,670 @@ pub fn format_relative_time(dt: &DateTime<Local>) -> String {
     let duration = now.signed_duration_since(*dt);
     
     if duration.num_days() > 30 {
-        dt.format("%b %-d, %Y").to_string()
+        format_date(dt.date_naive())
     } else if duration.num_days() > 0 {
-        format!("{}d ago", duration.num_days())
+        t!("time.days_ago", n = duration.num_days())
     } else if duration.num_hours() > 0 {
-        format!("{}h ago", duration.num_hours())
+        t!("time.hours_ago", n = duration.num_hours())
     } else if duration.num_minutes() > 0 {
-        format!("{}m ago", duration.num_minutes())
+        t!("time.minutes_ago", n = duration.num_minutes())
     } else {
-        "just now".to_string()
+        t!("time.just_now")
+    }
+}
+
+/// Describes a due date relative to today, without color
+fn relative_due(date: NaiveDate) -> String {
+    let today = Local::now().date_naive();
+    let days_until = (date - today).num_days();
+    
+    match days_until {
+        0 => t!("due.today"),
+        1 => t!("due.tomorrow"),
+        2..=6 => t!("due.in_days", n = days_until),
+        _ if days_until > 6 => format_short_date(date),
+        _ => t!("due.overdue", n = -days_until),
     }
 }
 
 /// Formats a due date with relative indicators (e.g., "Tomorrow", "3d overdue")
 pub fn format_due_date(due_date: Option<NaiveDate>) -> String {
     match due_date {
-        Some(date) => {
-            let today = Local::now().date_naive();
-            let days_until = (date - today).num_days();
-            
-            match days_until {
-                0 => "Today".to_string(),
-                1 => "Tomorrow".to_string(),
-                2..=6 => format!("in {}d", days_until),
-                _ if days_until > 6 => date.format(DATE_FORMAT).to_string(),
-                _ => format!("{}d overdue", -days_until).red().to_string(),
-            }
-        }
+        Some(date) if date < Local::now().date_naive() => paint(&relative_due(date), &theme().overdue),
+        Some(date) => relative_due(date),
         None => "-".to_string(),
     }
 }
 
-/// Formats a todo's status (completed or not)
-pub fn format_status(completed: bool) -> String {
-    if completed {
-        "[✔]".green().to_string()
-    } else {
-        "[ ]".to_string()
+/// Formats a todo's status, marking open todos blocked on another todo
+pub fn format_status(status: Status, blocked: bool) -> String {
+    match status {
+        Status::Done => paint("[✔]", &theme().done),
+        Status::Cancelled => "[✘]".dimmed().to_string(),
+        _ if blocked => "[b]".yellow().to_string(),
+        Status::InProgress => "[>]".cyan().to_string(),
+        Status::Waiting => "[w]".yellow().to_string(),
+        Status::Todo => "[ ]".to_string(),
     }
 }
 
 /// Formats a priority with color coding
 pub fn format_priority(priority: Priority) -> String {
-    match priority {
-        Priority::Low => priority.to_string().dimmed().to_string(),
-        Priority::Normal => priority.to_string(),
-        Priority::High => priority.to_string().yellow().to_string(),
-        Priority::Urgent => priority.to_string().red().bold().to_string(),
+    paint_priority(&priority.to_string(), priority)
+}
+
+/// Colors text the way its priority is shown, as set by the theme
+pub fn paint_priority(text: &str, priority: Priority) -> String {
+    match theme().priority_style(priority) {
+        Some(style) => paint(text, style),
+        None => text.to_string(),
+    }
+}
+
+const STYLES: [&str; 4] = ["bold", "dimmed", "italic", "underline"];
+
+/// Applies a style such as `"red bold"` or `"bright blue"`: color names and
+/// `bold`, `dimmed`, `italic`, or `underline`, separated by spaces
+///
+/// Words that aren't understood are ignored; see [`check_style`].
+pub fn paint(text: &str, style: &str) -> String {
+    let mut out = text.normal();
+    let words: Vec<&str> = style.split_whitespace().collect();
+    let mut i = 0;
+    while i < words.len() {
+        // "bright red" is one color spread over two words
+        let (color, used) = match words.get(i + 1) {
+            Some(next) if words[i].eq_ignore_ascii_case("bright") => (format!("bright {}", next), 2),
+            _ => (words[i].to_string(), 1),
+        };
+        out = match color.to_lowercase().as_str() {
+            "bold" => out.bold(),
+            "dimmed" => out.dimmed(),
+            "italic" => out.italic(),
+            "underline" => out.underline(),
+            _ => match color.parse::<Color>() {
+                Ok(color) => out.color(color),
+                Err(()) => out,
+            },
+        };
+        i += used;
+    }
+    out.to_string()
+}
+
+/// Checks that every word of a style is a color or a known style
+pub fn check_style(style: &str) -> Result<(), String> {
+    let words: Vec<&str> = style.split_whitespace().collect();
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i].to_lowercase();
+        if word == "bright" {
+            let color = words.get(i + 1).map(|next| format!("bright {}", next)).unwrap_or_default();
+            color.parse::<Color>().map_err(|()| format!("Unknown color '{}' in style '{}'", color, style))?;
+            i += 2;
+            continue;
+        }
+        if !STYLES.contains(&word.as_str()) && word.parse::<Color>().is_err() {
+            return Err(format!("Unknown color or style '{}' in '{}'", words[i], style));
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+/// Column sizing for the todo table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableLayout {
+    /// Width of the title column
+    pub title_width: usize,
+    /// Width of the whole table, used for the separator line
+    pub total_width: usize,
+    /// How to cut titles longer than the column; `None` shows them in full
+    pub truncation: Option<Truncation>,
+}
+
+impl TableLayout {
+    /// Width taken by every column except the title and assignee
+    const FIXED_WIDTH: usize = 50;
+    
+    /// Width of the assignee column, when shown
+    const ASSIGNEE_WIDTH: usize = 10;
+    
+    /// Width of the urgency column, when shown
+    const URGENCY_WIDTH: usize = 7;
+    
+    /// Narrowest title column a layout will use
+    const MIN_TITLE_WIDTH: usize = 10;
+    
+    /// Sizes the title column so the table spans `width` characters,
+    /// truncating longer titles as set by [`set_truncation`]
+    pub fn for_width(width: usize) -> Self {
+        let fixed = Self::FIXED_WIDTH
+            + if assignee_column() { Self::ASSIGNEE_WIDTH + 1 } else { 0 }
+            + if urgency_column() { Self::URGENCY_WIDTH + 1 } else { 0 };
+        Self {
+            title_width: width.saturating_sub(fixed).max(Self::MIN_TITLE_WIDTH),
+            total_width: width,
+            truncation: Some(TRUNCATION.get().cloned().unwrap_or_default()),
+        }
+    }
+    
+    /// Widens the title column to fit every title, spanning at least `width` characters
+    pub fn wide(todos: &[Todo], width: usize) -> Self {
+        let narrow = Self::for_width(width);
+        let longest = todos
+            .iter()
+            .map(|t| text_width(&icon_title(t)) + text_width(&progress_cell(t)))
+            .max()
+            .unwrap_or(0);
+        let title_width = narrow.title_width.max(longest);
+        Self {
+            title_width,
+            total_width: narrow.total_width + (title_width - narrow.title_width),
+            truncation: None,
+        }
+    }
+    
+    /// Fits the table to the terminal, or lays it out wide when not on one
+    pub fn auto(todos: &[Todo]) -> Self {
+        match terminal::width() {
+            Some(width) => Self::for_width(width),
+            None => Self::wide(todos, 80),
+        }
+    }
+}
+
+impl Default for TableLayout {
+    fn default() -> Self {
+        Self::for_width(80)
     }
 }
 
 /// Helper trait for displaying todos in different formats
 pub trait TodoDisplay {
-    fn display(&self) -> String;
+    /// Formats the todo as a table row using the default layout
+    fn display(&self) -> String {
+        self.display_with(&TableLayout::default())
+    }
+    
+    /// Formats the todo as a table row using the given layout
+    fn display_with(&self, layout: &TableLayout) -> String {
+        self.display_row(layout, false)
+    }
+    
+    /// Formats the todo as a table row, marking it blocked if `blocked`
+    fn display_row(&self, layout: &TableLayout, blocked: bool) -> String;
 }
 
 impl TodoDisplay for Todo {
-    fn display(&self) -> String {
-        let status = format_status(self.completed);
-        let priority = format_priority(self.priority);
+    fn display_row(&self, layout: &TableLayout, blocked: bool) -> String {
+        let status = format_status(self.status, blocked);
         let created = format_relative_time(&self.created_at);
+        let title = icon_title(self);
+        // The progress stays whole, and the title is cut to make room
+        let progress = progress_cell(self);
+        let title = match &layout.truncation {
+            Some(truncation) => {
+                let width = layout.title_width.saturating_sub(text_width(&progress));
+                truncate_with(&title, width, truncation)
+            }
+            None => title,
+        };
+
+        // Closed rows take the theme's completed style in place of their own colors
+        let completed = &theme().completed;
+        if self.status.is_closed() && !completed.is_empty() {
+            let due = self.due_date.map(relative_due).unwrap_or_else(|| "-".to_string());
+            let rest = format!(
+                "{} {}{}{} {:<14} {}",
+                pad(&self.priority.to_string(), 8), urgency_cell(self, blocked), assignee_cell(self),
+                pad(&(title + &progress), layout.title_width), created, due
+            );
+            let id = paint(&format!("{:<5}", self.id), completed);
+            return format!("{} {} {}", id, pad(&status, 7), paint(&rest, completed));
+        }
+
+        let priority = format_priority(self.priority);
         let due = format_due_date(self.due_date);
+        let stale_after = STALE_AFTER.get().copied().flatten();
+        let stale = stale_after.is_some_and(|age| self.is_stale(Local::now(), age.duration()));
+        let title = if stale { paint(&title, "dimmed italic") } else { paint_todo(&title, self) };
+        let title = format!("{}{}", title, progress.dimmed());
         
+        // Colored and wide text can't be padded by `format!`, which counts bytes
         format!(
-            "{:<5} {:<7} {:<8} {:<30} {:<14} {}",
-            self.id, status, priority, self.title, created, due
+            "{:<5} {} {} {}{}{} {:<14} {}",
+            self.id, pad(&status, 7), pad(&priority, 8), urgency_cell(self, blocked), assignee_cell(self),
+            pad(&title, layout.title_width), created, due
         )
     }
 }
 
-/// Displays a list of todos with a header
+/// Formats the table header line
+pub fn table_header(layout: &TableLayout) -> String {
+    let assignee = if assignee_column() {
+        format!("{:<width$} ", t!("table.assignee"), width = TableLayout::ASSIGNEE_WIDTH)
+    } else {
+        String::new()
+    };
+    let urgency = if urgency_column() {
+        format!("{:>width$} ", t!("table.urgency"), width = TableLayout::URGENCY_WIDTH)
+    } else {
+        String::new()
+    };
+    let header = format!(
+        "{:<5} {:<7} {:<8} {}{}{:<width$} {:<14} {}",
+        t!("table.id"),
+        t!("table.status"),
+        t!("table.priority"),
+        urgency,
+        assignee,
+        t!("table.title"),
+        t!("table.created"),
+        t!("table.due"),
+        width = layout.title_width
+    );
+    paint(&header, &theme().header)
+}
+
+/// Renders todos as a table with a header, one line per row
+///
+/// Todos whose UUID is in `blocked` are marked as waiting on a dependency.
+pub fn render_table(todos: &[Todo], layout: &TableLayout, blocked: &HashSet<String>) -> String {
+    if todos.is_empty() {
+        return format!("{}\n", t!("table.empty"));
+    }
+    
+    let mut out = format!("{}\n{}\n", table_header(layout), "-".repeat(layout.total_width));
+    for todo in todos {
+        out.push_str(&todo.display_row(layout, blocked.contains(&todo.uuid)));
+        out.push('\n');
+    }
+    out
+}
+
+/// Renders todos as a table without any color codes, e.g. for writing to files
+pub fn render_plain_table(todos: &[Todo], layout: &TableLayout, blocked: &HashSet<String>) -> String {
+    without_color(|| render_table(todos, layout, blocked))
+}
+
+/// Runs `render` with colors turned off, whatever the color mode
+pub fn without_color(render: impl FnOnce() -> String) -> String {
+    let colorize = colored::control::SHOULD_COLORIZE.should_colorize();
+    colored::control::set_override(false);
+    let out = render();
+    colored::control::set_override(colorize);
+    out
+}
+
+/// Ways to split todos into sections
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Grouping {
+    /// One section per priority, highest first
+    Priority,
+    /// One section per project, by name, then todos without one
+    Project,
+    /// One section per tag, by name, then untagged todos; todos with several tags appear in each
+    Tag,
+    /// Overdue todos, then one section per week of the due date, then undated todos
+    DueWeek,
+    /// One section per status, in the smart sort order
+    Status,
+}
+
+/// Todos sharing a section heading
+#[derive(Debug, Clone)]
+pub struct Group<'a> {
+    pub heading: String,
+    pub todos: Vec<&'a Todo>,
+}
+
+/// Names the week starting on `week`, as seen from `today`
+fn week_heading(week: NaiveDate, today: NaiveDate) -> String {
+    match weeks_between(today, week) {
+        0 => t!("group.this_week"),
+        1 => t!("group.next_week"),
+        _ => t!("group.week_of", date = format_short_date(week)),
+    }
+}
+
+/// Splits todos into sections, keeping their order within each
+///
+/// Sections come in the order described for each [`Grouping`]. Only open
+/// todos count as overdue; closed ones go by the week they were due.
+pub fn group_todos(todos: &[Todo], grouping: Grouping, today: NaiveDate) -> Vec<Group<'_>> {
+    // Keys sort the sections; dates are written as YYYY-MM-DD so they sort as text
+    let mut groups: BTreeMap<(usize, String), Group> = BTreeMap::new();
+    for todo in todos {
+        let keys: Vec<((usize, String), String)> = match grouping {
+            Grouping::Priority => {
+                let rank = Priority::highest().rank() - todo.priority.rank();
+                vec![((rank, String::new()), todo.priority.to_string())]
+            }
+            Grouping::Project => match &todo.project {
+                Some(project) => vec![((0, project.to_lowercase()), project.clone())],
+                None => vec![((1, String::new()), t!("common.no_project"))],
+            },
+            Grouping::Tag if todo.tags.is_empty() => vec![((1, String::new()), t!("group.no_tags"))],
+            Grouping::Tag => todo.tags.iter().map(|tag| ((0, tag.to_lowercase()), format!("#{}", tag))).collect(),
+            Grouping::DueWeek => match todo.due_date {
+                Some(_) if todo.is_overdue_on(today) => vec![((0, String::new()), t!("group.overdue"))],
+                Some(due) => {
+                    let week = start_of_week(due);
+                    vec![((1, week.to_string()), week_heading(week, today))]
+                }
+                None => vec![((2, String::new()), t!("group.no_due"))],
+            },
+            Grouping::Status => vec![((usize::from(todo.status.rank()), String::new()), todo.status.to_string())],
+        };
+        for (key, heading) in keys {
+            groups.entry(key).or_insert_with(|| Group { heading, todos: Vec::new() }).todos.push(todo);
+        }
+    }
+    groups.into_values().collect()
+}
+
+/// Renders todos as a table split into sections, each headed with its count
+pub fn render_grouped_table(
+    todos: &[Todo],
+    layout: &TableLayout,
+    blocked: &HashSet<String>,
+    grouping: Grouping,
+    today: NaiveDate,
+) -> String {
+    if todos.is_empty() {
+        return format!("{}\n", t!("table.empty"));
+    }
+
+    let mut out = format!("{}\n{}\n", table_header(layout), "-".repeat(layout.total_width));
+    for (i, group) in group_todos(todos, grouping, today).iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        out.push_str(&paint(&format!("{} ({})", group.heading, group.todos.len()), &theme().header));
+        out.push('\n');
+        for todo in &group.todos {
+            out.push_str(&todo.display_row(layout, blocked.contains(&todo.uuid)));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Renders todos as a section headed with `heading` and their count, to
+/// follow another table
+pub fn render_section(heading: &str, todos: &[Todo], layout: &TableLayout, blocked: &HashSet<String>) -> String {
+    let heading = paint(&format!("{} ({})", heading, todos.len()), &theme().header);
+    format!("\n{}\n{}", heading, render_table(todos, layout, blocked))
+}
+
+/// Displays a list of todos with a header, sized to the terminal
+///
+/// Todos hidden with `hide` are left out, as in the default `list`.
 pub fn display_todos(todos: &[Todo]) {
+    let today = Local::now().date_naive();
+    let visible: Vec<Todo> = todos.iter().filter(|t| !t.is_hidden_on(today)).cloned().collect();
+    print!("{}", render_table(&visible, &TableLayout::auto(&visible), &blocked_uuids(todos)));
+}
+
+/// Shows the list after a command changed it, unless output is quiet
+pub fn display_updated(todos: &[Todo]) {
+    if output::verbosity() > Verbosity::Quiet {
+        display_todos(todos);
+    }
+}
+
+/// Renders the table with a cursor on one row, for raw-mode terminals
+pub fn render_interactive(todos: &[Todo], cursor: usize, blocked: &HashSet<String>) -> String {
+    // Leave room for the cursor column
+    let width = terminal::width().unwrap_or(80).saturating_sub(2);
+    let layout = TableLayout::for_width(width);
+    let mut lines = vec![
+        format!("  {}", table_header(&layout)),
+        format!("  {}", "-".repeat(layout.total_width)),
+    ];
+    
     if todos.is_empty() {
-        println!("No todos found.");
-        return;
+        lines.push(format!("  {}", t!("table.empty")));
     }
     
-    println!(
-        "{:<5} {:<7} {:<8} {:<30} {:<14} {}",
-        "ID", "Status", "Priority", "Title", "Created", "Due"
-    );
-    println!("{}", "-".repeat(80));
+    for (i, todo) in todos.iter().enumerate() {
+        if i == cursor {
+            lines.push(format!("{} {}", ">".bold(), todo.display_row(&layout, blocked.contains(&todo.uuid))));
+        } else {
+            lines.push(format!("  {}", todo.display_row(&layout, blocked.contains(&todo.uuid))));
+        }
+    }
     
-    for todo in todos {
-        println!("{}", todo.display());
+    lines.push(String::new());
+    lines.push(t!("interactive.help").dimmed().to_string());
+    lines.join("\r\n")
+}
+
+/// Renders every set field of a todo, one per line, for `show`
+///
+/// Dependencies are looked up in `todos` to show their IDs, and `closed_by`
+/// is who closed the todo, when its history says.
+pub fn render_details(todo: &Todo, todos: &[Todo], blocked: bool, closed_by: Option<&str>) -> String {
+    let mut lines = vec![paint(&format!("#{} {}", todo.id, paint_todo(&icon_title(todo), todo)), &theme().header)];
+    let mut field = |name: &str, value: String| {
+        lines.push(format!("  {} {}", pad(&format!("{}:", name).dimmed().to_string(), 12), value));
+    };
+
+    field(&t!("show.status"), format!("{} {}", format_status(todo.status, blocked), todo.status));
+    field(&t!("show.priority"), format_priority(todo.priority));
+    if let Some(due) = todo.due_date {
+        field(&t!("show.due"), format!("{} ({})", format_date(due), format_due_date(Some(due))));
+    }
+    if let Some(start) = todo.start_date {
+        field(&t!("show.starts"), format_date(start));
+    }
+    if let Some(estimate) = todo.estimate {
+        field(&t!("show.estimate"), estimate.to_string());
+    }
+    if let Some(percent) = todo.progress_percent() {
+        field(&t!("show.progress"), format!("{} {}%", progress_bar(percent, 10), percent));
+    }
+    if let Some(project) = &todo.project {
+        field(&t!("show.project"), project.clone());
+    }
+    if let Some(assignee) = &todo.assignee {
+        field(&t!("show.assignee"), assignee.clone());
+    }
+    if let Some(external) = &todo.external_ref {
+        let source = match &external.url {
+            Some(url) if *url != external.id => format!("{} {} {}", external.kind, external.id, url.dimmed()),
+            _ => format!("{} {}", external.kind, external.id),
+        };
+        field(&t!("show.source"), source);
+    }
+    if !todo.tags.is_empty() {
+        field(&t!("show.tags"), todo.tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" "));
+    }
+    if !todo.depends_on.is_empty() {
+        let ids: Vec<String> = todo
+            .depends_on
+            .iter()
+            .map(|uuid| match todos.iter().find(|t| &t.uuid == uuid) {
+                Some(dependency) => format!("#{}", dependency.id),
+                None => uuid.chars().take(8).collect(),
+            })
+            .collect();
+        field(&t!("show.waits_on"), ids.join(", "));
+    }
+    if let Some(scheduled) = todo.scheduled {
+        field(&t!("show.scheduled"), format_date(scheduled));
+    }
+    if let Some(hidden) = todo.hidden_until {
+        field(&t!("show.hidden"), t!("show.until", date = format_date(hidden)));
+    }
+    let stamp = |at: &DateTime<Local>| format!("{} {}", format_date(at.date_naive()), at.format("%H:%M"));
+    field(&t!("show.created"), format!("{} ({})", stamp(&todo.created_at), format_relative_time(&todo.created_at)));
+    let modified = todo.modified();
+    let by = |name: Option<&str>| name.map(|name| t!("show.by", name = name)).unwrap_or_default();
+    field(&t!("show.modified"), format!(
+        "{} ({}){}",
+        stamp(&modified), format_relative_time(&modified), by(todo.modified_by.as_deref())
+    ));
+    if let Some(completed) = todo.completed_at {
+        field(&t!("show.closed"), format!(
+            "{} ({}){}",
+            stamp(&completed), format_relative_time(&completed), by(closed_by)
+        ));
+    }
+    field("UUID", todo.uuid.clone());
+
+    if let Some(notes) = &todo.notes {
+        lines.push(String::new());
+        lines.extend(notes.lines().map(|line| format!("  {}", line)));
+    }
+    if !todo.checklist.is_empty() {
+        lines.push(String::new());
+        lines.push(paint(&t!("show.checklist"), &theme().header));
+        for (i, item) in todo.checklist.iter().enumerate() {
+            let text = if item.done { item.text.dimmed().to_string() } else { item.text.clone() };
+            lines.push(format!("  {}. [{}] {}", i + 1, if item.done { "x" } else { " " }, text));
+        }
+    }
+    if !todo.attachments.is_empty() {
+        lines.push(String::new());
+        lines.push(paint(&t!("show.attachments"), &theme().header));
+        for (i, attachment) in todo.attachments.iter().enumerate() {
+            lines.push(format!("  {}. {} {}", i + 1, format!("[{}]", attachment.kind).dimmed(), attachment.target));
+        }
+    }
+    if !todo.comments.is_empty() {
+        lines.push(String::new());
+        lines.push(paint(&t!("show.comments"), &theme().header));
+        let mut comments: Vec<&Comment> = todo.comments.iter().collect();
+        comments.sort_by_key(|c| c.at);
+        for comment in comments {
+            let stamp = stamp(&comment.at);
+            let indent = " ".repeat(stamp.len() + 2);
+            for (i, line) in comment.text.lines().enumerate() {
+                let lead = if i == 0 { format!("  {}  ", stamp.dimmed()) } else { format!("  {}", indent) };
+                lines.push(format!("{}{}", lead, line));
+            }
+        }
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Formats a todo as an indented agenda line
+fn agenda_item(todo: &Todo, blocked: bool) -> String {
+    let mut line = format!(
+        "  {} #{:<4} {} {}",
+        format_status(todo.status, blocked),
+        todo.id,
+        pad(&format_priority(todo.priority), 8),
+        paint_todo(&icon_title(todo), todo)
+    );
+    if let Some(project) = &todo.project {
+        line.push_str(&format!(" [{}]", project).dimmed().to_string());
+    }
+    for tag in &todo.tags {
+        line.push_str(&format!(" #{}", tag).dimmed().to_string());
+    }
+    line
+}
+
+/// Renders open todos as a day-by-day agenda for `days` days from `today`
+///
+/// Overdue todos come first, then one section per day that has todos due
+/// (today always gets one), then the backlog of todos without a due date.
+/// Todos due after the period are only counted. Within a section todos
+/// keep their order in `todos`.
+pub fn render_agenda(todos: &[Todo], today: NaiveDate, days: i64, blocked: &HashSet<String>) -> String {
+    let open: Vec<&Todo> = todos.iter().filter(|t| !t.completed).collect();
+    let end = today + chrono::Duration::days(days);
+    let mut sections = Vec::new();
+
+    let overdue: Vec<&Todo> = open.iter().copied().filter(|t| t.is_overdue_on(today)).collect();
+    if !overdue.is_empty() {
+        let mut section = vec![paint(&t!("agenda.overdue"), &format!("{} bold", theme().overdue))];
+        for todo in overdue {
+            let late = format_due_date(todo.due_date);
+            section.push(format!("{} {}", agenda_item(todo, blocked.contains(&todo.uuid)), late));
+        }
+        sections.push(section);
+    }
+
+    let mut day = today;
+    while day < end {
+        let due: Vec<&Todo> = open.iter().copied().filter(|t| t.due_date == Some(day)).collect();
+        if !due.is_empty() || day == today {
+            let mut heading = format_day(day);
+            if day == today {
+                heading.push_str(&t!("agenda.today"));
+            }
+            let mut section = vec![paint(&heading, &theme().header)];
+            if due.is_empty() {
+                section.push(t!("agenda.nothing").dimmed().to_string());
+            }
+            section.extend(due.iter().map(|t| agenda_item(t, blocked.contains(&t.uuid))));
+            sections.push(section);
+        }
+        day += chrono::Duration::days(1);
+    }
+
+    let later = open.iter().filter(|t| t.due_date.is_some_and(|d| d >= end)).count();
+    if later > 0 {
+        sections.push(vec![t!("agenda.later", count = later, date = format_short_date(end)).dimmed().to_string()]);
+    }
+
+    let backlog: Vec<&Todo> = open.iter().copied().filter(|t| t.due_date.is_none()).collect();
+    if !backlog.is_empty() {
+        let mut section = vec![paint(&t!("agenda.backlog"), &theme().header)];
+        section.extend(backlog.iter().map(|t| agenda_item(t, blocked.contains(&t.uuid))));
+        sections.push(section);
+    }
+
+    let mut output: Vec<String> = sections.into_iter().map(|section| section.join("\n")).collect();
+    output.push(String::new());
+    output.join("\n\n")
+}
+
+/// Renders todos as side-by-side Backlog, In Progress, Waiting, and Done columns spanning `width`
+///
+/// Each card shows the todo's ID and title, in the todo's color or else its
+/// priority's; todos in `blocked` are marked. Cancelled todos are left out.
+/// Cards keep their order in `todos`.
+pub fn render_board(todos: &[Todo], width: usize, blocked: &HashSet<String>) -> String {
+    const GAP: &str = "  ";
+    let statuses = [Status::Todo, Status::InProgress, Status::Waiting, Status::Done];
+    let column_width = (width.saturating_sub(GAP.len() * (statuses.len() - 1)) / statuses.len()).max(16);
+    let truncation = TRUNCATION.get().cloned().unwrap_or_default();
+
+    let columns: Vec<Vec<String>> = statuses
+        .iter()
+        .map(|&status| {
+            let cards: Vec<&Todo> = todos.iter().filter(|t| t.status == status).collect();
+            let heading = format!("{} ({})", status, cards.len());
+            let heading = paint(&truncate(&heading, column_width), &theme().header);
+            let mut column = vec![heading, "─".repeat(column_width)];
+            for todo in cards {
+                let marker = if blocked.contains(&todo.uuid) { "[b] " } else { "" };
+                let card = format!("#{} {}{}", todo.id, marker, icon_title(todo));
+                let card = truncate_with(&card, column_width, &truncation);
+                column.push(match &todo.color {
+                    Some(_) => paint_todo(&card, todo),
+                    None => paint_priority(&card, todo.priority),
+                });
+            }
+            column
+        })
+        .collect();
+
+    let rows = columns.iter().map(Vec::len).max().unwrap_or(0);
+    let mut out = String::new();
+    for row in 0..rows {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|column| pad(column.get(row).map_or("", String::as_str), column_width))
+            .collect();
+        out.push_str(cells.join(GAP).trim_end());
+        out.push('\n');
     }
+    out
 }
@@ -0,0 +1,101 @@
+//! Human-friendly durations such as `15m`, `2h30m`, or `1w`
+
+use chrono::Duration;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// A duration written as a sequence of `<number><unit>` parts
+///
+/// Units are `w` (weeks), `d` (days), `h` (hours), `m` (minutes), and
+/// `s` (seconds), adding up to at most [`HumanDuration::MAX_DAYS`].
+/// Serialized as its string form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HumanDuration(pub Duration);
+
+impl HumanDuration {
+    /// Longest duration accepted, about 100 years, so times offset by one stay in range
+    pub const MAX_DAYS: i64 = 36_525;
+
+    /// Returns the wrapped duration
+    pub fn duration(self) -> Duration {
+        self.0
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.trim().to_lowercase();
+        if input.is_empty() {
+            return Err("Duration cannot be empty".to_string());
+        }
+
+        let mut total = Duration::zero();
+        let mut number = String::new();
+        for c in input.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+
+            let value: i64 = number
+                .parse()
+                .map_err(|_| format!("Invalid duration '{}': expected a number before '{}'", s, c))?;
+            let part = match c {
+                'w' => Duration::try_weeks(value),
+                'd' => Duration::try_days(value),
+                'h' => Duration::try_hours(value),
+                'm' => Duration::try_minutes(value),
+                's' => Duration::try_seconds(value),
+                _ => return Err(format!("Invalid duration '{}': unknown unit '{}' (use w, d, h, m, s)", s, c)),
+            };
+            total = part
+                .and_then(|part| total.checked_add(&part))
+                .filter(|total| total.num_days() <= Self::MAX_DAYS)
+                .ok_or_else(|| format!("Invalid duration '{}': longer than {} days", s, Self::MAX_DAYS))?;
+            number.clear();
+        }
+
+        if !number.is_empty() {
+            return Err(format!("Invalid duration '{}': missing unit after {}", s, number));
+        }
+
+        Ok(HumanDuration(total))
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut seconds = self.0.num_seconds();
+        if seconds == 0 {
+            return write!(f, "0m");
+        }
+        if seconds < 0 {
+            write!(f, "-")?;
+            seconds = -seconds;
+        }
+
+        for (unit, size) in [("w", 604_800), ("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)] {
+            if seconds >= size {
+                write!(f, "{}{}", seconds / size, unit)?;
+                seconds %= size;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for HumanDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for HumanDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
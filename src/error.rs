@@ -0,0 +1,132 @@
+//! Errors library consumers can match on
+//!
+//! Functions still return [`anyhow::Result`], with a [`TodoError`] at the
+//! root of the failures callers are likely to handle. Find it with
+//! `err.downcast_ref::<TodoError>()`, or use [`exit_code`] to turn any
+//! error into the process exit status.
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Exit status for errors without a more specific code
+pub const EXIT_FAILURE: u8 = 1;
+
+/// A failure with a well-known cause
+#[derive(Debug)]
+pub enum TodoError {
+    /// No todo matches the given ID or UUID prefix, as typed
+    NotFound { id: String },
+    /// A UUID prefix matches more than one todo
+    Ambiguous { prefix: String, matches: usize },
+    /// A title search matches more than one todo, listed by ID
+    AmbiguousTitle { query: String, ids: Vec<u64> },
+    /// Reading or writing a file failed
+    Storage { action: &'static str, path: PathBuf, source: io::Error },
+    /// A change was refused: the list is open read-only, or, with a
+    /// `source`, the file system doesn't allow writing to `path`
+    ReadOnly { path: PathBuf, source: Option<io::Error> },
+    /// A file isn't valid JSON of the expected shape
+    Parse { path: PathBuf, source: serde_json::Error },
+    /// Input was rejected, e.g. an empty title
+    Validation(String),
+    /// A plugin program exited unsuccessfully, with `code` as its status
+    Plugin { program: String, code: u8 },
+}
+
+impl TodoError {
+    /// Returns the process exit status for this error
+    ///
+    /// Not-found errors exit with 2, storage and read-only errors with 3,
+    /// parse errors with 4, and validation errors with 5. A failed plugin
+    /// passes on its own status.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            TodoError::NotFound { .. } | TodoError::Ambiguous { .. } | TodoError::AmbiguousTitle { .. } => 2,
+            TodoError::Storage { .. } | TodoError::ReadOnly { .. } => 3,
+            TodoError::Parse { .. } => 4,
+            TodoError::Validation(_) => 5,
+            TodoError::Plugin { code, .. } => *code,
+        }
+    }
+
+    /// Builds a storage error for a failed `action` ("read", "write to", ...) on `path`
+    ///
+    /// Writes the file system refuses, for lack of permission or because
+    /// it is mounted read-only, become [`TodoError::ReadOnly`].
+    pub fn storage(action: &'static str, path: impl Into<PathBuf>, source: io::Error) -> Self {
+        let refused = matches!(source.kind(), io::ErrorKind::ReadOnlyFilesystem | io::ErrorKind::PermissionDenied);
+        if refused && action != "read" {
+            return TodoError::ReadOnly { path: path.into(), source: Some(source) };
+        }
+        TodoError::Storage { action, path: path.into(), source }
+    }
+
+    /// Builds the error for a change to a list opened read-only
+    pub fn read_only(path: impl Into<PathBuf>) -> Self {
+        TodoError::ReadOnly { path: path.into(), source: None }
+    }
+}
+
+impl fmt::Display for TodoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TodoError::NotFound { id } if id.parse::<u64>().is_ok() => write!(f, "Todo #{} not found", id),
+            TodoError::NotFound { id } => write!(f, "Todo '{}' not found", id),
+            TodoError::Ambiguous { prefix, matches } => {
+                write!(f, "UUID prefix '{}' is ambiguous ({} matches)", prefix, matches)
+            }
+            TodoError::AmbiguousTitle { query, ids } => {
+                let ids: Vec<String> = ids.iter().map(|id| format!("#{}", id)).collect();
+                write!(f, "'{}' matches several todos ({}); use an ID", query, ids.join(", "))
+            }
+            TodoError::Storage { action, path, .. } => write!(f, "Failed to {} {}", action, path.display()),
+            TodoError::ReadOnly { path, source: None } => write!(
+                f,
+                "{} is open read-only (--read-only or read_only in the config), so nothing was changed",
+                path.display()
+            ),
+            TodoError::ReadOnly { path, source: Some(source) } => {
+                let reason = match source.kind() {
+                    io::ErrorKind::ReadOnlyFilesystem => "the file system is read-only",
+                    _ => "permission denied",
+                };
+                write!(
+                    f,
+                    "Cannot write to {}: {}. Pass --read-only to view the list without changing it",
+                    path.display(),
+                    reason
+                )
+            }
+            TodoError::Parse { path, .. } => write!(f, "Failed to parse {}", path.display()),
+            TodoError::Validation(message) => f.write_str(message),
+            TodoError::Plugin { program, code } => write!(f, "{} exited with status {}", program, code),
+        }
+    }
+}
+
+impl std::error::Error for TodoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TodoError::Storage { source, .. } => Some(source),
+            TodoError::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the exit status for an error from a command
+///
+/// Uses the first [`TodoError`] in the chain; I/O errors raised elsewhere
+/// count as storage errors.
+pub fn exit_code(err: &anyhow::Error) -> u8 {
+    for cause in err.chain() {
+        if let Some(err) = cause.downcast_ref::<TodoError>() {
+            return err.exit_code();
+        }
+    }
+    if err.chain().any(|cause| cause.is::<io::Error>()) {
+        return 3;
+    }
+    EXIT_FAILURE
+}
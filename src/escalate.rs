@@ -0,0 +1,115 @@
+//! Raising priorities as due dates approach
+//!
+//! A rule bumps a todo from one priority to a higher one once its due date
+//! is within `before` of today, or once it's overdue when `before` is unset.
+//! Rules are applied until none match, so an overdue Normal todo can pass
+//! through High on its way to Urgent in one go.
+
+use crate::duration::HumanDuration;
+use crate::models::{Priority, Todo};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A single escalation rule, e.g. `{"from": "normal", "to": "high", "before": "2d"}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawRule", into = "RawRule")]
+pub struct EscalationRule {
+    pub from: Priority,
+    pub to: Priority,
+    /// How close the due date must be; `None` waits until it has passed
+    pub before: Option<HumanDuration>,
+}
+
+/// The config file form of a rule, with priorities written in lowercase
+#[derive(Serialize, Deserialize)]
+struct RawRule {
+    from: String,
+    to: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    before: Option<HumanDuration>,
+}
+
+impl TryFrom<RawRule> for EscalationRule {
+    type Error = String;
+
+    fn try_from(raw: RawRule) -> Result<Self, Self::Error> {
+        let from = <Priority as FromStr>::from_str(&raw.from)?;
+        let to = <Priority as FromStr>::from_str(&raw.to)?;
+        if to <= from {
+            return Err(format!("Escalation from {} to {} must raise the priority", from, to));
+        }
+        Ok(Self { from, to, before: raw.before })
+    }
+}
+
+impl From<EscalationRule> for RawRule {
+    fn from(rule: EscalationRule) -> Self {
+        Self {
+            from: rule.from.to_string().to_lowercase(),
+            to: rule.to.to_string().to_lowercase(),
+            before: rule.before,
+        }
+    }
+}
+
+/// The default rules: Normal to High two days before due, High to Urgent when overdue
+///
+/// With custom priorities, the default level rises one step two days before
+/// due and one more once overdue, as far as the scale goes.
+pub fn default_rules() -> Vec<EscalationRule> {
+    let mut rules = Vec::new();
+    let Some(next) = Priority::default().raised() else {
+        return rules;
+    };
+    rules.push(EscalationRule {
+        from: Priority::default(),
+        to: next,
+        before: Some(HumanDuration(chrono::Duration::days(2))),
+    });
+    if let Some(top) = next.raised() {
+        rules.push(EscalationRule { from: next, to: top, before: None });
+    }
+    rules
+}
+
+impl EscalationRule {
+    /// Returns true if the rule applies to `todo` on `today`
+    pub fn matches(&self, todo: &Todo, today: NaiveDate) -> bool {
+        if todo.completed || todo.priority != self.from {
+            return false;
+        }
+        let Some(due) = todo.due_date else {
+            return false;
+        };
+        match self.before {
+            Some(before) => due - today <= before.duration(),
+            None => due < today,
+        }
+    }
+}
+
+/// A priority change made by [`escalate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Escalation {
+    /// Index of the todo in the slice given to [`escalate`]
+    pub index: usize,
+    pub from: Priority,
+    pub to: Priority,
+}
+
+/// Applies `rules` to `todos`, returning one change per escalated todo
+pub fn escalate(todos: &mut [Todo], rules: &[EscalationRule], today: NaiveDate) -> Vec<Escalation> {
+    let mut changes = Vec::new();
+    for (index, todo) in todos.iter_mut().enumerate() {
+        let from = todo.priority;
+        // Every rule raises the priority, so this ends after at most four steps
+        while let Some(rule) = rules.iter().find(|rule| rule.matches(todo, today)) {
+            todo.priority = rule.to;
+        }
+        if todo.priority != from {
+            changes.push(Escalation { index, from, to: todo.priority });
+        }
+    }
+    changes
+}
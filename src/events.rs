@@ -0,0 +1,263 @@
+//! Machine-readable change events for external automations
+//!
+//! Every save is compared with the todos it replaces, and each todo that
+//! was added, completed, edited, or removed becomes one JSON line with its
+//! state before and after. Events for a save are written in a fixed order:
+//! todos in their new list order, then removed ones in their old order.
+
+use crate::error::TodoError;
+use crate::history::FieldChange;
+use crate::journal::JournalEntry;
+use crate::models::{Status, Todo, TrashedTodo};
+use crate::storage::{StorageFormat, TodoStore};
+use crate::sync::same;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// What happened to a todo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    Added,
+    Completed,
+    Edited,
+    Removed,
+}
+
+/// A change to a single todo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub timestamp: DateTime<Local>,
+    #[serde(rename = "event")]
+    pub kind: EventKind,
+    pub id: u64,
+    pub uuid: String,
+    /// The todo before the change; `None` when it was added
+    pub before: Option<Todo>,
+    /// The todo after the change; `None` when it was removed
+    pub after: Option<Todo>,
+    /// Who saved the change, when the store knew
+    pub by: Option<String>,
+}
+
+impl Event {
+    /// Returns a line naming the change and the todo, e.g. `complete #3    Write tests`
+    pub fn describe(&self) -> String {
+        let verb = match self.kind {
+            EventKind::Added => "add",
+            EventKind::Completed => "complete",
+            EventKind::Edited => "change",
+            EventKind::Removed => "remove",
+        };
+        let title = self.after.as_ref().or(self.before.as_ref()).map_or("", |t| t.title.as_str());
+        format!("{:<8} #{:<4} {}", verb, self.id, title)
+    }
+}
+
+/// Lists the changes that turn `before` into `after`, matching todos by UUID
+///
+/// A todo that became done is reported as completed; any other change to
+/// it, reopening and cancelling included, as edited. Events are not
+/// credited to anyone; [`EventStore`] fills in who saved them.
+pub fn diff(before: &[Todo], after: &[Todo], timestamp: DateTime<Local>) -> Vec<Event> {
+    let old: HashMap<&str, &Todo> = before.iter().map(|t| (t.uuid.as_str(), t)).collect();
+    let kept: HashSet<&str> = after.iter().map(|t| t.uuid.as_str()).collect();
+    let event = |kind, todo: &Todo, before: Option<&Todo>, after: Option<&Todo>| Event {
+        timestamp,
+        kind,
+        id: todo.id,
+        uuid: todo.uuid.clone(),
+        before: before.cloned(),
+        after: after.cloned(),
+        by: None,
+    };
+
+    let mut events = Vec::new();
+    for todo in after {
+        match old.get(todo.uuid.as_str()) {
+            None => events.push(event(EventKind::Added, todo, None, Some(todo))),
+            Some(previous) if same(previous, todo) => {}
+            Some(previous) => {
+                let completed = previous.status != Status::Done && todo.status == Status::Done;
+                let kind = if completed { EventKind::Completed } else { EventKind::Edited };
+                events.push(event(kind, todo, Some(previous), Some(todo)));
+            }
+        }
+    }
+    for todo in before.iter().filter(|t| !kept.contains(t.uuid.as_str())) {
+        events.push(event(EventKind::Removed, todo, Some(todo), None));
+    }
+    events
+}
+
+/// Where events are sent
+#[derive(Debug, Clone)]
+pub struct EventSink {
+    target: Target,
+}
+
+#[derive(Debug, Clone)]
+enum Target {
+    File(PathBuf),
+    #[cfg(feature = "webhooks")]
+    Webhooks(Vec<crate::integrations::webhooks::Webhook>),
+}
+
+impl EventSink {
+    /// Appends to the file at `path`, creating it if needed
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self { target: Target::File(path.into()) }
+    }
+
+    /// Posts added and completed todos to the webhooks that want them
+    #[cfg(feature = "webhooks")]
+    pub fn webhooks(hooks: Vec<crate::integrations::webhooks::Webhook>) -> Self {
+        Self { target: Target::Webhooks(hooks) }
+    }
+
+    /// Writes to an open file descriptor inherited from the parent process
+    ///
+    /// Goes through `/dev/fd`, so this works on Linux and macOS only.
+    pub fn fd(fd: u32) -> Self {
+        Self::file(format!("/dev/fd/{}", fd))
+    }
+
+    /// Appends the events as JSON lines, or posts them to the webhooks
+    pub fn write(&self, events: &[Event]) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        match &self.target {
+            Target::File(path) => append(path, events),
+            #[cfg(feature = "webhooks")]
+            Target::Webhooks(hooks) => {
+                crate::integrations::webhooks::deliver_events(hooks, events);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Appends the events to the file at `path` as JSON lines
+fn append(path: &Path, events: &[Event]) -> Result<()> {
+    let mut lines = String::new();
+    for event in events {
+        lines.push_str(&serde_json::to_string(event).with_context(|| "Failed to serialize event")?);
+        lines.push('\n');
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| TodoError::storage("open", path, e))?;
+    file.write_all(lines.as_bytes())
+        .map_err(|e| TodoError::storage("write events to", path, e))?;
+    Ok(())
+}
+
+/// Wraps another store, writing an event for every todo each save changes
+pub struct EventStore {
+    inner: Box<dyn TodoStore>,
+    sink: EventSink,
+}
+
+impl EventStore {
+    /// Reports the changes saved to `inner` to `sink`
+    pub fn new(inner: Box<dyn TodoStore>, sink: EventSink) -> Self {
+        Self { inner, sink }
+    }
+}
+
+impl TodoStore for EventStore {
+    fn load(&mut self) -> Result<Vec<Todo>> {
+        self.inner.load()
+    }
+
+    fn for_each_todo(&mut self, visit: &mut dyn FnMut(Todo)) -> Result<()> {
+        self.inner.for_each_todo(visit)
+    }
+
+    /// Saves through the inner store, then writes the events for what changed,
+    /// credited to the store's actor
+    fn save(&mut self, todos: &[Todo]) -> Result<()> {
+        let before = self.inner.load()?;
+        self.inner.save(todos)?;
+        let mut events = diff(&before, todos, Local::now());
+        for event in &mut events {
+            event.by = self.inner.actor().map(String::from);
+        }
+        self.sink.write(&events)
+    }
+
+    fn append_journal(&mut self, entry: &JournalEntry) -> Result<()> {
+        self.inner.append_journal(entry)
+    }
+
+    fn load_journal(&self) -> Result<Vec<JournalEntry>> {
+        self.inner.load_journal()
+    }
+
+    fn load_history(&self) -> Result<Vec<FieldChange>> {
+        self.inner.load_history()
+    }
+
+    fn actor(&self) -> Option<&str> {
+        self.inner.actor()
+    }
+
+    fn last_modified(&self) -> Option<SystemTime> {
+        self.inner.last_modified()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.inner.is_read_only()
+    }
+
+    fn backup_location(&self) -> Option<(PathBuf, String)> {
+        self.inner.backup_location()
+    }
+
+    fn pending(&self) -> Result<Option<Vec<Todo>>> {
+        self.inner.pending()
+    }
+
+    fn discard_pending(&mut self) -> Result<()> {
+        self.inner.discard_pending()
+    }
+
+    /// Opens the other list with events going to the same sink
+    fn open_list(&self, name: &str) -> Result<Box<dyn TodoStore>> {
+        Ok(Box::new(EventStore::new(self.inner.open_list(name)?, self.sink.clone())))
+    }
+
+    fn load_trash(&self) -> Result<Vec<TrashedTodo>> {
+        self.inner.load_trash()
+    }
+
+    fn save_trash(&mut self, trash: &[TrashedTodo]) -> Result<()> {
+        self.inner.save_trash(trash)
+    }
+
+    fn load_cache(&self, name: &str) -> Option<String> {
+        self.inner.load_cache(name)
+    }
+
+    fn save_cache(&mut self, name: &str, content: &str) -> Result<()> {
+        self.inner.save_cache(name, content)
+    }
+
+    fn convert(&mut self, format: StorageFormat) -> Result<bool> {
+        self.inner.convert(format)
+    }
+
+    fn compact(&mut self) -> Result<usize> {
+        self.inner.compact()
+    }
+}
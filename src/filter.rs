@@ -0,0 +1,132 @@
+//! Reusable criteria for selecting todos
+
+use crate::calendar::{end_of_week, start_of_week};
+use crate::models::{Priority, Status, Todo};
+use crate::query::Query;
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use clap::ValueEnum;
+
+/// Due-date windows relative to today
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DueWindow {
+    /// Due today
+    Today,
+    /// Due within the next 7 days (including today)
+    Week,
+    /// Due this calendar week, from its first day to its last, overdue days included
+    ThisWeek,
+}
+
+/// Criteria a todo must satisfy to be selected
+///
+/// Every criterion left unset matches all todos.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// Only incomplete todos
+    pub active_only: bool,
+    /// Only todos at or above this priority
+    pub min_priority: Option<Priority>,
+    /// Only todos due within this window
+    pub due: Option<DueWindow>,
+    /// Only incomplete todos whose due date has passed
+    pub overdue: bool,
+    /// Only todos due strictly before this date
+    pub due_before: Option<NaiveDate>,
+    /// Only todos due strictly after this date
+    pub due_after: Option<NaiveDate>,
+    /// Only todos matching this query expression
+    pub query: Option<Query>,
+    /// Hide completed todos finished before this time
+    pub completed_since: Option<DateTime<Local>>,
+    /// Hide todos hidden or scheduled until a later date
+    pub visible_only: bool,
+    /// Only todos in this project (case-insensitive)
+    pub project: Option<String>,
+    /// Only todos assigned to this person (case-insensitive)
+    pub assignee: Option<String>,
+    /// Only todos in one of these statuses; empty means any
+    pub statuses: Vec<Status>,
+    /// Only todos on the focus list
+    pub focused: bool,
+}
+
+impl Filter {
+    /// Returns true if the todo satisfies every criterion, relative to `today`
+    pub fn matches_on(&self, todo: &Todo, today: NaiveDate) -> bool {
+        if self.active_only && todo.completed {
+            return false;
+        }
+
+        if let Some(since) = self.completed_since {
+            if todo.completed && todo.completed_at.is_none_or(|at| at < since) {
+                return false;
+            }
+        }
+
+        if self.visible_only && todo.is_hidden_on(today) {
+            return false;
+        }
+
+        if self.focused && !todo.focused {
+            return false;
+        }
+
+        if !self.statuses.is_empty() && !self.statuses.contains(&todo.status) {
+            return false;
+        }
+
+        if let Some(project) = &self.project {
+            if !todo.project.as_ref().is_some_and(|p| p.eq_ignore_ascii_case(project)) {
+                return false;
+            }
+        }
+
+        if let Some(assignee) = &self.assignee {
+            if !todo.assignee.as_ref().is_some_and(|a| a.eq_ignore_ascii_case(assignee)) {
+                return false;
+            }
+        }
+
+        if let Some(min_prio) = self.min_priority {
+            if todo.priority < min_prio {
+                return false;
+            }
+        }
+
+        if let Some(query) = &self.query {
+            if !query.matches_on(todo, today) {
+                return false;
+            }
+        }
+
+        let needs_due = self.due.is_some() || self.overdue
+            || self.due_before.is_some() || self.due_after.is_some();
+        let due = match todo.due_date {
+            Some(due) => due,
+            None => return !needs_due,
+        };
+
+        let in_window = match self.due {
+            Some(DueWindow::Today) => due == today,
+            Some(DueWindow::Week) => due >= today && due < today + Duration::days(7),
+            Some(DueWindow::ThisWeek) => (start_of_week(today)..=end_of_week(today)).contains(&due),
+            None => true,
+        };
+
+        in_window
+            && (!self.overdue || todo.is_overdue_on(today))
+            && self.due_before.is_none_or(|before| due < before)
+            && self.due_after.is_none_or(|after| due > after)
+    }
+
+    /// Returns true if the todo satisfies every criterion as of now
+    pub fn matches(&self, todo: &Todo) -> bool {
+        self.matches_on(todo, Local::now().date_naive())
+    }
+
+    /// Removes the todos that do not satisfy the filter
+    pub fn apply(&self, todos: &mut Vec<Todo>) {
+        let today = Local::now().date_naive();
+        todos.retain(|t| self.matches_on(t, today));
+    }
+}
@@ -0,0 +1,217 @@
+//! Reading iCalendar (`.ics`) files
+//!
+//! Every `VEVENT` and `VTODO` becomes a todo due on the day it's due or
+//! starts, which suits deadlines published as a calendar feed. The
+//! calendar's name (`X-WR-CALNAME`) becomes the project, categories
+//! become tags, the description becomes the notes, and the entry's `UID`
+//! and `URL` are kept for `open`. To-dos keep their priority and status;
+//! cancelled events are imported as cancelled. Repeating entries are only
+//! read once, for their first occurrence.
+//!
+//! Titles are the entry's summary unless a template says otherwise, with
+//! `{summary}`, `{location}`, `{categories}`, and `{calendar}` filled in,
+//! e.g. `"{calendar}: {summary}"`.
+
+use super::ImportedTodo;
+use crate::error::TodoError;
+use crate::models::{is_web_url, ExternalKind, ExternalRef, Priority, Status, TodoBuilder};
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+/// A content line: its name and its value as written, without parameters
+struct Property {
+    name: String,
+    value: String,
+}
+
+/// An entry with the properties it has directly, not those of nested alarms
+#[derive(Default)]
+struct Entry {
+    todo: bool,
+    properties: Vec<Property>,
+}
+
+impl Entry {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    /// Returns the unescaped text of a property, if set and not blank
+    fn text(&self, name: &str) -> Option<String> {
+        self.get(name).map(|p| unescape(&p.value)).filter(|text| !text.trim().is_empty())
+    }
+}
+
+/// Joins folded lines back together: a line starting with a space or tab continues the one before
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in input.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Splits a content line into a property, skipping a `:` quoted in its parameters
+fn property(line: &str) -> Option<Property> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| {
+        quoted ^= c == '"';
+        (c == ':' && !quoted).then_some(i)
+    })?;
+    let name = line[..colon].split(';').next()?.trim().to_ascii_uppercase();
+    Some(Property { name, value: line[colon + 1..].to_string() })
+}
+
+/// Undoes the escaping of text values: `\n`, `\,`, `\;`, and `\\`
+fn unescape(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+    text
+}
+
+/// Splits a list value such as `CATEGORIES` at the commas that aren't escaped
+fn list(value: &str) -> Vec<String> {
+    let (mut items, mut item) = (Vec::new(), String::new());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => item.extend([c].into_iter().chain(chars.next())),
+            ',' => items.push(std::mem::take(&mut item)),
+            _ => item.push(c),
+        }
+    }
+    items.push(item);
+    items.iter().map(|item| unescape(item).trim().to_string()).filter(|item| !item.is_empty()).collect()
+}
+
+/// Returns the local day a date or date-time property falls on
+///
+/// Times in UTC (ending in `Z`) are converted to local time; times with a
+/// `TZID` or none are taken as they are.
+fn day(property: &Property) -> Option<NaiveDate> {
+    let value = property.value.trim();
+    if let Some(utc) = value.strip_suffix(['Z', 'z']) {
+        let at = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&at).with_timezone(&Local).date_naive());
+    }
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+/// Maps a to-do's `PRIORITY`, 1 the highest and 9 the lowest, to a level of the scale
+fn priority(value: &str) -> Priority {
+    match value.trim().parse::<u8>() {
+        Ok(1..=4) => "high".parse().unwrap_or_else(|_| Priority::highest()),
+        Ok(6..=9) => "low".parse().unwrap_or_else(|_| Priority::lowest()),
+        _ => Priority::default(),
+    }
+}
+
+fn status(entry: &Entry) -> Status {
+    match entry.get("STATUS").map(|p| p.value.trim().to_ascii_uppercase()).as_deref() {
+        Some("CANCELLED") => Status::Cancelled,
+        Some("COMPLETED") => Status::Done,
+        Some("IN-PROCESS") => Status::InProgress,
+        _ if entry.todo && entry.get("COMPLETED").is_some() => Status::Done,
+        _ => Status::Todo,
+    }
+}
+
+/// Replaces each `{name}` in the title template with the entry's value for it
+fn title(template: &str, values: &[(&str, String)]) -> Result<String, TodoError> {
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let end = rest[start..].find('}').map(|i| start + i).ok_or_else(|| {
+            TodoError::Validation(format!("Unclosed '{{' in title template '{}'", template))
+        })?;
+        let name = &rest[start + 1..end];
+        let (_, value) = values.iter().find(|(n, _)| *n == name).ok_or_else(|| {
+            let names: Vec<String> = values.iter().map(|(n, _)| format!("{{{}}}", n)).collect();
+            TodoError::Validation(format!("Unknown placeholder '{{{}}}' in title template; use {}", name, names.join(", ")))
+        })?;
+        text.push_str(value);
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+    Ok(text.trim().to_string())
+}
+
+/// Reads the events and to-dos of a calendar, titled by `template` when given
+pub fn from_ics(input: &str, template: Option<&str>) -> Result<Vec<ImportedTodo>, TodoError> {
+    let lines = unfold(input.trim_start_matches('\u{feff}'));
+    if !lines.iter().any(|line| line.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err(TodoError::Validation("Not an iCalendar file: no BEGIN:VCALENDAR".to_string()));
+    }
+
+    let mut calendar = None;
+    let mut entries = Vec::new();
+    // Components opened inside the current entry, such as alarms
+    let (mut current, mut nested): (Option<Entry>, usize) = (None, 0);
+    for property in lines.iter().filter_map(|line| property(line)) {
+        let component = property.value.trim().to_ascii_uppercase();
+        match (property.name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if component == "VEVENT" || component == "VTODO" => {
+                current = Some(Entry { todo: component == "VTODO", ..Entry::default() });
+            }
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) => entries.extend(current.take()),
+            (_, Some(entry)) if nested == 0 => entry.properties.push(property),
+            ("X-WR-CALNAME", None) => {
+                calendar = Some(unescape(&property.value).trim().to_string()).filter(|name| !name.is_empty());
+            }
+            _ => {}
+        }
+    }
+
+    let mut items = Vec::new();
+    for entry in entries {
+        let Some(summary) = entry.text("SUMMARY") else { continue };
+        let categories: Vec<String> =
+            entry.properties.iter().filter(|p| p.name == "CATEGORIES").flat_map(|p| list(&p.value)).collect();
+        let name = match template {
+            Some(template) => title(template, &[
+                ("summary", summary.trim().to_string()),
+                ("location", entry.text("LOCATION").unwrap_or_default().trim().to_string()),
+                ("categories", categories.join(", ")),
+                ("calendar", calendar.clone().unwrap_or_default()),
+            ])?,
+            None => summary.trim().to_string(),
+        };
+
+        let due = if entry.todo { entry.get("DUE").or(entry.get("DTSTART")) } else { entry.get("DTSTART") };
+        let tags = categories.iter().map(|c| c.split_whitespace().collect::<Vec<_>>().join("-"));
+        let mut builder = TodoBuilder::new(name.clone())
+            .due_date(due.and_then(day))
+            .tags(tags)
+            .project(calendar.clone());
+        if entry.todo {
+            builder = builder.priority(entry.get("PRIORITY").map_or_else(Priority::default, |p| priority(&p.value)));
+        }
+        if let Some(description) = entry.text("DESCRIPTION") {
+            builder = builder.notes(description.trim().to_string());
+        }
+        let url = entry.get("URL").map(|p| p.value.trim().to_string()).filter(|url| !url.is_empty());
+        if let Some(uid) = entry.text("UID") {
+            builder = builder.external_ref(ExternalRef::new(ExternalKind::Calendar, uid.trim(), url));
+        } else if let Some(url) = url.filter(|url| is_web_url(url)) {
+            builder = builder.external_ref(ExternalRef::from_url(&url));
+        }
+        items.push(ImportedTodo { builder, title: name, status: status(&entry) });
+    }
+    Ok(items)
+}
@@ -0,0 +1,75 @@
+//! Reading JSON Feed documents
+//!
+//! Items are read as [JSON Feed](https://www.jsonfeed.org/version/1.1/)
+//! describes them: `id`, `title` (or `summary`), `url`, `tags`, and
+//! `content_text` as the notes. Feeds of deadlines give each item's due
+//! date in the `_due` extension, as `YYYY-MM-DD` or a full RFC 3339 time.
+//! The feed's `title` becomes the project. A document without a JSON Feed
+//! `version` or without `items` isn't a feed, such as an error page
+//! served as JSON, and is rejected rather than read as an empty one.
+
+use super::ImportedTodo;
+use crate::error::TodoError;
+use crate::models::{ExternalKind, ExternalRef, Status, TodoBuilder};
+use chrono::{DateTime, Local, NaiveDate};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Deserialize)]
+struct Feed {
+    version: String,
+    title: Option<String>,
+    items: Vec<Item>,
+}
+
+#[derive(Deserialize)]
+struct Item {
+    id: Value,
+    title: Option<String>,
+    summary: Option<String>,
+    url: Option<String>,
+    content_text: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(rename = "_due")]
+    due: Option<String>,
+}
+
+/// Reads a date, or the local day of an RFC 3339 time
+fn due_day(text: &str) -> Option<NaiveDate> {
+    let text = text.trim();
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(text).ok().map(|at| at.with_timezone(&Local).date_naive()))
+}
+
+/// Reads the items of a JSON Feed
+pub fn from_json_feed(input: &str) -> Result<Vec<ImportedTodo>, TodoError> {
+    let feed: Feed = serde_json::from_str(input)
+        .map_err(|e| TodoError::Validation(format!("Not a JSON Feed: {}", e)))?;
+    if !feed.version.starts_with("https://jsonfeed.org/version/") {
+        return Err(TodoError::Validation(format!("Not a JSON Feed: unknown version '{}'", feed.version)));
+    }
+    let project = feed.title.filter(|title| !title.trim().is_empty());
+
+    let mut items = Vec::new();
+    for item in feed.items {
+        let id = match item.id {
+            Value::String(id) => id,
+            Value::Number(id) => id.to_string(),
+            _ => continue,
+        };
+        let Some(title) = item.title.or(item.summary).filter(|title| !title.trim().is_empty()) else { continue };
+        let tags = item.tags.iter().map(|tag| tag.split_whitespace().collect::<Vec<_>>().join("-"));
+        let mut builder = TodoBuilder::new(title.clone())
+            .due_date(item.due.as_deref().and_then(due_day))
+            .tags(tags)
+            .project(project.clone())
+            .external_ref(ExternalRef::new(ExternalKind::Feed, id, item.url));
+        if let Some(notes) = item.content_text.filter(|notes| !notes.trim().is_empty()) {
+            builder = builder.notes(notes);
+        }
+        items.push(ImportedTodo { builder, title, status: Status::Todo });
+    }
+    Ok(items)
+}
@@ -0,0 +1,201 @@
+//! Converting todos to and from other formats
+//!
+//! Markdown is written as a checklist grouped under `##` headings:
+//!
+//! ```text
+//! ## Website
+//! - [ ] Fix the login form (due 2025-03-01) #bug
+//! - [x] Update the footer
+//! ```
+//!
+//! Reading accepts the same shape. Headings naming a priority set the
+//! priority of the items below them; any other heading sets their project.
+//!
+//! Exports from other apps can be imported too; see the submodules.
+
+mod csv;
+mod ics;
+mod jsonfeed;
+mod mstodo;
+mod trello;
+
+pub use ics::from_ics;
+pub use jsonfeed::from_json_feed;
+pub use mstodo::from_mstodo;
+pub use trello::from_trello;
+
+use crate::models::{Priority, Status, Todo, TodoBuilder};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Supported formats
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// A Markdown checklist
+    Markdown,
+    /// An AppleScript that adds the todos to Apple Reminders (export only)
+    #[cfg(feature = "macos-integration")]
+    Reminders,
+    /// A Microsoft To Do or Outlook Tasks export, as JSON or CSV (import only)
+    Mstodo,
+    /// A Trello board exported as JSON (import only)
+    Trello,
+    /// An iCalendar file or feed of events and to-dos (import only)
+    Ics,
+}
+
+/// How exported todos are grouped
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupBy {
+    /// One heading per project
+    Project,
+    /// One heading per priority, highest first
+    Priority,
+    /// A single flat list
+    None,
+}
+
+/// A checklist item read from another format
+#[derive(Debug, Clone)]
+pub struct ImportedTodo {
+    pub builder: TodoBuilder,
+    pub title: String,
+    /// Where the item is in its workflow, e.g. done for a ticked checkbox
+    pub status: Status,
+}
+
+fn markdown_item(todo: &Todo) -> String {
+    let mut line = format!("- [{}] {}", if todo.completed { "x" } else { " " }, todo.title);
+    if let Some(due) = todo.due_date {
+        line.push_str(&format!(" (due {})", due.format("%Y-%m-%d")));
+    }
+    for tag in &todo.tags {
+        line.push_str(&format!(" #{}", tag));
+    }
+    line
+}
+
+/// Writes todos as a Markdown checklist, in the order given within each group
+///
+/// With project grouping, todos without a project come first, without a
+/// heading.
+pub fn to_markdown(todos: &[Todo], group_by: GroupBy) -> String {
+    let mut groups: BTreeMap<(usize, String), Vec<&Todo>> = BTreeMap::new();
+    for todo in todos {
+        let key = match group_by {
+            GroupBy::Project => match &todo.project {
+                Some(project) => (1, project.clone()),
+                None => (0, String::new()),
+            },
+            GroupBy::Priority => (Priority::highest().rank() - todo.priority.rank(), todo.priority.to_string()),
+            GroupBy::None => (0, String::new()),
+        };
+        groups.entry(key).or_default().push(todo);
+    }
+
+    let mut sections = Vec::new();
+    for ((_, heading), todos) in groups {
+        let mut section = String::new();
+        if !heading.is_empty() {
+            let heading = if group_by == GroupBy::Priority { title_case(&heading) } else { heading };
+            section.push_str(&format!("## {}\n\n", heading));
+        }
+        for todo in todos {
+            section.push_str(&markdown_item(todo));
+            section.push('\n');
+        }
+        sections.push(section);
+    }
+    sections.join("\n")
+}
+
+fn title_case(s: &str) -> String {
+    let lower = s.to_lowercase();
+    let mut chars = lower.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Splits trailing `#tags` and a `(due YYYY-MM-DD)` off a checklist item's text
+fn parse_item_text(text: &str) -> (String, Option<NaiveDate>, Vec<String>) {
+    let mut rest = text.trim();
+    let mut tags = Vec::new();
+    while let Some((head, last)) = rest.rsplit_once(' ') {
+        match last.strip_prefix('#') {
+            Some(tag) if !tag.is_empty() && !tag.starts_with('#') => {
+                tags.push(tag.to_string());
+                rest = head.trim_end();
+            }
+            _ => break,
+        }
+    }
+    tags.reverse();
+
+    let mut due = None;
+    if let Some(head) = rest.strip_suffix(')') {
+        if let Some((title, date)) = head.rsplit_once("(due ") {
+            if let Ok(date) = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+                due = Some(date);
+                rest = title.trim_end();
+            }
+        }
+    }
+
+    (rest.to_string(), due, tags)
+}
+
+/// Reads the checklist items of a Markdown document
+///
+/// Lines that are neither headings nor `- [ ]`/`- [x]` items are ignored.
+pub fn from_markdown(input: &str) -> Vec<ImportedTodo> {
+    let mut project: Option<String> = None;
+    let mut priority = Priority::default();
+    let mut items = Vec::new();
+
+    for line in input.lines() {
+        let trimmed = line.trim();
+        if let Some(heading) = trimmed.strip_prefix('#') {
+            let heading = heading.trim_start_matches('#').trim();
+            match <Priority as FromStr>::from_str(heading) {
+                Ok(p) => {
+                    priority = p;
+                    project = None;
+                }
+                Err(_) => {
+                    priority = Priority::default();
+                    project = Some(heading.to_string()).filter(|h| !h.is_empty());
+                }
+            }
+            continue;
+        }
+
+        let Some(item) = trimmed.strip_prefix(['-', '*', '+']).map(str::trim_start) else {
+            continue;
+        };
+        let (completed, text) = match item.get(..3) {
+            Some("[ ]") => (false, &item[3..]),
+            Some("[x]") | Some("[X]") => (true, &item[3..]),
+            _ => continue,
+        };
+
+        let (title, due, tags) = parse_item_text(text);
+        if title.is_empty() {
+            continue;
+        }
+        items.push(ImportedTodo {
+            builder: TodoBuilder::new(title.clone())
+                .due_date(due)
+                .priority(priority)
+                .tags(tags)
+                .project(project.clone()),
+            title,
+            status: if completed { Status::Done } else { Status::Todo },
+        });
+    }
+
+    items
+}
@@ -0,0 +1,174 @@
+//! Reading exports from Microsoft To Do and Outlook Tasks
+//!
+//! JSON is read as the Microsoft Graph API returns it: task lists
+//! (`displayName` and `tasks`) or bare tasks, in an array or wrapped in
+//! `{"value": [...]}`. Each list becomes the project of its tasks, except
+//! the default "Tasks" list. CSV is read as Outlook writes it, with a
+//! header row; the `Subject`, `Due Date`, `Priority`, `Status`, `Date
+//! Completed`, `Notes`, and `Folder` columns are used, or `Title`,
+//! `Importance`, and `List` as other exporters call them. Dates in CSV may
+//! be `YYYY-MM-DD` or Outlook's US-style `M/D/YYYY`.
+//!
+//! High importance becomes the `high` priority and low importance `low`,
+//! or the highest and lowest levels of a scale without those names. Tasks
+//! in progress or waiting on others keep that status.
+
+use super::{csv, ImportedTodo};
+use crate::error::TodoError;
+use crate::models::{ExternalKind, ExternalRef, Priority, Status, TodoBuilder};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// A task as Microsoft Graph describes it
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Task {
+    id: Option<String>,
+    title: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    importance: String,
+    due_date_time: Option<DateTimeZone>,
+    body: Option<Body>,
+}
+
+/// A moment with the time zone it was given in
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DateTimeZone {
+    date_time: String,
+}
+
+/// The notes of a task
+#[derive(Deserialize)]
+struct Body {
+    #[serde(default)]
+    content: String,
+}
+
+/// A task list with its tasks
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskList {
+    display_name: String,
+    #[serde(default)]
+    wellknown_list_name: Option<String>,
+    #[serde(default)]
+    tasks: Vec<Task>,
+}
+
+/// Maps a To Do importance or Outlook priority to a level of the scale
+fn priority(importance: &str) -> Priority {
+    match importance.trim().to_lowercase().as_str() {
+        "high" => "high".parse().unwrap_or_else(|_| Priority::highest()),
+        "low" => "low".parse().unwrap_or_else(|_| Priority::lowest()),
+        _ => Priority::default(),
+    }
+}
+
+/// Maps a To Do or Outlook task status to ours, e.g. `inProgress` or `In Progress`
+fn status(name: &str) -> Status {
+    match name.to_lowercase().replace(' ', "").as_str() {
+        "completed" => Status::Done,
+        "inprogress" => Status::InProgress,
+        "waitingonothers" | "waitingonsomeoneelse" => Status::Waiting,
+        _ => Status::Todo,
+    }
+}
+
+/// Reads a date from the start of `text`, in either of the accepted forms
+fn date(text: &str) -> Option<NaiveDate> {
+    let text = text.trim();
+    let day = text.split(['T', ' ']).next().unwrap_or(text);
+    NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(day, "%m/%d/%Y"))
+        .ok()
+}
+
+/// Turns a Graph task into an imported todo in `project`
+fn task_item(task: Task, project: Option<&str>) -> ImportedTodo {
+    let mut builder = TodoBuilder::new(task.title.clone())
+        .priority(priority(&task.importance))
+        .due_date(task.due_date_time.and_then(|due| date(&due.date_time)))
+        .project(project.map(str::to_string));
+    if let Some(body) = task.body.filter(|body| !body.content.trim().is_empty()) {
+        builder = builder.notes(body.content);
+    }
+    if let Some(id) = task.id {
+        builder = builder.external_ref(ExternalRef::new(ExternalKind::Mstodo, id, None));
+    }
+    ImportedTodo { builder, title: task.title, status: status(&task.status) }
+}
+
+/// Reads the tasks of a Graph export
+fn from_json(input: &str) -> Result<Vec<ImportedTodo>, TodoError> {
+    let invalid = |e: serde_json::Error| TodoError::Validation(format!("Not a Microsoft To Do export: {}", e));
+    let value: Value = serde_json::from_str(input).map_err(invalid)?;
+    let entries = match value {
+        Value::Object(mut object) if object.contains_key("value") => object.remove("value").unwrap_or_default(),
+        value => value,
+    };
+    let entries = match entries {
+        Value::Array(entries) => entries,
+        entry => vec![entry],
+    };
+
+    let mut items = Vec::new();
+    for entry in entries {
+        if entry.get("tasks").is_some() || entry.get("displayName").is_some() {
+            let list: TaskList = serde_json::from_value(entry).map_err(invalid)?;
+            let default = list.wellknown_list_name.as_deref() == Some("defaultList");
+            let project = (!default).then_some(list.display_name.as_str());
+            items.extend(list.tasks.into_iter().map(|task| task_item(task, project)));
+        } else {
+            items.push(task_item(serde_json::from_value(entry).map_err(invalid)?, None));
+        }
+    }
+    Ok(items)
+}
+
+/// Reads the rows of an Outlook CSV export
+fn from_csv(input: &str) -> Result<Vec<ImportedTodo>, TodoError> {
+    let mut records = csv::records(input).into_iter();
+    let header: Vec<String> = records.next().unwrap_or_default().iter().map(|h| h.trim().to_lowercase()).collect();
+    let column = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+    let title = column(&["subject", "title"]).ok_or_else(|| {
+        TodoError::Validation("Not a Microsoft To Do export: no Subject or Title column".to_string())
+    })?;
+    let (due, importance) = (column(&["due date", "due"]), column(&["priority", "importance"]));
+    let (status_column, notes) = (column(&["status"]), column(&["notes", "body"]));
+    let list = column(&["folder", "list"]);
+    let completed_on = column(&["date completed"]);
+
+    let mut items = Vec::new();
+    for record in records {
+        let field = |index: Option<usize>| index.and_then(|i| record.get(i)).map(|f| f.trim()).unwrap_or("");
+        let name = field(Some(title));
+        if name.is_empty() {
+            continue;
+        }
+        let mut builder = TodoBuilder::new(name)
+            .priority(priority(field(importance)))
+            .due_date(date(field(due)))
+            .project(Some(field(list).to_string()).filter(|list| !list.is_empty()));
+        if !field(notes).is_empty() {
+            builder = builder.notes(field(notes));
+        }
+        let status = match date(field(completed_on)) {
+            Some(_) => Status::Done,
+            None => status(field(status_column)),
+        };
+        items.push(ImportedTodo { builder, title: name.to_string(), status });
+    }
+    Ok(items)
+}
+
+/// Reads a Microsoft To Do or Outlook Tasks export, telling JSON from CSV by its first character
+pub fn from_mstodo(input: &str) -> Result<Vec<ImportedTodo>, TodoError> {
+    match input.trim_start_matches('\u{feff}').trim_start().chars().next() {
+        Some('{' | '[') => from_json(input.trim_start_matches('\u{feff}')),
+        _ => from_csv(input),
+    }
+}
@@ -0,0 +1,160 @@
+//! Reading Trello board exports
+//!
+//! Every open card on an open list becomes a todo, in board order. A list
+//! named like a status ("To Do", "Doing", "Waiting", "Done", ...) sets the
+//! status of its cards; any other list sets their project. Labels become
+//! tags, named by their color when they have no name. Due dates carry
+//! over, and a card whose due date is marked complete is done. The card's
+//! checklists become its checklist, each item prefixed with the name of
+//! its checklist when there are several, and the card's link is kept for
+//! `open`.
+
+use super::ImportedTodo;
+use crate::error::TodoError;
+use crate::models::{ChecklistItem, ExternalKind, ExternalRef, Status, TodoBuilder};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::Deserialize;
+
+/// A board as Trello exports it, with only what's imported
+#[derive(Deserialize)]
+struct Board {
+    #[serde(default)]
+    lists: Vec<List>,
+    #[serde(default)]
+    cards: Vec<Card>,
+    #[serde(default)]
+    checklists: Vec<Checklist>,
+}
+
+#[derive(Deserialize)]
+struct List {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Card {
+    id: String,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    id_list: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+    due: Option<DateTime<Utc>>,
+    #[serde(default)]
+    due_complete: bool,
+    #[serde(default)]
+    labels: Vec<Label>,
+    short_url: Option<String>,
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    color: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Checklist {
+    id_card: String,
+    name: String,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    check_items: Vec<CheckItem>,
+}
+
+#[derive(Deserialize)]
+struct CheckItem {
+    name: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    pos: f64,
+}
+
+/// Returns the status a list's name stands for, if it names one
+fn list_status(name: &str) -> Option<Status> {
+    let name: String = name.to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect();
+    match name.as_str() {
+        "todo" | "backlog" => Some(Status::Todo),
+        "doing" | "inprogress" => Some(Status::InProgress),
+        "waiting" | "blocked" | "onhold" => Some(Status::Waiting),
+        "done" | "complete" | "completed" => Some(Status::Done),
+        "cancelled" | "canceled" => Some(Status::Cancelled),
+        _ => None,
+    }
+}
+
+/// Turns a label into a tag, joining the words of its name with `-`
+fn label_tag(label: &Label) -> Option<String> {
+    let name = label.name.split_whitespace().collect::<Vec<_>>().join("-");
+    Some(name).filter(|n| !n.is_empty()).or_else(|| label.color.clone())
+}
+
+/// Returns the local day a due moment falls on
+fn due_day(due: DateTime<Utc>) -> NaiveDate {
+    due.with_timezone(&Local).date_naive()
+}
+
+/// Gathers the items of the card's checklists, in board order
+fn checklist(card: &Card, checklists: &[Checklist]) -> Vec<ChecklistItem> {
+    let mut lists: Vec<&Checklist> = checklists.iter().filter(|c| c.id_card == card.id).collect();
+    lists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    let several = lists.len() > 1;
+
+    let mut items = Vec::new();
+    for list in lists {
+        let mut check_items: Vec<&CheckItem> = list.check_items.iter().collect();
+        check_items.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+        items.extend(check_items.into_iter().map(|item| ChecklistItem {
+            text: if several { format!("{}: {}", list.name, item.name) } else { item.name.clone() },
+            done: item.state == "complete",
+        }));
+    }
+    items
+}
+
+/// Reads the open cards of a Trello board export
+pub fn from_trello(input: &str) -> Result<Vec<ImportedTodo>, TodoError> {
+    let board: Board = serde_json::from_str(input)
+        .map_err(|e| TodoError::Validation(format!("Not a Trello board export: {}", e)))?;
+
+    let mut lists: Vec<&List> = board.lists.iter().filter(|l| !l.closed).collect();
+    lists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+
+    let mut items = Vec::new();
+    for list in lists {
+        let status = list_status(&list.name);
+        let project = if status.is_some() { None } else { Some(list.name.clone()) };
+        let mut cards: Vec<&Card> = board.cards.iter().filter(|c| c.id_list == list.id && !c.closed).collect();
+        cards.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+
+        for card in cards {
+            let mut builder = TodoBuilder::new(card.name.clone())
+                .due_date(card.due.map(due_day))
+                .tags(card.labels.iter().filter_map(label_tag))
+                .project(project.clone())
+                .checklist(checklist(card, &board.checklists))
+                .external_ref(ExternalRef::new(ExternalKind::Trello, card.id.clone(), card.short_url.clone().or(card.url.clone())));
+            if !card.desc.trim().is_empty() {
+                builder = builder.notes(card.desc.clone());
+            }
+            let status = if card.due_complete { Status::Done } else { status.unwrap_or_default() };
+            items.push(ImportedTodo { builder, title: card.name.clone(), status });
+        }
+    }
+    Ok(items)
+}
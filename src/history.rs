@@ -0,0 +1,116 @@
+//! Per-todo log of field changes
+//!
+//! Every save that changes a todo appends a line per changed field to
+//! `todos.history.jsonl` next to the database: the todo, the field as it is
+//! stored, its old and new values, and when. Todos are matched by UUID, so
+//! their history survives renumbering. `history <id>` reads it back.
+
+use crate::error::TodoError;
+use crate::models::Todo;
+use crate::timestamp;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// One field of one todo changing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    #[serde(serialize_with = "timestamp::serialize")]
+    pub timestamp: DateTime<Local>,
+    pub id: u64,
+    pub uuid: String,
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+    /// Who made the change, when the store knew
+    #[serde(default)]
+    pub by: Option<String>,
+}
+
+/// Lists the fields that differ between two versions of a todo, with their old and new values
+///
+/// Fields are named as they are stored, in order. The modification time and
+/// author are left out, since they change with everything else.
+pub fn field_changes(before: &Todo, after: &Todo) -> Result<Vec<(String, Value, Value)>> {
+    let (Value::Object(mut old), Value::Object(mut new)) = (serde_json::to_value(before)?, serde_json::to_value(after)?)
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut names: Vec<String> = old.keys().chain(new.keys()).cloned().collect();
+    names.sort();
+    names.dedup();
+    Ok(names
+        .into_iter()
+        .filter(|name| name != "modified_at" && name != "modified_by")
+        .filter_map(|name| {
+            let (was, is) = (old.remove(&name).unwrap_or(Value::Null), new.remove(&name).unwrap_or(Value::Null));
+            (was != is).then_some((name, was, is))
+        })
+        .collect())
+}
+
+/// Lists the field changes a save from `before` to `after` makes
+///
+/// Only todos that were already there are compared, and only those whose
+/// modification time moved, which every changed todo's does once stamped.
+/// Changes are timestamped with that time and credited to whoever the todo
+/// says made it, so ones synced from elsewhere keep when and by whom they
+/// were made; `now` is for todos without a time.
+pub fn changes(before: &[Todo], after: &[Todo], now: DateTime<Local>) -> Result<Vec<FieldChange>> {
+    let before: HashMap<&str, &Todo> = before.iter().map(|t| (t.uuid.as_str(), t)).collect();
+    let mut changes = Vec::new();
+    for todo in after {
+        let Some(old) = before.get(todo.uuid.as_str()).filter(|old| old.modified_at != todo.modified_at) else {
+            continue;
+        };
+        for (field, old, new) in field_changes(old, todo)? {
+            let timestamp = todo.modified_at.unwrap_or(now);
+            let by = todo.modified_by.clone();
+            changes.push(FieldChange { timestamp, id: todo.id, uuid: todo.uuid.clone(), field, old, new, by });
+        }
+    }
+    Ok(changes)
+}
+
+/// Appends changes to the history file at `path`
+pub fn append_to(path: &Path, changes: &[FieldChange]) -> Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let mut lines = String::new();
+    for change in changes {
+        lines.push_str(&serde_json::to_string(change).with_context(|| "Failed to serialize history")?);
+        lines.push('\n');
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| TodoError::storage("open", path, e))?;
+
+    file.write_all(lines.as_bytes()).map_err(|e| TodoError::storage("write to", path, e))?;
+
+    Ok(())
+}
+
+/// Reads all changes from the history file at `path`, skipping lines that cannot be parsed
+pub fn read_from(path: &Path) -> Result<Vec<FieldChange>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| TodoError::storage("read", path, e))?;
+
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
@@ -0,0 +1,211 @@
+//! User scripts run when todos change
+//!
+//! Executables in `hooks` in the config directory are named for what they
+//! hear about: `on-add`, `on-done`, `on-edit`, and `on-remove` run after a
+//! save for each todo it added, completed, otherwise changed, or removed,
+//! and `pre-save` runs before the save for each of them. Each gets the todo
+//! as JSON on stdin (as it was, for removed todos) and the change in
+//! `TODO_EVENT`. A `pre-save` hook that fails aborts the save; the others
+//! only warn.
+
+use crate::config::config_dir;
+use crate::error::TodoError;
+use crate::events::{diff, Event, EventKind};
+use crate::history::FieldChange;
+use crate::journal::JournalEntry;
+use crate::models::{Todo, TrashedTodo};
+use crate::storage::{StorageFormat, TodoStore};
+use crate::t;
+use anyhow::Result;
+use chrono::Local;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+
+/// Hook run before each change is saved, able to refuse it
+pub const PRE_SAVE: &str = "pre-save";
+
+/// Returns the directory hooks are looked up in
+pub fn hooks_dir() -> PathBuf {
+    config_dir().join("hooks")
+}
+
+/// Returns the hook run after a save for changes of this kind
+pub fn hook_name(kind: EventKind) -> &'static str {
+    match kind {
+        EventKind::Added => "on-add",
+        EventKind::Completed => "on-done",
+        EventKind::Edited => "on-edit",
+        EventKind::Removed => "on-remove",
+    }
+}
+
+/// Returns true if `path` is a file the user may run
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = path.metadata() else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
+
+/// Runs the hook at `path` with the event's todo on stdin
+///
+/// Fails with the reason if the hook can't be started or exits
+/// unsuccessfully: what it printed on stderr, or else its exit status.
+fn run_hook(path: &Path, event: &Event) -> Result<(), String> {
+    let todo = event.after.as_ref().or(event.before.as_ref());
+    let input = serde_json::to_string(&todo).map_err(|e| e.to_string())?;
+    let kind = serde_json::to_value(event.kind).map_err(|e| e.to_string())?;
+    let mut child = Command::new(path)
+        .env("TODO_EVENT", kind.as_str().unwrap_or_default())
+        .env("TODO_ID", event.id.to_string())
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("couldn't run {}: {}", path.display(), e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that doesn't read its input closes the pipe early, which is fine
+        let _ = stdin.write_all(input.as_bytes());
+    }
+
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.trim() {
+            "" => output.status.to_string(),
+            stderr => stderr.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Wraps another store, running the hooks around each save
+pub struct HookStore {
+    inner: Box<dyn TodoStore>,
+    dir: PathBuf,
+}
+
+impl HookStore {
+    /// Runs the hooks in `dir` around saves to `inner`
+    pub fn new(inner: Box<dyn TodoStore>, dir: impl Into<PathBuf>) -> Self {
+        Self { inner, dir: dir.into() }
+    }
+
+    /// Returns the hook called `name`, if there's one that can be run
+    fn hook(&self, name: &str) -> Option<PathBuf> {
+        Some(self.dir.join(name)).filter(|path| is_executable(path))
+    }
+}
+
+impl TodoStore for HookStore {
+    fn load(&mut self) -> Result<Vec<Todo>> {
+        self.inner.load()
+    }
+
+    fn for_each_todo(&mut self, visit: &mut dyn FnMut(Todo)) -> Result<()> {
+        self.inner.for_each_todo(visit)
+    }
+
+    /// Runs `pre-save` for each change, saves if none of them failed, then
+    /// runs the hook for each kind of change
+    fn save(&mut self, todos: &[Todo]) -> Result<()> {
+        let before = self.inner.load()?;
+        let mut events = diff(&before, todos, Local::now());
+        for event in &mut events {
+            event.by = self.inner.actor().map(String::from);
+        }
+
+        if let Some(pre_save) = self.hook(PRE_SAVE) {
+            for event in &events {
+                run_hook(&pre_save, event).map_err(|reason| {
+                    let message = format!("The {} hook refused the change to #{}: {}", PRE_SAVE, event.id, reason);
+                    TodoError::Validation(message)
+                })?;
+            }
+        }
+        self.inner.save(todos)?;
+        for event in &events {
+            let name = hook_name(event.kind);
+            if let Some(hook) = self.hook(name) {
+                if let Err(reason) = run_hook(&hook, event) {
+                    eprintln!("{}", t!("hooks.failed", hook = name, id = event.id, reason = reason));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn append_journal(&mut self, entry: &JournalEntry) -> Result<()> {
+        self.inner.append_journal(entry)
+    }
+
+    fn load_journal(&self) -> Result<Vec<JournalEntry>> {
+        self.inner.load_journal()
+    }
+
+    fn load_history(&self) -> Result<Vec<FieldChange>> {
+        self.inner.load_history()
+    }
+
+    fn actor(&self) -> Option<&str> {
+        self.inner.actor()
+    }
+
+    fn last_modified(&self) -> Option<SystemTime> {
+        self.inner.last_modified()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.inner.is_read_only()
+    }
+
+    fn backup_location(&self) -> Option<(PathBuf, String)> {
+        self.inner.backup_location()
+    }
+
+    fn pending(&self) -> Result<Option<Vec<Todo>>> {
+        self.inner.pending()
+    }
+
+    fn discard_pending(&mut self) -> Result<()> {
+        self.inner.discard_pending()
+    }
+
+    /// Opens the other list with the same hooks
+    fn open_list(&self, name: &str) -> Result<Box<dyn TodoStore>> {
+        Ok(Box::new(HookStore::new(self.inner.open_list(name)?, self.dir.clone())))
+    }
+
+    fn load_trash(&self) -> Result<Vec<TrashedTodo>> {
+        self.inner.load_trash()
+    }
+
+    fn save_trash(&mut self, trash: &[TrashedTodo]) -> Result<()> {
+        self.inner.save_trash(trash)
+    }
+
+    fn load_cache(&self, name: &str) -> Option<String> {
+        self.inner.load_cache(name)
+    }
+
+    fn save_cache(&mut self, name: &str, content: &str) -> Result<()> {
+        self.inner.save_cache(name, content)
+    }
+
+    fn convert(&mut self, format: StorageFormat) -> Result<bool> {
+        self.inner.convert(format)
+    }
+
+    fn compact(&mut self) -> Result<usize> {
+        self.inner.compact()
+    }
+}
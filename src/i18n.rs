@@ -0,0 +1,752 @@
+//! Translated messages and locale-aware dates
+//!
+//! Every message a command prints is looked up by key in the catalog of
+//! the current locale, falling back to English for keys a catalog lacks.
+//! Messages name their placeholders, e.g. `Added todo #{id}`, and are
+//! filled in with [`t!`]. The locale is `locale` in the config, or else the
+//! first of `LC_ALL`, `LC_MESSAGES`, and `LANG` that is set.
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// Languages there are catalogs for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    /// Every locale, English first
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::De];
+
+    /// Picks the locale for a tag like `de`, `de_DE.UTF-8`, or `en-GB`
+    ///
+    /// Returns `None` for languages without a catalog.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['_', '-', '.', '@']).next().unwrap_or("").to_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    /// Takes the locale from `LC_ALL`, `LC_MESSAGES`, or `LANG`, English if none names a known language
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+            .and_then(|tag| Self::from_tag(&tag))
+            .unwrap_or_default()
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::De => DE,
+        }
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Sets the locale for the rest of the process
+///
+/// Only the first call has an effect.
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+/// Returns the locale set with [`set_locale`], or else the one the environment names
+pub fn locale() -> Locale {
+    *LOCALE.get_or_init(Locale::from_env)
+}
+
+/// Returns the message for `key` in the current locale
+///
+/// Falls back to English, and to the key itself if no catalog has it.
+pub fn text(key: &'static str) -> &'static str {
+    let find = |catalog: &'static [(&'static str, &'static str)]| {
+        catalog.iter().find(|(k, _)| *k == key).map(|(_, message)| *message)
+    };
+    find(locale().catalog()).or_else(|| find(EN)).unwrap_or(key)
+}
+
+/// Fills in the `{name}` placeholders of `message`
+///
+/// Placeholders without a value are left as they are.
+pub fn fill(message: &str, values: &[(&str, &dyn Display)]) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after
+            .find('}')
+            .and_then(|close| values.iter().find(|(name, _)| *name == &after[..close]).map(|(_, v)| (close, v)));
+        match value {
+            Some((close, value)) => {
+                out.push_str(&value.to_string());
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Looks up a message in the current locale and fills in its placeholders
+///
+/// ```
+/// use todo_cli::t;
+/// assert_eq!(t!("remove.removed", id = 3), "Removed todo #3");
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::text($key).to_string()
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::text($key), &[$((stringify!($name), &$value as &dyn std::fmt::Display)),+])
+    };
+}
+
+/// Returns the keys English has but `locale`'s catalog lacks
+pub fn missing_keys(locale: Locale) -> Vec<&'static str> {
+    let catalog = locale.catalog();
+    EN.iter().map(|(key, _)| *key).filter(|key| !catalog.iter().any(|(k, _)| k == key)).collect()
+}
+
+/// Formats a date in full, e.g. 2025-11-15, or 15.11.2025 in German
+pub fn format_date(date: NaiveDate) -> String {
+    match locale() {
+        Locale::En => date.format("%Y-%m-%d").to_string(),
+        Locale::De => date.format("%d.%m.%Y").to_string(),
+    }
+}
+
+/// Formats a date without the year, e.g. Nov 15, or 15.11. in German
+pub fn format_short_date(date: NaiveDate) -> String {
+    match locale() {
+        Locale::En => date.format("%b %-d").to_string(),
+        Locale::De => date.format("%-d.%-m.").to_string(),
+    }
+}
+
+/// Formats a date with its weekday and without the year, e.g. Sat Nov 15, or Sa 15.11. in German
+pub fn format_day(date: NaiveDate) -> String {
+    format!("{} {}", weekday_name(date.weekday()), format_short_date(date))
+}
+
+/// Formats a date in full with its weekday, e.g. Sat 2025-11-15, or Sa 15.11.2025 in German
+pub fn format_full_day(date: NaiveDate) -> String {
+    format!("{} {}", weekday_name(date.weekday()), format_date(date))
+}
+
+/// Returns the short name of a weekday, e.g. Mon, or Mo in German
+pub fn weekday_name(day: Weekday) -> &'static str {
+    const EN_DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const DE_DAYS: [&str; 7] = ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"];
+    let index = day.num_days_from_monday() as usize;
+    match locale() {
+        Locale::En => EN_DAYS[index],
+        Locale::De => DE_DAYS[index],
+    }
+}
+
+/// English messages, which every other catalog falls back to
+const EN: &[(&str, &str)] = &[
+    ("add.added", "Added todo #{id} '{title}' (Priority: {priority})"),
+    ("add.added_due", "Added todo #{id} '{title}' (Priority: {priority}, Due: {due})"),
+    ("add.uuid", "UUID: {uuid}"),
+    ("add.nothing", "No todos to add."),
+    ("alias.added", "Added alias '{name}' = {expansion}"),
+    ("alias.updated", "Updated alias '{name}' = {expansion}"),
+    ("alias.none", "No aliases in {path}."),
+    ("alias.removed", "Removed alias '{name}' = {expansion}"),
+    ("attach.already", "Todo #{id} already has {target} attached."),
+    ("attach.attached", "Attached {kind} {target} to todo #{id}: {title}"),
+    ("attach.opened", "Opened {target}"),
+    ("backup.done", "Backed up {count} todos to {path}"),
+    ("backup.rotated", "Removed {count} old backups, keeping the newest {keep}."),
+    ("restore.same", "The todos already match {path}."),
+    ("restore.preview", "Restoring {path} would make these changes:"),
+    ("restore.done", "Restored {count} todos from {path}"),
+    ("common.cancelled", "Operation cancelled."),
+    ("block.already", "Todo #{id} already depends on #{dependency}."),
+    ("block.blocked", "Todo #{id} now depends on #{dependency}: {title}"),
+    ("unblock.not_blocked", "Todo #{id} does not depend on #{dependency}."),
+    ("unblock.unblocked", "Todo #{id} no longer depends on #{dependency}."),
+    ("unblock.all", "Removed {count} dependencies from todo #{id}."),
+    ("clear.done", "All todos have been moved to the trash."),
+    ("comment.added", "Commented on todo #{id}: {title}"),
+    ("compact.nothing", "Nothing to compact; the database file is up to date."),
+    ("compact.one", "Folded 1 patch into the database file."),
+    ("compact.many", "Folded {count} patches into the database file."),
+    ("convert.done", "Converted the database to {format}."),
+    ("convert.already", "The database is already stored as {format}."),
+    ("daemon.already_running", "Daemon is already running (pid {pid})."),
+    ("daemon.started", "Started daemon (pid {pid}), logging to {path}"),
+    ("daemon.not_running", "Daemon is not running."),
+    ("daemon.stopped", "Stopped daemon (pid {pid})."),
+    ("daemon.running", "Daemon is running (pid {pid})."),
+    ("daemon.watching", "{time} Watching {count} todos"),
+    ("daemon.reloaded", "{time} Reloaded {count} todos"),
+    ("diff.added", "Added"),
+    ("diff.removed", "Removed"),
+    ("diff.changed", "Changed"),
+    ("diff.database", "the database"),
+    ("diff.none", "No differences between {old} and {new}."),
+    ("diff.header", "Changes from {old} to {new}:"),
+    ("diff.summary", "{added} added, {removed} removed, {changed} changed"),
+    ("digest.nothing", "Nothing due today; no digest sent."),
+    ("digest.sent", "Sent the digest to {channel}."),
+    ("doctor.duplicate_id_fixed", "[{store}] Duplicate ID #{id} ('{title}') reassigned to #{new_id}"),
+    ("doctor.duplicate_id", "[{store}] Duplicate ID #{id} ('{title}')"),
+    ("doctor.missing_uuid_fixed", "[{store}] Missing UUID on #{id} ('{title}') replaced with {uuid}"),
+    ("doctor.missing_uuid", "[{store}] Missing UUID '{uuid}' on #{id} ('{title}')"),
+    ("doctor.duplicate_uuid_fixed", "[{store}] Duplicate UUID on #{id} ('{title}') replaced with {uuid}"),
+    ("doctor.duplicate_uuid", "[{store}] Duplicate UUID '{uuid}' on #{id} ('{title}')"),
+    ("doctor.ok", "No problems found."),
+    ("doctor.fixed", "Fixed {count} problem(s)."),
+    ("doctor.found", "Found {count} problem(s). Run with --fix to repair them."),
+    ("done.unfinished_dependency", "Warning: todo #{id} depends on #{dependency} which is not done: {title}"),
+    ("done.already", "Todo #{id} is already marked as done."),
+    ("done.done", "Marked todo #{id} as done: {title}"),
+    ("done.no_match", "No incomplete todos match the query."),
+    ("duplicate.done", "Duplicated todo #{id} as #{new_id}: {title}"),
+    ("edit.unchanged", "Todo #{id} is unchanged."),
+    ("edit.updated", "Updated {fields} of todo #{id}: {title}"),
+    ("escalate.escalated", "Escalated todo #{id} from {from} to {to}: {title}"),
+    ("escalate.nothing", "No todos to escalate."),
+    ("escalate.dry_run", "Dry run: {count} todos would be escalated."),
+    ("export.done", "Exported {count} todos to {path}"),
+    ("import.replaced", "Replaced {count} todos with {imported} imported ones."),
+    ("import.done", "Imported {count} todos ({skipped} already present)."),
+    ("github.pulled", "Added {count} todos from issues assigned to you in {repo}."),
+    ("github.closed", "Closed {url}"),
+    ("github.done", "Marked todo #{id} as done."),
+    ("hide.hidden", "Hid todo #{id} until {until}: {title}"),
+    ("unhide.not_hidden", "Todo #{id} is not hidden."),
+    ("unhide.done", "Todo #{id} is visible again."),
+    ("history.none_field", "No changes to {field} recorded for #{id} {title}."),
+    ("history.none", "No changes recorded for #{id} {title}."),
+    ("history.header", "History of #{id} {title}"),
+    ("list.written", "Wrote {count} todos to {path}"),
+    ("list.watching", "Every {seconds}s and on changes, last at {time}. Ctrl-C to quit."),
+    ("common.error", "Error: {error}"),
+    ("list.discarded", "Changes discarded."),
+    ("list.saved", "Saved: {completed} completed, {removed} removed."),
+    ("merge.conflict", "#{id} {title}: {field} differs"),
+    ("merge.here", "  1) here:  {value}"),
+    ("merge.other", "  2) other: {value}"),
+    ("merge.nothing", "Nothing to merge; the database already has everything in {path}."),
+    ("merge.done", "Merged {path}: {added} added, {updated} updated, {conflicts} conflicting fields resolved"),
+    ("nag.nag", "Todo: {message}"),
+    ("nag.inbox", "{count} inbox todos older than {age}"),
+    ("nag.overdue_one", "urgent todo #{id} is overdue: {title}"),
+    ("nag.overdue_many", "{count} urgent todos are overdue"),
+    ("next.nothing", "Nothing to do next."),
+    ("notify.due", "Due {when} ({offset} before)"),
+    ("notify.reminder", "Reminder: #{id} {title} - {body}"),
+    ("notify.resurfaced", "Back on your list: #{id} {title}"),
+    ("pomodoro.started", "Pomodoro on todo #{id}: {title} ({work} work, {rest} break)"),
+    ("pomodoro.finished", "Finished pomodoro {done} on #{id} ({count} in total)"),
+    ("pomodoro.work", "Work "),
+    ("pomodoro.break", "Break"),
+    ("priority.same", "Todo #{id} already has priority: {priority}"),
+    ("priority.updated", "Updated priority of todo #{id} from {from} to {to}"),
+    ("recover.summary", "{added} added, {removed} removed, {updated} updated"),
+    ("recover.replayed", "Replayed pending changes ({summary})."),
+    ("recover.none", "No pending changes."),
+    ("recover.discarded", "Discarded pending changes ({summary})."),
+    ("recover.found", "An interrupted save left unsaved changes ({summary})."),
+    ("recover.ask", "Replay or discard them? (r/d): "),
+    ("recover.discarded_all", "Discarded pending changes."),
+    ("bench.header", "{count} synthetic todos stored as {format} ({size} MB), quickest of {runs} runs:"),
+    ("remind.nothing", "Nothing due today."),
+    ("remind.summary", "Todos for {day}: {overdue} overdue, {due} due today"),
+    ("remind.overdue", "overdue, due {date}"),
+    ("remind.due_today", "due today"),
+    ("remind.no_email", "Nothing due today; no email sent."),
+    ("remind.emailed", "Emailed {count} todos to {to}."),
+    ("remove.confirm", "Remove todo #{id}: {title}?"),
+    ("remove.removed", "Removed todo #{id}"),
+    ("remove.no_match", "No todos match the query."),
+    ("remove.confirm_many", "Remove these {count} todos?"),
+    ("remove.removed_title", "Removed todo #{id}: {title}"),
+    ("report.nothing_tracked", "No time tracked since {date}."),
+    ("report.week_of", "Week of {date}"),
+    ("report.total", "Total"),
+    ("report.range", "{since} to {until}"),
+    ("report.nothing_done", "Nothing completed from {range}."),
+    ("report.done", "Completed from {range}: {count}"),
+    ("common.no_project", "No project"),
+    ("resolve.several", "'{query}' matches several todos:"),
+    ("resolve.which", "Which one? (1-{count}, Enter to cancel): "),
+    ("share.nothing", "No open todos to share."),
+    ("share.gist", "a secret gist"),
+    ("share.preview", "Sharing {count} todos to {place}; anyone with the link can read this:\n"),
+    ("share.confirm", "Upload it?"),
+    ("share.shared", "Shared {count} todos: {url}"),
+    ("share.import", "Import them with: todo-cli import --from-url {url}"),
+    ("stale.none", "No open todos have gone {days} days without a change."),
+    ("stale.changed", "changed {when}"),
+    ("stale.summary", "{count} open todos unchanged for {days} days or more; finish, reschedule, or remove them."),
+    ("stats.totals", "{count} todos: {active} active, {completed} completed"),
+    ("stats.cancelled", ", {count} cancelled"),
+    ("stats.week_of", "Week of"),
+    ("stats.added", "Added"),
+    ("stats.completed", "Completed"),
+    ("stats.removed", "Removed"),
+    ("status.same", "Todo #{id} is already {status}."),
+    ("status.moved", "Moved todo #{id} from {from} to {to}: {title}"),
+    ("sync.up_to_date", "Local todos are up to date with the remote."),
+    ("sync.pulled", "Pulled changes: {added} added, {removed} removed, {updated} updated"),
+    ("sync.committed", "Committed {count} todos to {path}"),
+    ("sync.pushed", "Pushed to remote branch '{branch}'"),
+    ("sync.no_remote", "No sync remote configured; set sync.remote in the config or pass --remote."),
+    ("template.saved", "Saved template '{name}' from todo #{id}: {title}"),
+    ("template.updated", "Updated template '{name}' from todo #{id}: {title}"),
+    ("template.none", "No templates in {path}."),
+    ("track.stopped", "Stopped #{id} {title} after {duration}"),
+    ("track.already", "Timer is already running on todo #{id}."),
+    ("track.started", "Started timer on todo #{id}: {title}"),
+    ("track.none", "No timer is running."),
+    ("transfer.moved", "Moved todo #{id} to list '{list}' as #{new_id}: {title}"),
+    ("transfer.copied", "Copied todo #{id} to list '{list}' as #{new_id}: {title}"),
+    ("transfer.dropped", "Dropped {count} dependencies that would cross lists."),
+    ("trash.empty", "The trash is empty."),
+    ("trash.removed", "Removed"),
+    ("trash.already", "Todo #{id} is already on the list; dropped it from the trash."),
+    ("trash.restored", "Restored todo #{id}: {title}"),
+    ("trash.restored_as", "Restored todo #{id} as #{new_id}: {title}"),
+    ("trash.confirm", "Permanently delete {count} todos from the trash?"),
+    ("trash.deleted", "Deleted {count} todos from the trash ({left} left)."),
+    ("workload.header", "Estimated work due per day (capacity {capacity}):"),
+    ("workload.todos", "{count} todos"),
+    ("workload.over", "over by {hours}"),
+    ("workload.ok", "No day is overcommitted."),
+    ("workload.over_one", "1 day is overcommitted."),
+    ("workload.over_many", "{count} days are overcommitted."),
+    ("workload.unestimated_one", "1 open todo due by then has no estimate; add one with `edit --estimate`."),
+    ("workload.unestimated_many", "{count} open todos due by then have no estimate; add them with `edit --estimate`."),
+    ("common.title", "Title"),
+    ("restore.confirm", "Restore this backup?"),
+    ("clear.confirm", "Are you sure you want to clear all todos?"),
+    ("digest.overdue", "Overdue"),
+    ("digest.due_today", "Due today"),
+    ("pomodoro.finished_title", "Pomodoro finished"),
+    ("pomodoro.take_break", "Take a break from: {title}"),
+    ("pomodoro.break_over", "Break is over"),
+    ("pomodoro.back_to", "Back to: {title}"),
+    ("nag.title", "Todo"),
+    ("notify.title", "Todo: {title}"),
+    ("notify.resurfaced_title", "Todo is back"),
+    ("time.days_ago", "{n}d ago"),
+    ("time.hours_ago", "{n}h ago"),
+    ("time.minutes_ago", "{n}m ago"),
+    ("time.just_now", "just now"),
+    ("due.today", "Today"),
+    ("due.tomorrow", "Tomorrow"),
+    ("due.in_days", "in {n}d"),
+    ("due.overdue", "{n}d overdue"),
+    ("table.id", "ID"),
+    ("table.status", "Status"),
+    ("table.priority", "Priority"),
+    ("table.assignee", "Assignee"),
+    ("table.title", "Title"),
+    ("table.created", "Created"),
+    ("table.due", "Due"),
+    ("table.empty", "No todos found."),
+    ("group.this_week", "This week"),
+    ("group.next_week", "Next week"),
+    ("group.week_of", "Week of {date}"),
+    ("group.no_tags", "No tags"),
+    ("group.overdue", "Overdue"),
+    ("group.no_due", "No due date"),
+    ("interactive.help", "  ↑/↓ move  space toggle done  d delete  q save and quit  Ctrl-C discard"),
+    ("show.status", "Status"),
+    ("show.priority", "Priority"),
+    ("show.due", "Due"),
+    ("show.starts", "Starts"),
+    ("show.estimate", "Estimate"),
+    ("show.project", "Project"),
+    ("show.assignee", "Assignee"),
+    ("show.tags", "Tags"),
+    ("show.waits_on", "Waits on"),
+    ("show.scheduled", "Scheduled"),
+    ("show.hidden", "Hidden"),
+    ("show.until", "until {date}"),
+    ("show.created", "Created"),
+    ("show.modified", "Modified"),
+    ("show.closed", "Closed"),
+    ("show.by", " by {name}"),
+    ("show.attachments", "Attachments"),
+    ("show.comments", "Comments"),
+    ("agenda.overdue", "Overdue"),
+    ("agenda.today", " (today)"),
+    ("agenda.nothing", "  Nothing due."),
+    ("agenda.later", "{count} more due after {date}"),
+    ("agenda.backlog", "Backlog"),
+    ("table.urgency", "Urgency"),
+    ("review.prompt", "[k]eep, [r]eschedule, [p]riority, [t]ag, [a]rchive, [d]elete, [s]kip, [q]uit:"),
+    ("review.help", "Answer k, r, p, t, a, d, s, or q; Enter keeps the todo."),
+    ("review.ask_due", "New due date (empty for none):"),
+    ("review.ask_priority", "New priority:"),
+    ("review.ask_tag", "Tag to add:"),
+    ("review.progress", "Reviewing {n} of {total}"),
+    ("review.archived", "Archived '{title}'"),
+    ("review.nothing", "Nothing to review; every open todo was reviewed recently."),
+    ("review.done", "Reviewed {reviewed} of {total} todos"),
+    ("focus.added", "Focused on todo #{id} '{title}'"),
+    ("focus.already", "Todo #{id} is already in focus"),
+    ("focus.removed", "Took todo #{id} '{title}' out of focus"),
+    ("focus.not_focused", "Todo #{id} is not in focus"),
+    ("focus.empty", "Nothing in focus; pick todos with `focus add ID`."),
+    ("reorder.before", "Moved todo #{id} '{title}' before #{other}"),
+    ("reorder.after", "Moved todo #{id} '{title}' after #{other}"),
+    ("pin.pinned", "Pinned todo #{id}: {title}"),
+    ("pin.already", "Todo #{id} is already pinned."),
+    ("unpin.not_pinned", "Todo #{id} is not pinned."),
+    ("unpin.done", "Unpinned todo #{id}."),
+    ("check.added", "Added item {number} to the checklist of todo #{id}: {title}"),
+    ("check.done", "Ticked off item {number} of todo #{id}: {text}"),
+    ("check.already", "Item {number} of todo #{id} is already ticked off."),
+    ("show.checklist", "Checklist"),
+    ("progress.set", "Todo #{id} is {percent}% done."),
+    ("show.progress", "Progress"),
+    ("list.archived", "Archived, kept in the list named archive"),
+    ("jira.pulled", "Added {added} and refreshed {refreshed} todos from Jira issues assigned to you."),
+    ("jira.moved", "Moved {key} to {status}"),
+    ("jira.done", "Marked todo #{id} as done."),
+    ("show.source", "Source"),
+    ("open.source", "Opened {kind} {id}"),
+    ("feed.added", "Subscribed to {url}, synced at most every {every}."),
+    ("feed.updated", "Updated the subscription to {url}, synced at most every {every}."),
+    ("feed.removed", "Unsubscribed from {url}; its todos stay."),
+    ("feed.none", "No feeds yet. Subscribe to one with `feed add <url>`."),
+    ("feed.every", "every {every}"),
+    ("feed.synced", "Synced {url}: {added} added, {updated} updated, {closed} closed."),
+    ("recover.discarded_unreadable", "Removed the unreadable changes left by an interrupted save."),
+    ("remove.would_remove", "Would remove todo #{id}"),
+    ("remove.would_remove_title", "Would remove todo #{id}: {title}"),
+    ("include.archive", "  Archive: {count} todos"),
+    ("include.trash", "  Trash: {count} todos"),
+    ("include.archive_merged", "  Archive: {added} added, {updated} updated"),
+    ("include.trash_merged", "  Trash: {added} added"),
+    ("doctor.references_fixed", "[{store}] {count} dependencies on {old} moved to {uuid}"),
+    ("dry_run.nothing", "Dry run: nothing would change."),
+    ("dry_run.changes", "Dry run: nothing was saved. These changes would be made:"),
+    ("dry_run.purged", "{title} (from the trash, for good)"),
+    ("hooks.failed", "Warning: the {hook} hook failed for #{id}: {reason}"),
+    ("recover.unreadable", "Warning: {error}, left by an interrupted save; run `todo-cli recover --discard` to remove it"),
+    ("feed.failed", "Warning: feed {url} failed: {error}"),
+    ("feed.empty", "Warning: feed {url} has no items; its todos are left open"),
+    ("feed.sync_failed", "Warning: syncing feeds failed: {error}"),
+    ("webhook.serialize_failed", "Warning: failed to serialize webhook payload: {error}"),
+    ("webhook.failed", "Warning: webhook {url} failed: {error}"),
+];
+
+/// German messages
+const DE: &[(&str, &str)] = &[
+    ("add.added", "Aufgabe #{id} '{title}' hinzugefügt (Priorität: {priority})"),
+    ("add.added_due", "Aufgabe #{id} '{title}' hinzugefügt (Priorität: {priority}, fällig: {due})"),
+    ("add.uuid", "UUID: {uuid}"),
+    ("add.nothing", "Keine Aufgaben zum Hinzufügen."),
+    ("alias.added", "Alias '{name}' = {expansion} angelegt"),
+    ("alias.updated", "Alias '{name}' = {expansion} geändert"),
+    ("alias.none", "Keine Aliase in {path}."),
+    ("alias.removed", "Alias '{name}' = {expansion} entfernt"),
+    ("attach.already", "Aufgabe #{id} hat {target} bereits als Anhang."),
+    ("attach.attached", "{kind} {target} an Aufgabe #{id} angehängt: {title}"),
+    ("attach.opened", "{target} geöffnet"),
+    ("backup.done", "{count} Aufgaben nach {path} gesichert"),
+    ("backup.rotated", "{count} alte Sicherungen entfernt, die neuesten {keep} bleiben."),
+    ("restore.same", "Die Aufgaben stimmen bereits mit {path} überein."),
+    ("restore.preview", "Die Wiederherstellung von {path} würde Folgendes ändern:"),
+    ("restore.done", "{count} Aufgaben aus {path} wiederhergestellt"),
+    ("common.cancelled", "Vorgang abgebrochen."),
+    ("block.already", "Aufgabe #{id} hängt bereits von #{dependency} ab."),
+    ("block.blocked", "Aufgabe #{id} hängt jetzt von #{dependency} ab: {title}"),
+    ("unblock.not_blocked", "Aufgabe #{id} hängt nicht von #{dependency} ab."),
+    ("unblock.unblocked", "Aufgabe #{id} hängt nicht mehr von #{dependency} ab."),
+    ("unblock.all", "{count} Abhängigkeiten von Aufgabe #{id} entfernt."),
+    ("clear.done", "Alle Aufgaben wurden in den Papierkorb verschoben."),
+    ("comment.added", "Aufgabe #{id} kommentiert: {title}"),
+    ("compact.nothing", "Nichts zu verdichten; die Datenbankdatei ist aktuell."),
+    ("compact.one", "1 Änderung in die Datenbankdatei übernommen."),
+    ("compact.many", "{count} Änderungen in die Datenbankdatei übernommen."),
+    ("convert.done", "Datenbank in {format} umgewandelt."),
+    ("convert.already", "Die Datenbank ist bereits im Format {format} gespeichert."),
+    ("daemon.already_running", "Der Hintergrunddienst läuft bereits (PID {pid})."),
+    ("daemon.started", "Hintergrunddienst gestartet (PID {pid}), Protokoll in {path}"),
+    ("daemon.not_running", "Der Hintergrunddienst läuft nicht."),
+    ("daemon.stopped", "Hintergrunddienst beendet (PID {pid})."),
+    ("daemon.running", "Der Hintergrunddienst läuft (PID {pid})."),
+    ("daemon.watching", "{time} Beobachte {count} Aufgaben"),
+    ("daemon.reloaded", "{time} {count} Aufgaben neu geladen"),
+    ("diff.added", "Hinzugefügt"),
+    ("diff.removed", "Entfernt"),
+    ("diff.changed", "Geändert"),
+    ("diff.database", "der Datenbank"),
+    ("diff.none", "Keine Unterschiede zwischen {old} und {new}."),
+    ("diff.header", "Änderungen von {old} zu {new}:"),
+    ("diff.summary", "{added} hinzugefügt, {removed} entfernt, {changed} geändert"),
+    ("digest.nothing", "Heute ist nichts fällig; keine Übersicht gesendet."),
+    ("digest.sent", "Übersicht an {channel} gesendet."),
+    ("doctor.duplicate_id_fixed", "[{store}] Doppelte ID #{id} ('{title}') in #{new_id} geändert"),
+    ("doctor.duplicate_id", "[{store}] Doppelte ID #{id} ('{title}')"),
+    ("doctor.missing_uuid_fixed", "[{store}] Fehlende UUID bei #{id} ('{title}') durch {uuid} ersetzt"),
+    ("doctor.missing_uuid", "[{store}] Fehlende UUID '{uuid}' bei #{id} ('{title}')"),
+    ("doctor.duplicate_uuid_fixed", "[{store}] Doppelte UUID bei #{id} ('{title}') durch {uuid} ersetzt"),
+    ("doctor.duplicate_uuid", "[{store}] Doppelte UUID '{uuid}' bei #{id} ('{title}')"),
+    ("doctor.ok", "Keine Probleme gefunden."),
+    ("doctor.fixed", "{count} Problem(e) behoben."),
+    ("doctor.found", "{count} Problem(e) gefunden. Mit --fix beheben."),
+    ("done.unfinished_dependency", "Warnung: Aufgabe #{id} hängt von #{dependency} ab, die nicht erledigt ist: {title}"),
+    ("done.already", "Aufgabe #{id} ist bereits erledigt."),
+    ("done.done", "Aufgabe #{id} als erledigt markiert: {title}"),
+    ("done.no_match", "Keine offenen Aufgaben passen zur Abfrage."),
+    ("duplicate.done", "Aufgabe #{id} als #{new_id} kopiert: {title}"),
+    ("edit.unchanged", "Aufgabe #{id} ist unverändert."),
+    ("edit.updated", "{fields} von Aufgabe #{id} geändert: {title}"),
+    ("escalate.escalated", "Aufgabe #{id} von {from} auf {to} hochgestuft: {title}"),
+    ("escalate.nothing", "Keine Aufgaben hochzustufen."),
+    ("escalate.dry_run", "Probelauf: {count} Aufgaben würden hochgestuft."),
+    ("export.done", "{count} Aufgaben nach {path} exportiert"),
+    ("import.replaced", "{count} Aufgaben durch {imported} importierte ersetzt."),
+    ("import.done", "{count} Aufgaben importiert ({skipped} schon vorhanden)."),
+    ("github.pulled", "{count} Aufgaben aus deinen Issues in {repo} hinzugefügt."),
+    ("github.closed", "{url} geschlossen"),
+    ("github.done", "Aufgabe #{id} als erledigt markiert."),
+    ("hide.hidden", "Aufgabe #{id} bis {until} ausgeblendet: {title}"),
+    ("unhide.not_hidden", "Aufgabe #{id} ist nicht ausgeblendet."),
+    ("unhide.done", "Aufgabe #{id} ist wieder sichtbar."),
+    ("history.none_field", "Keine Änderungen an {field} für #{id} {title} aufgezeichnet."),
+    ("history.none", "Keine Änderungen für #{id} {title} aufgezeichnet."),
+    ("history.header", "Verlauf von #{id} {title}"),
+    ("list.written", "{count} Aufgaben nach {path} geschrieben"),
+    ("list.watching", "Alle {seconds}s und bei Änderungen, zuletzt um {time}. Strg-C beendet."),
+    ("common.error", "Fehler: {error}"),
+    ("list.discarded", "Änderungen verworfen."),
+    ("list.saved", "Gespeichert: {completed} erledigt, {removed} entfernt."),
+    ("merge.conflict", "#{id} {title}: {field} unterscheidet sich"),
+    ("merge.here", "  1) hier:  {value}"),
+    ("merge.other", "  2) dort:  {value}"),
+    ("merge.nothing", "Nichts zusammenzuführen; die Datenbank enthält bereits alles aus {path}."),
+    ("merge.done", "{path} zusammengeführt: {added} hinzugefügt, {updated} geändert, {conflicts} widersprüchliche Felder aufgelöst"),
+    ("nag.nag", "Aufgabe: {message}"),
+    ("nag.inbox", "{count} Aufgaben im Eingang älter als {age}"),
+    ("nag.overdue_one", "dringende Aufgabe #{id} ist überfällig: {title}"),
+    ("nag.overdue_many", "{count} dringende Aufgaben sind überfällig"),
+    ("next.nothing", "Als Nächstes ist nichts zu tun."),
+    ("notify.due", "Fällig {when} ({offset} vorher)"),
+    ("notify.reminder", "Erinnerung: #{id} {title} - {body}"),
+    ("notify.resurfaced", "Wieder auf der Liste: #{id} {title}"),
+    ("pomodoro.started", "Pomodoro für Aufgabe #{id}: {title} ({work} Arbeit, {rest} Pause)"),
+    ("pomodoro.finished", "Pomodoro {done} für #{id} beendet ({count} insgesamt)"),
+    ("pomodoro.work", "Arbeit"),
+    ("pomodoro.break", "Pause "),
+    ("priority.same", "Aufgabe #{id} hat bereits die Priorität {priority}"),
+    ("priority.updated", "Priorität von Aufgabe #{id} von {from} auf {to} geändert"),
+    ("recover.summary", "{added} hinzugefügt, {removed} entfernt, {updated} geändert"),
+    ("recover.replayed", "Ausstehende Änderungen wiederhergestellt ({summary})."),
+    ("recover.none", "Keine ausstehenden Änderungen."),
+    ("recover.discarded", "Ausstehende Änderungen verworfen ({summary})."),
+    ("recover.found", "Ein unterbrochenes Speichern hat ungespeicherte Änderungen hinterlassen ({summary})."),
+    ("recover.ask", "Wiederherstellen oder verwerfen? (r/d): "),
+    ("recover.discarded_all", "Ausstehende Änderungen verworfen."),
+    ("bench.header", "{count} erzeugte Aufgaben als {format} gespeichert ({size} MB), schnellster von {runs} Läufen:"),
+    ("remind.nothing", "Heute ist nichts fällig."),
+    ("remind.summary", "Aufgaben für {day}: {overdue} überfällig, {due} heute fällig"),
+    ("remind.overdue", "überfällig, fällig am {date}"),
+    ("remind.due_today", "heute fällig"),
+    ("remind.no_email", "Heute ist nichts fällig; keine E-Mail gesendet."),
+    ("remind.emailed", "{count} Aufgaben per E-Mail an {to} gesendet."),
+    ("remove.confirm", "Aufgabe #{id} entfernen: {title}?"),
+    ("remove.removed", "Aufgabe #{id} entfernt"),
+    ("remove.no_match", "Keine Aufgaben passen zur Abfrage."),
+    ("remove.confirm_many", "Diese {count} Aufgaben entfernen?"),
+    ("remove.removed_title", "Aufgabe #{id} entfernt: {title}"),
+    ("report.nothing_tracked", "Seit {date} keine Zeit erfasst."),
+    ("report.week_of", "Woche vom {date}"),
+    ("report.total", "Gesamt"),
+    ("report.range", "{since} bis {until}"),
+    ("report.nothing_done", "Von {range} nichts erledigt."),
+    ("report.done", "Erledigt von {range}: {count}"),
+    ("common.no_project", "Kein Projekt"),
+    ("resolve.several", "'{query}' passt auf mehrere Aufgaben:"),
+    ("resolve.which", "Welche? (1-{count}, Enter bricht ab): "),
+    ("share.nothing", "Keine offenen Aufgaben zum Teilen."),
+    ("share.gist", "ein geheimes Gist"),
+    ("share.preview", "{count} Aufgaben werden nach {place} geteilt; jeder mit dem Link kann dies lesen:\n"),
+    ("share.confirm", "Hochladen?"),
+    ("share.shared", "{count} Aufgaben geteilt: {url}"),
+    ("share.import", "Importieren mit: todo-cli import --from-url {url}"),
+    ("stale.none", "Keine offene Aufgabe ist seit {days} Tagen unverändert."),
+    ("stale.changed", "geändert {when}"),
+    ("stale.summary", "{count} offene Aufgaben seit {days} oder mehr Tagen unverändert; erledigen, verschieben oder entfernen."),
+    ("stats.totals", "{count} Aufgaben: {active} offen, {completed} erledigt"),
+    ("stats.cancelled", ", {count} abgebrochen"),
+    ("stats.week_of", "Woche vom"),
+    ("stats.added", "Neu"),
+    ("stats.completed", "Erledigt"),
+    ("stats.removed", "Entfernt"),
+    ("status.same", "Aufgabe #{id} ist bereits {status}."),
+    ("status.moved", "Aufgabe #{id} von {from} nach {to} verschoben: {title}"),
+    ("sync.up_to_date", "Die lokalen Aufgaben sind auf dem Stand des Remotes."),
+    ("sync.pulled", "Änderungen geholt: {added} hinzugefügt, {removed} entfernt, {updated} geändert"),
+    ("sync.committed", "{count} Aufgaben in {path} committet"),
+    ("sync.pushed", "Auf den Remote-Branch '{branch}' gepusht"),
+    ("sync.no_remote", "Kein Sync-Remote eingerichtet; sync.remote in der Konfiguration setzen oder --remote angeben."),
+    ("template.saved", "Vorlage '{name}' aus Aufgabe #{id} gespeichert: {title}"),
+    ("template.updated", "Vorlage '{name}' aus Aufgabe #{id} aktualisiert: {title}"),
+    ("template.none", "Keine Vorlagen in {path}."),
+    ("track.stopped", "#{id} {title} nach {duration} gestoppt"),
+    ("track.already", "Für Aufgabe #{id} läuft bereits ein Timer."),
+    ("track.started", "Timer für Aufgabe #{id} gestartet: {title}"),
+    ("track.none", "Es läuft kein Timer."),
+    ("transfer.moved", "Aufgabe #{id} als #{new_id} in die Liste '{list}' verschoben: {title}"),
+    ("transfer.copied", "Aufgabe #{id} als #{new_id} in die Liste '{list}' kopiert: {title}"),
+    ("transfer.dropped", "{count} listenübergreifende Abhängigkeiten entfernt."),
+    ("trash.empty", "Der Papierkorb ist leer."),
+    ("trash.removed", "Entfernt"),
+    ("trash.already", "Aufgabe #{id} ist bereits auf der Liste; aus dem Papierkorb entfernt."),
+    ("trash.restored", "Aufgabe #{id} wiederhergestellt: {title}"),
+    ("trash.restored_as", "Aufgabe #{id} als #{new_id} wiederhergestellt: {title}"),
+    ("trash.confirm", "{count} Aufgaben endgültig aus dem Papierkorb löschen?"),
+    ("trash.deleted", "{count} Aufgaben aus dem Papierkorb gelöscht ({left} übrig)."),
+    ("workload.header", "Geschätzte fällige Arbeit pro Tag (Kapazität {capacity}):"),
+    ("workload.todos", "{count} Aufgaben"),
+    ("workload.over", "{hours} zu viel"),
+    ("workload.ok", "Kein Tag ist überlastet."),
+    ("workload.over_one", "1 Tag ist überlastet."),
+    ("workload.over_many", "{count} Tage sind überlastet."),
+    ("workload.unestimated_one", "1 bis dahin fällige Aufgabe hat keine Schätzung; mit `edit --estimate` ergänzen."),
+    ("workload.unestimated_many", "{count} bis dahin fällige Aufgaben haben keine Schätzung; mit `edit --estimate` ergänzen."),
+    ("common.title", "Titel"),
+    ("restore.confirm", "Diese Sicherung wiederherstellen?"),
+    ("clear.confirm", "Wirklich alle Aufgaben löschen?"),
+    ("digest.overdue", "Überfällig"),
+    ("digest.due_today", "Heute fällig"),
+    ("pomodoro.finished_title", "Pomodoro beendet"),
+    ("pomodoro.take_break", "Pause von: {title}"),
+    ("pomodoro.break_over", "Die Pause ist vorbei"),
+    ("pomodoro.back_to", "Zurück zu: {title}"),
+    ("nag.title", "Aufgabe"),
+    ("notify.title", "Aufgabe: {title}"),
+    ("notify.resurfaced_title", "Aufgabe ist zurück"),
+    ("time.days_ago", "vor {n} T."),
+    ("time.hours_ago", "vor {n} Std."),
+    ("time.minutes_ago", "vor {n} Min."),
+    ("time.just_now", "gerade eben"),
+    ("due.today", "Heute"),
+    ("due.tomorrow", "Morgen"),
+    ("due.in_days", "in {n} T."),
+    ("due.overdue", "{n} T. überfällig"),
+    ("table.id", "Nr."),
+    ("table.status", "Status"),
+    ("table.priority", "Prio"),
+    ("table.assignee", "Zuständig"),
+    ("table.title", "Titel"),
+    ("table.created", "Erstellt"),
+    ("table.due", "Fällig"),
+    ("table.empty", "Keine Aufgaben gefunden."),
+    ("group.this_week", "Diese Woche"),
+    ("group.next_week", "Nächste Woche"),
+    ("group.week_of", "Woche vom {date}"),
+    ("group.no_tags", "Keine Schlagwörter"),
+    ("group.overdue", "Überfällig"),
+    ("group.no_due", "Ohne Fälligkeit"),
+    ("interactive.help", "  ↑/↓ bewegen  Leertaste erledigt umschalten  d löschen  q speichern und beenden  Strg-C verwerfen"),
+    ("show.status", "Status"),
+    ("show.priority", "Priorität"),
+    ("show.due", "Fällig"),
+    ("show.starts", "Beginnt"),
+    ("show.estimate", "Schätzung"),
+    ("show.project", "Projekt"),
+    ("show.assignee", "Zuständig"),
+    ("show.tags", "Schlagwörter"),
+    ("show.waits_on", "Wartet auf"),
+    ("show.scheduled", "Geplant"),
+    ("show.hidden", "Versteckt"),
+    ("show.until", "bis {date}"),
+    ("show.created", "Erstellt"),
+    ("show.modified", "Geändert"),
+    ("show.closed", "Geschlossen"),
+    ("show.by", " von {name}"),
+    ("show.attachments", "Anhänge"),
+    ("show.comments", "Kommentare"),
+    ("agenda.overdue", "Überfällig"),
+    ("agenda.today", " (heute)"),
+    ("agenda.nothing", "  Nichts fällig."),
+    ("agenda.later", "{count} weitere fällig nach dem {date}"),
+    ("agenda.backlog", "Ohne Termin"),
+    ("table.urgency", "Dringl."),
+    ("review.prompt", "[k] behalten, [r] verschieben, [p] Priorität, [t] Tag, [a] archivieren, [d] löschen, [s] überspringen, [q] beenden:"),
+    ("review.help", "Antworte mit k, r, p, t, a, d, s oder q; Enter behält die Aufgabe."),
+    ("review.ask_due", "Neues Fälligkeitsdatum (leer für keines):"),
+    ("review.ask_priority", "Neue Priorität:"),
+    ("review.ask_tag", "Hinzuzufügender Tag:"),
+    ("review.progress", "Durchsicht {n} von {total}"),
+    ("review.archived", "'{title}' archiviert"),
+    ("review.nothing", "Nichts durchzusehen; alle offenen Aufgaben wurden kürzlich durchgesehen."),
+    ("review.done", "{reviewed} von {total} Aufgaben durchgesehen"),
+    ("focus.added", "Aufgabe #{id} '{title}' im Fokus"),
+    ("focus.already", "Aufgabe #{id} ist bereits im Fokus"),
+    ("focus.removed", "Aufgabe #{id} '{title}' aus dem Fokus genommen"),
+    ("focus.not_focused", "Aufgabe #{id} ist nicht im Fokus"),
+    ("focus.empty", "Nichts im Fokus; wähle Aufgaben mit `focus add ID`."),
+    ("reorder.before", "Aufgabe #{id} '{title}' vor #{other} verschoben"),
+    ("reorder.after", "Aufgabe #{id} '{title}' hinter #{other} verschoben"),
+    ("pin.pinned", "Aufgabe #{id} angeheftet: {title}"),
+    ("pin.already", "Aufgabe #{id} ist bereits angeheftet."),
+    ("unpin.not_pinned", "Aufgabe #{id} ist nicht angeheftet."),
+    ("unpin.done", "Aufgabe #{id} losgelöst."),
+    ("check.added", "Punkt {number} zur Checkliste von Aufgabe #{id} hinzugefügt: {title}"),
+    ("check.done", "Punkt {number} von Aufgabe #{id} abgehakt: {text}"),
+    ("check.already", "Punkt {number} von Aufgabe #{id} ist bereits abgehakt."),
+    ("show.checklist", "Checkliste"),
+    ("progress.set", "Aufgabe #{id} ist zu {percent}% erledigt."),
+    ("show.progress", "Fortschritt"),
+    ("list.archived", "Archiviert, in der Liste namens archive"),
+    ("jira.pulled", "{added} Aufgaben aus deinen Jira-Issues hinzugefügt und {refreshed} aktualisiert."),
+    ("jira.moved", "{key} nach {status} verschoben"),
+    ("jira.done", "Aufgabe #{id} als erledigt markiert."),
+    ("show.source", "Quelle"),
+    ("open.source", "{kind} {id} geöffnet"),
+    ("feed.added", "{url} abonniert, höchstens alle {every} abgeglichen."),
+    ("feed.updated", "Abonnement von {url} aktualisiert, höchstens alle {every} abgeglichen."),
+    ("feed.removed", "{url} abbestellt; seine Aufgaben bleiben."),
+    ("feed.none", "Noch keine Feeds. Abonniere einen mit `feed add <url>`."),
+    ("feed.every", "alle {every}"),
+    ("feed.synced", "{url} abgeglichen: {added} hinzugefügt, {updated} aktualisiert, {closed} geschlossen."),
+    ("recover.discarded_unreadable", "Unlesbare Änderungen eines unterbrochenen Speicherns entfernt."),
+    ("remove.would_remove", "Aufgabe #{id} würde entfernt"),
+    ("remove.would_remove_title", "Aufgabe #{id} würde entfernt: {title}"),
+    ("include.archive", "  Archiv: {count} Aufgaben"),
+    ("include.trash", "  Papierkorb: {count} Aufgaben"),
+    ("include.archive_merged", "  Archiv: {added} hinzugefügt, {updated} geändert"),
+    ("include.trash_merged", "  Papierkorb: {added} hinzugefügt"),
+    ("doctor.references_fixed", "[{store}] {count} Abhängigkeiten von {old} auf {uuid} umgestellt"),
+    ("dry_run.nothing", "Probelauf: nichts würde sich ändern."),
+    ("dry_run.changes", "Probelauf: nichts wurde gespeichert. Diese Änderungen würden vorgenommen:"),
+    ("dry_run.purged", "{title} (endgültig aus dem Papierkorb)"),
+    ("hooks.failed", "Warnung: der Hook {hook} ist für #{id} fehlgeschlagen: {reason}"),
+    ("recover.unreadable", "Warnung: {error}, von einem unterbrochenen Speichern hinterlassen; `todo-cli recover --discard` entfernt es"),
+    ("feed.failed", "Warnung: Feed {url} fehlgeschlagen: {error}"),
+    ("feed.empty", "Warnung: Feed {url} hat keine Einträge; seine Aufgaben bleiben offen"),
+    ("feed.sync_failed", "Warnung: Abgleich der Feeds fehlgeschlagen: {error}"),
+    ("webhook.serialize_failed", "Warnung: Webhook-Nutzdaten konnten nicht serialisiert werden: {error}"),
+    ("webhook.failed", "Warnung: Webhook {url} fehlgeschlagen: {error}"),
+];
@@ -0,0 +1,179 @@
+//! Email through an SMTP server or a local sendmail
+//!
+//! With `email.smtp_url` set, messages go out through `curl`, as for the
+//! other integrations. TLS is required: `smtps://` URLs use it from the
+//! start and `smtp://` ones must upgrade with STARTTLS. The password is
+//! read from `$TODO_SMTP_PASSWORD`, then from `email.password`. Without a
+//! server, the message is piped to `sendmail -t`.
+
+use super::{curl, curl_error};
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// Settings for `remind --email`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailConfig {
+    /// Address the reminders go to
+    pub to: Option<String>,
+    /// Sender address; defaults to `to`
+    pub from: Option<String>,
+    /// SMTP server, e.g. `smtps://smtp.example.com:465` or `smtp://smtp.example.com:587`
+    pub smtp_url: Option<String>,
+    /// SMTP login
+    pub username: Option<String>,
+    /// SMTP password; `$TODO_SMTP_PASSWORD` takes precedence
+    pub password: Option<String>,
+    /// Sendmail-compatible program used when no SMTP server is set
+    pub sendmail: String,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            to: None,
+            from: None,
+            smtp_url: None,
+            username: None,
+            password: None,
+            sendmail: "sendmail".to_string(),
+        }
+    }
+}
+
+/// A message with a plain-text and an HTML body
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+impl Email {
+    /// Returns the message as `multipart/alternative` MIME, with lines ending in `\n`
+    pub fn to_mime(&self, from: &str, to: &str) -> String {
+        let boundary = format!("todo-cli-{}", Local::now().timestamp_nanos_opt().unwrap_or_default());
+        let part = |kind: &str, body: &str| {
+            format!(
+                "--{}\nContent-Type: text/{}; charset=utf-8\nContent-Transfer-Encoding: 8bit\n\n{}\n",
+                boundary,
+                kind,
+                body.trim_end()
+            )
+        };
+        format!(
+            "From: {}\nTo: {}\nSubject: {}\nDate: {}\nMIME-Version: 1.0\n\
+             Content-Type: multipart/alternative; boundary=\"{}\"\n\n{}{}--{}--\n",
+            from,
+            to,
+            self.subject,
+            Local::now().to_rfc2822(),
+            boundary,
+            part("plain", &self.text),
+            part("html", &self.html),
+            boundary
+        )
+    }
+}
+
+/// Escapes `&`, `<`, `>`, and `"` for HTML
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Sends `email` to `email.to` over SMTP, or through sendmail without a server
+pub fn send(config: &EmailConfig, email: &Email) -> Result<()> {
+    let to = config
+        .to
+        .as_deref()
+        .ok_or_else(|| anyhow!("No recipient: set email.to in the config file"))?;
+    let from = config.from.as_deref().unwrap_or(to);
+    let message = email.to_mime(from, to);
+
+    match &config.smtp_url {
+        Some(url) => send_smtp(config, url, from, to, &message),
+        None => send_sendmail(&config.sendmail, &message),
+    }
+}
+
+/// Hands the message to curl, which insists on TLS and converts line endings to CRLF
+fn send_smtp(config: &EmailConfig, url: &str, from: &str, to: &str, message: &str) -> Result<()> {
+    let path = write_message(message)?;
+    let output = run_curl(config, url, from, to, &path);
+    let _ = fs::remove_file(&path);
+
+    let output = output?;
+    if !output.status.success() {
+        return Err(anyhow!("Sending mail through {} failed: {}", url, curl_error(&output)));
+    }
+    Ok(())
+}
+
+/// Writes the message to a new file in the temporary directory, readable only by its owner
+///
+/// The file must not exist yet, so a file or symlink someone else planted
+/// under the same name is never written through.
+fn write_message(message: &str) -> Result<PathBuf> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let nanos = Local::now().timestamp_subsec_nanos();
+    for attempt in 0..10u32 {
+        let name = format!("todo-cli-mail-{}-{}-{}.eml", std::process::id(), nanos, attempt);
+        let path = std::env::temp_dir().join(name);
+        match options.open(&path) {
+            Ok(mut file) => {
+                let written = file.write_all(message.as_bytes());
+                if let Err(e) = written {
+                    let _ = fs::remove_file(&path);
+                    return Err(e).with_context(|| format!("Failed to write to {}", path.display()));
+                }
+                return Ok(path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e).with_context(|| format!("Failed to create {}", path.display())),
+        }
+    }
+    Err(anyhow!("Failed to create a file for the message in {}", std::env::temp_dir().display()))
+}
+
+/// Runs curl to upload the message at `path`, passing the login as its config
+fn run_curl(config: &EmailConfig, url: &str, from: &str, to: &str, path: &Path) -> Result<Output> {
+    let login = config.username.as_ref().map(|user| {
+        let password = std::env::var("TODO_SMTP_PASSWORD").ok().or_else(|| config.password.clone());
+        let login = format!("{}:{}", user, password.unwrap_or_default());
+        format!("user = \"{}\"\n", login.replace('\\', "\\\\").replace('"', "\\\""))
+    });
+    let path = path.to_string_lossy();
+    let args = ["--ssl-reqd", "--crlf", "--config", "-", "--url", url, "--mail-from", from, "--mail-rcpt", to];
+    curl(&[&args[..], &["--upload-file", &path]].concat(), login.unwrap_or_default().as_bytes())
+}
+
+/// Pipes the message to `program -t -oi`, which reads the recipients from its headers
+fn send_sendmail(program: &str, message: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(["-t", "-oi"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {}; set email.smtp_url or email.sendmail", program))?;
+    child
+        .stdin
+        .take()
+        .context("Failed to open sendmail's stdin")?
+        .write_all(message.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! Calendar and JSON feeds subscribed to with `feed add`
+//!
+//! Subscriptions live under `feeds` in the config file. `feed sync`
+//! downloads each one through the same `curl` download as `import
+//! --from-url`, reads it as iCalendar or, when it starts with `{`, as a
+//! JSON Feed, and brings the todos from it up to date. Todos remember
+//! the feed and the item they came from in their external reference, so
+//! renamed or rescheduled items are updated rather than added again.
+
+use crate::config::{save_setting, Config};
+use crate::duration::HumanDuration;
+use crate::error::TodoError;
+use crate::formats::{from_ics, from_json_feed, ImportedTodo};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// A subscription to a feed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Feed {
+    /// Address of the feed; `webcal://` is fetched over https
+    pub url: String,
+    /// Title for calendar entries, as for `import --title`
+    #[serde(default)]
+    pub title: Option<String>,
+    /// How long `feed sync` leaves the feed alone after syncing it
+    #[serde(default = "default_every")]
+    pub every: HumanDuration,
+}
+
+fn default_every() -> HumanDuration {
+    HumanDuration(chrono::Duration::hours(1))
+}
+
+impl Feed {
+    /// Subscribes to `url`, synced at most every `every` (an hour by default)
+    pub fn new(url: &str, title: Option<String>, every: Option<HumanDuration>) -> Result<Self> {
+        let url = url.trim();
+        if !["https://", "http://", "webcal://"].iter().any(|scheme| url.starts_with(scheme)) {
+            return Err(TodoError::Validation(format!("Not an http, https, or webcal URL: {}", url)).into());
+        }
+        Ok(Self { url: url.to_string(), title, every: every.unwrap_or_else(default_every) })
+    }
+
+    /// Downloads the feed and reads its items
+    pub fn fetch(&self) -> Result<Vec<ImportedTodo>> {
+        let input = crate::integrations::share::fetch(&self.url)?;
+        Ok(self.read(&input)?)
+    }
+
+    /// Reads the items of a downloaded feed, telling JSON from iCalendar by its first character
+    pub fn read(&self, input: &str) -> Result<Vec<ImportedTodo>, TodoError> {
+        match input.trim_start_matches('\u{feff}').trim_start().chars().next() {
+            Some('{') if self.title.is_some() => {
+                Err(TodoError::Validation(format!("{} is a JSON Feed; title templates are for calendars", self.url)))
+            }
+            Some('{') => from_json_feed(input),
+            _ => from_ics(input, self.title.as_deref()),
+        }
+    }
+}
+
+/// Loads the subscriptions from the configuration file
+pub fn load_feeds() -> Result<Vec<Feed>> {
+    Ok(Config::load()?.feeds)
+}
+
+/// Replaces the subscriptions in the configuration file, leaving its other settings alone
+pub fn save_feeds(feeds: &[Feed]) -> Result<()> {
+    let value = (!feeds.is_empty()).then(|| serde_json::to_value(feeds)).transpose()?;
+    save_setting("feeds", value)
+}
@@ -0,0 +1,161 @@
+//! GitHub issues as todos
+//!
+//! Requests go to the REST API through `curl`. The token is read from
+//! `$GITHUB_TOKEN`, then from `github.token` in the config file.
+
+use super::{curl_error, request_json};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const API_URL: &str = "https://api.github.com";
+
+/// Settings for `github`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GithubConfig {
+    /// API token; `$GITHUB_TOKEN` takes precedence
+    pub token: Option<String>,
+    /// Repository `github pull` uses when none is given, as `owner/name`
+    pub repo: Option<String>,
+}
+
+/// An open issue assigned to the authenticated user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub labels: Vec<String>,
+}
+
+/// A GitHub API client authenticated with a token
+#[derive(Debug, Clone)]
+pub struct GithubClient {
+    token: String,
+}
+
+impl GithubClient {
+    /// Creates a client from `$GITHUB_TOKEN` or the configured token
+    pub fn from_config(config: &GithubConfig) -> Result<Self> {
+        let token = std::env::var("GITHUB_TOKEN")
+            .ok()
+            .filter(|t| !t.trim().is_empty())
+            .or_else(|| config.token.clone())
+            .ok_or_else(|| anyhow!("No GitHub token: set GITHUB_TOKEN or github.token in the config file"))?;
+        Ok(Self { token: token.trim().to_string() })
+    }
+
+    /// Sends a request and parses the JSON response
+    fn request(&self, method: &str, path: &str, body: Option<&Value>) -> Result<Value> {
+        let headers = format!(
+            "Authorization: Bearer {}\nAccept: application/vnd.github+json\nUser-Agent: todo-cli\n",
+            self.token
+        );
+        let output = request_json(method, &format!("{}{}", API_URL, path), &headers, body)?;
+
+        let response = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() {
+            let message = serde_json::from_str::<Value>(&response)
+                .ok()
+                .and_then(|v| v["message"].as_str().map(str::to_string))
+                .unwrap_or_else(|| curl_error(&output));
+            return Err(anyhow!("GitHub {} {} failed: {}", method, path, message));
+        }
+        serde_json::from_str(&response).with_context(|| format!("Unexpected response from GitHub {}", path))
+    }
+
+    /// Returns the login of the token's owner
+    pub fn login(&self) -> Result<String> {
+        let user = self.request("GET", "/user", None)?;
+        user["login"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("GitHub did not return a login for the token"))
+    }
+
+    /// Lists the open issues in `repo` (`owner/name`) assigned to the token's owner
+    ///
+    /// Pull requests, which the API reports as issues too, are left out.
+    pub fn assigned_issues(&self, repo: &str) -> Result<Vec<Issue>> {
+        check_repo(repo)?;
+        let login = self.login()?;
+        let mut issues = Vec::new();
+        for page in 1.. {
+            let path = format!("/repos/{}/issues?assignee={}&state=open&per_page=100&page={}", repo, login, page);
+            let batch = self.request("GET", &path, None)?;
+            let items = batch.as_array().ok_or_else(|| anyhow!("Unexpected response from GitHub {}", path))?;
+            issues.extend(items.iter().filter(|item| item.get("pull_request").is_none()).filter_map(parse_issue));
+            if items.len() < 100 {
+                break;
+            }
+        }
+        Ok(issues)
+    }
+
+    /// Closes the issue at `url`, an issue page such as `https://github.com/owner/name/issues/12`
+    pub fn close_issue(&self, url: &str) -> Result<()> {
+        let (repo, number) = parse_issue_url(url)?;
+        let body = serde_json::json!({ "state": "closed" });
+        self.request("PATCH", &format!("/repos/{}/issues/{}", repo, number), Some(&body))?;
+        Ok(())
+    }
+
+    /// Creates a secret gist holding one file, returning its page and the file's raw URL
+    ///
+    /// Secret gists are unlisted rather than private: anyone with the URL can read them.
+    #[cfg(feature = "share")]
+    pub fn create_gist(&self, description: &str, filename: &str, content: &str) -> Result<(String, String)> {
+        let body = serde_json::json!({
+            "description": description,
+            "public": false,
+            "files": { filename: { "content": content } },
+        });
+        let gist = self.request("POST", "/gists", Some(&body))?;
+        let url = gist["html_url"].as_str();
+        let raw_url = gist["files"][filename]["raw_url"].as_str();
+        match (url, raw_url) {
+            (Some(url), Some(raw_url)) => Ok((url.to_string(), raw_url.to_string())),
+            _ => Err(anyhow!("GitHub did not return the new gist's URL")),
+        }
+    }
+}
+
+fn parse_issue(item: &Value) -> Option<Issue> {
+    Some(Issue {
+        number: item["number"].as_u64()?,
+        title: item["title"].as_str()?.to_string(),
+        url: item["html_url"].as_str()?.to_string(),
+        labels: item["labels"]
+            .as_array()
+            .map(|labels| labels.iter().filter_map(|l| l["name"].as_str().map(str::to_string)).collect())
+            .unwrap_or_default(),
+    })
+}
+
+fn check_repo(repo: &str) -> Result<()> {
+    let valid = repo.split_once('/').is_some_and(|(owner, name)| {
+        let part = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || "-_.".contains(c));
+        part(owner) && part(name)
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid repository '{}': use owner/name", repo))
+    }
+}
+
+/// Splits an issue page URL into `owner/name` and the issue number
+pub fn parse_issue_url(url: &str) -> Result<(String, u64)> {
+    let invalid = || anyhow!("Not a GitHub issue URL: {}", url);
+    let path = url.strip_prefix("https://github.com/").ok_or_else(invalid)?;
+    let parts: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    match parts.as_slice() {
+        [owner, name, "issues", number] => {
+            let repo = format!("{}/{}", owner, name);
+            check_repo(&repo)?;
+            Ok((repo, number.parse().map_err(|_| invalid())?))
+        }
+        _ => Err(invalid()),
+    }
+}
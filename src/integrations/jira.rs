@@ -0,0 +1,197 @@
+//! Jira issues as todos
+//!
+//! Requests go to the REST API (version 2, which Jira Cloud and Jira
+//! Server both speak) through `curl`, as for GitHub. The token is read
+//! from `$JIRA_TOKEN`, then from `jira.token` in the config file. With
+//! `jira.email` set it is sent as an Atlassian Cloud API token, otherwise
+//! as a Server or Data Center personal access token.
+
+use super::{curl_error, request_json};
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Settings for `jira`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JiraConfig {
+    /// Address of the Jira site, e.g. `https://example.atlassian.net`
+    pub url: Option<String>,
+    /// Account email, for Jira Cloud API tokens
+    pub email: Option<String>,
+    /// API token or personal access token; `$JIRA_TOKEN` takes precedence
+    pub token: Option<String>,
+    /// Transition `jira done` uses, by name; defaults to the first one into a done status
+    pub done_transition: Option<String>,
+}
+
+/// An unresolved issue assigned to the token's owner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub key: String,
+    pub summary: String,
+    pub url: String,
+    /// Name of the issue's priority, e.g. `High`
+    pub priority: Option<String>,
+    pub due_date: Option<NaiveDate>,
+    pub labels: Vec<String>,
+}
+
+/// A Jira API client for one site, authenticated with a token
+#[derive(Debug, Clone)]
+pub struct JiraClient {
+    url: String,
+    authorization: String,
+    done_transition: Option<String>,
+}
+
+impl JiraClient {
+    /// Creates a client from the configured site and `$JIRA_TOKEN` or the configured token
+    pub fn from_config(config: &JiraConfig) -> Result<Self> {
+        let url = config
+            .url
+            .as_deref()
+            .map(|url| url.trim().trim_end_matches('/'))
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| anyhow!("No Jira site: set jira.url in the config file"))?;
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(anyhow!("Invalid jira.url '{}': use an http or https address", url));
+        }
+        let token = std::env::var("JIRA_TOKEN")
+            .ok()
+            .filter(|t| !t.trim().is_empty())
+            .or_else(|| config.token.clone())
+            .ok_or_else(|| anyhow!("No Jira token: set JIRA_TOKEN or jira.token in the config file"))?;
+        let authorization = match config.email.as_deref().map(str::trim).filter(|e| !e.is_empty()) {
+            Some(email) => format!("Basic {}", base64(format!("{}:{}", email, token.trim()).as_bytes())),
+            None => format!("Bearer {}", token.trim()),
+        };
+        Ok(Self { url: url.to_string(), authorization, done_transition: config.done_transition.clone() })
+    }
+
+    /// Sends a request and parses the JSON response, if there is one
+    fn request(&self, method: &str, path: &str, body: Option<&Value>) -> Result<Value> {
+        let headers = format!(
+            "Authorization: {}\nAccept: application/json\nContent-Type: application/json\nUser-Agent: todo-cli\n",
+            self.authorization
+        );
+        let output = request_json(method, &format!("{}{}", self.url, path), &headers, body)?;
+
+        let response = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() {
+            let message = serde_json::from_str::<Value>(&response)
+                .ok()
+                .and_then(|v| {
+                    let messages = v["errorMessages"].as_array()?;
+                    let messages: Vec<&str> = messages.iter().filter_map(Value::as_str).collect();
+                    (!messages.is_empty()).then(|| messages.join("; "))
+                })
+                .unwrap_or_else(|| curl_error(&output));
+            return Err(anyhow!("Jira {} {} failed: {}", method, path, message));
+        }
+        if response.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(&response).with_context(|| format!("Unexpected response from Jira {}", path))
+    }
+
+    /// Returns the address of the issue's page
+    pub fn browse_url(&self, key: &str) -> String {
+        format!("{}/browse/{}", self.url, key)
+    }
+
+    /// Lists the unresolved issues assigned to the token's owner
+    pub fn assigned_issues(&self) -> Result<Vec<Issue>> {
+        const PAGE: usize = 100;
+        let jql = "assignee%20%3D%20currentUser()%20AND%20resolution%20%3D%20Unresolved%20ORDER%20BY%20key";
+        let mut issues = Vec::new();
+        loop {
+            let path = format!(
+                "/rest/api/2/search?jql={}&fields=summary,priority,duedate,labels&startAt={}&maxResults={}",
+                jql,
+                issues.len(),
+                PAGE
+            );
+            let page = self.request("GET", &path, None)?;
+            let items = page["issues"].as_array().ok_or_else(|| anyhow!("Unexpected response from Jira {}", path))?;
+            issues.extend(items.iter().filter_map(|item| self.parse_issue(item)));
+            let total = page["total"].as_u64().unwrap_or(0) as usize;
+            if items.len() < PAGE || issues.len() >= total {
+                break;
+            }
+        }
+        Ok(issues)
+    }
+
+    fn parse_issue(&self, item: &Value) -> Option<Issue> {
+        let key = item["key"].as_str()?;
+        let fields = &item["fields"];
+        Some(Issue {
+            key: key.to_string(),
+            summary: fields["summary"].as_str()?.to_string(),
+            url: self.browse_url(key),
+            priority: fields["priority"]["name"].as_str().map(str::to_string),
+            due_date: fields["duedate"].as_str().and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()),
+            labels: fields["labels"]
+                .as_array()
+                .map(|labels| labels.iter().filter_map(|l| l.as_str().map(str::to_string)).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Moves the issue into a done status, returning the name of the status it ends up in
+    ///
+    /// Uses the transition named by `jira.done_transition`, or else the first
+    /// one available whose target status is in Jira's done category.
+    pub fn transition_to_done(&self, key: &str) -> Result<String> {
+        check_key(key)?;
+        let path = format!("/rest/api/2/issue/{}/transitions", key);
+        let response = self.request("GET", &path, None)?;
+        let transitions = response["transitions"].as_array().map(Vec::as_slice).unwrap_or_default();
+        let transition = match &self.done_transition {
+            Some(name) => transitions.iter().find(|t| t["name"].as_str().is_some_and(|n| n.eq_ignore_ascii_case(name))),
+            None => transitions.iter().find(|t| t["to"]["statusCategory"]["key"].as_str() == Some("done")),
+        };
+        let transition = transition.ok_or_else(|| match &self.done_transition {
+            Some(name) => anyhow!("{} has no transition named '{}'", key, name),
+            None => anyhow!("{} has no transition to a done status; set jira.done_transition", key),
+        })?;
+        let id = transition["id"].as_str().ok_or_else(|| anyhow!("Unexpected response from Jira {}", path))?;
+
+        let body = serde_json::json!({ "transition": { "id": id } });
+        self.request("POST", &path, Some(&body))?;
+        Ok(transition["to"]["name"].as_str().or(transition["name"].as_str()).unwrap_or("Done").to_string())
+    }
+}
+
+fn check_key(key: &str) -> Result<()> {
+    let valid = key.split_once('-').is_some_and(|(project, number)| {
+        !project.is_empty()
+            && project.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit())
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid Jira issue key '{}': use PROJECT-123", key))
+    }
+}
+
+/// Encodes bytes as standard, padded base64, for basic authentication
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
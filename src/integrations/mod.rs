@@ -0,0 +1,63 @@
+//! Connections to outside services, each behind its own cargo feature
+//!
+//! Services are reached through the `curl` program, the way `sync` drives
+//! `git`, so no feature needs extra crates. [`curl`] runs it for all of
+//! them; tokens and passwords go to it on stdin, as headers or a config,
+//! so they never show up in the process list.
+
+#[cfg(feature = "email")]
+pub mod email;
+#[cfg(feature = "feeds")]
+pub mod feeds;
+#[cfg(feature = "github")]
+pub mod github;
+#[cfg(feature = "jira")]
+pub mod jira;
+#[cfg(feature = "macos-integration")]
+pub mod reminders;
+#[cfg(feature = "share")]
+pub mod share;
+#[cfg(feature = "webhooks")]
+pub mod webhooks;
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Runs `curl --silent --show-error` with `args`, writing `input` to its stdin
+///
+/// Only fails if curl can't be run; check the output's status for the
+/// request itself, and [`curl_error`] for why it failed.
+pub(crate) fn curl(args: &[&str], input: &[u8]) -> Result<Output> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| "Failed to run curl; is it installed?")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+    Ok(child.wait_with_output()?)
+}
+
+/// Returns curl's own message about a failed request
+pub(crate) fn curl_error(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).trim().to_string()
+}
+
+/// Sends a request to a JSON API with `headers`, one `Name: value` per line
+///
+/// A failed request keeps the body of the error response, which APIs use
+/// to say what went wrong.
+#[cfg(any(feature = "github", feature = "jira"))]
+pub(crate) fn request_json(method: &str, url: &str, headers: &str, body: Option<&serde_json::Value>) -> Result<Output> {
+    let body = body.map(|body| body.to_string());
+    let mut args = vec!["--fail-with-body", "--location", "--request", method, "--header", "@-", url];
+    if let Some(body) = &body {
+        args.extend(["--data", body]);
+    }
+    curl(&args, headers.as_bytes())
+}
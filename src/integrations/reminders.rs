@@ -0,0 +1,104 @@
+//! Apple Reminders, through AppleScript
+//!
+//! `export --format reminders` writes a script that `osascript` runs to
+//! create one reminder per todo.
+//! Todos with a project go into a Reminders list of the same name, made
+//! if it's missing; the rest go into the default list.
+
+use crate::models::{Priority, Status, Todo};
+use chrono::{Datelike, NaiveDate};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// Returns the Reminders priority for a todo: 1 high, 5 medium, 9 low
+///
+/// Levels above the scale's default are high, the default is medium, and
+/// levels below it are low.
+pub fn reminders_priority(priority: Priority) -> u8 {
+    match priority.cmp(&Priority::default()) {
+        Ordering::Greater => 1,
+        Ordering::Equal => 5,
+        Ordering::Less => 9,
+    }
+}
+
+/// Quotes `text` as an AppleScript string literal
+fn quote(text: &str) -> String {
+    let escaped = text.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", escaped)
+}
+
+/// Builds a date without going through the locale's date format
+///
+/// The day is set to 1 first so a short month can't overflow on the way.
+fn date_lines(date: NaiveDate, indent: &str) -> String {
+    [
+        "set dueDate to current date".to_string(),
+        "set day of dueDate to 1".to_string(),
+        format!("set year of dueDate to {}", date.year()),
+        format!("set month of dueDate to {}", date.month()),
+        format!("set day of dueDate to {}", date.day()),
+        "set time of dueDate to 9 * hours".to_string(),
+    ]
+    .iter()
+    .map(|line| format!("{}{}\n", indent, line))
+    .collect()
+}
+
+/// Returns the `make new reminder` statement for a todo, with any date setup before it
+fn reminder(todo: &Todo, indent: &str) -> String {
+    let mut properties = vec![format!("name:{}", quote(&todo.title))];
+    let mut body: Vec<String> = todo.notes.iter().cloned().collect();
+    if !todo.tags.is_empty() {
+        body.push(todo.tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" "));
+    }
+    if !body.is_empty() {
+        properties.push(format!("body:{}", quote(&body.join("\n\n"))));
+    }
+    properties.push(format!("priority:{}", reminders_priority(todo.priority)));
+    if todo.status == Status::Done {
+        properties.push("completed:true".to_string());
+    }
+
+    let mut script = String::new();
+    if let Some(due) = todo.due_date {
+        script.push_str(&date_lines(due, indent));
+        properties.push("due date:dueDate".to_string());
+    }
+    script.push_str(&format!("{}make new reminder with properties {{{}}}\n", indent, properties.join(", ")));
+    script
+}
+
+/// Returns an AppleScript that adds `todos` to Apple Reminders
+///
+/// Run it with `osascript`; due dates are set for 9:00 local time.
+pub fn to_reminders_script(todos: &[Todo]) -> String {
+    let mut by_list: BTreeMap<Option<&str>, Vec<&Todo>> = BTreeMap::new();
+    for todo in todos {
+        by_list.entry(todo.project.as_deref()).or_default().push(todo);
+    }
+
+    let mut script = String::from("tell application \"Reminders\"\n");
+    for (list, todos) in by_list {
+        match list {
+            None => {
+                for todo in todos {
+                    script.push_str(&reminder(todo, "    "));
+                }
+            }
+            Some(name) => {
+                let name = quote(name);
+                script.push_str(&format!("    if not (exists list {}) then\n", name));
+                script.push_str(&format!("        make new list with properties {{name:{}}}\n", name));
+                script.push_str("    end if\n");
+                script.push_str(&format!("    tell list {}\n", name));
+                for todo in todos {
+                    script.push_str(&reminder(todo, "        "));
+                }
+                script.push_str("    end tell\n");
+            }
+        }
+    }
+    script.push_str("end tell\n");
+    script
+}
@@ -0,0 +1,95 @@
+//! Sharing todos through a link
+//!
+//! `share` uploads a Markdown checklist of the chosen todos, which carries
+//! titles, due dates, tags, and projects but never notes, comments, or
+//! attachments. It goes to a secret GitHub gist, using the token `github`
+//! commands use, or to a paste service such as 0x0.st. Either way anyone
+//! with the link can read it. `import --from-url` downloads one again.
+//! Requests go through `curl`, as for the other integrations.
+
+use super::{curl, curl_error};
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::process::Output;
+
+/// Largest download `import --from-url` accepts, in bytes
+const MAX_DOWNLOAD: u64 = 5 * 1024 * 1024;
+
+/// Where shared todos are uploaded
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareService {
+    /// A secret GitHub gist, which needs a token with the gist scope
+    #[default]
+    Gist,
+    /// An anonymous paste at `share.paste_url`
+    Paste,
+}
+
+/// Settings for `share`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShareConfig {
+    /// Service used when `share` isn't given `--to`
+    pub to: ShareService,
+    /// Paste service that takes a file upload and answers with its URL, 0x0.st style
+    pub paste_url: String,
+}
+
+impl Default for ShareConfig {
+    fn default() -> Self {
+        Self { to: ShareService::Gist, paste_url: "https://0x0.st".to_string() }
+    }
+}
+
+/// Runs curl for an upload or download, giving up after 30 seconds
+///
+/// Redirects are followed, but only to http and https URLs, so a server
+/// can't send curl on to `file://` or another protocol.
+fn transfer(args: &[&str], input: &str) -> Result<Output> {
+    let options = ["--fail", "--location", "--proto", "=http,https", "--proto-redir", "=http,https", "--max-time", "30"];
+    let args = [&options[..], args].concat();
+    curl(&args, input.as_bytes())
+}
+
+/// Refuses anything but http and https URLs, so curl never reads local files
+fn check_url(url: &str) -> Result<()> {
+    if url.starts_with("https://") || url.starts_with("http://") {
+        Ok(())
+    } else {
+        Err(anyhow!("Not an http or https URL: {}", url))
+    }
+}
+
+/// Uploads `content` as a file to a paste service, returning the URL it answers with
+pub fn upload_paste(paste_url: &str, filename: &str, content: &str) -> Result<String> {
+    check_url(paste_url)?;
+    let form = format!("file=@-;filename={}", filename);
+    let output = transfer(&["--user-agent", "todo-cli", "--form", &form, paste_url], content)?;
+    if !output.status.success() {
+        return Err(anyhow!("Upload to {} failed: {}", paste_url, curl_error(&output)));
+    }
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !url.starts_with("http") {
+        return Err(anyhow!("Unexpected response from {}: {}", paste_url, url));
+    }
+    Ok(url)
+}
+
+/// Downloads the text at `url`, up to [`MAX_DOWNLOAD`] bytes
+///
+/// Calendar subscription links (`webcal://`) are fetched over https.
+pub fn fetch(url: &str) -> Result<String> {
+    let url = match url.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => url.to_string(),
+    };
+    check_url(&url)?;
+    let max = MAX_DOWNLOAD.to_string();
+    let output = transfer(&["--max-filesize", &max, &url], "")?;
+    if !output.status.success() {
+        return Err(anyhow!("Download from {} failed: {}", url, curl_error(&output)));
+    }
+    String::from_utf8(output.stdout).map_err(|_| anyhow!("{} is not text", url))
+}
@@ -0,0 +1,116 @@
+//! HTTP webhooks for added, completed, and overdue todos
+//!
+//! Each webhook in the config names a URL and the events it wants, e.g.
+//! `{"webhooks": [{"url": "https://hooks.slack.com/...", "events": ["completed"]}]}`;
+//! with no events it gets them all. Posts go out through `curl`, the way
+//! the GitHub integration talks to its API. A webhook that fails is
+//! reported on stderr and never fails the command that changed the todo.
+
+use crate::events::{Event, EventKind};
+use crate::models::Todo;
+use crate::notify::post_json;
+use crate::t;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// What a webhook can be told about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookEvent {
+    Added,
+    Completed,
+    /// The todo's due date passed; checked by `notify` and the daemon
+    Overdue,
+}
+
+/// A URL and the events posted to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    /// Events to post; all of them when empty
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+impl Webhook {
+    /// Returns whether this webhook wants `event`
+    pub fn wants(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// The JSON body of a post
+///
+/// `text` is a one-line summary, which is what Slack-style incoming
+/// webhooks show.
+#[derive(Debug, Serialize)]
+pub struct Payload<'a> {
+    pub event: WebhookEvent,
+    pub timestamp: DateTime<Local>,
+    pub text: String,
+    pub todo: &'a Todo,
+}
+
+impl<'a> Payload<'a> {
+    pub fn new(event: WebhookEvent, todo: &'a Todo, timestamp: DateTime<Local>) -> Self {
+        let text = match event {
+            WebhookEvent::Added => format!("Added todo #{}: {}", todo.id, todo.title),
+            WebhookEvent::Completed => format!("Completed todo #{}: {}", todo.id, todo.title),
+            WebhookEvent::Overdue => format!("Todo #{} is overdue: {}", todo.id, todo.title),
+        };
+        Self { event, timestamp, text, todo }
+    }
+}
+
+/// Posts `event` for `todo` to each webhook that wants it
+pub fn deliver(hooks: &[Webhook], event: WebhookEvent, todo: &Todo, now: DateTime<Local>) {
+    let hooks: Vec<&Webhook> = hooks.iter().filter(|h| h.wants(event)).collect();
+    if hooks.is_empty() {
+        return;
+    }
+
+    let body = match serde_json::to_string(&Payload::new(event, todo, now)) {
+        Ok(body) => body,
+        Err(err) => return eprintln!("{}", t!("webhook.serialize_failed", error = err)),
+    };
+    for hook in hooks {
+        if let Err(err) = post_json(&hook.url, &body) {
+            eprintln!("{}", t!("webhook.failed", url = hook.url, error = err));
+        }
+    }
+}
+
+/// Posts the added and completed todos among `events`
+pub fn deliver_events(hooks: &[Webhook], events: &[Event]) {
+    for event in events {
+        let kind = match event.kind {
+            EventKind::Added => WebhookEvent::Added,
+            EventKind::Completed => WebhookEvent::Completed,
+            EventKind::Edited | EventKind::Removed => continue,
+        };
+        if let Some(todo) = &event.after {
+            deliver(hooks, kind, todo, event.timestamp);
+        }
+    }
+}
+
+/// Returns the incomplete todos that became overdue in `(from, to]`
+///
+/// A todo is overdue from the end of its due day, see [`Todo::overdue_from`].
+pub fn overdue_between(todos: &[Todo], from: DateTime<Local>, to: DateTime<Local>) -> Vec<&Todo> {
+    todos
+        .iter()
+        .filter(|t| !t.completed)
+        .filter(|t| t.overdue_from().is_some_and(|at| at > from && at <= to))
+        .collect()
+}
+
+/// Posts the todos that became overdue in `(from, to]`
+pub fn deliver_overdue(hooks: &[Webhook], todos: &[Todo], from: DateTime<Local>, to: DateTime<Local>) {
+    if !hooks.iter().any(|h| h.wants(WebhookEvent::Overdue)) {
+        return;
+    }
+    for todo in overdue_between(todos, from, to) {
+        deliver(hooks, WebhookEvent::Overdue, todo, to);
+    }
+}
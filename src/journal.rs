@@ -0,0 +1,82 @@
+//! Append-only log of operations performed on todos
+
+use crate::error::TodoError;
+use crate::models::Todo;
+use crate::timestamp;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Kind of operation recorded in the journal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    /// A todo was created
+    Added,
+    /// A todo was marked as done
+    Completed,
+    /// A todo was removed
+    Removed,
+    /// A todo was changed to match its source, e.g. by `feed sync`
+    Updated,
+    /// A todo was moved to the archive list
+    Archived,
+}
+
+/// A single journal line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    #[serde(serialize_with = "timestamp::serialize")]
+    pub timestamp: DateTime<Local>,
+    pub operation: Operation,
+    pub id: u64,
+    pub uuid: String,
+    pub title: String,
+}
+
+impl JournalEntry {
+    /// Creates an entry for the given operation on a todo, timestamped now
+    pub fn new(operation: Operation, todo: &Todo) -> Self {
+        Self {
+            timestamp: Local::now(),
+            operation,
+            id: todo.id,
+            uuid: todo.uuid.clone(),
+            title: todo.title.clone(),
+        }
+    }
+}
+
+/// Appends an entry to the journal file at `path`
+pub fn append_to(path: &Path, entry: &JournalEntry) -> Result<()> {
+    let line = serde_json::to_string(entry)
+        .with_context(|| "Failed to serialize journal entry")?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| TodoError::storage("open", path, e))?;
+
+    writeln!(file, "{}", line).map_err(|e| TodoError::storage("write to", path, e))?;
+
+    Ok(())
+}
+
+/// Reads all entries from the journal file at `path`, skipping lines that cannot be parsed
+pub fn read_from(path: &Path) -> Result<Vec<JournalEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| TodoError::storage("read", path, e))?;
+
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
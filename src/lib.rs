@@ -5,6 +5,39 @@ pub mod storage;
 pub mod commands;
 pub mod display;
 pub mod cli;
+pub mod app;
+pub mod aliases;
+pub mod archive;
+pub mod backup;
+pub mod calendar;
+pub mod filter;
+pub mod formats;
+pub mod integrations;
+pub mod query;
+pub mod templates;
+pub mod terminal;
+pub mod timeline;
+pub mod tokens;
+pub mod width;
+pub mod journal;
+pub mod deps;
+pub mod config;
+pub mod duration;
+pub mod error;
+pub mod escalate;
+pub mod events;
+pub mod history;
+pub mod hooks;
+pub mod i18n;
+pub mod msgpack;
+pub mod notify;
+pub mod output;
+pub mod plugins;
+pub mod staging;
+pub mod sync;
+pub mod timestamp;
+pub mod urgency;
+pub mod validate;
 
 // Re-exports for easier access to commonly used items
 pub use models::*;
@@ -1,30 +1,182 @@
-use chrono::{DateTime, Local, NaiveDate};
+use crate::calendar;
+use crate::duration::HumanDuration;
+use crate::timestamp;
+use crate::error::TodoError;
+use crate::validate;
+use chrono::{DateTime, Datelike, Local, NaiveDate};
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, str::FromStr};
+use std::{
+    cmp::Ordering,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
+    sync::OnceLock,
+};
+
+/// One level of the priority scale, e.g. `{"name": "urgent", "label": "URGENT", "color": "red bold"}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriorityLevel {
+    /// Name stored in the database and accepted on the command line (case-insensitive)
+    pub name: String,
+    /// How the level is shown in tables; defaults to the name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Colors and styles for the label, e.g. `"yellow"` or `"red bold"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl PriorityLevel {
+    fn new(name: &str, label: Option<&str>, color: Option<&str>) -> Self {
+        Self { name: name.to_string(), label: label.map(str::to_string), color: color.map(str::to_string) }
+    }
+}
+
+/// The priority levels in use, lowest first, and the one new todos get
+///
+/// Defaults to Low, Normal, High, and Urgent, with Normal for new todos.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawPriorityScale", into = "RawPriorityScale")]
+pub struct PriorityScale {
+    levels: Vec<PriorityLevel>,
+    default: u8,
+}
+
+/// The config file form of a scale, naming the default level
+#[derive(Serialize, Deserialize)]
+struct RawPriorityScale {
+    levels: Vec<PriorityLevel>,
+    default: String,
+}
+
+impl TryFrom<RawPriorityScale> for PriorityScale {
+    type Error = String;
+
+    fn try_from(raw: RawPriorityScale) -> Result<Self, Self::Error> {
+        if raw.levels.is_empty() || raw.levels.len() > u8::MAX as usize {
+            return Err(format!("A priority scale needs 1 to {} levels", u8::MAX));
+        }
+        for (i, level) in raw.levels.iter().enumerate() {
+            if level.name.trim().is_empty() || level.name.contains(char::is_whitespace) {
+                return Err(format!("Invalid priority name '{}': use a single word", level.name));
+            }
+            if raw.levels[..i].iter().any(|l| l.name.eq_ignore_ascii_case(&level.name)) {
+                return Err(format!("Priority '{}' is defined twice", level.name));
+            }
+            if let Some(color) = &level.color {
+                crate::display::check_style(color)?;
+            }
+        }
+        let default = raw
+            .levels
+            .iter()
+            .position(|l| l.name.eq_ignore_ascii_case(&raw.default))
+            .ok_or_else(|| format!("Default priority '{}' is not one of the levels", raw.default))?;
+        Ok(Self { levels: raw.levels, default: default as u8 })
+    }
+}
+
+impl From<PriorityScale> for RawPriorityScale {
+    fn from(scale: PriorityScale) -> Self {
+        let default = scale.levels[scale.default as usize].name.clone();
+        Self { levels: scale.levels, default }
+    }
+}
+
+impl Default for PriorityScale {
+    fn default() -> Self {
+        Self {
+            levels: vec![
+                PriorityLevel::new("Low", None, Some("dimmed")),
+                PriorityLevel::new("Normal", None, None),
+                PriorityLevel::new("High", None, Some("yellow")),
+                PriorityLevel::new("Urgent", Some("URGENT"), Some("red bold")),
+            ],
+            default: 1,
+        }
+    }
+}
+
+impl PriorityScale {
+    /// Returns the levels, lowest first
+    pub fn levels(&self) -> &[PriorityLevel] {
+        &self.levels
+    }
+
+    /// Returns every priority in the scale, lowest first
+    pub fn priorities(&self) -> impl Iterator<Item = Priority> {
+        (0..self.levels.len() as u8).map(Priority)
+    }
+}
+
+static PRIORITY_SCALE: OnceLock<PriorityScale> = OnceLock::new();
+
+/// Sets the priority levels for the rest of the process
+///
+/// Only the first call has an effect, and only before any priority is
+/// used; until then the default scale applies.
+pub fn set_priority_scale(scale: PriorityScale) {
+    let _ = PRIORITY_SCALE.set(scale);
+}
+
+/// Returns the priority levels in use
+pub fn priority_scale() -> &'static PriorityScale {
+    PRIORITY_SCALE.get_or_init(PriorityScale::default)
+}
 
 /// Represents the priority level of a todo item
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default, ValueEnum)]
-pub enum Priority {
-    /// Low priority task
-    Low,
-    /// Normal priority task (default)
-    #[default]
-    Normal,
-    /// High priority task
-    High,
-    /// Urgent priority task
-    Urgent,
+///
+/// A position in the [`PriorityScale`], so priorities compare by rank. They
+/// are stored and parsed by level name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Priority(u8);
+
+impl Priority {
+    /// Returns the lowest priority
+    pub fn lowest() -> Self {
+        Priority(0)
+    }
+
+    /// Returns the highest priority
+    pub fn highest() -> Self {
+        Priority(priority_scale().levels.len() as u8 - 1)
+    }
+
+    /// Returns the next priority up, if this isn't the highest
+    pub fn raised(self) -> Option<Self> {
+        (self < Self::highest()).then_some(Priority(self.0 + 1))
+    }
+
+    /// Returns the position in the scale, 0 being the lowest
+    pub fn rank(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Returns this priority's level in the scale
+    pub fn level(self) -> &'static PriorityLevel {
+        let levels = &priority_scale().levels;
+        &levels[self.rank().min(levels.len() - 1)]
+    }
+
+    /// Returns the name the priority is stored under
+    pub fn name(self) -> &'static str {
+        &self.level().name
+    }
+}
+
+impl Default for Priority {
+    /// The level new todos get
+    fn default() -> Self {
+        Priority(priority_scale().default)
+    }
 }
 
 impl std::fmt::Display for Priority {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Priority::Low => write!(f, "Low"),
-            Priority::Normal => write!(f, "Normal"),
-            Priority::High => write!(f, "High"),
-            Priority::Urgent => write!(f, "URGENT"),
-        }
+        let level = self.level();
+        f.write_str(level.label.as_deref().unwrap_or(&level.name))
     }
 }
 
@@ -32,12 +184,75 @@ impl FromStr for Priority {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "low" => Ok(Priority::Low),
-            "normal" => Ok(Priority::Normal),
-            "high" => Ok(Priority::High),
-            "urgent" => Ok(Priority::Urgent),
-            _ => Err(format!("Invalid priority: {}", s)),
+        let scale = priority_scale();
+        match scale.levels.iter().position(|l| l.name.eq_ignore_ascii_case(s.trim())) {
+            Some(rank) => Ok(Priority(rank as u8)),
+            None => {
+                let names: Vec<String> = scale.levels.iter().map(|l| l.name.to_lowercase()).collect();
+                Err(format!("Invalid priority: {} (expected one of {})", s, names.join(", ")))
+            }
+        }
+    }
+}
+
+impl Serialize for Priority {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Priority {
+    /// Reads a level by name; names the scale doesn't have, e.g. after
+    /// switching to a custom scale, read as the default level
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(name.parse().unwrap_or_default())
+    }
+}
+
+/// Where a todo is in its workflow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// Not started yet
+    #[default]
+    Todo,
+    /// Being worked on
+    InProgress,
+    /// Stalled on someone or something else
+    Waiting,
+    /// Finished
+    Done,
+    /// Dropped without being finished
+    Cancelled,
+}
+
+impl Status {
+    /// Returns true for done and cancelled todos, which count as completed
+    pub fn is_closed(self) -> bool {
+        matches!(self, Status::Done | Status::Cancelled)
+    }
+
+    /// Position in the smart sort: in progress first, then to do, waiting, done, and cancelled
+    pub fn rank(self) -> u8 {
+        match self {
+            Status::InProgress => 0,
+            Status::Todo => 1,
+            Status::Waiting => 2,
+            Status::Done => 3,
+            Status::Cancelled => 4,
+        }
+    }
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Todo => write!(f, "Backlog"),
+            Status::InProgress => write!(f, "In Progress"),
+            Status::Waiting => write!(f, "Waiting"),
+            Status::Done => write!(f, "Done"),
+            Status::Cancelled => write!(f, "Cancelled"),
         }
     }
 }
@@ -47,35 +262,735 @@ impl FromStr for Priority {
 pub enum DateInput {
     Today,
     Tomorrow,
+    /// First day of next week
+    NextWeek,
+    /// Last day of this week
+    EndOfWeek,
+    /// First day of next month
+    NextMonth,
+    /// This many days from today
+    InDays(i64),
     Date(NaiveDate),
 }
 
+impl DateInput {
+    /// Resolves the input to a calendar date relative to the local day
+    pub fn to_date(self) -> NaiveDate {
+        let today = Local::now().date_naive();
+        match self {
+            DateInput::Today => today,
+            DateInput::Tomorrow => today + chrono::Duration::days(1),
+            DateInput::NextWeek => calendar::next_week(today),
+            DateInput::EndOfWeek => calendar::end_of_week(today),
+            DateInput::NextMonth => {
+                let (year, month) = match today.month() {
+                    12 => (today.year() + 1, 1),
+                    month => (today.year(), month + 1),
+                };
+                NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(today)
+            }
+            // Offsets past the last date there is stop at it
+            DateInput::InDays(days) => chrono::Duration::try_days(days)
+                .and_then(|offset| today.checked_add_signed(offset))
+                .unwrap_or(if days < 0 { NaiveDate::MIN } else { NaiveDate::MAX }),
+            DateInput::Date(date) => date,
+        }
+    }
+}
+
+/// A span of time spent working on a todo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeEntry {
+    #[serde(serialize_with = "timestamp::serialize")]
+    pub start: DateTime<Local>,
+    /// When work stopped; `None` while the timer is running
+    #[serde(default, serialize_with = "timestamp::option::serialize")]
+    pub end: Option<DateTime<Local>>,
+}
+
+impl TimeEntry {
+    /// Returns the time spent, counting a running entry up to `now`
+    pub fn duration_at(&self, now: DateTime<Local>) -> chrono::Duration {
+        self.end.unwrap_or(now) - self.start
+    }
+}
+
+/// What an attachment points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentKind {
+    /// A web page or other URL
+    Link,
+    /// A file or directory on this machine
+    File,
+}
+
+impl std::fmt::Display for AttachmentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttachmentKind::Link => write!(f, "link"),
+            AttachmentKind::File => write!(f, "file"),
+        }
+    }
+}
+
+/// A URL or path attached to a todo
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    pub kind: AttachmentKind,
+    /// The URL, or the file's path
+    pub target: String,
+}
+
+impl Attachment {
+    /// Tells links from paths: anything with a `scheme://`, `mailto:` or
+    /// `tel:` is a link, as is a bare `www.` address, which gets `https://`
+    pub fn detect(target: &str) -> Self {
+        let target = target.trim();
+        let scheme = target.split_once("://").is_some_and(|(scheme, _)| {
+            scheme.len() > 1 && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+        });
+        if scheme || target.starts_with("mailto:") || target.starts_with("tel:") {
+            Self { kind: AttachmentKind::Link, target: target.to_string() }
+        } else if target.starts_with("www.") {
+            Self { kind: AttachmentKind::Link, target: format!("https://{}", target) }
+        } else {
+            Self { kind: AttachmentKind::File, target: target.to_string() }
+        }
+    }
+
+    /// Finds the web links in free text, in order and without repeats
+    ///
+    /// Only `http://`, `https://`, and `www.` words count; surrounding
+    /// brackets, quotes, and trailing punctuation are dropped.
+    pub fn links_in(text: &str) -> Vec<Self> {
+        let mut links: Vec<Self> = Vec::new();
+        for word in text.split_whitespace() {
+            let word = word
+                .trim_start_matches(['<', '(', '[', '"', '\''])
+                .trim_end_matches(['>', ')', ']', '"', '\'', '.', ',', ';', ':', '!', '?']);
+            if !["http://", "https://", "www."].iter().any(|prefix| word.starts_with(prefix)) {
+                continue;
+            }
+            let link = Self::detect(word);
+            if !links.contains(&link) {
+                links.push(link);
+            }
+        }
+        links
+    }
+}
+
+/// A timestamped remark added with `comment`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+    #[serde(serialize_with = "timestamp::serialize")]
+    pub at: DateTime<Local>,
+    pub text: String,
+}
+
+/// A step on a todo's checklist, added with `check add`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// The system an [`ExternalRef`] points into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalKind {
+    /// A GitHub issue, from `github pull`
+    Github,
+    /// A Jira issue, from `jira pull`
+    Jira,
+    /// A Trello card, from `import --format trello`
+    Trello,
+    /// A Microsoft To Do task, from `import --format mstodo`
+    Mstodo,
+    /// A calendar event or to-do, by its `UID`, from `import --format ics`
+    Calendar,
+    /// An item of a JSON Feed, from `feed sync`
+    Feed,
+    /// Any other web page
+    Link,
+}
+
+impl std::fmt::Display for ExternalKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExternalKind::Github => write!(f, "GitHub"),
+            ExternalKind::Jira => write!(f, "Jira"),
+            ExternalKind::Trello => write!(f, "Trello"),
+            ExternalKind::Mstodo => write!(f, "Microsoft To Do"),
+            ExternalKind::Calendar => write!(f, "calendar"),
+            ExternalKind::Feed => write!(f, "feed"),
+            ExternalKind::Link => write!(f, "link"),
+        }
+    }
+}
+
+/// The item in another system a todo was imported or pulled from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalRef {
+    pub kind: ExternalKind,
+    /// The item's ID there, e.g. `owner/name#12` for GitHub or `PROJ-12` for Jira
+    pub id: String,
+    /// The item's page, which `open` goes to
+    #[serde(default)]
+    pub url: Option<String>,
+    /// URL of the feed `feed sync` keeps the todo in step with, for todos from one
+    #[serde(default)]
+    pub feed: Option<String>,
+}
+
+/// Returns true for an `http` or `https` URL, the only pages `open` goes to from a reference
+///
+/// References come from files anyone could have written, such as a
+/// shared calendar, so other schemes like `file:` are never opened.
+pub fn is_web_url(url: &str) -> bool {
+    let scheme = url.split_once("://").map(|(scheme, _)| scheme.to_ascii_lowercase());
+    matches!(scheme.as_deref(), Some("http" | "https")) && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+impl ExternalRef {
+    /// Refers to item `id` of `kind`, at `url` when it has a web page
+    ///
+    /// URLs that aren't [`is_web_url`] are dropped.
+    pub fn new(kind: ExternalKind, id: impl Into<String>, url: Option<String>) -> Self {
+        Self { kind, id: id.into(), url: url.filter(|url| is_web_url(url)), feed: None }
+    }
+
+    /// Works out what a page's URL refers to, as kept by `issue_url` before references had kinds
+    ///
+    /// GitHub issue pages and Jira `/browse/KEY` pages are recognized;
+    /// anything else is a plain link with the URL as its ID.
+    pub fn from_url(url: &str) -> Self {
+        let url = url.trim();
+        let path = url.trim_end_matches('/');
+        if let Some(path) = path.strip_prefix("https://github.com/") {
+            if let [owner, name, "issues", number] = path.split('/').collect::<Vec<_>>()[..] {
+                if number.parse::<u64>().is_ok() {
+                    return Self::new(ExternalKind::Github, format!("{}/{}#{}", owner, name, number), Some(url.to_string()));
+                }
+            }
+        }
+        if let Some((_, key)) = path.rsplit_once("/browse/") {
+            let is_key = key.split_once('-').is_some_and(|(project, number)| {
+                !project.is_empty()
+                    && project.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    && !number.is_empty()
+                    && number.chars().all(|c| c.is_ascii_digit())
+            });
+            if is_key {
+                return Self::new(ExternalKind::Jira, key, Some(url.to_string()));
+            }
+        }
+        Self::new(ExternalKind::Link, url, Some(url.to_string()))
+    }
+
+    /// Reads a reference, or the bare URL files from before references had kinds kept in `issue_url`
+    fn deserialize_stored<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Self>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Ref(ExternalRef),
+            Url(String),
+        }
+
+        Ok(Option::<Stored>::deserialize(deserializer)?.map(|stored| match stored {
+            Stored::Ref(external) => external,
+            Stored::Url(url) => Self::from_url(&url),
+        }))
+    }
+}
+
+/// A removed todo kept until the trash is emptied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedTodo {
+    #[serde(serialize_with = "timestamp::serialize")]
+    pub deleted_at: DateTime<Local>,
+    pub todo: Todo,
+}
+
 /// Represents a todo item
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Todo {
+    /// Display index; may be reused after removals
     pub id: u64,
+    /// Stable identifier that is never reused
+    #[serde(default)]
+    pub uuid: String,
     pub title: String,
     pub completed: bool,
+    /// Workflow stage; done or cancelled exactly when `completed` is set
+    #[serde(default)]
+    pub status: Status,
+    /// When the todo was added; this and the other timestamps are stored in UTC
+    #[serde(serialize_with = "timestamp::serialize")]
     pub created_at: DateTime<Local>,
+    /// When the todo was marked as done or cancelled
+    #[serde(default, serialize_with = "timestamp::option::serialize")]
+    pub completed_at: Option<DateTime<Local>>,
+    /// When a save last changed the todo; unset in files from before this was kept
+    #[serde(default, serialize_with = "timestamp::option::serialize")]
+    pub modified_at: Option<DateTime<Local>>,
+    /// Who made that change, as set by the store's actor; unset when it wasn't known
+    #[serde(default)]
+    pub modified_by: Option<String>,
+    /// When `review` last went over the todo
+    #[serde(default)]
+    pub last_reviewed: Option<NaiveDate>,
+    /// When work on the todo is planned to begin
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
     pub due_date: Option<NaiveDate>,
     #[serde(default)]
     pub priority: Priority,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Reminder offsets before the due time; empty means use the configured defaults
+    #[serde(default)]
+    pub reminders: Vec<HumanDuration>,
+    /// How long the todo is expected to take
+    #[serde(default)]
+    pub estimate: Option<HumanDuration>,
+    /// Left out of default views until this date
+    #[serde(default)]
+    pub hidden_until: Option<NaiveDate>,
+    /// On the hand-picked list of what to work on, kept with `focus`
+    #[serde(default)]
+    pub focused: bool,
+    /// Kept at the top of every list whatever the sort, set with `pin`
+    #[serde(default)]
+    pub pinned: bool,
+    /// Place in the manual order, set by `move --before` and `--after`; see [`Todo::manual_key`]
+    #[serde(default)]
+    pub sort_key: Option<f64>,
+    /// Not shown before this date, like `hidden_until` but set when adding
+    #[serde(default)]
+    pub scheduled: Option<NaiveDate>,
+    /// Time tracked with `start` and `stop`
+    #[serde(default)]
+    pub time_entries: Vec<TimeEntry>,
+    /// UUIDs of the todos this one waits on
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Project the todo belongs to
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Who the todo is for, on lists shared by several people
+    #[serde(default)]
+    pub assignee: Option<String>,
+    /// Work intervals finished with `pomodoro`
+    #[serde(default)]
+    pub pomodoros_completed: u32,
+    /// Item in another system the todo came from, e.g. an issue from `github pull`
+    #[serde(default, alias = "issue_url", deserialize_with = "ExternalRef::deserialize_stored")]
+    pub external_ref: Option<ExternalRef>,
+    /// Emoji or symbol shown before the title
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Style for the title, e.g. `"green"` or `"blue bold"`
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Links and files added with `attach`
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    /// Progress notes added with `comment`, oldest first; unlike `notes`, only ever added to
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    /// Small steps ticked off with `check done`, in the order added
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    /// How far along the todo is, in percent, set with `progress`; see [`Todo::progress_percent`]
+    #[serde(default)]
+    pub progress: Option<u8>,
 }
 
 impl Todo {
-    /// Creates a new Todo with the given parameters
-    pub fn new(id: u64, title: String, due_date: Option<NaiveDate>, priority: Priority) -> Self {
+    /// Returns when the todo last changed, falling back to when it was
+    /// created or closed for todos saved before changes were tracked
+    pub fn modified(&self) -> DateTime<Local> {
+        self.modified_at
+            .unwrap_or_else(|| self.completed_at.map_or(self.created_at, |at| at.max(self.created_at)))
+    }
+
+    /// Returns the todo's ID for its item in a system of `kind`, if it came from one
+    pub fn external_id(&self, kind: ExternalKind) -> Option<&str> {
+        self.external_ref.as_ref().filter(|external| external.kind == kind).map(|external| external.id.as_str())
+    }
+
+    /// Returns how many checklist items are done and how many there are,
+    /// or `None` without a checklist
+    pub fn checklist_progress(&self) -> Option<(usize, usize)> {
+        if self.checklist.is_empty() {
+            return None;
+        }
+        Some((self.checklist.iter().filter(|item| item.done).count(), self.checklist.len()))
+    }
+
+    /// Returns how far along the todo is, in percent: the share of its
+    /// checklist ticked off when it has one, or else the progress set by hand
+    pub fn progress_percent(&self) -> Option<u8> {
+        match self.checklist_progress() {
+            Some((done, total)) => Some((done * 100 / total) as u8),
+            None => self.progress,
+        }
+    }
+
+    /// Returns true if the todo is open and hasn't changed for `age` as of `now`
+    pub fn is_stale(&self, now: DateTime<Local>, age: chrono::Duration) -> bool {
+        !self.completed && now - self.modified() >= age
+    }
+
+    /// Returns true if the todo is open and the day it was due on ended before `today`
+    ///
+    /// Due dates are calendar days without a time zone, so a todo due on the
+    /// 14th is overdue from local midnight at the end of the 14th, by the
+    /// clock of wherever the user is now; see [`Todo::overdue_from`].
+    pub fn is_overdue_on(&self, today: NaiveDate) -> bool {
+        !self.completed && self.due_date.is_some_and(|due| due < today)
+    }
+
+    /// Returns the moment the todo becomes overdue, the end of its due day in local time
+    pub fn overdue_from(&self) -> Option<DateTime<Local>> {
+        self.due_date.map(calendar::end_of_day)
+    }
+
+    /// Returns the todo's place in the manual order, lowest first
+    ///
+    /// Todos never moved by hand keep the place their ID gives them.
+    pub fn manual_key(&self) -> f64 {
+        self.sort_key.unwrap_or(self.id as f64)
+    }
+
+    /// Returns true if the todo is hidden from default views on `today`,
+    /// by `hide` or because it is scheduled for a later day
+    pub fn is_hidden_on(&self, today: NaiveDate) -> bool {
+        self.hidden_until.is_some_and(|until| until > today) || self.scheduled.is_some_and(|day| day > today)
+    }
+
+    /// Moves the todo to `status`, keeping `completed` and `completed_at` in step
+    pub fn set_status(&mut self, status: Status) {
+        let closed = status.is_closed();
+        if closed && status != self.status {
+            self.completed_at = Some(Local::now());
+        } else if !closed {
+            self.completed_at = None;
+        }
+        self.completed = closed;
+        self.status = status;
+    }
+
+    /// Returns the running time entry, if the timer is on
+    pub fn running_entry(&mut self) -> Option<&mut TimeEntry> {
+        self.time_entries.iter_mut().find(|e| e.end.is_none())
+    }
+}
+
+/// Builds a new `Todo`, validating its fields
+///
+/// Only the title is required:
+///
+/// ```
+/// use todo_cli::models::TodoBuilder;
+///
+/// let todo = TodoBuilder::new("Pay rent")
+///     .priority("high".parse().unwrap())
+///     .tag("home")
+///     .build(1)
+///     .unwrap();
+/// assert_eq!(todo.title, "Pay rent");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TodoBuilder {
+    title: String,
+    start_date: Option<NaiveDate>,
+    due_date: Option<NaiveDate>,
+    scheduled: Option<NaiveDate>,
+    priority: Priority,
+    tags: Vec<String>,
+    notes: Option<String>,
+    reminders: Vec<HumanDuration>,
+    estimate: Option<HumanDuration>,
+    project: Option<String>,
+    assignee: Option<String>,
+    external_ref: Option<ExternalRef>,
+    icon: Option<String>,
+    color: Option<String>,
+    attachments: Vec<Attachment>,
+    checklist: Vec<ChecklistItem>,
+}
+
+impl TodoBuilder {
+    /// Starts a todo with the given title and default priority
+    pub fn new(title: impl Into<String>) -> Self {
         Self {
+            title: title.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Replaces the title
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the due date
+    pub fn due(mut self, due_date: NaiveDate) -> Self {
+        self.due_date = Some(due_date);
+        self
+    }
+
+    /// Sets or clears the due date
+    pub fn due_date(mut self, due_date: Option<NaiveDate>) -> Self {
+        self.due_date = due_date;
+        self
+    }
+
+    /// Sets or clears the planned start date
+    pub fn start_date(mut self, start_date: Option<NaiveDate>) -> Self {
+        self.start_date = start_date;
+        self
+    }
+
+    /// Sets or clears the day the todo is scheduled for
+    pub fn scheduled(mut self, scheduled: Option<NaiveDate>) -> Self {
+        self.scheduled = scheduled;
+        self
+    }
+
+    /// Sets the priority
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Adds a tag
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Adds several tags
+    pub fn tags<I, T>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets the free-form notes
+    pub fn notes(mut self, notes: impl Into<String>) -> Self {
+        self.notes = Some(notes.into());
+        self
+    }
+
+    /// Adds reminder offsets before the due time
+    pub fn reminders(mut self, offsets: impl IntoIterator<Item = HumanDuration>) -> Self {
+        self.reminders.extend(offsets);
+        self
+    }
+
+    /// Sets or clears how long the todo is expected to take
+    pub fn estimate(mut self, estimate: Option<HumanDuration>) -> Self {
+        self.estimate = estimate;
+        self
+    }
+
+    /// Adds items to the end of the checklist
+    pub fn checklist(mut self, items: impl IntoIterator<Item = ChecklistItem>) -> Self {
+        self.checklist.extend(items);
+        self
+    }
+
+    /// Sets or clears the project
+    pub fn project(mut self, project: Option<String>) -> Self {
+        self.project = project;
+        self
+    }
+
+    /// Sets or clears who the todo is for
+    pub fn assignee(mut self, assignee: Option<String>) -> Self {
+        self.assignee = assignee;
+        self
+    }
+
+    /// Links the todo to the item in another system it comes from
+    pub fn external_ref(mut self, external: ExternalRef) -> Self {
+        self.external_ref = Some(external);
+        self
+    }
+
+    /// Sets or clears the icon shown before the title
+    pub fn icon(mut self, icon: Option<String>) -> Self {
+        self.icon = icon;
+        self
+    }
+
+    /// Sets or clears the title style
+    pub fn color(mut self, color: Option<String>) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Adds attachments
+    pub fn attachments(mut self, attachments: impl IntoIterator<Item = Attachment>) -> Self {
+        self.attachments.extend(attachments);
+        self
+    }
+
+    /// Validates the fields and creates the todo with the given ID
+    ///
+    /// Fails if the title is empty or too long, a tag is empty or contains
+    /// whitespace, the start date is after the due date, or the color isn't
+    /// a known style. Titles, tags, notes, the project, the assignee, and
+    /// the icon are cleaned up as described in [`crate::validate`] and
+    /// duplicate tags are dropped.
+    pub fn build(self, id: u64) -> anyhow::Result<Todo> {
+        let title = validate::title(&self.title)?;
+        if let (Some(start), Some(due)) = (self.start_date, self.due_date) {
+            if start > due {
+                let message = format!("Start date {} is after the due date {}", start, due);
+                return Err(TodoError::Validation(message).into());
+            }
+        }
+
+        let mut tags: Vec<String> = Vec::with_capacity(self.tags.len());
+        for tag in self.tags {
+            let tag = validate::tag(&tag)?;
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        let notes = self.notes.as_deref().and_then(validate::notes);
+        let project = self.project.as_deref().and_then(validate::text);
+        let assignee = self.assignee.as_deref().and_then(validate::text);
+        let icon = self.icon.as_deref().and_then(validate::text);
+        let color = self.color.as_deref().and_then(validate::text);
+        let checklist = self
+            .checklist
+            .into_iter()
+            .filter_map(|item| Some(ChecklistItem { text: validate::text(&item.text)?, done: item.done }))
+            .collect();
+        if let Some(color) = &color {
+            crate::display::check_style(color).map_err(TodoError::Validation)?;
+        }
+
+        Ok(Todo {
             id,
+            uuid: generate_uuid(),
             title,
             completed: false,
+            status: Status::Todo,
             created_at: Local::now(),
-            due_date,
-            priority,
+            completed_at: None,
+            modified_at: None,
+            modified_by: None,
+            last_reviewed: None,
+            start_date: self.start_date,
+            due_date: self.due_date,
+            priority: self.priority,
+            tags,
+            notes,
+            reminders: self.reminders,
+            estimate: self.estimate,
+            hidden_until: None,
+            focused: false,
+            pinned: false,
+            sort_key: None,
+            scheduled: self.scheduled,
+            time_entries: Vec::new(),
+            depends_on: Vec::new(),
+            project,
+            assignee,
+            pomodoros_completed: 0,
+            external_ref: self.external_ref,
+            icon,
+            color,
+            attachments: self.attachments,
+            comments: Vec::new(),
+            checklist,
+            progress: None,
+        })
+    }
+}
+
+/// Generates a random (version 4) UUID string
+pub fn generate_uuid() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+
+    // Each RandomState is seeded with fresh random keys, so hashing the
+    // current time and a process-wide counter yields unpredictable bytes
+    let mut bytes = [0u8; 16];
+    for chunk in bytes.chunks_mut(8) {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(nanos);
+        hasher.write_u64(COUNTER.fetch_add(1, AtomicOrdering::Relaxed));
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+
+    // Set the version (4) and variant (RFC 4122) bits
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32]
+    )
+}
+
+/// How the smart sort ranks todos of the same status
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmartSort {
+    /// Overdue todos first, then the rest as with `priority`
+    #[default]
+    OverdueFirst,
+    /// By priority, then due date, then age: the todos' own order
+    Priority,
+}
+
+impl SmartSort {
+    /// Compares two todos as of `today`
+    pub fn compare(self, a: &Todo, b: &Todo, today: NaiveDate) -> Ordering {
+        match self {
+            SmartSort::OverdueFirst => a.status.rank().cmp(&b.status.rank())
+                .then_with(|| b.is_overdue_on(today).cmp(&a.is_overdue_on(today)))
+                .then_with(|| a.cmp(b)),
+            SmartSort::Priority => a.cmp(b),
         }
     }
 }
 
+static SMART_SORT: OnceLock<SmartSort> = OnceLock::new();
+
+/// Sets how the smart sort ranks todos for the rest of the process
+///
+/// Only the first call has an effect.
+pub fn set_smart_sort(strategy: SmartSort) {
+    let _ = SMART_SORT.set(strategy);
+}
+
+/// Returns how the smart sort ranks todos, overdue ones first unless set otherwise
+pub fn smart_sort() -> SmartSort {
+    SMART_SORT.get().copied().unwrap_or_default()
+}
+
 // Implement ordering for todos based on priority, due date, and creation time
 impl PartialEq for Todo {
     fn eq(&self, other: &Self) -> bool {
@@ -93,11 +1008,9 @@ impl PartialOrd for Todo {
 
 impl Ord for Todo {
     fn cmp(&self, other: &Self) -> Ordering {
-        // First, sort by completion status (incomplete first)
-        match (self.completed, other.completed) {
-            (true, false) => Ordering::Greater,
-            (false, true) => Ordering::Less,
-            _ => {
+        // First, sort by status (in progress first, cancelled last)
+        match self.status.rank().cmp(&other.status.rank()) {
+            Ordering::Equal => {
                 // Then by priority (highest first)
                 match self.priority.cmp(&other.priority).reverse() {
                     Ordering::Equal => {
@@ -115,6 +1028,7 @@ impl Ord for Todo {
                     ordering => ordering,
                 }
             }
+            ordering => ordering,
         }
     }
 }
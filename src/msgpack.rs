@@ -0,0 +1,372 @@
+//! A small MessagePack codec for the database's binary format
+//!
+//! Only what JSON can hold is supported, so a database converts to and
+//! from JSON without loss: nil, booleans, integers, floats, strings,
+//! arrays, and maps with string keys. Binary and extension types are
+//! rejected when reading. Reading goes straight into the target type
+//! through serde, which is what makes the format quicker than JSON.
+
+use serde::de::{self, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::{Number, Value};
+use std::fmt::{self, Display};
+
+/// Deepest nesting accepted when reading, well past anything a todo has
+const MAX_DEPTH: usize = 128;
+
+/// Encodes `value` as MessagePack
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_value(&mut out, value);
+    out
+}
+
+/// Writes a length with the smallest of the three header sizes, or packed into `fix` below `fix_max`
+fn write_len(out: &mut Vec<u8>, len: usize, fix: u8, fix_max: usize, [small, medium, large]: [u8; 3]) {
+    match len {
+        len if len < fix_max => out.push(fix | len as u8),
+        len if small != 0 && len <= u8::MAX as usize => out.extend([small, len as u8]),
+        len if len <= u16::MAX as usize => {
+            out.push(medium);
+            out.extend((len as u16).to_be_bytes());
+        }
+        len => {
+            out.push(large);
+            out.extend((len as u32).to_be_bytes());
+        }
+    }
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(0xc0),
+        Value::Bool(false) => out.push(0xc2),
+        Value::Bool(true) => out.push(0xc3),
+        Value::Number(number) => write_number(out, number),
+        Value::String(text) => {
+            write_len(out, text.len(), 0xa0, 32, [0xd9, 0xda, 0xdb]);
+            out.extend(text.as_bytes());
+        }
+        Value::Array(items) => {
+            write_len(out, items.len(), 0x90, 16, [0, 0xdc, 0xdd]);
+            items.iter().for_each(|item| write_value(out, item));
+        }
+        Value::Object(map) => {
+            write_len(out, map.len(), 0x80, 16, [0, 0xde, 0xdf]);
+            for (key, item) in map {
+                write_value(out, &Value::String(key.clone()));
+                write_value(out, item);
+            }
+        }
+    }
+}
+
+fn write_number(out: &mut Vec<u8>, number: &Number) {
+    if let Some(n) = number.as_u64() {
+        match n {
+            0..=0x7f => out.push(n as u8),
+            n if n <= u8::MAX as u64 => out.extend([0xcc, n as u8]),
+            n if n <= u16::MAX as u64 => {
+                out.push(0xcd);
+                out.extend((n as u16).to_be_bytes());
+            }
+            n if n <= u32::MAX as u64 => {
+                out.push(0xce);
+                out.extend((n as u32).to_be_bytes());
+            }
+            n => {
+                out.push(0xcf);
+                out.extend(n.to_be_bytes());
+            }
+        }
+    } else if let Some(n) = number.as_i64() {
+        match n {
+            -32..=-1 => out.push(n as i8 as u8),
+            n if n >= i8::MIN as i64 => out.extend([0xd0, n as i8 as u8]),
+            n if n >= i16::MIN as i64 => {
+                out.push(0xd1);
+                out.extend((n as i16).to_be_bytes());
+            }
+            n if n >= i32::MIN as i64 => {
+                out.push(0xd2);
+                out.extend((n as i32).to_be_bytes());
+            }
+            n => {
+                out.push(0xd3);
+                out.extend(n.to_be_bytes());
+            }
+        }
+    } else {
+        out.push(0xcb);
+        out.extend(number.as_f64().unwrap_or_default().to_be_bytes());
+    }
+}
+
+/// Returns true if `bytes` start like a MessagePack array, which no JSON document does
+pub fn looks_like_array(bytes: &[u8]) -> bool {
+    matches!(bytes.first(), Some(0x90..=0x9f | 0xdc | 0xdd))
+}
+
+/// Why MessagePack data couldn't be read
+#[derive(Debug)]
+pub struct Error(String);
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Self(message.to_string())
+    }
+}
+
+/// Reads a `T` from MessagePack data that holds exactly one value
+pub fn from_slice<'de, T: de::Deserialize<'de>>(bytes: &'de [u8]) -> Result<T, Error> {
+    let mut reader = Reader { bytes, pos: 0, depth: 0 };
+    let value = T::deserialize(&mut reader)?;
+    if reader.pos != bytes.len() {
+        return Err(Error(format!("{} bytes left over after the value", bytes.len() - reader.pos)));
+    }
+    Ok(value)
+}
+
+/// The start of a value: scalars in full, arrays and maps with their lengths
+enum Header<'de> {
+    Nil,
+    Bool(bool),
+    Uint(u64),
+    Int(i64),
+    Float(f64),
+    Str(&'de str),
+    Array(usize),
+    Map(usize),
+}
+
+struct Reader<'de> {
+    bytes: &'de [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'de> Reader<'de> {
+    fn take(&mut self, n: usize) -> Result<&'de [u8], Error> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len());
+        let end = end.ok_or_else(|| Error(format!("unexpected end of data at byte {}", self.pos)))?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn str(&mut self, len: usize) -> Result<Header<'de>, Error> {
+        let start = self.pos;
+        let bytes = self.take(len)?;
+        let text = std::str::from_utf8(bytes).map_err(|_| Error(format!("invalid UTF-8 in the string at byte {}", start)))?;
+        Ok(Header::Str(text))
+    }
+
+    fn header(&mut self) -> Result<Header<'de>, Error> {
+        let at = self.pos;
+        let marker = self.take(1)?[0];
+        let u8_len = |r: &mut Self| r.take(1).map(|b| b[0] as usize);
+        let u16_len = |r: &mut Self| r.array().map(|b| u16::from_be_bytes(b) as usize);
+        let u32_len = |r: &mut Self| r.array().map(|b| u32::from_be_bytes(b) as usize);
+        Ok(match marker {
+            0x00..=0x7f => Header::Uint(marker as u64),
+            0x80..=0x8f => Header::Map((marker & 0x0f) as usize),
+            0x90..=0x9f => Header::Array((marker & 0x0f) as usize),
+            0xa0..=0xbf => self.str((marker & 0x1f) as usize)?,
+            0xc0 => Header::Nil,
+            0xc2 => Header::Bool(false),
+            0xc3 => Header::Bool(true),
+            0xca => Header::Float(f32::from_be_bytes(self.array()?) as f64),
+            0xcb => Header::Float(f64::from_be_bytes(self.array()?)),
+            0xcc => Header::Uint(self.take(1)?[0] as u64),
+            0xcd => Header::Uint(u16::from_be_bytes(self.array()?) as u64),
+            0xce => Header::Uint(u32::from_be_bytes(self.array()?) as u64),
+            0xcf => Header::Uint(u64::from_be_bytes(self.array()?)),
+            0xd0 => Header::Int(self.take(1)?[0] as i8 as i64),
+            0xd1 => Header::Int(i16::from_be_bytes(self.array()?) as i64),
+            0xd2 => Header::Int(i32::from_be_bytes(self.array()?) as i64),
+            0xd3 => Header::Int(i64::from_be_bytes(self.array()?)),
+            0xd9 => {
+                let len = u8_len(self)?;
+                self.str(len)?
+            }
+            0xda => {
+                let len = u16_len(self)?;
+                self.str(len)?
+            }
+            0xdb => {
+                let len = u32_len(self)?;
+                self.str(len)?
+            }
+            0xdc => Header::Array(u16_len(self)?),
+            0xdd => Header::Array(u32_len(self)?),
+            0xde => Header::Map(u16_len(self)?),
+            0xdf => Header::Map(u32_len(self)?),
+            0xe0..=0xff => Header::Int(marker as i8 as i64),
+            marker => return Err(Error(format!("unsupported type 0x{:02x} at byte {}", marker, at))),
+        })
+    }
+
+    /// Runs `read` one level deeper, refusing data nested past [`MAX_DEPTH`]
+    fn nested<T>(&mut self, read: impl FnOnce(&mut Self) -> Result<T, Error>) -> Result<T, Error> {
+        if self.depth == MAX_DEPTH {
+            return Err(Error(format!("nested more than {} deep at byte {}", MAX_DEPTH, self.pos)));
+        }
+        self.depth += 1;
+        let value = read(self);
+        self.depth -= 1;
+        value
+    }
+}
+
+impl<'de> Deserializer<'de> for &mut Reader<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.header()? {
+            Header::Nil => visitor.visit_unit(),
+            Header::Bool(b) => visitor.visit_bool(b),
+            Header::Uint(n) => visitor.visit_u64(n),
+            Header::Int(n) => visitor.visit_i64(n),
+            Header::Float(f) => visitor.visit_f64(f),
+            Header::Str(text) => visitor.visit_borrowed_str(text),
+            Header::Array(len) => self.nested(|reader| {
+                let mut items = Items { reader, left: len };
+                let value = visitor.visit_seq(&mut items)?;
+                items.finish(value)
+            }),
+            Header::Map(len) => self.nested(|reader| {
+                let mut items = Items { reader, left: len };
+                let value = visitor.visit_map(&mut items)?;
+                items.finish(value)
+            }),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.bytes.get(self.pos) == Some(&0xc0) {
+            self.pos += 1;
+            return visitor.visit_none();
+        }
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Reads unit variants from their names, and others from a map of the name to the content, as JSON has them
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let at = self.pos;
+        match self.header()? {
+            Header::Str(name) => visitor.visit_enum(name.into_deserializer()),
+            Header::Map(1) => self.nested(|reader| visitor.visit_enum(reader)),
+            _ => Err(Error(format!("expected an enum variant at byte {}", at))),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit unit_struct seq
+        tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// The items of an array, or the entries of a map, still to be read
+struct Items<'a, 'de> {
+    reader: &'a mut Reader<'de>,
+    left: usize,
+}
+
+impl Items<'_, '_> {
+    /// Fails if the visitor stopped before the end, as the next value would be misread
+    fn finish<T>(&self, value: T) -> Result<T, Error> {
+        if self.left > 0 {
+            return Err(Error(format!("{} unread items before byte {}", self.left, self.reader.pos)));
+        }
+        Ok(value)
+    }
+}
+
+impl<'de> SeqAccess<'de> for Items<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        if self.left == 0 {
+            return Ok(None);
+        }
+        self.left -= 1;
+        seed.deserialize(&mut *self.reader).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        // Every item takes at least a byte, so a bogus length can't reserve much
+        Some(self.left.min(self.reader.bytes.len() - self.reader.pos))
+    }
+}
+
+impl<'de> MapAccess<'de> for Items<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        if self.left == 0 {
+            return Ok(None);
+        }
+        self.left -= 1;
+        seed.deserialize(&mut *self.reader).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.reader)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.left.min(self.reader.bytes.len() - self.reader.pos))
+    }
+}
+
+impl<'de> EnumAccess<'de> for &mut Reader<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        Ok((seed.deserialize(&mut *self)?, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for &mut Reader<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+}
@@ -0,0 +1,152 @@
+//! Reminder scheduling, desktop notifications, and posts to chat webhooks
+
+use crate::config::NotifyConfig;
+use crate::duration::HumanDuration;
+use crate::integrations::{curl, curl_error};
+use crate::models::{Priority, Todo};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+/// A reminder for a todo at a point in time
+#[derive(Debug, Clone)]
+pub struct Reminder {
+    pub id: u64,
+    pub uuid: String,
+    pub title: String,
+    /// When the reminder fires
+    pub at: DateTime<Local>,
+    /// When the todo is due
+    pub due: DateTime<Local>,
+    /// How long before the due time the reminder fires
+    pub offset: HumanDuration,
+}
+
+/// Returns the moment a todo due on `date` becomes due, per `due_time`
+pub fn due_instant(date: NaiveDate, config: &NotifyConfig) -> Option<DateTime<Local>> {
+    let time = NaiveTime::parse_from_str(&config.due_time, "%H:%M").unwrap_or(NaiveTime::MIN);
+    Local.from_local_datetime(&date.and_time(time)).earliest()
+}
+
+/// Returns the reminder offsets that apply to a todo
+///
+/// Explicit reminders on the todo win. Otherwise the offsets configured
+/// for its tags and its priority are combined; if there are none, the
+/// default offsets apply.
+pub fn effective_offsets(todo: &Todo, config: &NotifyConfig) -> Vec<HumanDuration> {
+    if !todo.reminders.is_empty() {
+        return todo.reminders.clone();
+    }
+
+    let mut offsets: Vec<HumanDuration> = config
+        .by_tag
+        .iter()
+        .filter(|(tag, _)| todo.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+        .flat_map(|(_, offsets)| offsets.iter().copied())
+        .chain(
+            config
+                .by_priority
+                .iter()
+                .filter(|(name, _)| Priority::from_str(name).ok() == Some(todo.priority))
+                .flat_map(|(_, offsets)| offsets.iter().copied()),
+        )
+        .collect();
+
+    if offsets.is_empty() {
+        offsets = config.default_offsets.clone();
+    }
+    offsets.sort();
+    offsets.dedup();
+    offsets
+}
+
+/// Returns the reminders of incomplete todos firing in `(from, to]`, earliest first
+pub fn reminders_between(
+    todos: &[Todo],
+    config: &NotifyConfig,
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+) -> Vec<Reminder> {
+    let mut reminders: Vec<Reminder> = todos
+        .iter()
+        .filter(|t| !t.completed)
+        .filter_map(|t| t.due_date.and_then(|d| due_instant(d, config)).map(|due| (t, due)))
+        .flat_map(|(todo, due)| {
+            effective_offsets(todo, config).into_iter().map(move |offset| Reminder {
+                id: todo.id,
+                uuid: todo.uuid.clone(),
+                title: todo.title.clone(),
+                at: due - offset.duration(),
+                due,
+                offset,
+            })
+        })
+        .filter(|r| r.at > from && r.at <= to)
+        .collect();
+
+    reminders.sort_by_key(|r| r.at);
+    reminders
+}
+
+/// Returns the incomplete hidden todos that resurface in `(from, to]`
+///
+/// A todo hidden until a date, or scheduled for it, resurfaces at the
+/// configured due time on that date.
+pub fn resurfacing_between<'a>(
+    todos: &'a [Todo],
+    config: &NotifyConfig,
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+) -> Vec<&'a Todo> {
+    todos
+        .iter()
+        .filter(|t| !t.completed)
+        .filter(|t| {
+            [t.hidden_until, t.scheduled]
+                .into_iter()
+                .flatten()
+                .filter_map(|d| due_instant(d, config))
+                .any(|at| at > from && at <= to)
+        })
+        .collect()
+}
+
+/// Sends a desktop notification, returning false if no notifier is available
+///
+/// Uses `osascript` on macOS and `notify-send` elsewhere.
+pub fn send_desktop(summary: &str, body: &str) -> bool {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {:?} with title {:?}",
+            body, summary
+        );
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(script);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.arg(summary).arg(body);
+        command
+    };
+
+    command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// POSTs `body` as JSON to `url` through `curl`, giving up after ten seconds
+pub fn post_json(url: &str, body: &str) -> Result<()> {
+    let args = [
+        "--fail", "--max-time", "10", "--request", "POST",
+        "--header", "Content-Type: application/json", "--data-binary", "@-", url,
+    ];
+    let output = curl(&args, body.as_bytes())?;
+    if !output.status.success() {
+        return Err(anyhow!("{}", curl_error(&output)));
+    }
+    Ok(())
+}
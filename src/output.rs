@@ -0,0 +1,37 @@
+//! How chatty the command line is, set once from the global flags
+
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+static VERBOSITY: OnceLock<Verbosity> = OnceLock::new();
+
+/// How much a command prints besides its result
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Skip the list that commands show after changing it
+    Quiet,
+    /// Show the list after every change
+    #[default]
+    Normal,
+    /// Also report which files are read and written, on stderr
+    Verbose,
+}
+
+/// Sets the verbosity for the rest of the process
+///
+/// Only the first call has an effect.
+pub fn set_verbosity(verbosity: Verbosity) {
+    let _ = VERBOSITY.set(verbosity);
+}
+
+/// Returns the verbosity set with [`set_verbosity`]
+pub fn verbosity() -> Verbosity {
+    VERBOSITY.get().copied().unwrap_or_default()
+}
+
+/// Prints a detail to stderr in verbose mode
+pub fn info(message: impl Display) {
+    if verbosity() == Verbosity::Verbose {
+        eprintln!("{}", message);
+    }
+}
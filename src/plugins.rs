@@ -0,0 +1,60 @@
+//! Subcommands provided by other programs, git-style
+//!
+//! `todo-cli foo args...` runs `todo-foo args...` from `PATH` when `foo`
+//! isn't a built-in command or an alias. The plugin inherits the terminal
+//! and learns the database and global options from the environment:
+//! `TODO_FILE` (the database, with `--list` applied), `TODO_CONFIG`,
+//! `TODO_COLOR`, and `TODO_QUIET` and `TODO_VERBOSE` set to 1 when given.
+//! `TODO_READ_ONLY` is 1 when the list is read-only, by `--read-only` or
+//! the config. `TODO_BIN` is this program, for running commands back
+//! against the same database.
+
+use crate::cli::Cli;
+use crate::config::config_path;
+use crate::error::TodoError;
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command;
+
+/// Prefix of plugin program names
+pub const PLUGIN_PREFIX: &str = "todo-";
+
+/// Runs the plugin named by the first of `args`, passing it the rest
+///
+/// A plugin that exits unsuccessfully fails with [`TodoError::Plugin`],
+/// whose exit code is the plugin's status, so scripts see it as they would
+/// the plugin's own.
+pub fn run_plugin(args: &[String], cli: &Cli, database: &Path, read_only: bool) -> Result<()> {
+    let (name, rest) = args.split_first().context("No command given")?;
+    let program = format!("{}{}", PLUGIN_PREFIX, name);
+    let flag = |set: bool| if set { "1" } else { "" };
+    let color = cli.color.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default();
+
+    let mut command = Command::new(&program);
+    command
+        .args(rest)
+        .env("TODO_FILE", database)
+        .env("TODO_CONFIG", config_path())
+        .env("TODO_COLOR", color)
+        .env("TODO_QUIET", flag(cli.quiet))
+        .env("TODO_VERBOSE", flag(cli.verbose))
+        .env("TODO_READ_ONLY", flag(read_only));
+    if let Ok(exe) = std::env::current_exe() {
+        command.env("TODO_BIN", exe);
+    }
+
+    let status = match command.status() {
+        Ok(status) => status,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Err(anyhow!("Unknown command '{}', and no {} plugin on PATH", name, program));
+        }
+        Err(e) => return Err(e).with_context(|| format!("Failed to run {}", program)),
+    };
+    if !status.success() {
+        let code = status.code().and_then(|code| u8::try_from(code).ok()).unwrap_or(1);
+        return Err(TodoError::Plugin { program, code }.into());
+    }
+    Ok(())
+}
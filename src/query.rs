@@ -0,0 +1,381 @@
+//! A small expression language for selecting todos
+//!
+//! Expressions combine conditions with `and`, `or`, `not`, and parentheses:
+//!
+//! ```text
+//! priority>=high and due<=+3d and not completed and tag:work
+//! ```
+//!
+//! Conditions are `FIELD OP VALUE` comparisons, `tag:NAME`, `status:NAME`
+//! (e.g. `status:in-progress`), or one of the keywords `completed`, `active`,
+//! `overdue`, and `hidden`. Fields are `priority`, `due`, `created`, `id`, and
+//! `title`; operators are `=`, `!=`, `<`, `<=`, `>`, `>=`, and `~` (title
+//! contains). Dates may be `today`, `tomorrow`, `YYYY-MM-DD`, or an offset
+//! from today such as `+3d`, `-1w`; `due=none` matches todos without a due
+//! date.
+
+use crate::models::{Priority, Status, Todo};
+use chrono::{Duration, Local, NaiveDate};
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// Comparison operator in a condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+impl CompareOp {
+    fn matches(self, ordering: Ordering) -> bool {
+        match self {
+            CompareOp::Eq => ordering == Ordering::Equal,
+            CompareOp::Ne => ordering != Ordering::Equal,
+            CompareOp::Lt => ordering == Ordering::Less,
+            CompareOp::Le => ordering != Ordering::Greater,
+            CompareOp::Gt => ordering == Ordering::Greater,
+            CompareOp::Ge => ordering != Ordering::Less,
+            CompareOp::Contains => false,
+        }
+    }
+}
+
+/// A date in a condition, possibly relative to the day it is evaluated on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateValue {
+    Absolute(NaiveDate),
+    /// Days from today
+    Relative(i64),
+    /// No date at all
+    None,
+}
+
+impl DateValue {
+    fn resolve(self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            DateValue::Absolute(date) => Some(date),
+            DateValue::Relative(days) => Duration::try_days(days).and_then(|offset| today.checked_add_signed(offset)),
+            DateValue::None => None,
+        }
+    }
+}
+
+/// A single condition on a todo
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Priority(CompareOp, Priority),
+    Due(CompareOp, DateValue),
+    Created(CompareOp, DateValue),
+    Id(CompareOp, u64),
+    Title(CompareOp, String),
+    Tag(String),
+    Status(Status),
+    Completed,
+    Active,
+    Overdue,
+    Hidden,
+}
+
+/// A parsed query expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    Condition(Condition),
+    Not(Box<Query>),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+}
+
+impl Query {
+    /// Returns true if the todo satisfies the expression, relative to `today`
+    pub fn matches_on(&self, todo: &Todo, today: NaiveDate) -> bool {
+        match self {
+            Query::Condition(condition) => condition.matches_on(todo, today),
+            Query::Not(inner) => !inner.matches_on(todo, today),
+            Query::And(a, b) => a.matches_on(todo, today) && b.matches_on(todo, today),
+            Query::Or(a, b) => a.matches_on(todo, today) || b.matches_on(todo, today),
+        }
+    }
+
+    /// Returns true if the todo satisfies the expression as of now
+    pub fn matches(&self, todo: &Todo) -> bool {
+        self.matches_on(todo, Local::now().date_naive())
+    }
+}
+
+impl Condition {
+    fn matches_on(&self, todo: &Todo, today: NaiveDate) -> bool {
+        match self {
+            Condition::Priority(op, priority) => op.matches(todo.priority.cmp(priority)),
+            Condition::Due(op, value) => compare_dates(*op, todo.due_date, value.resolve(today)),
+            Condition::Created(op, value) => {
+                compare_dates(*op, Some(todo.created_at.date_naive()), value.resolve(today))
+            }
+            Condition::Id(op, id) => op.matches(todo.id.cmp(id)),
+            Condition::Title(CompareOp::Contains, text) => {
+                todo.title.to_lowercase().contains(&text.to_lowercase())
+            }
+            Condition::Title(op, text) => {
+                op.matches(todo.title.to_lowercase().cmp(&text.to_lowercase()))
+            }
+            Condition::Tag(tag) => todo.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            Condition::Status(status) => todo.status == *status,
+            Condition::Completed => todo.completed,
+            Condition::Active => !todo.completed,
+            Condition::Overdue => todo.is_overdue_on(today),
+            Condition::Hidden => todo.is_hidden_on(today),
+        }
+    }
+}
+
+/// Compares an optional date; a missing date only equals `none`
+fn compare_dates(op: CompareOp, actual: Option<NaiveDate>, expected: Option<NaiveDate>) -> bool {
+    match (actual, expected) {
+        (Some(a), Some(e)) => op.matches(a.cmp(&e)),
+        (None, None) => op == CompareOp::Eq,
+        (Some(_), None) => op == CompareOp::Ne,
+        (None, Some(_)) => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Op(CompareOp),
+    LParen,
+    RParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some(ch) if ch == c => break,
+                        Some(ch) => word.push(ch),
+                        None => return Err("Unterminated quoted string".to_string()),
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+            '=' | '!' | '<' | '>' | '~' => {
+                chars.next();
+                let followed_by_eq = chars.peek() == Some(&'=');
+                let op = match (c, followed_by_eq) {
+                    ('=', _) => CompareOp::Eq,
+                    ('!', true) => CompareOp::Ne,
+                    ('<', true) => CompareOp::Le,
+                    ('<', false) => CompareOp::Lt,
+                    ('>', true) => CompareOp::Ge,
+                    ('>', false) => CompareOp::Gt,
+                    ('~', _) => CompareOp::Contains,
+                    _ => return Err("Expected '=' after '!'".to_string()),
+                };
+                if followed_by_eq && c != '~' {
+                    chars.next();
+                }
+                tokens.push(Token::Op(op));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || "()=!<>~\"'".contains(ch) {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<Query, String> {
+        let mut left = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            let right = self.parse_and()?;
+            left = Query::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Query, String> {
+        let mut left = self.parse_unary()?;
+        while self.peek_keyword("and") {
+            self.pos += 1;
+            let right = self.parse_unary()?;
+            left = Query::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Query, String> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            return Ok(Query::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Query, String> {
+        match self.next() {
+            Some(Token::LParen) => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(inner),
+                    _ => Err("Expected ')'".to_string()),
+                }
+            }
+            Some(Token::Word(word)) => {
+                if let Some(Token::Op(op)) = self.peek().cloned() {
+                    self.pos += 1;
+                    let value = match self.next() {
+                        Some(Token::Word(value)) => value,
+                        _ => return Err(format!("Expected a value after '{}'", word)),
+                    };
+                    return parse_comparison(&word, op, &value).map(Query::Condition);
+                }
+                parse_keyword(&word).map(Query::Condition)
+            }
+            Some(token) => Err(format!("Unexpected {:?}", token)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+fn parse_keyword(word: &str) -> Result<Condition, String> {
+    let lower = word.to_lowercase();
+    if let Some(tag) = lower.strip_prefix("tag:") {
+        return Ok(Condition::Tag(tag.to_string()));
+    }
+    if let Some(status) = lower.strip_prefix("status:") {
+        return <Status as clap::ValueEnum>::from_str(status, true)
+            .map(Condition::Status)
+            .map_err(|_| format!("Unknown status '{}'", status));
+    }
+    match lower.as_str() {
+        "completed" | "done" => Ok(Condition::Completed),
+        "active" => Ok(Condition::Active),
+        "overdue" => Ok(Condition::Overdue),
+        "hidden" => Ok(Condition::Hidden),
+        _ => Err(format!("Unknown condition '{}'", word)),
+    }
+}
+
+fn parse_comparison(field: &str, op: CompareOp, value: &str) -> Result<Condition, String> {
+    let field = field.to_lowercase();
+    if op == CompareOp::Contains && field != "title" {
+        return Err("'~' can only be used with title".to_string());
+    }
+
+    match field.as_str() {
+        "priority" | "prio" => Ok(Condition::Priority(op, Priority::from_str(value)?)),
+        "due" => Ok(Condition::Due(op, parse_date_value(value)?)),
+        "created" => Ok(Condition::Created(op, parse_date_value(value)?)),
+        "id" => value
+            .parse()
+            .map(|id| Condition::Id(op, id))
+            .map_err(|_| format!("Invalid ID '{}'", value)),
+        "title" => Ok(Condition::Title(op, value.to_string())),
+        _ => Err(format!("Unknown field '{}'", field)),
+    }
+}
+
+/// Parses `today`, `tomorrow`, `yesterday`, `none`, `YYYY-MM-DD`, or `+Nd`/`-Nw` offsets
+fn parse_date_value(value: &str) -> Result<DateValue, String> {
+    let lower = value.to_lowercase();
+    match lower.as_str() {
+        "today" => return Ok(DateValue::Relative(0)),
+        "tomorrow" => return Ok(DateValue::Relative(1)),
+        "yesterday" => return Ok(DateValue::Relative(-1)),
+        "none" => return Ok(DateValue::None),
+        _ => {}
+    }
+
+    if let Some(sign) = lower.chars().next().filter(|c| *c == '+' || *c == '-') {
+        let body = &lower[1..];
+        let (number, multiplier) = match (body.strip_suffix('d'), body.strip_suffix('w')) {
+            (Some(number), _) => (number, 1),
+            (_, Some(number)) => (number, 7),
+            _ => return Err(format!("Invalid date offset '{}' (use d or w)", value)),
+        };
+        let count: i64 = number
+            .parse()
+            .map_err(|_| format!("Invalid date offset '{}'", value))?;
+        let days = count.checked_mul(if sign == '-' { -multiplier } else { multiplier });
+        // Offsets past the last date there is would overflow when evaluated
+        let today = Local::now().date_naive();
+        return days
+            .filter(|&days| Duration::try_days(days).and_then(|offset| today.checked_add_signed(offset)).is_some())
+            .map(DateValue::Relative)
+            .ok_or_else(|| format!("Invalid date offset '{}': too far from today", value));
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(DateValue::Absolute)
+        .map_err(|_| format!("Invalid date '{}'", value))
+}
+
+/// Parses a query expression
+pub fn parse(input: &str) -> Result<Query, String> {
+    let mut parser = Parser { tokens: tokenize(input)?, pos: 0 };
+    let query = parser.parse_or()?;
+
+    match parser.peek() {
+        None => Ok(query),
+        Some(token) => Err(format!("Unexpected {:?} after expression", token)),
+    }
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
@@ -0,0 +1,137 @@
+//! A store that stages saves in memory, for `--dry-run`
+//!
+//! Commands run unchanged against a [`StagedStore`]: they see their own
+//! saves on the next load, but nothing reaches the database, the trash,
+//! the journal, or the history. Afterwards [`StagedStore::report`] prints what would have
+//! changed, so every command taking `--dry-run` describes it the same way.
+
+use crate::error::TodoError;
+use crate::events::{diff, Event};
+use crate::history::FieldChange;
+use crate::journal::JournalEntry;
+use crate::models::{Todo, TrashedTodo};
+use crate::storage::TodoStore;
+use crate::t;
+use anyhow::Result;
+use chrono::Local;
+use std::time::SystemTime;
+
+/// Wraps another store, keeping every save in memory instead
+pub struct StagedStore<'a> {
+    inner: &'a mut dyn TodoStore,
+    todos: Option<Vec<Todo>>,
+    trash: Option<Vec<TrashedTodo>>,
+}
+
+impl<'a> StagedStore<'a> {
+    /// Stages saves on top of what `inner` holds now
+    pub fn new(inner: &'a mut dyn TodoStore) -> Self {
+        Self { inner, todos: None, trash: None }
+    }
+
+    /// Lists the changes the staged saves would make to the todos
+    pub fn changes(&mut self) -> Result<Vec<Event>> {
+        match &self.todos {
+            Some(todos) => Ok(diff(&self.inner.load()?, todos, Local::now())),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Lists the todos the staged saves would delete from the trash for good
+    pub fn purged(&self) -> Result<Vec<TrashedTodo>> {
+        let Some(trash) = &self.trash else {
+            return Ok(Vec::new());
+        };
+        let mut purged = self.inner.load_trash()?;
+        purged.retain(|old| !trash.iter().any(|t| t.todo.uuid == old.todo.uuid));
+        Ok(purged)
+    }
+
+    /// Prints what the staged saves would change, one line per todo
+    pub fn report(&mut self) -> Result<()> {
+        let changes = self.changes()?;
+        let purged = self.purged()?;
+        println!();
+        if changes.is_empty() && purged.is_empty() {
+            println!("{}", t!("dry_run.nothing"));
+            return Ok(());
+        }
+
+        println!("{}", t!("dry_run.changes"));
+        for event in &changes {
+            println!("  {}", event.describe());
+        }
+        for trashed in &purged {
+            println!("  {:<8} #{:<4} {}", "delete", trashed.todo.id, t!("dry_run.purged", title = trashed.todo.title));
+        }
+        Ok(())
+    }
+}
+
+impl TodoStore for StagedStore<'_> {
+    /// Loads the staged todos, or the inner store's before the first save
+    fn load(&mut self) -> Result<Vec<Todo>> {
+        match &self.todos {
+            Some(todos) => Ok(todos.clone()),
+            None => self.inner.load(),
+        }
+    }
+
+    fn save(&mut self, todos: &[Todo]) -> Result<()> {
+        self.todos = Some(todos.to_vec());
+        Ok(())
+    }
+
+    /// Drops the entry; nothing happened for the journal to record
+    fn append_journal(&mut self, _entry: &JournalEntry) -> Result<()> {
+        Ok(())
+    }
+
+    fn load_journal(&self) -> Result<Vec<JournalEntry>> {
+        self.inner.load_journal()
+    }
+
+    fn load_history(&self) -> Result<Vec<FieldChange>> {
+        self.inner.load_history()
+    }
+
+    fn actor(&self) -> Option<&str> {
+        self.inner.actor()
+    }
+
+    fn last_modified(&self) -> Option<SystemTime> {
+        self.inner.last_modified()
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.inner.is_read_only()
+    }
+
+    fn pending(&self) -> Result<Option<Vec<Todo>>> {
+        self.inner.pending()
+    }
+
+    fn discard_pending(&mut self) -> Result<()> {
+        Err(TodoError::Validation("--dry-run can't discard pending changes".to_string()).into())
+    }
+
+    fn open_list(&self, _name: &str) -> Result<Box<dyn TodoStore>> {
+        Err(TodoError::Validation("--dry-run can't change other lists".to_string()).into())
+    }
+
+    fn load_trash(&self) -> Result<Vec<TrashedTodo>> {
+        match &self.trash {
+            Some(trash) => Ok(trash.clone()),
+            None => self.inner.load_trash(),
+        }
+    }
+
+    fn save_trash(&mut self, trash: &[TrashedTodo]) -> Result<()> {
+        self.trash = Some(trash.to_vec());
+        Ok(())
+    }
+
+    fn load_cache(&self, name: &str) -> Option<String> {
+        self.inner.load_cache(name)
+    }
+}
@@ -1,62 +1,941 @@
+use crate::error::TodoError;
+use crate::history::{self, FieldChange};
+use crate::journal::{self, JournalEntry, Operation};
 use crate::models::*;
-use anyhow::{Context, Result};
-use serde::Deserialize;
-use std::fs;
-use std::path::Path;
+use crate::msgpack;
+use crate::output;
+use crate::sync::same;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, FixedOffset};
+use clap::ValueEnum;
+use serde::de::{Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-const TODO_FILE: &str = "todos.json";
+/// Default database path, relative to the working directory
+pub const TODO_FILE: &str = "todos.json";
 
-/// Loads todos from the JSON file, migrating legacy format if needed
-pub fn load_todos() -> Result<Vec<Todo>> {
-    if !Path::new(TODO_FILE).exists() {
-        return Ok(Vec::new());
+/// How the database file is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+    /// Pretty-printed JSON, easy to read and edit by hand
+    #[default]
+    Json,
+    /// MessagePack, smaller and quicker to read once notes and history pile up
+    Msgpack,
+}
+
+impl StorageFormat {
+    /// Tells which format saved todos are in from their first byte
+    fn of(bytes: &[u8]) -> Self {
+        if msgpack::looks_like_array(bytes) {
+            Self::Msgpack
+        } else {
+            Self::Json
+        }
     }
 
-    let content = fs::read_to_string(TODO_FILE)
-        .with_context(|| format!("Failed to read {}", TODO_FILE))?;
+    /// Returns the name shown in messages, e.g. "MessagePack"
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Msgpack => "MessagePack",
+        }
+    }
 
-    // Try to parse as new format first
-    if let Ok(todos) = serde_json::from_str::<Vec<Todo>>(&content) {
-        return Ok(todos);
+    fn encode<T: Serialize + ?Sized>(self, todos: &T) -> Result<Vec<u8>> {
+        Ok(match self {
+            Self::Json => serde_json::to_vec_pretty(todos).with_context(|| "Failed to serialize todos")?,
+            Self::Msgpack => msgpack::encode(&serde_json::to_value(todos).with_context(|| "Failed to serialize todos")?),
+        })
     }
+}
 
-    // If that fails, try to parse as legacy format
+/// Parses saved todos in either format
+fn parse_todos(bytes: &[u8]) -> serde_json::Result<Vec<Todo>> {
+    if StorageFormat::of(bytes) == StorageFormat::Msgpack {
+        return msgpack::from_slice(bytes)
+            .map_err(|e| <serde_json::Error as serde::de::Error>::custom(format_args!("invalid MessagePack: {}", e)));
+    }
+    serde_json::from_slice(bytes)
+}
+
+/// Returns true if the todos in `bytes` were saved with their local offset rather than in UTC
+///
+/// A save writes every timestamp the same way, so the creation times tell.
+fn saved_in_local_time(bytes: &[u8]) -> bool {
     #[derive(Deserialize)]
-    struct LegacyTodo {
-        id: u64,
-        title: String,
-        completed: bool,
-    }
-
-    let legacy_todos: Vec<LegacyTodo> = serde_json::from_str(&content)
-        .with_context(|| format!("Failed to parse {}", TODO_FILE))?;
-
-    // Convert legacy todos to new format
-    let todos: Vec<Todo> = legacy_todos
-        .into_iter()
-        .map(|t| Todo {
-            id: t.id,
-            title: t.title,
-            completed: t.completed,
-            created_at: chrono::Local::now(),
-            due_date: None,
-            priority: Priority::Normal,
+    struct Stamped {
+        created_at: DateTime<FixedOffset>,
+    }
+
+    let stamped: Option<Vec<Stamped>> = match StorageFormat::of(bytes) {
+        StorageFormat::Msgpack => msgpack::from_slice(bytes).ok(),
+        StorageFormat::Json => serde_json::from_slice(bytes).ok(),
+    };
+    stamped.is_some_and(|todos| todos.iter().any(|t| t.created_at.offset().local_minus_utc() != 0))
+}
+
+/// Persistence backend for todos and their operation journal
+pub trait TodoStore {
+    /// Loads all todos
+    fn load(&mut self) -> Result<Vec<Todo>>;
+
+    /// Calls `visit` with each todo in turn, without holding them all in memory if the backend can
+    fn for_each_todo(&mut self, visit: &mut dyn FnMut(Todo)) -> Result<()> {
+        self.load()?.into_iter().for_each(visit);
+        Ok(())
+    }
+
+    /// Replaces all todos
+    fn save(&mut self, todos: &[Todo]) -> Result<()>;
+
+    /// Appends an entry to the operation journal
+    fn append_journal(&mut self, entry: &JournalEntry) -> Result<()>;
+
+    /// Loads every journal entry
+    fn load_journal(&self) -> Result<Vec<JournalEntry>>;
+
+    /// Loads every recorded field change, oldest first
+    ///
+    /// Stores that keep a history record it as part of each save.
+    fn load_history(&self) -> Result<Vec<FieldChange>> {
+        Ok(Vec::new())
+    }
+
+    /// Returns who saves through this store are credited to, if anyone
+    fn actor(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns when the todos were last saved, if the backend knows
+    fn last_modified(&self) -> Option<SystemTime> {
+        None
+    }
+
+    /// Returns whether every change is refused, e.g. for `--read-only`
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// Returns the directory backups go to by default and the name they start with
+    ///
+    /// `None`, the default, for stores that aren't backed up.
+    fn backup_location(&self) -> Option<(PathBuf, String)> {
+        None
+    }
+
+    /// Returns changes that an interrupted save computed but never committed
+    fn pending(&self) -> Result<Option<Vec<Todo>>> {
+        Ok(None)
+    }
+
+    /// Drops changes left by an interrupted save
+    fn discard_pending(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Opens another named list kept alongside this one
+    fn open_list(&self, name: &str) -> Result<Box<dyn TodoStore>> {
+        Err(anyhow!("This database does not support other lists (asked for '{}')", name))
+    }
+
+    /// Loads the removed todos kept in the trash
+    fn load_trash(&self) -> Result<Vec<TrashedTodo>> {
+        Ok(Vec::new())
+    }
+
+    /// Replaces the contents of the trash
+    fn save_trash(&mut self, _trash: &[TrashedTodo]) -> Result<()> {
+        Err(anyhow!("This database does not keep a trash"))
+    }
+
+    /// Adds removed todos to the trash, timestamped now
+    fn trash(&mut self, todos: &[Todo]) -> Result<()> {
+        if todos.is_empty() {
+            return Ok(());
+        }
+        let mut trash = self.load_trash()?;
+        let now = chrono::Local::now();
+        trash.extend(todos.iter().map(|todo| TrashedTodo { deleted_at: now, todo: todo.clone() }));
+        self.save_trash(&trash)
+    }
+
+    /// Reads data derived from the todos kept under `name`, if the backend keeps any
+    fn load_cache(&self, _name: &str) -> Option<String> {
+        None
+    }
+
+    /// Keeps data derived from the todos under `name`; backends without a cache ignore it
+    fn save_cache(&mut self, _name: &str, _content: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Records an operation on a todo in the journal, timestamped now
+    fn record(&mut self, operation: Operation, todo: &Todo) -> Result<()> {
+        self.append_journal(&JournalEntry::new(operation, todo))
+    }
+
+    /// Rewrites the todos in `format`, returning false if they're stored that way already
+    fn convert(&mut self, format: StorageFormat) -> Result<bool> {
+        Err(anyhow!("This database can't be stored as {}", format.name()))
+    }
+
+    /// Folds changes kept apart from the todos back into them, returning how many there were
+    ///
+    /// Backends that always rewrite everything have none.
+    fn compact(&mut self) -> Result<usize> {
+        Ok(0)
+    }
+}
+
+/// Sets `modified_at` to `now` and `modified_by` to `by` on the todos in
+/// `after` that a save changes
+///
+/// Todos are matched to `before` by UUID. Unchanged todos keep their stamp,
+/// and so do new or changed ones whose stamp the caller set, e.g. from a
+/// merge.
+pub fn stamp_modified(before: &[Todo], after: &mut [Todo], now: chrono::DateTime<chrono::Local>, by: Option<&str>) {
+    let before: HashMap<&str, &Todo> = before.iter().map(|t| (t.uuid.as_str(), t)).collect();
+    let bytes = |todo: &Todo| serde_json::to_vec(todo).ok();
+    for todo in after.iter_mut() {
+        let (modified_at, modified_by) = match before.get(todo.uuid.as_str()) {
+            // Most todos come back as they were loaded, stamp included
+            Some(old) if old.modified_at == todo.modified_at && bytes(old) == bytes(todo) => {
+                (old.modified_at, old.modified_by.clone())
+            }
+            Some(old) if same(old, todo) => {
+                (todo.modified_at.or(old.modified_at), todo.modified_by.clone().or(old.modified_by.clone()))
+            }
+            Some(old) if todo.modified_at.is_some() && todo.modified_at != old.modified_at => {
+                (todo.modified_at, todo.modified_by.clone())
+            }
+            None if todo.modified_at.is_some() => (todo.modified_at, todo.modified_by.clone()),
+            _ => (Some(now), by.map(String::from)),
+        };
+        todo.modified_at = modified_at;
+        todo.modified_by = modified_by;
+    }
+}
+
+/// One save appended to the patch log: the todos it changed or added, and the ones it removed
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Patch {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    put: Vec<Todo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    remove: Vec<String>,
+}
+
+impl Patch {
+    /// Works out the patch that turns `before` into `after`, matching todos by UUID
+    ///
+    /// `None` if `after` reorders the todos, which a patch can't express:
+    /// todos keep their places and new ones go at the end.
+    fn between(before: &[Todo], after: &[Todo]) -> Option<Self> {
+        let old: HashMap<&str, &Todo> = before.iter().map(|t| (t.uuid.as_str(), t)).collect();
+        let new: HashSet<&str> = after.iter().map(|t| t.uuid.as_str()).collect();
+        if old.len() != before.len() || new.len() != after.len() {
+            return None;
+        }
+
+        let kept = before.iter().map(|t| t.uuid.as_str()).filter(|uuid| new.contains(uuid));
+        let added = after.iter().map(|t| t.uuid.as_str()).filter(|uuid| !old.contains_key(uuid));
+        if !kept.chain(added).eq(after.iter().map(|t| t.uuid.as_str())) {
+            return None;
+        }
+
+        let bytes = |todo: &Todo| serde_json::to_vec(todo).ok();
+        Some(Self {
+            put: after
+                .iter()
+                .filter(|t| old.get(t.uuid.as_str()).is_none_or(|o| bytes(o) != bytes(t)))
+                .cloned()
+                .collect(),
+            remove: before.iter().filter(|t| !new.contains(t.uuid.as_str())).map(|t| t.uuid.clone()).collect(),
         })
-        .collect();
+    }
+
+    fn len(&self) -> usize {
+        self.put.len() + self.remove.len()
+    }
+
+    fn apply(self, todos: &mut Vec<Todo>) {
+        todos.retain(|t| !self.remove.contains(&t.uuid));
+        for todo in self.put {
+            match todos.iter_mut().find(|t| t.uuid == todo.uuid) {
+                Some(old) => *old = todo,
+                None => todos.push(todo),
+            }
+        }
+    }
+}
+
+/// Hands the todos of a JSON array to a callback as each one is parsed
+struct EachTodo<'a> {
+    visit: &'a mut dyn FnMut(Todo),
+    seen: &'a mut usize,
+}
+
+impl<'de> Visitor<'de> for EachTodo<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a list of todos")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(mut todo) = seq.next_element::<Todo>()? {
+            if todo.completed != todo.status.is_closed() {
+                todo.status = if todo.completed { Status::Done } else { Status::Todo };
+            }
+            (self.visit)(todo);
+            *self.seen += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Size and modification time of a file, to notice another process writing it
+type Stat = Option<(SystemTime, u64)>;
+
+fn stat(path: &Path) -> Stat {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// The todos as the last load or save left them on disk
+#[derive(Debug, Clone)]
+struct OnDisk {
+    todos: Vec<Todo>,
+    stats: (Stat, Stat),
+    /// Lines in the patch log
+    patches: usize,
+    /// Whether the log ends in a line an interrupted append left unfinished
+    torn: bool,
+}
+
+/// Stores todos as pretty-printed JSON, with the journal alongside
+///
+/// For `todos.json` the journal is `todos.journal.jsonl` in the same
+/// directory, and removed todos go to `todos.trash.json`.
+///
+/// Saves are crash-safe: the new todos are first written to
+/// `todos.pending.json`, then to a temporary file that replaces the
+/// database in one rename. A pending file that outlives a save means the
+/// process died or the write failed, and can be replayed from.
+///
+/// The file can be MessagePack instead, which is smaller and quicker to
+/// read; either format is recognized when loading, and saves keep the
+/// one the file is in, as do pending files. Backups are always JSON.
+///
+/// Large databases can skip rewriting the whole file for small changes:
+/// with [`patches`](Self::patches) set, a save that changes a few todos
+/// appends them as one line to `todos.patches.jsonl` instead. Loads apply
+/// the log on top of the file, and a full save, or [`TodoStore::compact`],
+/// folds it back in.
+#[derive(Debug, Clone)]
+pub struct JsonFileStore {
+    path: PathBuf,
+    read_only: bool,
+    /// Fewest todos for saves to append patches, and most patches before a full save
+    patches: Option<(usize, usize)>,
+    /// Format of the file, or of a new one
+    format: StorageFormat,
+    /// Who changed todos are stamped as modified by
+    actor: Option<String>,
+    on_disk: Option<OnDisk>,
+}
+
+impl JsonFileStore {
+    /// Creates a store backed by the file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            read_only: false,
+            patches: None,
+            format: StorageFormat::Json,
+            actor: None,
+            on_disk: None,
+        }
+    }
+
+    /// Refuses every change when `read_only` is set, migrations of old files included
+    ///
+    /// Caches are still read but no longer written.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Appends small changes to the patch log once the database holds `above` todos
+    ///
+    /// After `compact_after` patches the next save rewrites the file. Without
+    /// this every save does, though existing patches are always read.
+    pub fn patches(mut self, above: Option<usize>, compact_after: usize) -> Self {
+        self.patches = above.map(|above| (above, compact_after));
+        self
+    }
+
+    /// Writes a new database in `format`; an existing file keeps the format it's in
+    pub fn format(mut self, format: StorageFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Credits the todos each save changes to `actor`, and their history with them
+    pub fn actor(mut self, actor: Option<String>) -> Self {
+        self.actor = actor;
+        self
+    }
+
+    /// Fails if the store is read-only
+    fn check_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(TodoError::read_only(&self.path).into());
+        }
+        Ok(())
+    }
+
+    /// Returns the database file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a path next to the database sharing its stem, e.g. `todos.journal.jsonl`
+    pub fn sibling(&self, suffix: &str) -> PathBuf {
+        let stem = self.path.file_stem().and_then(|s| s.to_str()).unwrap_or("todos");
+        self.path.with_file_name(format!("{}.{}", stem, suffix))
+    }
+
+    /// Returns the journal file path
+    pub fn journal_path(&self) -> PathBuf {
+        self.sibling("journal.jsonl")
+    }
+
+    /// Returns the path of the per-todo history, e.g. `todos.history.jsonl`
+    pub fn history_path(&self) -> PathBuf {
+        self.sibling("history.jsonl")
+    }
+
+    /// Returns the path of changes left by an interrupted save
+    pub fn pending_path(&self) -> PathBuf {
+        self.sibling("pending.json")
+    }
+
+    /// Returns the path of the patch log, e.g. `todos.patches.jsonl`
+    pub fn patches_path(&self) -> PathBuf {
+        self.sibling("patches.jsonl")
+    }
+
+    /// Returns the path of the trash, e.g. `todos.trash.json`
+    pub fn trash_path(&self) -> PathBuf {
+        self.sibling("trash.json")
+    }
+
+    /// Returns the path of a cache, e.g. `todos.summary.cache` for `summary`
+    pub fn cache_path(&self, name: &str) -> PathBuf {
+        self.sibling(&format!("{}.cache", name))
+    }
+
+    fn stats(&self) -> (Stat, Stat) {
+        (stat(&self.path), stat(&self.patches_path()))
+    }
+
+    /// Applies the patch log to `todos`, returning how many lines it had and whether the last was torn
+    fn apply_patches(&self, todos: &mut Vec<Todo>) -> Result<(usize, bool)> {
+        let path = self.patches_path();
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((0, false)),
+            Err(e) => return Err(TodoError::storage("read", &path, e).into()),
+        };
+
+        let lines: Vec<&str> = content.lines().collect();
+        for (n, line) in lines.iter().enumerate() {
+            match serde_json::from_str::<Patch>(line) {
+                Ok(patch) => patch.apply(todos),
+                // The process died while appending, so that save never happened
+                Err(_) if n + 1 == lines.len() && !content.ends_with('\n') => return Ok((n, true)),
+                Err(source) => return Err(TodoError::Parse { path, source }.into()),
+            }
+        }
+        Ok((lines.len(), false))
+    }
+
+    /// Reads the saved todos with their patches applied, or none if they don't parse
+    fn read_saved(&self) -> Vec<Todo> {
+        let content = fs::read(&self.path).ok();
+        let Some(mut todos) = content.and_then(|c| parse_todos(&c).ok()) else {
+            return Vec::new();
+        };
+        let _ = self.apply_patches(&mut todos);
+        todos
+    }
+
+    /// Saves stamped todos as a patch against what's on disk, or by rewriting the file
+    fn write_changes(&mut self, on_disk: Option<OnDisk>, todos: Vec<Todo>) -> Result<()> {
+        let Some(on_disk) = on_disk else {
+            return self.write_whole(&todos);
+        };
+
+        let patch = match self.patches {
+            Some((above, compact_after))
+                if on_disk.todos.len() >= above && on_disk.patches < compact_after && !on_disk.torn =>
+            {
+                Patch::between(&on_disk.todos, &todos)
+            }
+            _ => None,
+        };
+        // Past a tenth of the todos, rewriting the file is about as cheap
+        let Some(patch) = patch.filter(|p| p.len() <= on_disk.todos.len() / 10) else {
+            return self.write_whole(&todos);
+        };
+
+        let mut patches = on_disk.patches;
+        if patch.len() > 0 {
+            self.append_patch(&patch)?;
+            patches += 1;
+        }
+        output::info(format_args!("Saved {} changed todos to {}", patch.len(), self.patches_path().display()));
+        self.on_disk = Some(OnDisk { todos, stats: self.stats(), patches, torn: false });
+        Ok(())
+    }
+
+    /// Rewrites the whole file through the pending file, folding in and removing the patch log
+    fn write_whole(&mut self, todos: &[Todo]) -> Result<()> {
+        let content = self.format.encode(todos)?;
+
+        let pending = self.pending_path();
+        write_synced(&pending, &content)?;
+
+        let temp = self.sibling("json.tmp");
+        write_synced(&temp, &content)?;
+        fs::rename(&temp, &self.path)
+            .map_err(|e| TodoError::storage("write to", &self.path, e))?;
 
-    // Save the migrated todos back to the file
-    save_todos(&todos)?;
+        // Replaying the log onto the new file changes nothing, so a crash here is harmless
+        let patches = self.patches_path();
+        if patches.exists() {
+            fs::remove_file(&patches)
+                .map_err(|e| TodoError::storage("remove", &patches, e))?;
+        }
+        fs::remove_file(&pending)
+            .map_err(|e| TodoError::storage("remove", &pending, e))?;
 
-    Ok(todos)
+        output::info(format_args!("Saved {} todos to {}", todos.len(), self.path.display()));
+        self.on_disk = Some(OnDisk { todos: todos.to_vec(), stats: self.stats(), patches: 0, torn: false });
+        Ok(())
+    }
+
+    /// Appends `patch` to the log as one line and flushes it to disk
+    fn append_patch(&self, patch: &Patch) -> Result<()> {
+        let path = self.patches_path();
+        let line = serde_json::to_string(patch)
+            .with_context(|| "Failed to serialize todos")?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| TodoError::storage("open", &path, e))?;
+        writeln!(file, "{}", line)
+            .and_then(|()| file.sync_all())
+            .map_err(|e| TodoError::storage("write to", &path, e))?;
+        Ok(())
+    }
 }
 
-/// Saves todos to the JSON file
-pub fn save_todos(todos: &[Todo]) -> Result<()> {
-    let content = serde_json::to_string_pretty(todos)
-        .with_context(|| "Failed to serialize todos")?;
-    
-    fs::write(TODO_FILE, content)
-        .with_context(|| format!("Failed to write to {}", TODO_FILE))?;
-    
+/// Writes `content` to `path` and flushes it to disk
+fn write_synced(path: &Path, content: &[u8]) -> Result<()> {
+    let mut file = File::create(path)
+        .map_err(|e| TodoError::storage("create", path, e))?;
+    file.write_all(content)
+        .and_then(|()| file.sync_all())
+        .map_err(|e| TodoError::storage("write to", path, e))?;
     Ok(())
 }
+
+/// Returns the database path for the list `name` in `dir`, e.g. `todos-work.json`
+///
+/// Names may contain letters, digits, `-`, and `_`.
+pub fn list_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(TodoError::Validation(format!(
+            "Invalid list name '{}': use letters, digits, '-', or '_'", name
+        )).into());
+    }
+    Ok(dir.join(format!("todos-{}.json", name)))
+}
+
+impl Default for JsonFileStore {
+    fn default() -> Self {
+        Self::new(TODO_FILE)
+    }
+}
+
+impl TodoStore for JsonFileStore {
+    /// Loads todos from the JSON file, migrating legacy format if needed
+    fn load(&mut self) -> Result<Vec<Todo>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read(&self.path)
+            .map_err(|e| TodoError::storage("read", &self.path, e))?;
+        self.format = StorageFormat::of(&content);
+
+        // Try to parse as new format first
+        let error = match parse_todos(&content) {
+            Ok(mut todos) => {
+                let (patches, torn) = self.apply_patches(&mut todos)?;
+                // Rewrite timestamps saved with the local offset in UTC
+                let mut migrated = saved_in_local_time(&content);
+                // Assign UUIDs to todos saved before they existed
+                for todo in todos.iter_mut().filter(|t| t.uuid.is_empty()) {
+                    todo.uuid = generate_uuid();
+                    migrated = true;
+                }
+                // Todos saved before statuses existed only say whether they're done
+                for todo in todos.iter_mut().filter(|t| t.completed != t.status.is_closed()) {
+                    todo.status = if todo.completed { Status::Done } else { Status::Todo };
+                }
+                if migrated && !self.read_only {
+                    self.save(&todos)?;
+                } else {
+                    self.on_disk = Some(OnDisk { todos: todos.clone(), stats: self.stats(), patches, torn });
+                }
+                return Ok(todos);
+            }
+            Err(error) => error,
+        };
+
+        // Legacy files are always JSON, so a broken MessagePack file is just broken
+        if self.format == StorageFormat::Msgpack {
+            return Err(TodoError::Parse { path: self.path.clone(), source: error }.into());
+        }
+
+        // If that fails, try to parse as legacy format: todos with nothing
+        // but an ID, a title, and whether they're done
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct LegacyTodo {
+            id: u64,
+            title: String,
+            completed: bool,
+        }
+
+        let legacy_todos: Vec<LegacyTodo> = serde_json::from_slice(&content)
+            .map_err(|_| TodoError::Parse { path: self.path.clone(), source: error })?;
+
+        // Convert legacy todos to new format
+        let todos: Vec<Todo> = legacy_todos
+            .into_iter()
+            .map(|t| Todo {
+                id: t.id,
+                uuid: generate_uuid(),
+                title: t.title,
+                completed: t.completed,
+                status: if t.completed { Status::Done } else { Status::Todo },
+                created_at: chrono::Local::now(),
+                completed_at: None,
+                modified_at: None,
+                modified_by: None,
+                last_reviewed: None,
+                start_date: None,
+                due_date: None,
+                priority: Priority::default(),
+                tags: Vec::new(),
+                notes: None,
+                reminders: Vec::new(),
+                estimate: None,
+                hidden_until: None,
+                focused: false,
+                pinned: false,
+                sort_key: None,
+                scheduled: None,
+                time_entries: Vec::new(),
+                depends_on: Vec::new(),
+                project: None,
+                assignee: None,
+                pomodoros_completed: 0,
+                external_ref: None,
+                icon: None,
+                color: None,
+                attachments: Vec::new(),
+                comments: Vec::new(),
+                checklist: Vec::new(),
+                progress: None,
+            })
+            .collect();
+
+        // Save the migrated todos back to the file
+        if !self.read_only {
+            self.save(&todos)?;
+        }
+
+        Ok(todos)
+    }
+
+    /// Parses the file one todo at a time instead of reading it all in
+    ///
+    /// Falls back to [`load`](TodoStore::load) when there are patches to
+    /// apply, or when the first todo doesn't parse, so MessagePack files
+    /// and old formats are read and errors reported as usual. Nothing is saved, so todos
+    /// from before UUIDs were kept have none here.
+    fn for_each_todo(&mut self, visit: &mut dyn FnMut(Todo)) -> Result<()> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+        if self.patches_path().exists() {
+            self.load()?.into_iter().for_each(visit);
+            return Ok(());
+        }
+
+        let file = File::open(&self.path).map_err(|e| TodoError::storage("read", &self.path, e))?;
+        let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+        let mut seen = 0;
+        let streamed = (&mut deserializer)
+            .deserialize_seq(EachTodo { visit, seen: &mut seen })
+            .and_then(|()| deserializer.end());
+        match streamed {
+            Ok(()) => Ok(()),
+            Err(_) if seen == 0 => {
+                self.load()?.into_iter().for_each(visit);
+                Ok(())
+            }
+            Err(source) => Err(TodoError::Parse { path: self.path.clone(), source }.into()),
+        }
+    }
+
+    /// Saves todos to the JSON file, recording them as pending until the write lands
+    ///
+    /// Todos that differ from the saved ones are stamped as modified now by
+    /// the actor, and each changed field is added to the history once the
+    /// save lands.
+    /// With patches on, a change to a few todos of a large database that
+    /// nothing else has written since it was loaded is appended to the
+    /// patch log instead.
+    fn save(&mut self, todos: &[Todo]) -> Result<()> {
+        self.check_writable()?;
+        let mut todos = todos.to_vec();
+        let now = chrono::Local::now();
+        let on_disk = self.on_disk.take().filter(|d| d.stats == self.stats());
+        let saved;
+        let before = match &on_disk {
+            Some(on_disk) => &on_disk.todos,
+            None => {
+                saved = self.read_saved();
+                &saved
+            }
+        };
+        stamp_modified(before, &mut todos, now, self.actor.as_deref());
+        let changes = history::changes(before, &todos, now)?;
+
+        self.write_changes(on_disk, todos)?;
+        // The todos are saved by now, so a failure here loses only their history
+        history::append_to(&self.history_path(), &changes)
+    }
+
+    fn pending(&self) -> Result<Option<Vec<Todo>>> {
+        let path = self.pending_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read(&path)
+            .map_err(|e| TodoError::storage("read", &path, e))?;
+        let todos = parse_todos(&content)
+            .map_err(|source| TodoError::Parse { path, source })?;
+        Ok(Some(todos))
+    }
+
+    fn discard_pending(&mut self) -> Result<()> {
+        self.check_writable()?;
+        let path = self.pending_path();
+        if path.exists() {
+            fs::remove_file(&path)
+                .map_err(|e| TodoError::storage("remove", &path, e))?;
+        }
+        Ok(())
+    }
+
+    fn append_journal(&mut self, entry: &JournalEntry) -> Result<()> {
+        self.check_writable()?;
+        journal::append_to(&self.journal_path(), entry)
+    }
+
+    fn load_journal(&self) -> Result<Vec<JournalEntry>> {
+        journal::read_from(&self.journal_path())
+    }
+
+    fn load_history(&self) -> Result<Vec<FieldChange>> {
+        history::read_from(&self.history_path())
+    }
+
+    fn actor(&self) -> Option<&str> {
+        self.actor.as_deref()
+    }
+
+    /// The later of when the file and the patch log were written
+    fn last_modified(&self) -> Option<SystemTime> {
+        let (file, patches) = self.stats();
+        file.map(|(time, _)| time).max(patches.map(|(time, _)| time))
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Backups go to `todos.backups/` next to `todos.json`, named `todos-<time>.json`
+    fn backup_location(&self) -> Option<(PathBuf, String)> {
+        let stem = self.path.file_stem().and_then(|s| s.to_str()).unwrap_or("todos");
+        Some((self.sibling("backups"), stem.to_string()))
+    }
+
+    fn load_trash(&self) -> Result<Vec<TrashedTodo>> {
+        let path = self.trash_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| TodoError::storage("read", &path, e))?;
+        let trash = serde_json::from_str(&content)
+            .map_err(|source| TodoError::Parse { path, source })?;
+        Ok(trash)
+    }
+
+    fn save_trash(&mut self, trash: &[TrashedTodo]) -> Result<()> {
+        self.check_writable()?;
+        let content = serde_json::to_vec_pretty(trash)
+            .with_context(|| "Failed to serialize the trash")?;
+
+        let path = self.trash_path();
+        let temp = self.sibling("trash.json.tmp");
+        write_synced(&temp, &content)?;
+        fs::rename(&temp, &path)
+            .map_err(|e| TodoError::storage("write to", &path, e))?;
+        Ok(())
+    }
+
+    fn open_list(&self, name: &str) -> Result<Box<dyn TodoStore>> {
+        let dir = self.path.parent().unwrap_or(Path::new(""));
+        let store = JsonFileStore::new(list_path(dir, name)?).read_only(self.read_only).format(self.format);
+        Ok(Box::new(JsonFileStore { patches: self.patches, actor: self.actor.clone(), ..store }))
+    }
+
+    fn load_cache(&self, name: &str) -> Option<String> {
+        fs::read_to_string(self.cache_path(name)).ok()
+    }
+
+    /// Writes the cache in place; a torn write only means a cache miss
+    fn save_cache(&mut self, name: &str, content: &str) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        let path = self.cache_path(name);
+        fs::write(&path, content).map_err(|e| TodoError::storage("write to", &path, e))?;
+        Ok(())
+    }
+
+    fn convert(&mut self, format: StorageFormat) -> Result<bool> {
+        self.check_writable()?;
+        let todos = self.load()?;
+        if self.format == format {
+            return Ok(false);
+        }
+        self.format = format;
+        self.write_whole(&todos)?;
+        Ok(true)
+    }
+
+    /// Rewrites the file with the patch log applied, then removes the log
+    fn compact(&mut self) -> Result<usize> {
+        self.check_writable()?;
+        let todos = self.load()?;
+        let patches = self.on_disk.as_ref().map_or(0, |d| d.patches + usize::from(d.torn));
+        if patches > 0 {
+            self.write_whole(&todos)?;
+        }
+        Ok(patches)
+    }
+}
+
+/// Keeps todos, the journal, and the trash in memory, e.g. for tests
+///
+/// Nothing touches the file system, so commands can be run against it
+/// through [`crate::app::dispatch`] and the results inspected directly.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    pub todos: Vec<Todo>,
+    pub journal: Vec<JournalEntry>,
+    pub history: Vec<FieldChange>,
+    pub trash: Vec<TrashedTodo>,
+    /// Who saves are credited to
+    pub actor: Option<String>,
+    saved_at: Option<SystemTime>,
+}
+
+impl MemoryStore {
+    /// Creates an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a store holding `todos`
+    pub fn with_todos(todos: Vec<Todo>) -> Self {
+        Self { todos, ..Self::default() }
+    }
+}
+
+impl TodoStore for MemoryStore {
+    fn load(&mut self) -> Result<Vec<Todo>> {
+        Ok(self.todos.clone())
+    }
+
+    fn save(&mut self, todos: &[Todo]) -> Result<()> {
+        let mut todos = todos.to_vec();
+        let now = chrono::Local::now();
+        stamp_modified(&self.todos, &mut todos, now, self.actor.as_deref());
+        self.history.extend(history::changes(&self.todos, &todos, now)?);
+        self.todos = todos;
+        self.saved_at = Some(SystemTime::now());
+        Ok(())
+    }
+
+    fn append_journal(&mut self, entry: &JournalEntry) -> Result<()> {
+        self.journal.push(entry.clone());
+        Ok(())
+    }
+
+    fn load_journal(&self) -> Result<Vec<JournalEntry>> {
+        Ok(self.journal.clone())
+    }
+
+    fn load_history(&self) -> Result<Vec<FieldChange>> {
+        Ok(self.history.clone())
+    }
+
+    fn actor(&self) -> Option<&str> {
+        self.actor.as_deref()
+    }
+
+    fn last_modified(&self) -> Option<SystemTime> {
+        self.saved_at
+    }
+
+    fn load_trash(&self) -> Result<Vec<TrashedTodo>> {
+        Ok(self.trash.clone())
+    }
+
+    fn save_trash(&mut self, trash: &[TrashedTodo]) -> Result<()> {
+        self.trash = trash.to_vec();
+        Ok(())
+    }
+}
@@ -0,0 +1,386 @@
+//! Synchronizing the database between machines through a git repository
+
+use crate::models::Todo;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+
+/// Strategy for reconciling the local and remote copies of the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    /// Keep whichever copy was written most recently
+    LastWriteWins,
+    /// Merge todo by todo, keeping additions and removals from both sides
+    #[default]
+    PerItem,
+}
+
+/// Changes applied to the local copy by a merge
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub updated: usize,
+}
+
+impl MergeSummary {
+    /// Returns true if the merge changed nothing locally
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.removed == 0 && self.updated == 0
+    }
+}
+
+/// Compares two todos field by field, apart from when and by whom they were
+/// last modified (`Todo`'s `PartialEq` only compares IDs)
+pub(crate) fn same(a: &Todo, b: &Todo) -> bool {
+    let fields = |todo: &Todo| serde_json::to_value(Todo { modified_at: None, modified_by: None, ..todo.clone() }).ok();
+    fields(a) == fields(b)
+}
+
+/// Picks a side when both changed the same todo since the common base
+///
+/// Completing a todo is the change most worth keeping. Otherwise the side
+/// changed last wins, and the local one on a tie.
+fn resolve_conflict(local: Todo, remote: Todo) -> Todo {
+    if remote.completed != local.completed {
+        return if remote.completed { remote } else { local };
+    }
+    if remote.modified() > local.modified() {
+        remote
+    } else {
+        local
+    }
+}
+
+/// Three-way merges two copies of the database against their common base
+///
+/// Todos are matched by UUID. A todo changed on only one side takes that
+/// side's version; a todo removed on one side and untouched on the other is
+/// dropped; new todos from the remote are appended, renumbered if their ID
+/// is already taken locally.
+pub fn merge_per_item(base: &[Todo], local: Vec<Todo>, remote: Vec<Todo>) -> Vec<Todo> {
+    let base: HashMap<&str, &Todo> = base.iter().map(|t| (t.uuid.as_str(), t)).collect();
+    let local_uuids: HashSet<String> = local.iter().map(|t| t.uuid.clone()).collect();
+    let mut remote_order: Vec<String> = remote.iter().map(|t| t.uuid.clone()).collect();
+    let mut remote: HashMap<String, Todo> = remote.into_iter().map(|t| (t.uuid.clone(), t)).collect();
+    let mut merged = Vec::with_capacity(local.len());
+
+    for todo in local {
+        let base_version = base.get(todo.uuid.as_str()).copied();
+        match remote.remove(&todo.uuid) {
+            Some(theirs) if same(&todo, &theirs) => merged.push(todo),
+            Some(theirs) => match base_version {
+                Some(b) if same(b, &todo) => merged.push(theirs),
+                Some(b) if same(b, &theirs) => merged.push(todo),
+                _ => merged.push(resolve_conflict(todo, theirs)),
+            },
+            // Removed remotely; keep it only if it was edited locally
+            None => match base_version {
+                Some(b) if same(b, &todo) => {}
+                _ => merged.push(todo),
+            },
+        }
+    }
+
+    remote_order.retain(|uuid| !local_uuids.contains(uuid));
+    for uuid in remote_order {
+        let Some(mut theirs) = remote.remove(&uuid) else { continue };
+
+        // Removed locally; keep it only if it was edited remotely
+        if matches!(base.get(uuid.as_str()), Some(b) if same(b, &theirs)) {
+            continue;
+        }
+
+        if merged.iter().any(|t| t.id == theirs.id) {
+            theirs.id = merged.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+        }
+        merged.push(theirs);
+    }
+
+    merged
+}
+
+/// Fields that only make sense together and are merged as one, under the first name
+const STATUS_FIELDS: [&str; 3] = ["status", "completed", "completed_at"];
+
+/// Which copy a merged field is taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Ours,
+    Theirs,
+}
+
+/// A field two copies of the same todo disagree on
+#[derive(Debug, Clone)]
+pub struct FieldConflict {
+    pub id: u64,
+    pub title: String,
+    /// Field name as stored; `status` also stands for `completed` and `completed_at`
+    pub field: String,
+    pub ours: Value,
+    pub theirs: Value,
+    pub ours_modified: DateTime<Local>,
+    pub theirs_modified: DateTime<Local>,
+}
+
+impl FieldConflict {
+    /// Returns the side changed last, ours on a tie
+    pub fn newer(&self) -> Side {
+        if self.theirs_modified > self.ours_modified {
+            Side::Theirs
+        } else {
+            Side::Ours
+        }
+    }
+}
+
+/// Merges two todos field by field, asking `pick` for each field they disagree on
+///
+/// The ID and UUID are always ours, and the merged todo counts as modified when, and
+/// by whom, the later of the two was. Comments from both are kept, in order.
+fn merge_fields(
+    ours: &Todo,
+    theirs: &Todo,
+    pick: &mut dyn FnMut(&FieldConflict) -> Result<Side>,
+) -> Result<Todo> {
+    let (Value::Object(mut merged), Value::Object(other)) = (serde_json::to_value(ours)?, serde_json::to_value(theirs)?)
+    else {
+        return Ok(ours.clone());
+    };
+
+    let mut names: Vec<String> = merged.keys().chain(other.keys()).cloned().collect();
+    names.sort();
+    names.dedup();
+    names.retain(|name| {
+        !matches!(name.as_str(), "id" | "uuid" | "modified_at" | "modified_by" | "comments")
+            && !STATUS_FIELDS[1..].contains(&name.as_str())
+    });
+
+    for name in names {
+        let group: Vec<&str> = if name == STATUS_FIELDS[0] { STATUS_FIELDS.to_vec() } else { vec![name.as_str()] };
+        let value = |map: &Map<String, Value>, field: &str| map.get(field).cloned().unwrap_or(Value::Null);
+        if group.iter().all(|field| value(&merged, field) == value(&other, field)) {
+            continue;
+        }
+
+        let conflict = FieldConflict {
+            id: ours.id,
+            title: ours.title.clone(),
+            field: name.clone(),
+            ours: value(&merged, &name),
+            theirs: value(&other, &name),
+            ours_modified: ours.modified(),
+            theirs_modified: theirs.modified(),
+        };
+        if pick(&conflict)? == Side::Theirs {
+            for field in group {
+                merged.insert(field.to_string(), value(&other, field));
+            }
+        }
+    }
+
+    let mut todo: Todo = serde_json::from_value(Value::Object(merged)).context("Failed to merge todo fields")?;
+    let later = if theirs.modified_at > ours.modified_at { theirs } else { ours };
+    todo.modified_at = later.modified_at;
+    todo.modified_by = later.modified_by.clone();
+    // Comments are only ever added, so both copies' are kept rather than picked between
+    todo.comments = ours.comments.iter().chain(&theirs.comments).cloned().collect();
+    todo.comments.sort_by_key(|c| c.at);
+    todo.comments.dedup();
+    Ok(todo)
+}
+
+/// Merges two copies of the database that have no common base, such as sync conflict copies
+///
+/// Todos are matched by UUID, or by ID and creation time for copies made
+/// before UUIDs were kept. Every todo in either copy is kept, since without
+/// a base a removal can't be told apart from an addition. Where matched
+/// todos disagree on a field, `pick` chooses the side; todos only in
+/// `theirs` are appended, renumbered if their ID is already taken.
+pub fn merge_copies(
+    ours: Vec<Todo>,
+    theirs: Vec<Todo>,
+    mut pick: impl FnMut(&FieldConflict) -> Result<Side>,
+) -> Result<Vec<Todo>> {
+    let mut theirs: Vec<Option<Todo>> = theirs.into_iter().map(Some).collect();
+    let mut merged = Vec::with_capacity(ours.len());
+
+    for todo in ours {
+        let matching = theirs.iter().position(|t| matches!(t, Some(t) if t.uuid == todo.uuid)).or_else(|| {
+            theirs
+                .iter()
+                .position(|t| matches!(t, Some(t) if t.id == todo.id && t.created_at == todo.created_at))
+        });
+        match matching.and_then(|i| theirs[i].take()) {
+            Some(other) if !same(&todo, &other) => merged.push(merge_fields(&todo, &other, &mut pick)?),
+            _ => merged.push(todo),
+        }
+    }
+
+    for mut other in theirs.into_iter().flatten() {
+        if merged.iter().any(|t| t.id == other.id) {
+            other.id = merged.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+        }
+        merged.push(other);
+    }
+
+    Ok(merged)
+}
+
+/// Counts how `after` differs from `before`, matching todos by UUID
+pub fn summarize(before: &[Todo], after: &[Todo]) -> MergeSummary {
+    let before: HashMap<&str, &Todo> = before.iter().map(|t| (t.uuid.as_str(), t)).collect();
+    let after_uuids: HashSet<&str> = after.iter().map(|t| t.uuid.as_str()).collect();
+    let mut summary = MergeSummary {
+        removed: before.keys().filter(|uuid| !after_uuids.contains(*uuid)).count(),
+        ..MergeSummary::default()
+    };
+
+    for todo in after {
+        match before.get(todo.uuid.as_str()) {
+            None => summary.added += 1,
+            Some(old) if !same(old, todo) => summary.updated += 1,
+            Some(_) => {}
+        }
+    }
+
+    summary
+}
+
+/// A git working copy used to exchange the database with a remote
+///
+/// Each list is kept in its own file, named after its database (the
+/// default list in `todos.json`, `--list work` in `todos-work.json`), so
+/// several lists can share a repository and remote.
+pub struct SyncRepo {
+    dir: PathBuf,
+    file: String,
+}
+
+impl SyncRepo {
+    /// Opens the repository at `dir` for the list named `name`, initializing it if needed
+    pub fn open(dir: &Path, name: &str) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let repo = Self { dir: dir.to_path_buf(), file: format!("{}.json", name) };
+        if !dir.join(".git").exists() {
+            repo.git(&["init", "--quiet"])?;
+        }
+        Ok(repo)
+    }
+
+    /// Runs a git command in the repository and returns its standard output
+    fn git(&self, args: &[&str]) -> Result<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.dir)
+            .args(args)
+            .output()
+            .with_context(|| "Failed to run git; is it installed?")?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Points the `origin` remote at `url`
+    pub fn set_remote(&self, url: &str) -> Result<()> {
+        if self.git(&["remote", "get-url", "origin"]).is_ok() {
+            self.git(&["remote", "set-url", "origin", url])?;
+        } else {
+            self.git(&["remote", "add", "origin", url])?;
+        }
+        Ok(())
+    }
+
+    /// Reads the database as of the last sync commit, if any
+    pub fn committed_todos(&self) -> Result<Vec<Todo>> {
+        self.read_revision("HEAD")
+    }
+
+    /// Fetches `branch` from the remote, returning its todos and commit time
+    ///
+    /// Returns `None` if the remote does not have the branch yet.
+    pub fn fetch(&self, branch: &str) -> Result<Option<(Vec<Todo>, i64)>> {
+        if self.git(&["ls-remote", "--heads", "origin", branch])?.trim().is_empty() {
+            return Ok(None);
+        }
+
+        self.git(&["fetch", "--quiet", "origin", branch])?;
+        let todos = self.read_revision("FETCH_HEAD")?;
+        let time = self
+            .git(&["log", "-1", "--format=%ct", "FETCH_HEAD"])?
+            .trim()
+            .parse()
+            .unwrap_or(0);
+
+        Ok(Some((todos, time)))
+    }
+
+    fn read_revision(&self, revision: &str) -> Result<Vec<Todo>> {
+        let spec = format!("{}:{}", revision, self.file);
+        match self.git(&["show", &spec]) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {} from {}", self.file, revision)),
+            Err(_) => Ok(Vec::new()),
+        }
+    }
+
+    /// Commits `todos` and merges in the fetched remote head
+    ///
+    /// Our file already holds the merged todos, so it's kept as it is;
+    /// the other lists' files take the remote's changes. Returns true if a
+    /// new commit was created.
+    pub fn commit(&self, todos: &[Todo], message: &str, merged_remote: bool) -> Result<bool> {
+        let content = serde_json::to_string_pretty(todos)
+            .with_context(|| "Failed to serialize todos")?;
+        let changed = self.write(&content)?;
+        if changed {
+            self.git(&["commit", "--quiet", "-m", message])?;
+        }
+
+        // Record the remote head as a parent so the push fast-forwards
+        if merged_remote {
+            self.git(&[
+                "merge", "--quiet", "--no-commit", "--no-ff", "-X", "ours",
+                "--allow-unrelated-histories", "FETCH_HEAD",
+            ])?;
+            self.write(&content)?;
+            if self.git(&["rev-parse", "--quiet", "--verify", "MERGE_HEAD"]).is_ok() {
+                self.git(&["commit", "--quiet", "--no-edit"])?;
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Writes our file and stages it, returning true if anything is left to commit
+    fn write(&self, content: &str) -> Result<bool> {
+        let path = self.dir.join(&self.file);
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write to {}", path.display()))?;
+        self.git(&["add", &self.file])?;
+        Ok(!self.git(&["status", "--porcelain"])?.trim().is_empty())
+    }
+
+    /// Pushes the current branch to `branch` on the remote
+    pub fn push(&self, branch: &str) -> Result<()> {
+        let refspec = format!("HEAD:refs/heads/{}", branch);
+        self.git(&["push", "--quiet", "origin", &refspec])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+//! Reusable todo shapes, kept in `templates.json` in the config directory
+
+use crate::config::config_dir;
+use crate::duration::HumanDuration;
+use crate::error::TodoError;
+use crate::models::{Priority, Todo, TodoBuilder};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+const TEMPLATES_FILE: &str = "templates.json";
+
+/// The fields a template fills in for a new todo
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Template {
+    /// Title used when `add` is given none
+    pub title: String,
+    pub priority: Priority,
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+    pub project: Option<String>,
+    pub reminders: Vec<HumanDuration>,
+    pub estimate: Option<HumanDuration>,
+    pub icon: Option<String>,
+    pub color: Option<String>,
+}
+
+impl Template {
+    /// Takes the reusable fields of a todo, leaving out dates and progress
+    pub fn from_todo(todo: &Todo) -> Self {
+        Self {
+            title: todo.title.clone(),
+            priority: todo.priority,
+            tags: todo.tags.clone(),
+            notes: todo.notes.clone(),
+            project: todo.project.clone(),
+            reminders: todo.reminders.clone(),
+            estimate: todo.estimate,
+            icon: todo.icon.clone(),
+            color: todo.color.clone(),
+        }
+    }
+
+    /// Starts a todo from the template, titled `title` or the template's own title
+    pub fn builder(&self, title: Option<String>) -> TodoBuilder {
+        let mut builder = TodoBuilder::new(title.unwrap_or_else(|| self.title.clone()))
+            .priority(self.priority)
+            .tags(self.tags.iter().cloned())
+            .reminders(self.reminders.iter().copied())
+            .estimate(self.estimate)
+            .project(self.project.clone())
+            .icon(self.icon.clone())
+            .color(self.color.clone());
+        if let Some(notes) = &self.notes {
+            builder = builder.notes(notes.clone());
+        }
+        builder
+    }
+}
+
+/// Returns the path of the templates file
+pub fn templates_path() -> PathBuf {
+    config_dir().join(TEMPLATES_FILE)
+}
+
+/// Loads every template by name
+pub fn load_templates() -> Result<BTreeMap<String, Template>> {
+    let path = templates_path();
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| TodoError::storage("read", &path, e))?;
+    let templates = serde_json::from_str(&content).map_err(|source| TodoError::Parse { path, source })?;
+    Ok(templates)
+}
+
+/// Replaces the stored templates
+pub fn save_templates(templates: &BTreeMap<String, Template>) -> Result<()> {
+    let path = templates_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| TodoError::storage("create", dir, e))?;
+    }
+    let content = serde_json::to_string_pretty(templates)?;
+    fs::write(&path, content).map_err(|e| TodoError::storage("write to", &path, e))?;
+    Ok(())
+}
+
+/// Returns the template called `name`
+pub fn find_template(name: &str) -> Result<Template> {
+    load_templates()?.remove(name).ok_or_else(|| {
+        TodoError::Validation(format!("No template named '{}'; save one with `template save`", name)).into()
+    })
+}
@@ -0,0 +1,196 @@
+//! Minimal terminal control: raw mode, key reading, screen clearing, paging,
+//! and the clipboard
+
+use anyhow::{anyhow, Context, Result};
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+/// A key press read in raw mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Enter,
+    Escape,
+    CtrlC,
+    Char(char),
+    Other,
+}
+
+/// Returns true if both stdin and stdout are attached to a terminal
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+/// Returns the terminal width in columns
+///
+/// `$COLUMNS` takes precedence; otherwise the width is asked from the
+/// terminal, which only works when stdout is one.
+pub fn width() -> Option<usize> {
+    if let Some(columns) = std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()).filter(|&c| c > 0) {
+        return Some(columns);
+    }
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    stty(&["size"]).ok()?.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Returns the terminal height in lines
+///
+/// `$LINES` takes precedence, as `$COLUMNS` does for [`width`].
+pub fn height() -> Option<usize> {
+    if let Some(lines) = std::env::var("LINES").ok().and_then(|l| l.parse().ok()).filter(|&l| l > 0) {
+        return Some(lines);
+    }
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    stty(&["size"]).ok()?.split_whitespace().next()?.parse().ok()
+}
+
+fn stty(args: &[&str]) -> Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .with_context(|| "Failed to run stty")?;
+
+    if !output.status.success() {
+        return Err(anyhow!("stty {} failed", args.join(" ")));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Puts the terminal in raw mode until dropped
+///
+/// Keys are delivered one at a time without echo. Output written while the
+/// guard is alive needs explicit `\r\n` line endings.
+pub struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    /// Enables raw mode, remembering the previous settings
+    pub fn enable() -> Result<Self> {
+        if !is_interactive() {
+            return Err(anyhow!("Interactive mode requires a terminal"));
+        }
+
+        let saved = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        print!("\x1b[?25l");
+        io::stdout().flush()?;
+        Ok(Self { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        print!("\x1b[?25h");
+        let _ = io::stdout().flush();
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+/// Blocks until a key is pressed and decodes it
+pub fn read_key() -> Result<Key> {
+    let mut stdin = io::stdin().lock();
+    let mut byte = [0u8; 1];
+    stdin.read_exact(&mut byte)?;
+
+    let key = match byte[0] {
+        0x1b => {
+            let mut seq = [0u8; 2];
+            match stdin.read_exact(&mut seq) {
+                Ok(()) if seq == *b"[A" => Key::Up,
+                Ok(()) if seq == *b"[B" => Key::Down,
+                Ok(()) => Key::Other,
+                Err(_) => Key::Escape,
+            }
+        }
+        0x03 => Key::CtrlC,
+        b'\r' | b'\n' => Key::Enter,
+        b if b.is_ascii() => Key::Char(b as char),
+        _ => Key::Other,
+    };
+
+    Ok(key)
+}
+
+/// Clears the screen and moves the cursor to the top-left corner
+pub fn clear_screen() {
+    print!("\x1b[2J\x1b[H");
+}
+
+/// Prints `text`, through `$PAGER` (`less -R` by default) when stdout is a
+/// terminal and the text is taller than it
+///
+/// Falls back to printing directly if the pager can't be started.
+pub fn page(text: &str) -> Result<()> {
+    let fits = height().is_none_or(|lines| text.lines().count() < lines);
+    if fits || !io::stdout().is_terminal() {
+        print!("{}", text);
+        return Ok(());
+    }
+
+    let pager = std::env::var("PAGER").ok().filter(|p| !p.trim().is_empty()).unwrap_or_else(|| "less -R".to_string());
+    let mut words = pager.split_whitespace();
+    let program = words.next().unwrap_or("less");
+    let Ok(mut child) = Command::new(program).args(words).stdin(Stdio::piped()).spawn() else {
+        print!("{}", text);
+        return Ok(());
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closing early, e.g. on `q`, is not an error
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait().with_context(|| format!("Failed to wait for the pager '{}'", pager))?;
+    Ok(())
+}
+
+/// Reads the text on the system clipboard
+///
+/// Shells out to `pbpaste` on macOS and PowerShell on Windows; elsewhere
+/// `wl-paste`, `xclip`, and `xsel` are tried in turn.
+pub fn read_clipboard() -> Result<String> {
+    let tools: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("pbpaste", &[])]
+    } else if cfg!(windows) {
+        &[("powershell", &["-NoProfile", "-Command", "Get-Clipboard"])]
+    } else {
+        &[
+            ("wl-paste", &["--no-newline"]),
+            ("xclip", &["-selection", "clipboard", "-out"]),
+            ("xsel", &["--clipboard", "--output"]),
+        ]
+    };
+
+    for (program, args) in tools {
+        let Ok(output) = Command::new(program).args(*args).stderr(Stdio::null()).output() else {
+            continue;
+        };
+        if output.status.success() {
+            return String::from_utf8(output.stdout).context("The clipboard doesn't hold text");
+        }
+    }
+    let names: Vec<&str> = tools.iter().map(|(program, _)| *program).collect();
+    Err(anyhow!("Could not read the clipboard; install one of: {}", names.join(", ")))
+}
+
+/// Asks a yes/no question, taking anything but `y` or `yes` as no
+///
+/// Reads the answer from stdin even when it isn't a terminal, so answers
+/// can be piped in. Fails when stdin ends without an answer, rather than
+/// taking that as a no, so scripts learn to pass `--yes`.
+pub fn confirm(question: &str) -> Result<bool> {
+    print!("{} (y/N): ", question);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        println!();
+        return Err(anyhow!("No answer to confirm on stdin; pass --yes to go ahead without asking"));
+    }
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -0,0 +1,134 @@
+//! Gantt-style timeline of todos between their start and due dates
+//!
+//! Each todo gets a bar from its start date (or creation date) to its due
+//! date, drawn against a day axis grouped into weeks. Bars cut off by the
+//! axis end in `<` or `>`. A todo that starts before one of its
+//! dependencies is due is flagged, and a closing row counts how many bars
+//! overlap on each day.
+
+use crate::models::Todo;
+use crate::width::{pad, text_width, truncate};
+use chrono::{Datelike, Duration, NaiveDate};
+use colored::*;
+use std::collections::HashMap;
+
+/// Widest the label column gets
+const MAX_LABEL_WIDTH: usize = 30;
+
+/// Space kept after the bars for dependency notes
+const NOTE_WIDTH: usize = 16;
+
+/// The dates a todo spans on the timeline, if it has a due date
+pub fn span(todo: &Todo) -> Option<(NaiveDate, NaiveDate)> {
+    let due = todo.due_date?;
+    let start = todo.start_date.unwrap_or_else(|| todo.created_at.date_naive()).min(due);
+    Some((start, due))
+}
+
+/// Renders the todos with a due date across `days` days from `from`, fitting `width` columns
+///
+/// Todos are ordered by start and then due date; those entirely outside the
+/// axis are left out. `today` is marked on the axis.
+pub fn render_timeline(todos: &[Todo], from: NaiveDate, days: usize, today: NaiveDate, width: usize) -> String {
+    let to = from + Duration::days(days as i64 - 1);
+    let mut rows: Vec<(&Todo, NaiveDate, NaiveDate)> = todos
+        .iter()
+        .filter_map(|t| span(t).map(|(start, due)| (t, start, due)))
+        .filter(|&(_, start, due)| start <= to && due >= from)
+        .collect();
+    if rows.is_empty() {
+        return "No todos with due dates in this period.\n".to_string();
+    }
+    rows.sort_by_key(|&(t, start, due)| (start, due, t.id));
+
+    let labels: Vec<String> = rows.iter().map(|(t, _, _)| format!("#{} {}", t.id, t.title)).collect();
+    let widest = labels.iter().map(|l| text_width(l)).max().unwrap_or(0);
+    let label_width = widest.clamp("Overlap".len(), MAX_LABEL_WIDTH);
+    let cell = (width.saturating_sub(label_width + 1 + NOTE_WIDTH) / days).max(1);
+
+    let column = |date: NaiveDate| (date - from).num_days() as usize;
+    let mut lines = Vec::new();
+
+    // Week starts, then day initials with today marked
+    let mut weeks = " ".repeat(label_width + 1);
+    let mut day_axis = " ".repeat(label_width + 1);
+    for i in 0..days {
+        let date = from + Duration::days(i as i64);
+        if i % 7 == 0 {
+            weeks.push_str(&pad(&truncate(&date.format("%b %-d").to_string(), 7 * cell - 1), 7 * cell));
+        }
+        let initial = date.weekday().to_string()[..1].to_string();
+        let initial = if date == today { initial.bold().underline().to_string() } else { initial };
+        day_axis.push_str(&initial);
+        day_axis.push_str(&" ".repeat(cell - 1));
+    }
+    lines.push(weeks.trim_end().to_string());
+    lines.push(day_axis.trim_end().to_string());
+
+    let spans: HashMap<&str, (NaiveDate, NaiveDate)> =
+        rows.iter().map(|&(t, start, due)| (t.uuid.as_str(), (start, due))).collect();
+    let ids: HashMap<&str, u64> = todos.iter().map(|t| (t.uuid.as_str(), t.id)).collect();
+    let mut load = vec![0usize; days];
+
+    for ((todo, start, due), label) in rows.iter().zip(&labels) {
+        let first = column((*start).max(from));
+        let last = column((*due).min(to));
+        let mut bar = String::new();
+        for (i, load) in load.iter_mut().enumerate() {
+            let date = from + Duration::days(i as i64);
+            let fill = "█".repeat(cell - 1);
+            if i < first || i > last {
+                bar.push_str(if date == today { "|" } else { "·" });
+                bar.push_str(&" ".repeat(cell - 1));
+                continue;
+            }
+            *load += 1;
+            if i == first && *start < from {
+                bar.push('<');
+                bar.push_str(&fill);
+            } else if i == last && *due > to {
+                bar.push_str(&fill);
+                bar.push('>');
+            } else {
+                bar.push('█');
+                bar.push_str(&fill);
+            }
+        }
+
+        let mut line = format!("{} {}", pad(&truncate(label, label_width), label_width), bar.trim_end());
+        let clashes: Vec<u64> = todo
+            .depends_on
+            .iter()
+            .filter(|uuid| spans.get(uuid.as_str()).is_some_and(|&(_, dep_due)| *start <= dep_due))
+            .filter_map(|uuid| ids.get(uuid.as_str()).copied())
+            .collect();
+        let after: Vec<String> = todo
+            .depends_on
+            .iter()
+            .filter_map(|uuid| ids.get(uuid.as_str()).map(|id| format!("#{}", id)))
+            .collect();
+        if !clashes.is_empty() {
+            let ids: Vec<String> = clashes.iter().map(|id| format!("#{}", id)).collect();
+            line.push_str(&format!(" ! starts before {} is due", ids.join(", ")).red().to_string());
+        } else if !after.is_empty() {
+            line.push_str(&format!(" after {}", after.join(", ")));
+        }
+        lines.push(line);
+    }
+
+    // Days where more than one bar runs
+    let mut overlap = pad("Overlap", label_width);
+    overlap.push(' ');
+    for count in load {
+        let mark = match count {
+            0 | 1 => " ".to_string(),
+            2..=9 => count.to_string().yellow().to_string(),
+            _ => "+".yellow().to_string(),
+        };
+        overlap.push_str(&mark);
+        overlap.push_str(&" ".repeat(cell - 1));
+    }
+    lines.push(overlap.trim_end().to_string());
+
+    lines.join("\n") + "\n"
+}
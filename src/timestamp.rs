@@ -0,0 +1,29 @@
+//! Stored timestamps, written in UTC and shown in local time
+//!
+//! Timestamps are kept as `DateTime<Local>`, which compare as instants
+//! whatever offset they carry, but are always written with a `Z` offset so
+//! the same moment reads the same on every machine and across DST changes.
+//! Files from before this kept the writer's local offset; they still load,
+//! and the file store rewrites them in UTC the first time it reads them.
+//!
+//! Use with `#[serde(serialize_with = "timestamp::serialize")]`, or
+//! [`option::serialize`] for optional fields. Reading needs nothing, since
+//! `DateTime<Local>` accepts any offset.
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Serialize, Serializer};
+
+/// Writes a timestamp in UTC
+pub fn serialize<S: Serializer>(at: &DateTime<Local>, serializer: S) -> Result<S::Ok, S::Error> {
+    at.with_timezone(&Utc).serialize(serializer)
+}
+
+/// Writing optional timestamps in UTC
+pub mod option {
+    use super::*;
+
+    /// Writes an optional timestamp in UTC
+    pub fn serialize<S: Serializer>(at: &Option<DateTime<Local>>, serializer: S) -> Result<S::Ok, S::Error> {
+        at.map(|at| at.with_timezone(&Utc)).serialize(serializer)
+    }
+}
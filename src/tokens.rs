@@ -0,0 +1,144 @@
+//! Inline metadata in free-text titles
+//!
+//! `capture` and batch adds take a single line such as
+//! `Submit report !high @work #finance due:friday` and pull the priority,
+//! project, tags, and due date out of it. Whatever isn't a token is left as
+//! the title, in order.
+
+use crate::cli::parse_date_input;
+use crate::models::{DateInput, Priority, TodoBuilder};
+
+/// The fields found in a line, and what's left of it as the title
+#[derive(Debug, Clone, Default)]
+pub struct Tokens {
+    pub title: String,
+    /// From `!high` or `prio:high`
+    pub priority: Option<Priority>,
+    /// From `@work`; the last one wins
+    pub project: Option<String>,
+    /// From `#finance`, in the order given
+    pub tags: Vec<String>,
+    /// From `due:friday`, in any form `add --due` takes
+    pub due: Option<DateInput>,
+}
+
+impl Tokens {
+    /// Sets the fields found on `builder`, leaving the others as they were
+    pub fn apply(self, builder: TodoBuilder) -> TodoBuilder {
+        let mut builder = builder.title(self.title).tags(self.tags);
+        if let Some(priority) = self.priority {
+            builder = builder.priority(priority);
+        }
+        if self.project.is_some() {
+            builder = builder.project(self.project);
+        }
+        if let Some(due) = self.due {
+            builder = builder.due_date(Some(due.to_date()));
+        }
+        builder
+    }
+}
+
+/// Returns what follows `prefix` in `word`, if anything does
+fn sigil<'a>(word: &'a str, prefix: &str) -> Option<&'a str> {
+    word.strip_prefix(prefix).filter(|rest| !rest.is_empty())
+}
+
+/// Splits `line` into a title and the tokens in it
+///
+/// A sigil on its own, such as a lone `!` or `#`, is kept in the title, as
+/// is a word escaped with a leading backslash, without it: `\#12` is `#12`.
+/// Fails on an unknown priority or a due date that can't be read.
+pub fn parse(line: &str) -> Result<Tokens, String> {
+    let mut tokens = Tokens::default();
+    let mut title = Vec::new();
+    for word in line.split_whitespace() {
+        if let Some(word) = sigil(word, "\\") {
+            title.push(word);
+        } else if let Some(due) = word.strip_prefix("due:") {
+            tokens.due = Some(parse_date_input(due)?);
+        } else if let Some(priority) = sigil(word, "prio:").or_else(|| sigil(word, "!")) {
+            tokens.priority = Some(priority.parse()?);
+        } else if let Some(project) = sigil(word, "@") {
+            tokens.project = Some(project.to_string());
+        } else if let Some(tag) = sigil(word, "#") {
+            tokens.tags.push(tag.to_string());
+        } else {
+            title.push(word);
+        }
+    }
+    tokens.title = title.join(" ");
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn pulls_each_kind_of_token_out_of_the_title() {
+        let tokens = parse("Submit report !high @work #finance #q3 due:2025-03-14").unwrap();
+        assert_eq!(tokens.title, "Submit report");
+        assert_eq!(tokens.priority, Some("high".parse().unwrap()));
+        assert_eq!(tokens.project.as_deref(), Some("work"));
+        assert_eq!(tokens.tags, ["finance", "q3"]);
+        assert_eq!(tokens.due.map(|due| due.to_date()), NaiveDate::from_ymd_opt(2025, 3, 14));
+    }
+
+    #[test]
+    fn later_projects_and_priorities_win() {
+        let tokens = parse("Plan @home !low prio:urgent @work").unwrap();
+        assert_eq!(tokens.title, "Plan");
+        assert_eq!(tokens.priority, Some("urgent".parse().unwrap()));
+        assert_eq!(tokens.project.as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn lone_sigils_and_sigils_inside_words_stay_in_the_title() {
+        let tokens = parse("Learn C# ! and mail a@b.c about # and +tag").unwrap();
+        assert_eq!(tokens.title, "Learn C# ! and mail a@b.c about # and +tag");
+        assert!(tokens.tags.is_empty());
+        assert_eq!(tokens.project, None);
+        assert_eq!(tokens.priority, None);
+    }
+
+    #[test]
+    fn quotes_are_title_text_and_group_nothing() {
+        let tokens = parse(r#"Read "the #1 hits" and '!high' notes"#).unwrap();
+        assert_eq!(tokens.title, r#"Read "the hits" and '!high' notes"#);
+        assert_eq!(tokens.tags, ["1"]);
+        assert_eq!(tokens.priority, None);
+    }
+
+    #[test]
+    fn a_backslash_keeps_a_token_in_the_title() {
+        let tokens = parse(r"Fix \#12 \!important \due:today \\ \ #bug").unwrap();
+        assert_eq!(tokens.title, r"Fix #12 !important due:today \ \");
+        assert_eq!(tokens.tags, ["bug"]);
+        assert_eq!(tokens.priority, None);
+        assert!(tokens.due.is_none());
+    }
+
+    #[test]
+    fn whitespace_between_words_is_collapsed() {
+        let tokens = parse("  Buy\tmilk   #home  ").unwrap();
+        assert_eq!(tokens.title, "Buy milk");
+        assert_eq!(tokens.tags, ["home"]);
+        assert_eq!(parse("").unwrap().title, "");
+    }
+
+    #[test]
+    fn malformed_due_dates_fail() {
+        for line in ["Pay rent due:", "Pay rent due:someday", "Pay rent due:2025-13-01", "Pay rent due:+3x"] {
+            assert!(parse(line).is_err(), "{}", line);
+        }
+        assert!(parse("Pay rent due:+3y").unwrap_err().contains("Invalid date offset"));
+    }
+
+    #[test]
+    fn unknown_priorities_fail() {
+        assert!(parse("Pay rent !whenever").is_err());
+        assert!(parse("Pay rent prio:").is_ok_and(|tokens| tokens.title == "Pay rent prio:"));
+    }
+}
@@ -0,0 +1,195 @@
+//! How urgent an open todo is, as a single number
+//!
+//! The score adds up a few terms, each between 0 and 1 and weighted by a
+//! coefficient from the `urgency` section of the config: priority (the
+//! level's place in the scale), how close the due date is, how long ago the
+//! todo was added, and how many tags it has. Todos waiting on a dependency
+//! add the `blocked` coefficient, which is negative by default. Closed todos
+//! score 0.
+
+use crate::models::{priority_scale, Todo};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Weights of the terms making up the score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Coefficients {
+    /// Weight of the highest priority; lower levels get a share by rank
+    pub priority: f64,
+    /// Weight of a todo a week or more overdue; see [`due_term`]
+    pub due: f64,
+    /// Weight of a todo a year old or older
+    pub age: f64,
+    /// Weight of a todo with three tags or more
+    pub tags: f64,
+    /// Added for todos waiting on a dependency
+    pub blocked: f64,
+}
+
+impl Default for Coefficients {
+    fn default() -> Self {
+        Self {
+            priority: 6.0,
+            due: 12.0,
+            age: 2.0,
+            tags: 1.0,
+            blocked: -5.0,
+        }
+    }
+}
+
+static COEFFICIENTS: OnceLock<Coefficients> = OnceLock::new();
+
+/// Sets the coefficients for the rest of the process
+///
+/// Only the first call has an effect.
+pub fn set_coefficients(coefficients: Coefficients) {
+    let _ = COEFFICIENTS.set(coefficients);
+}
+
+/// Returns the coefficients in use, the defaults unless set otherwise
+pub fn coefficients() -> &'static Coefficients {
+    COEFFICIENTS.get_or_init(Coefficients::default)
+}
+
+/// Returns the urgency of `todo` as of `now`, with the configured coefficients
+pub fn urgency(todo: &Todo, now: DateTime<Local>, blocked: bool) -> f64 {
+    score(todo, now, blocked, coefficients())
+}
+
+/// Returns the urgency of `todo` as of `now`, with the given coefficients
+pub fn score(todo: &Todo, now: DateTime<Local>, blocked: bool, coefficients: &Coefficients) -> f64 {
+    if todo.completed {
+        return 0.0;
+    }
+    let mut score = coefficients.priority * priority_term(todo)
+        + coefficients.due * due_term(todo, now)
+        + coefficients.age * age_term(todo, now)
+        + coefficients.tags * tags_term(todo);
+    if blocked {
+        score += coefficients.blocked;
+    }
+    score
+}
+
+/// The priority's rank as a share of the highest, 0 on a scale with one level
+pub fn priority_term(todo: &Todo) -> f64 {
+    let top = priority_scale().levels().len().saturating_sub(1);
+    if top == 0 {
+        return 0.0;
+    }
+    todo.priority.rank() as f64 / top as f64
+}
+
+/// 1 for todos a week or more overdue, falling evenly to 0.2 for those due
+/// two weeks from now, and staying there for later ones; 0 without a due date
+pub fn due_term(todo: &Todo, now: DateTime<Local>) -> f64 {
+    let Some(due) = todo.due_date else {
+        return 0.0;
+    };
+    let days_overdue = (now.date_naive() - due).num_days();
+    if days_overdue >= 7 {
+        1.0
+    } else if days_overdue >= -14 {
+        (days_overdue + 14) as f64 * 0.8 / 21.0 + 0.2
+    } else {
+        0.2
+    }
+}
+
+/// The todo's age as a share of a year, at most 1
+pub fn age_term(todo: &Todo, now: DateTime<Local>) -> f64 {
+    let days = (now - todo.created_at).num_days().max(0);
+    (days as f64 / 365.0).min(1.0)
+}
+
+/// 0.8 for one tag, 0.9 for two, and 1 for three or more
+pub fn tags_term(todo: &Todo) -> f64 {
+    match todo.tags.len() {
+        0 => 0.0,
+        1 => 0.8,
+        2 => 0.9,
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Priority, TodoBuilder};
+    use chrono::{Duration, TimeZone};
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 6, 15, 12, 0, 0).unwrap()
+    }
+
+    fn todo() -> Todo {
+        Todo { created_at: now(), ..TodoBuilder::new("Test").build(1).unwrap() }
+    }
+
+    fn due_in(days: i64) -> Todo {
+        Todo { due_date: Some(now().date_naive() + Duration::days(days)), ..todo() }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn priority_term_is_the_share_of_the_highest_rank() {
+        assert_close(priority_term(&Todo { priority: Priority::lowest(), ..todo() }), 0.0);
+        assert_close(priority_term(&Todo { priority: Priority::default(), ..todo() }), 1.0 / 3.0);
+        assert_close(priority_term(&Todo { priority: Priority::highest(), ..todo() }), 1.0);
+    }
+
+    #[test]
+    fn due_term_rises_from_two_weeks_ahead_to_a_week_overdue() {
+        assert_close(due_term(&todo(), now()), 0.0);
+        assert_close(due_term(&due_in(60), now()), 0.2);
+        assert_close(due_term(&due_in(14), now()), 0.2);
+        assert_close(due_term(&due_in(0), now()), 14.0 * 0.8 / 21.0 + 0.2);
+        assert_close(due_term(&due_in(-6), now()), 20.0 * 0.8 / 21.0 + 0.2);
+        assert_close(due_term(&due_in(-7), now()), 1.0);
+        assert_close(due_term(&due_in(-400), now()), 1.0);
+    }
+
+    #[test]
+    fn age_term_is_clamped_between_zero_and_a_year() {
+        assert_close(age_term(&todo(), now()), 0.0);
+        assert_close(age_term(&todo(), now() - Duration::days(73)), 0.0);
+        assert_close(age_term(&todo(), now() + Duration::days(73)), 0.2);
+        assert_close(age_term(&todo(), now() + Duration::days(365)), 1.0);
+        assert_close(age_term(&todo(), now() + Duration::days(1000)), 1.0);
+    }
+
+    #[test]
+    fn tags_term_levels_off_at_three_tags() {
+        let tagged = |count: usize| Todo { tags: (0..count).map(|i| format!("tag{}", i)).collect(), ..todo() };
+        let terms: Vec<f64> = (0..5).map(|count| tags_term(&tagged(count))).collect();
+        assert_eq!(terms, [0.0, 0.8, 0.9, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn score_weighs_each_term_by_its_coefficient() {
+        let coefficients = Coefficients { priority: 1.0, due: 10.0, age: 100.0, tags: 1000.0, blocked: -10000.0 };
+        let todo = Todo {
+            priority: Priority::highest(),
+            tags: vec!["home".into()],
+            ..due_in(-7)
+        };
+        let later = now() + Duration::days(73);
+        assert_close(score(&todo, later, false, &coefficients), 1.0 + 10.0 + 20.0 + 800.0);
+        assert_close(score(&todo, later, true, &coefficients), 1.0 + 10.0 + 20.0 + 800.0 - 10000.0);
+        let zero = Coefficients { priority: 0.0, due: 0.0, age: 0.0, tags: 0.0, blocked: 0.0 };
+        assert_close(score(&todo, later, true, &zero), 0.0);
+    }
+
+    #[test]
+    fn closed_todos_score_zero_even_when_blocked() {
+        let todo = Todo { completed: true, priority: Priority::highest(), ..due_in(-30) };
+        assert_close(score(&todo, now(), false, &Coefficients::default()), 0.0);
+        assert_close(score(&todo, now(), true, &Coefficients::default()), 0.0);
+    }
+}
@@ -0,0 +1,115 @@
+//! Checks and cleanup for the text that goes into todos
+//!
+//! Titles, notes, and the other text fields come from the command line, the
+//! clipboard, and imported files. ANSI escape sequences and other control
+//! characters are stripped from all of them before they're stored, since
+//! they would otherwise reach the terminal whenever the todos are shown.
+
+use crate::error::TodoError;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::sync::OnceLock;
+
+/// Longest title accepted unless `validation.max_title_length` says otherwise
+pub const DEFAULT_MAX_TITLE_LENGTH: usize = 200;
+
+static MAX_TITLE_LENGTH: OnceLock<usize> = OnceLock::new();
+
+/// Sets the longest title accepted, in characters; 0 means no limit
+///
+/// Only the first call has an effect.
+pub fn set_max_title_length(max: usize) {
+    let _ = MAX_TITLE_LENGTH.set(max);
+}
+
+/// Returns the limit set with [`set_max_title_length`]
+pub fn max_title_length() -> usize {
+    MAX_TITLE_LENGTH.get().copied().unwrap_or(DEFAULT_MAX_TITLE_LENGTH)
+}
+
+/// Skips the rest of an escape sequence that started with `first`
+///
+/// Handles CSI sequences such as colors and cursor movement, and string
+/// sequences such as OSC window titles and hyperlinks, which run to a BEL
+/// or `ESC \`. Anything else after an ESC is taken as a two-character
+/// sequence.
+fn skip_escape(first: char, chars: &mut Peekable<Chars>) {
+    let kind = if first == '\u{9b}' { Some('[') } else { chars.next() };
+    match kind {
+        Some('[') => {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+        Some(']' | 'P' | 'X' | '^' | '_') => {
+            while let Some(c) = chars.next() {
+                if c == '\u{7}' {
+                    break;
+                }
+                if c == '\u{1b}' {
+                    chars.next_if_eq(&'\\');
+                    break;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Removes ANSI escape sequences and control characters from `text`
+///
+/// Newlines and tabs are kept when `multiline` is set and become spaces
+/// otherwise; carriage returns are dropped or become spaces likewise.
+pub fn sanitize(text: &str, multiline: bool) -> String {
+    let mut clean = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' | '\u{9b}' => skip_escape(c, &mut chars),
+            '\n' | '\t' if multiline => clean.push(c),
+            '\r' if multiline => {}
+            '\n' | '\t' | '\r' => clean.push(' '),
+            c if c.is_control() => {}
+            c => clean.push(c),
+        }
+    }
+    clean
+}
+
+/// Cleans up a title and checks that it isn't blank or over the length limit
+pub fn title(text: &str) -> Result<String, TodoError> {
+    let title = sanitize(text, false).trim().to_string();
+    if title.is_empty() {
+        return Err(TodoError::Validation("Title cannot be empty".to_string()));
+    }
+
+    let (length, max) = (title.chars().count(), max_title_length());
+    if max > 0 && length > max {
+        return Err(TodoError::Validation(format!(
+            "Title is {} characters long, over the limit of {} (validation.max_title_length in the config)",
+            length, max
+        )));
+    }
+    Ok(title)
+}
+
+/// Cleans up a tag, dropping a leading `#`, and checks that it is a single word
+pub fn tag(text: &str) -> Result<String, TodoError> {
+    let tag = sanitize(text, false).trim().trim_start_matches('#').to_string();
+    if tag.is_empty() || tag.contains(char::is_whitespace) {
+        return Err(TodoError::Validation(format!("Invalid tag '{}': tags must be non-empty words", tag)));
+    }
+    Ok(tag)
+}
+
+/// Cleans up a one-line field such as the project, taking a blank one as none
+pub fn text(text: &str) -> Option<String> {
+    Some(sanitize(text, false).trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Cleans up notes, which may span several lines, taking blank ones as none
+pub fn notes(text: &str) -> Option<String> {
+    Some(sanitize(text, true).trim().to_string()).filter(|t| !t.is_empty())
+}
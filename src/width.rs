@@ -0,0 +1,187 @@
+//! Display width of terminal text, for aligning columns
+//!
+//! Wide East Asian characters and most emoji take two cells, combining
+//! marks and zero-width joiners take none, and ANSI escape sequences are
+//! not printed at all. This covers the common ranges rather than the full
+//! Unicode tables.
+
+use serde::{Deserialize, Serialize};
+
+/// Returns how many terminal cells `c` occupies
+pub fn char_width(c: char) -> usize {
+    let cp = c as u32;
+    match cp {
+        0 => 0,
+        _ if c.is_control() => 0,
+        // Combining marks, zero-width spaces and joiners, variation selectors
+        0x0300..=0x036F
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x200B..=0x200F
+        | 0x20D0..=0x20FF
+        | 0xFE00..=0xFE0F
+        | 0xFE20..=0xFE2F
+        | 0xE0100..=0xE01EF => 0,
+        // Hangul Jamo, CJK, Hangul syllables, fullwidth forms
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x3FFFD => 2,
+        // Emoji
+        0x231A..=0x231B
+        | 0x23E9..=0x23EC
+        | 0x23F0
+        | 0x23F3
+        | 0x25FD..=0x25FE
+        | 0x2614..=0x2615
+        | 0x2648..=0x2653
+        | 0x267F
+        | 0x2693
+        | 0x26A1
+        | 0x26AA..=0x26AB
+        | 0x26BD..=0x26BE
+        | 0x26C4..=0x26C5
+        | 0x26CE
+        | 0x26D4
+        | 0x26EA
+        | 0x26F2..=0x26F5
+        | 0x26FA
+        | 0x26FD
+        | 0x2705
+        | 0x270A..=0x270B
+        | 0x2728
+        | 0x274C
+        | 0x274E
+        | 0x2753..=0x2755
+        | 0x2757
+        | 0x2795..=0x2797
+        | 0x27B0
+        | 0x27BF
+        | 0x2B1B..=0x2B1C
+        | 0x2B50
+        | 0x2B55
+        | 0x1F004
+        | 0x1F0CF
+        | 0x1F18E
+        | 0x1F191..=0x1F19A
+        | 0x1F200..=0x1F251
+        | 0x1F300..=0x1F64F
+        | 0x1F680..=0x1F6FF
+        | 0x1F900..=0x1F9FF
+        | 0x1FA70..=0x1FAFF => 2,
+        _ => 1,
+    }
+}
+
+/// Returns the byte length of the ANSI escape sequence starting `s`, if any
+fn escape_len(s: &str) -> Option<usize> {
+    let rest = s.strip_prefix("\x1b[")?;
+    let end = rest.find(|c: char| ('\x40'..='\x7e').contains(&c))?;
+    Some(2 + end + 1)
+}
+
+/// Returns how many terminal cells `s` occupies, ignoring ANSI escapes
+pub fn text_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        match escape_len(rest) {
+            Some(len) => rest = &rest[len..],
+            None => {
+                width += char_width(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    width
+}
+
+/// Where to cut text that doesn't fit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TruncateStyle {
+    /// Keep the start: "Fix the flaky…"
+    #[default]
+    End,
+    /// Keep the start and the end: "Fix the…prod bug"
+    Middle,
+}
+
+/// How to shorten text that doesn't fit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Truncation {
+    /// Where to cut
+    pub style: TruncateStyle,
+    /// Marks where text was cut
+    pub ellipsis: String,
+}
+
+impl Default for Truncation {
+    fn default() -> Self {
+        Self {
+            style: TruncateStyle::End,
+            ellipsis: "…".to_string(),
+        }
+    }
+}
+
+/// Takes characters from `chars` until `width` cells are used
+fn take_width(chars: impl Iterator<Item = char>, width: usize) -> Vec<char> {
+    let mut used = 0;
+    chars
+        .take_while(|&c| {
+            used += char_width(c);
+            used <= width
+        })
+        .collect()
+}
+
+/// Shortens `s` to at most `width` cells, ending in `…` when cut
+///
+/// Meant for plain text such as titles, not colored strings.
+pub fn truncate(s: &str, width: usize) -> String {
+    truncate_with(s, width, &Truncation::default())
+}
+
+/// Shortens `s` to at most `width` cells in the given style
+///
+/// If the ellipsis itself doesn't fit, the text is cut without one.
+pub fn truncate_with(s: &str, width: usize, truncation: &Truncation) -> String {
+    if text_width(s) <= width {
+        return s.to_string();
+    }
+
+    let ellipsis_width = text_width(&truncation.ellipsis);
+    if ellipsis_width >= width {
+        return take_width(s.chars(), width).into_iter().collect();
+    }
+
+    let available = width - ellipsis_width;
+    match truncation.style {
+        TruncateStyle::End => {
+            let head: String = take_width(s.chars(), available).into_iter().collect();
+            head + &truncation.ellipsis
+        }
+        TruncateStyle::Middle => {
+            let tail_width = available / 2;
+            let head: String = take_width(s.chars(), available - tail_width).into_iter().collect();
+            let tail: String = take_width(s.chars().rev(), tail_width).into_iter().rev().collect();
+            format!("{}{}{}", head.trim_end(), truncation.ellipsis, tail.trim_start())
+        }
+    }
+}
+
+/// Pads `s` with spaces on the right to `width` cells
+pub fn pad(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(text_width(s));
+    format!("{}{}", s, " ".repeat(padding))
+}
@@ -0,0 +1,1086 @@
+//! End-to-end runs of the binary against a scratch database
+
+use predicates::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A scratch directory holding the database and config, removed on drop
+struct Sandbox {
+    dir: PathBuf,
+}
+
+impl Sandbox {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("todo-cli-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self { dir }
+    }
+
+    fn db(&self) -> PathBuf {
+        self.dir.join("todos.json")
+    }
+
+    /// Runs the binary in the sandbox without colors, at a fixed width
+    fn cmd(&self, args: &[&str]) -> assert_cmd::assert::Assert {
+        self.cmd_env(args, &[])
+    }
+
+    /// Runs the binary like `cmd`, with extra environment variables
+    fn cmd_env(&self, args: &[&str], vars: &[(&str, &std::ffi::OsStr)]) -> assert_cmd::assert::Assert {
+        self.command(args, vars).assert()
+    }
+
+    /// Runs the binary like `cmd`, answering its questions from `input`
+    fn cmd_input(&self, args: &[&str], input: &str) -> assert_cmd::assert::Assert {
+        self.command(args, &[]).write_stdin(input).assert()
+    }
+
+    fn command(&self, args: &[&str], vars: &[(&str, &std::ffi::OsStr)]) -> assert_cmd::Command {
+        let mut command = assert_cmd::cargo::cargo_bin_cmd!("todo-cli");
+        command
+            .current_dir(&self.dir)
+            .env("TODO_CONFIG", self.dir.join("config.json"))
+            .env("XDG_CONFIG_HOME", self.dir.join("xdg"))
+            .env("COLUMNS", "100")
+            .env_remove("LC_ALL")
+            .env("LC_MESSAGES", "C")
+            .env_remove("TODO_FILE")
+            .envs(vars.iter().copied())
+            .arg("--color")
+            .arg("never")
+            .args(args);
+        command
+    }
+
+    fn read(&self, path: &Path) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[test]
+fn add_list_done_remove_round_trip() {
+    let sandbox = Sandbox::new("round-trip");
+
+    sandbox
+        .cmd(&["add", "Buy milk", "--priority", "high", "--tag", "home"])
+        .success()
+        .stdout(predicate::str::contains("Added todo #1 'Buy milk' (Priority: High)"));
+    sandbox.cmd(&["-q", "add", "Walk the dog"]).success();
+
+    sandbox
+        .cmd(&["list"])
+        .success()
+        .stdout(predicate::str::contains("Buy milk").and(predicate::str::contains("Walk the dog")));
+
+    sandbox
+        .cmd(&["-q", "done", "1"])
+        .success()
+        .stdout(predicate::str::contains("Marked todo #1 as done: Buy milk"));
+    sandbox.cmd(&["list", "--active"]).success().stdout(predicate::str::contains("Buy milk").not());
+
+    sandbox.cmd(&["-q", "remove", "2"]).success();
+    sandbox.cmd(&["list"]).success().stdout(predicate::str::contains("Walk the dog").not());
+    sandbox.cmd(&["trash", "list"]).success().stdout(predicate::str::contains("Walk the dog"));
+
+    let journal = sandbox.read(&sandbox.dir.join("todos.journal.jsonl"));
+    assert_eq!(journal.lines().count(), 4, "two adds, one completion, one removal");
+}
+
+#[test]
+fn lists_sync_to_their_own_files_without_dropping_each_other() {
+    let sandbox = Sandbox::new("sync-lists");
+    let remote = sandbox.dir.join("remote.git");
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git").args(args).output().unwrap();
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    git(&["init", "--quiet", "--bare", "--initial-branch=main", &remote.to_string_lossy()]);
+    let machine = |repo: &str| {
+        let config = serde_json::json!({"sync": {"remote": remote, "repo_dir": sandbox.dir.join(repo)}});
+        fs::write(sandbox.dir.join("config.json"), config.to_string()).unwrap();
+    };
+    let identity = [
+        ("GIT_AUTHOR_NAME", std::ffi::OsStr::new("Test")),
+        ("GIT_AUTHOR_EMAIL", std::ffi::OsStr::new("test@example.com")),
+        ("GIT_COMMITTER_NAME", std::ffi::OsStr::new("Test")),
+        ("GIT_COMMITTER_EMAIL", std::ffi::OsStr::new("test@example.com")),
+    ];
+    let sync = |args: &[&str]| sandbox.cmd_env(args, &identity).success();
+
+    machine("laptop");
+    sandbox.cmd(&["-q", "add", "Home chore"]).success();
+    sandbox.cmd(&["-q", "--list", "work", "add", "Work task"]).success();
+    sync(&["sync"]);
+    sync(&["--list", "work", "sync"]);
+
+    // Another machine adds to the default list, then the laptop syncs only its work list
+    machine("desktop");
+    fs::remove_file(sandbox.db()).unwrap();
+    sync(&["sync"]);
+    sandbox.cmd(&["-q", "add", "Desktop chore"]).success();
+    sync(&["sync"]);
+    machine("laptop");
+    sync(&["--list", "work", "sync"]);
+
+    let show = |file: &str| git(&["--git-dir", &remote.to_string_lossy(), "show", &format!("main:{}", file)]);
+    let default = show("todos.json");
+    assert!(default.contains("Home chore") && default.contains("Desktop chore"), "{}", default);
+    assert!(!default.contains("Work task"));
+    assert!(show("todos-work.json").contains("Work task"));
+}
+
+#[test]
+fn backups_can_take_the_archive_and_trash_along() {
+    let sandbox = Sandbox::new("backup-include");
+    sandbox.cmd(&["-q", "add", "Keep"]).success();
+    sandbox.cmd(&["-q", "add", "Throw away"]).success();
+    sandbox.cmd(&["-q", "remove", "2"]).success();
+    sandbox.cmd(&["-q", "--list", "archive", "add", "Filed"]).success();
+
+    sandbox
+        .cmd(&["backup", "--to", "snapshots", "--include", "archive,trash"])
+        .success()
+        .stdout(predicate::str::contains("Archive: 1 todos").and(predicate::str::contains("Trash: 1 todos")));
+    let snapshot = fs::read_dir(sandbox.dir.join("snapshots"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .find(|path| !path.to_string_lossy().contains(".archive.") && !path.to_string_lossy().contains(".trash."))
+        .unwrap();
+    assert!(sandbox.read(&snapshot.with_extension("archive.json")).contains("Filed"));
+    assert!(sandbox.read(&snapshot.with_extension("trash.json")).contains("Throw away"));
+
+    let exported = sandbox.dir.join("all.md");
+    sandbox.cmd(&["export", "--include", "archive,trash", "--out", &exported.to_string_lossy()]).success();
+    let content = sandbox.read(&exported);
+    assert!(content.contains("# Archive") && content.contains("Filed"), "{}", content);
+    assert!(content.contains("# Trash") && content.contains("Throw away"), "{}", content);
+
+    // Lose the archive and trash, then get them back
+    fs::remove_file(sandbox.dir.join("todos-archive.json")).unwrap();
+    sandbox.cmd(&["-q", "trash", "empty", "-y"]).success();
+    let from = snapshot.to_string_lossy().into_owned();
+    sandbox
+        .cmd(&["restore", "--from", &from, "--include", "archive,trash", "--yes"])
+        .success()
+        .stdout(predicate::str::contains("Archive: 1 todos"));
+    sandbox.cmd(&["--list", "archive", "list"]).success().stdout(predicate::str::contains("Filed"));
+    sandbox.cmd(&["trash", "list"]).success().stdout(predicate::str::contains("Throw away"));
+
+    // Merging takes only what's missing
+    sandbox.cmd(&["-q", "trash", "empty", "-y"]).success();
+    sandbox
+        .cmd(&["merge", &from, "--include", "archive,trash"])
+        .success()
+        .stdout(predicate::str::contains("Archive: 0 added, 0 updated").and(predicate::str::contains("Trash: 1 added")));
+    fs::remove_file(snapshot.with_extension("trash.json")).unwrap();
+    sandbox.cmd(&["restore", "--from", &from, "--include", "trash", "--yes"]).failure().stderr(predicate::str::contains("No trash saved"));
+}
+
+#[test]
+fn doctor_checks_uuids_across_the_archive_and_trash() {
+    let sandbox = Sandbox::new("doctor-stores");
+    sandbox.cmd(&["-q", "add", "Shared"]).success();
+    sandbox.cmd(&["-q", "add", "Gone"]).success();
+    sandbox.cmd(&["-q", "remove", "2"]).success();
+    sandbox.cmd(&["-q", "--list", "archive", "add", "Copy"]).success();
+    sandbox.cmd(&["-q", "--list", "archive", "add", "Waits on the copy"]).success();
+
+    // A copy of the active todo in the archive, with a dependency on it, and in the trash
+    let todos: serde_json::Value = serde_json::from_str(&sandbox.read(&sandbox.db())).unwrap();
+    let uuid = todos[0]["uuid"].as_str().unwrap().to_string();
+    let archive_path = sandbox.dir.join("todos-archive.json");
+    let mut archive: serde_json::Value = serde_json::from_str(&sandbox.read(&archive_path)).unwrap();
+    archive[0]["uuid"] = uuid.clone().into();
+    archive[1]["depends_on"] = serde_json::json!([uuid]);
+    fs::write(&archive_path, archive.to_string()).unwrap();
+    let trash_path = sandbox.dir.join("todos.trash.json");
+    let mut trash: serde_json::Value = serde_json::from_str(&sandbox.read(&trash_path)).unwrap();
+    trash[0]["todo"]["uuid"] = uuid.clone().into();
+    fs::write(&trash_path, trash.to_string()).unwrap();
+
+    sandbox
+        .cmd(&["doctor", "--ids"])
+        .success()
+        .stdout(predicate::str::contains("[archive] Duplicate UUID").and(predicate::str::contains("[trash] Duplicate UUID")));
+    sandbox
+        .cmd(&["doctor", "--ids", "--fix"])
+        .success()
+        .stdout(predicate::str::contains("[archive] 1 dependencies on").and(predicate::str::contains("Fixed 2 problem(s)")));
+
+    let todos: serde_json::Value = serde_json::from_str(&sandbox.read(&sandbox.db())).unwrap();
+    assert_eq!(todos[0]["uuid"], uuid.as_str(), "the active todo keeps its UUID");
+    let archive: serde_json::Value = serde_json::from_str(&sandbox.read(&archive_path)).unwrap();
+    assert_ne!(archive[0]["uuid"], uuid.as_str());
+    assert_eq!(archive[1]["depends_on"][0], archive[0]["uuid"], "the dependency follows the archived copy");
+    let trash: serde_json::Value = serde_json::from_str(&sandbox.read(&trash_path)).unwrap();
+    assert_ne!(trash[0]["todo"]["uuid"], uuid.as_str());
+    sandbox.cmd(&["doctor"]).success().stdout(predicate::str::contains("No problems found"));
+    sandbox.cmd(&["--list", "archive", "doctor"]).success().stdout(predicate::str::contains("No problems found"));
+}
+
+#[test]
+fn stats_count_removals_of_restored_todos_and_archived_todos() {
+    let sandbox = Sandbox::new("stats-history");
+    sandbox.cmd(&["-q", "add", "Second thoughts"]).success();
+    sandbox.cmd(&["-q", "remove", "1"]).success();
+    sandbox.cmd(&["-q", "trash", "restore", "1"]).success();
+    sandbox.cmd(&["-q", "--list", "archive", "add", "Filed away"]).success();
+    sandbox.cmd(&["-q", "--list", "archive", "done", "1"]).success();
+
+    // This week: the live and the archived todo added, the archived one done, one removal
+    let output = sandbox.cmd(&["stats", "--weeks", "1"]).success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+    let this_week: Vec<&str> = output.lines().last().unwrap().split_whitespace().collect();
+    assert_eq!(this_week[1..], ["2", "1", "1"], "{}", output);
+}
+
+#[test]
+fn dry_run_removals_say_what_would_be_removed() {
+    let sandbox = Sandbox::new("dry-remove");
+    sandbox.cmd(&["-q", "add", "Ship it"]).success();
+
+    sandbox
+        .cmd(&["remove", "1", "--dry-run"])
+        .success()
+        .stdout(predicate::str::contains("Would remove todo #1").and(predicate::str::contains("Removed").not()));
+    sandbox
+        .cmd(&["remove", "--where", "title~ship", "--dry-run"])
+        .success()
+        .stdout(predicate::str::contains("Would remove todo #1: Ship it"));
+    sandbox.cmd(&["list"]).success().stdout(predicate::str::contains("Ship it"));
+}
+
+#[test]
+fn titles_resolve_like_ids() {
+    let sandbox = Sandbox::new("titles");
+    sandbox.cmd(&["-q", "add", "Dentist appointment"]).success();
+    sandbox.cmd(&["-q", "add", "Call the dentist back"]).success();
+
+    sandbox
+        .cmd(&["-q", "done", "dentist"])
+        .code(2)
+        .stderr(predicate::str::contains("matches several todos (#1, #2)"));
+    sandbox
+        .cmd(&["-q", "done", "appointment"])
+        .success()
+        .stdout(predicate::str::contains("Marked todo #1 as done"));
+}
+
+#[test]
+fn migrates_legacy_files() {
+    let sandbox = Sandbox::new("legacy");
+    fs::write(
+        sandbox.db(),
+        r#"[{"id": 1, "title": "Old and done", "completed": true}, {"id": 2, "title": "Old and open", "completed": false}]"#,
+    )
+    .unwrap();
+
+    // Legacy completions have no completion time, so only --all shows them
+    sandbox
+        .cmd(&["list", "--all"])
+        .success()
+        .stdout(predicate::str::contains("Old and done").and(predicate::str::contains("Old and open")));
+
+    let todos: serde_json::Value = serde_json::from_str(&sandbox.read(&sandbox.db())).unwrap();
+    let todos = todos.as_array().unwrap();
+    assert_eq!(todos.len(), 2);
+    assert!(todos.iter().all(|t| t["uuid"].as_str().is_some_and(|u| u.len() == 36)));
+    assert_eq!(todos[0]["status"], "done");
+    assert_eq!(todos[1]["status"], "todo");
+}
+
+#[test]
+fn files_that_fail_to_parse_are_not_mistaken_for_legacy_ones() {
+    let sandbox = Sandbox::new("not-legacy");
+    sandbox.cmd(&["-q", "add", "File taxes", "--priority", "high", "--due", "2030-04-15", "--tag", "money"]).success();
+
+    // Levels the scale doesn't have read as its default, keeping the rest of the todo
+    let config = r#"{"priorities": {"levels": [{"name": "someday"}, {"name": "soon"}], "default": "soon"}}"#;
+    fs::write(sandbox.dir.join("config.json"), config).unwrap();
+    sandbox.cmd(&["-q", "add", "Renew passport"]).success();
+    let todos: serde_json::Value = serde_json::from_str(&sandbox.read(&sandbox.db())).unwrap();
+    assert_eq!(todos[0]["priority"], "soon");
+    assert_eq!(todos[0]["due_date"], "2030-04-15");
+    assert_eq!(todos[0]["tags"][0], "money");
+
+    let broken = r#"[{"id": 1, "title": "Old", "completed": false, "tags": "money"}]"#;
+    fs::write(sandbox.db(), broken).unwrap();
+    sandbox.cmd(&["list"]).code(4).stderr(predicate::str::contains("Failed to parse"));
+    assert_eq!(sandbox.read(&sandbox.db()), broken);
+
+    // A torn MessagePack file says so, naming the file
+    fs::write(sandbox.db(), b"\x92\xc1\x01").unwrap();
+    sandbox
+        .cmd(&["list"])
+        .code(4)
+        .stderr(predicate::str::contains("Failed to parse todos.json").and(predicate::str::contains("invalid MessagePack")));
+}
+
+#[test]
+fn torn_pending_changes_are_warned_about_and_discarded() {
+    let sandbox = Sandbox::new("torn-pending");
+    sandbox.cmd(&["-q", "add", "Ship it"]).success();
+    let pending = sandbox.dir.join("todos.pending.json");
+    fs::write(&pending, r#"[{"id": 1, "title": "Ship"#).unwrap();
+
+    sandbox
+        .cmd(&["list"])
+        .success()
+        .stdout(predicate::str::contains("Ship it"))
+        .stderr(predicate::str::contains("Warning: Failed to parse").and(predicate::str::contains("recover --discard")));
+    sandbox.cmd(&["recover"]).code(4);
+    sandbox.cmd(&["recover", "--discard"]).success().stdout(predicate::str::contains("Removed the unreadable changes"));
+    assert!(!pending.exists());
+    sandbox.cmd(&["list"]).success().stderr(predicate::str::contains("Warning").not());
+}
+
+#[test]
+fn files_without_statuses_take_them_from_completed() {
+    let sandbox = Sandbox::new("statuses");
+    sandbox.cmd(&["-q", "add", "Ship it"]).success();
+    sandbox.cmd(&["-q", "done", "1"]).success();
+
+    // Drop the status the way a file written before statuses existed would look
+    let mut todos: serde_json::Value = serde_json::from_str(&sandbox.read(&sandbox.db())).unwrap();
+    todos[0].as_object_mut().unwrap().remove("status");
+    fs::write(sandbox.db(), todos.to_string()).unwrap();
+
+    sandbox
+        .cmd(&["list", "--status", "done"])
+        .success()
+        .stdout(predicate::str::contains("Ship it"));
+}
+
+#[test]
+fn errors_map_to_exit_codes() {
+    let sandbox = Sandbox::new("exit-codes");
+    sandbox.cmd(&["done", "7"]).code(2).stderr(predicate::str::contains("Todo #7 not found"));
+    sandbox.cmd(&["add", "  "]).code(5).stderr(predicate::str::contains("Title cannot be empty"));
+
+    fs::write(sandbox.db(), "not json").unwrap();
+    sandbox.cmd(&["list"]).code(4);
+}
+
+#[test]
+fn themes_come_from_the_config_or_the_command_line() {
+    let sandbox = Sandbox::new("themes");
+    sandbox.cmd(&["--theme", "solarized", "list"]).success();
+    sandbox.cmd(&["--theme", "sepia", "list"]).code(5).stderr(predicate::str::contains("Unknown theme 'sepia'"));
+
+    let config = r#"{"display": {"theme": "sepia", "themes": {"sepia": {"header": "yellow bold"}}}}"#;
+    fs::write(sandbox.dir.join("config.json"), config).unwrap();
+    sandbox.cmd(&["list"]).success();
+    sandbox.cmd(&["--theme", "monochrome", "list"]).success();
+}
+
+#[test]
+fn limit_and_offset_take_one_page_of_the_list() {
+    let sandbox = Sandbox::new("paging");
+    for title in ["Alpha", "Bravo", "Charlie", "Delta"] {
+        sandbox.cmd(&["-q", "add", title]).success();
+    }
+    sandbox
+        .cmd(&["list", "--sort", "title", "--offset", "1", "--limit", "2"])
+        .success()
+        .stdout(predicate::str::contains("Bravo").and(predicate::str::contains("Charlie")))
+        .stdout(predicate::str::contains("Alpha").not().and(predicate::str::contains("Delta").not()));
+}
+
+#[test]
+fn scheduled_todos_stay_out_of_list_and_next_until_their_day() {
+    let sandbox = Sandbox::new("scheduled");
+    sandbox.cmd(&["-q", "add", "Plan the retreat", "--scheduled", "+3d"]).success();
+
+    sandbox.cmd(&["list"]).success().stdout(predicate::str::contains("Plan the retreat").not());
+    sandbox.cmd(&["next"]).success().stdout(predicate::str::contains("Nothing to do next."));
+    sandbox.cmd(&["list", "--all"]).success().stdout(predicate::str::contains("Plan the retreat"));
+    sandbox.cmd(&["next", "--all"]).success().stdout(predicate::str::contains("Plan the retreat"));
+}
+
+#[test]
+fn confirmations_follow_the_flags_then_the_config() {
+    let sandbox = Sandbox::new("confirm");
+    sandbox.cmd(&["-q", "add", "Keep me"]).success();
+    sandbox.cmd(&["-q", "add", "Drop me"]).success();
+
+    // No one is there to answer, so the default prompt for clear fails
+    sandbox.cmd(&["clear"]).failure().stderr(predicate::str::contains("pass --yes"));
+    sandbox.cmd(&["-q", "remove", "2", "--confirm"]).failure();
+    sandbox.cmd(&["-q", "remove", "2"]).success();
+
+    fs::write(sandbox.dir.join("config.json"), r#"{"confirm": {"clear": false, "trash_empty": true}}"#).unwrap();
+    sandbox.cmd(&["trash", "empty"]).failure();
+    sandbox.cmd(&["trash", "empty", "-y"]).success().stdout(predicate::str::contains("Deleted 1 todos"));
+    sandbox.cmd(&["clear"]).success().stdout(predicate::str::contains("moved to the trash"));
+    sandbox.cmd(&["clear", "--yes", "--confirm"]).code(2);
+}
+
+#[test]
+fn modified_times_show_up_and_sort_most_recent_first() {
+    let sandbox = Sandbox::new("modified");
+    let json = r#"[
+  {"id": 1, "uuid": "a", "title": "Alpha", "completed": false, "created_at": "2026-01-01T12:00:00+00:00",
+   "due_date": null, "modified_at": "2026-03-01T12:00:00+00:00"},
+  {"id": 2, "uuid": "b", "title": "Bravo", "completed": false, "created_at": "2026-01-01T12:00:00+00:00",
+   "due_date": null, "modified_at": "2026-02-01T12:00:00+00:00"},
+  {"id": 3, "uuid": "c", "title": "Charlie", "completed": false, "created_at": "2026-01-01T12:00:00+00:00",
+   "due_date": null}
+]"#;
+    fs::write(sandbox.db(), json).unwrap();
+    sandbox.cmd(&["-q", "priority", "3", "high"]).success();
+
+    sandbox
+        .cmd(&["show", "2", "--json"])
+        .success()
+        .stdout(predicate::str::contains(r#""modified_at": "2026-02-01"#));
+    sandbox.cmd(&["show", "2"]).success().stdout(predicate::str::contains("Modified:"));
+    let out = sandbox.cmd(&["list", "--sort", "modified"]).success().get_output().stdout.clone();
+    let out = String::from_utf8(out).unwrap();
+    let position = |title: &str| out.find(title).unwrap();
+    assert!(position("Charlie") < position("Alpha") && position("Alpha") < position("Bravo"));
+}
+
+#[test]
+fn digests_list_overdue_then_due_today_with_the_configured_lines() {
+    let sandbox = Sandbox::new("digest");
+    sandbox.cmd(&["-q", "add", "Renew passport", "--due", "2020-01-01"]).success();
+    sandbox.cmd(&["-q", "add", "Call the bank", "--due", "today", "--project", "Home"]).success();
+    sandbox.cmd(&["-q", "add", "Someday"]).success();
+
+    fs::write(sandbox.dir.join("config.json"), r#"{"digest": {"item": "- {title} [{project}]"}}"#).unwrap();
+    sandbox
+        .cmd(&["digest", "--channel", "discord", "--print"])
+        .success()
+        .stdout(predicate::str::contains("1 overdue, 1 due today"))
+        .stdout(predicate::str::contains("**Overdue**\n- Renew passport []\n\n**Due today**\n- Call the bank [Home]"))
+        .stdout(predicate::str::contains("Someday").not());
+    sandbox.cmd(&["digest"]).failure().stderr(predicate::str::contains("set digest.slack_url"));
+
+    fs::write(sandbox.dir.join("config.json"), r#"{"digest": {"item": "{name}"}}"#).unwrap();
+    sandbox.cmd(&["digest", "--print"]).code(5).stderr(predicate::str::contains("Unknown placeholder '{name}'"));
+}
+
+#[test]
+fn remind_groups_due_and_overdue_todos_by_priority() {
+    let sandbox = Sandbox::new("remind");
+    sandbox.cmd(&["remind"]).success().stdout(predicate::str::contains("Nothing due today."));
+    sandbox.cmd(&["-q", "add", "Renew passport", "--due", "2020-01-01"]).success();
+    sandbox.cmd(&["-q", "add", "Call the bank", "--due", "today", "--priority", "high"]).success();
+    sandbox.cmd(&["-q", "add", "Plan the trip", "--due", "tomorrow", "--priority", "high"]).success();
+
+    sandbox
+        .cmd(&["remind"])
+        .success()
+        .stdout(predicate::str::contains("1 overdue, 1 due today"))
+        .stdout(predicate::str::contains("High\n  #2 Call the bank (due today)\n\nNormal\n  #1 Renew passport (overdue"))
+        .stdout(predicate::str::contains("Plan the trip").not());
+}
+
+#[cfg(all(feature = "email", unix))]
+#[test]
+fn remind_email_pipes_a_plain_and_html_message_to_sendmail() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let sandbox = Sandbox::new("remind-email");
+    let sendmail = sandbox.dir.join("sendmail");
+    let mail = sandbox.dir.join("mail.eml");
+    fs::write(&sendmail, format!("#!/bin/sh\ncat > '{}'\n", mail.display())).unwrap();
+    fs::set_permissions(&sendmail, fs::Permissions::from_mode(0o755)).unwrap();
+    let config = format!(r#"{{"email": {{"to": "me@example.com", "sendmail": "{}"}}}}"#, sendmail.display());
+    fs::write(sandbox.dir.join("config.json"), config).unwrap();
+
+    sandbox.cmd(&["remind", "--email"]).success().stdout(predicate::str::contains("no email sent"));
+    sandbox.cmd(&["-q", "add", "Fish & chips", "--due", "today"]).success();
+    sandbox.cmd(&["remind", "--email"]).success().stdout(predicate::str::contains("Emailed 1 todos to me@example.com"));
+
+    let mail = sandbox.read(&mail);
+    assert!(mail.contains("To: me@example.com\n"));
+    assert!(mail.contains("Content-Type: multipart/alternative"));
+    assert!(mail.contains("#1 Fish & chips (due today)"));
+    assert!(mail.contains("<li>#1 Fish &amp; chips <em>(due today)</em></li>"));
+}
+
+#[test]
+fn read_only_lists_can_be_viewed_but_not_changed() {
+    let sandbox = Sandbox::new("read-only");
+    sandbox.cmd(&["-q", "add", "Shared"]).success();
+    let before = sandbox.read(&sandbox.db());
+
+    sandbox
+        .cmd(&["--read-only", "add", "Mine"])
+        .code(3)
+        .stderr(predicate::str::contains("is open read-only").and(predicate::str::contains("Caused by").not()));
+    sandbox.cmd(&["--read-only", "list"]).success().stdout(predicate::str::contains("Shared"));
+
+    fs::write(sandbox.dir.join("config.json"), r#"{"read_only": true}"#).unwrap();
+    sandbox.cmd(&["done", "1"]).code(3);
+    sandbox.cmd(&["trash", "empty", "-y"]).success();
+    sandbox.cmd(&["sync"]).code(5).stderr(predicate::str::contains("Can't sync a list opened read-only"));
+    sandbox.cmd(&["clear", "-y"]).code(3);
+    sandbox.cmd(&["restore", "--from", &sandbox.db().to_string_lossy(), "--yes"]).code(5);
+    assert!(!sandbox.dir.join("todos.backups").exists(), "nothing is backed up for a change that's refused");
+    assert_eq!(sandbox.read(&sandbox.db()), before);
+}
+
+#[test]
+fn backups_rotate_and_restore_after_a_preview() {
+    let sandbox = Sandbox::new("backup");
+    fs::write(sandbox.dir.join("config.json"), r#"{"backup": {"keep": 2}}"#).unwrap();
+    let backups = sandbox.dir.join("todos.backups");
+    let count = || fs::read_dir(&backups).map_or(0, |d| d.count());
+    sandbox.cmd(&["-q", "add", "Keep me"]).success();
+    sandbox.cmd(&["backup"]).success().stdout(predicate::str::contains("Backed up 1 todos to todos.backups/todos-"));
+    sandbox.cmd(&["-q", "add", "Added later"]).success();
+
+    sandbox.cmd(&["restore"]).failure().stderr(predicate::str::contains("pass --yes"));
+    sandbox
+        .cmd(&["-q", "restore", "--yes"])
+        .success()
+        .stdout(predicate::str::contains("remove   #2    Added later"))
+        .stdout(predicate::str::contains("Restored 1 todos"));
+    sandbox.cmd(&["list"]).success().stdout(predicate::str::contains("Added later").not());
+    assert_eq!(count(), 2, "the backup and the one taken before restoring");
+
+    fs::write(sandbox.dir.join("list.md"), "- [ ] Imported\n").unwrap();
+    sandbox.cmd(&["-q", "import", "list.md", "--replace"]).success().stdout(predicate::str::contains("Replaced 1 todos"));
+    sandbox.cmd(&["-q", "backup"]).success().stdout(predicate::str::contains("Removed 1 old backups"));
+    assert_eq!(count(), 2);
+}
+
+#[test]
+fn backups_taken_in_the_same_second_sort_by_number() {
+    let sandbox = Sandbox::new("backup-order");
+    fs::write(sandbox.dir.join("config.json"), r#"{"backup": {"keep": 3}}"#).unwrap();
+    let backups = sandbox.dir.join("todos.backups");
+    sandbox.cmd(&["-q", "add", "Snapshot"]).success();
+    sandbox.cmd(&["-q", "backup"]).success();
+    let taken = fs::read_dir(&backups).unwrap().next().unwrap().unwrap().path();
+    let content = sandbox.read(&taken);
+    fs::remove_file(taken).unwrap();
+    for n in 1..=12 {
+        let name = match n {
+            1 => "todos-20990101-000000.json".to_string(),
+            n => format!("todos-20990101-000000-{}.json", n),
+        };
+        fs::write(backups.join(name), content.replace("Snapshot", &format!("Snapshot {}", n))).unwrap();
+    }
+
+    sandbox.cmd(&["-q", "restore", "--yes"]).success();
+    sandbox.cmd(&["list"]).success().stdout(predicate::str::contains("Snapshot 12"));
+    let mut left: Vec<String> = fs::read_dir(&backups)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.starts_with("todos-2099"))
+        .collect();
+    left.sort();
+    // The backup taken before restoring is older than these, so it went too
+    assert_eq!(left, ["todos-20990101-000000-10.json", "todos-20990101-000000-11.json", "todos-20990101-000000-12.json"]);
+}
+
+#[test]
+fn limited_lists_match_a_page_of_the_full_list() {
+    let sandbox = Sandbox::new("limit");
+    let priorities = ["low", "normal", "high", "urgent"];
+    let lines: Vec<String> = (0..300).map(|n| format!("Task {} prio:{}", n, priorities[n * 7 % 4])).collect();
+    fs::write(sandbox.dir.join("tasks.txt"), lines.join("\n")).unwrap();
+    sandbox.cmd(&["-q", "add", "--from-file", "tasks.txt"]).success();
+    sandbox.cmd(&["-q", "done", "5"]).success();
+    sandbox.cmd(&["-q", "block", "9", "--on", "2"]).success();
+
+    let rows = |args: &[&str]| {
+        let out = sandbox.cmd(args).success().get_output().stdout.clone();
+        String::from_utf8(out).unwrap().lines().skip(2).map(str::to_string).collect::<Vec<_>>()
+    };
+    for sort in ["smart", "priority,title:desc"] {
+        let all = rows(&["list", "--no-pager", "--sort", sort]);
+        let page = rows(&["list", "--no-pager", "--sort", sort, "--offset", "70", "--limit", "20"]);
+        assert_eq!(page, all[70..90], "sorted by {}", sort);
+    }
+    sandbox.cmd(&["list", "--count", "--status", "done"]).success().stdout("1\n");
+}
+
+#[test]
+fn small_changes_to_large_databases_are_appended_as_patches() {
+    let sandbox = Sandbox::new("patches");
+    fs::write(sandbox.dir.join("config.json"), r#"{"storage": {"patch_above": 10, "compact_after": 2}}"#).unwrap();
+    let titles: Vec<String> = (1..=20).map(|n| format!("Task {}", n)).collect();
+    fs::write(sandbox.dir.join("tasks.txt"), titles.join("\n")).unwrap();
+    sandbox.cmd(&["-q", "add", "--from-file", "tasks.txt"]).success();
+    let file = sandbox.dir.join("todos.json");
+    let patches = sandbox.dir.join("todos.patches.jsonl");
+    let written = fs::read_to_string(&file).unwrap();
+
+    sandbox.cmd(&["-q", "done", "3"]).success();
+    sandbox.cmd(&["-q", "remove", "4"]).success();
+    assert_eq!(fs::read_to_string(&file).unwrap(), written, "the file itself is left alone");
+    assert_eq!(fs::read_to_string(&patches).unwrap().lines().count(), 2);
+    sandbox.cmd(&["list"]).success().stdout(predicate::str::contains("Task 4").not());
+    sandbox.cmd(&["summary"]).success().stdout(predicate::str::contains("18 active"));
+
+    // A line torn by a crash is ignored, and the next save rewrites the file
+    let mut log = fs::read_to_string(&patches).unwrap();
+    log.push_str(r#"{"put":[{"id":"#);
+    fs::write(&patches, log).unwrap();
+    sandbox.cmd(&["list"]).success().stdout(predicate::str::contains("Task 5"));
+    sandbox.cmd(&["-q", "done", "5"]).success();
+    assert!(!patches.exists());
+    sandbox.cmd(&["-q", "done", "6"]).success();
+    sandbox.cmd(&["compact"]).success().stdout(predicate::str::contains("Folded 1 patch into"));
+    assert!(!patches.exists());
+    sandbox.cmd(&["summary"]).success().stdout(predicate::str::contains("16 active"));
+}
+
+#[test]
+fn databases_convert_to_messagepack_and_back() {
+    let sandbox = Sandbox::new("convert");
+    sandbox.cmd(&["-q", "add", "Write report", "--tag", "work"]).success();
+    let file = sandbox.dir.join("todos.json");
+
+    sandbox.cmd(&["convert", "--to", "msgpack"]).success().stdout(predicate::str::contains("to MessagePack"));
+    let bytes = fs::read(&file).unwrap();
+    assert!(matches!(bytes[0], 0x90..=0x9f), "starts with an array header");
+    sandbox.cmd(&["-q", "add", "Call Sam"]).success();
+    assert!(matches!(fs::read(&file).unwrap()[0], 0x90..=0x9f), "saves keep the format");
+    sandbox.cmd(&["list"]).success().stdout(predicate::str::contains("Write report").and(predicate::str::contains("Call Sam")));
+    sandbox.cmd(&["convert", "--to", "msgpack"]).success().stdout(predicate::str::contains("already stored as MessagePack"));
+
+    sandbox.cmd(&["convert", "--to", "json"]).success();
+    let todos: Vec<serde_json::Value> = serde_json::from_slice(&fs::read(&file).unwrap()).unwrap();
+    assert_eq!(todos.len(), 2);
+    assert_eq!(todos[0]["tags"], serde_json::json!(["work"]));
+}
+
+#[test]
+fn bench_times_a_scratch_database_and_leaves_the_real_one_alone() {
+    let sandbox = Sandbox::new("bench");
+    sandbox
+        .cmd(&["bench", "--todos", "50", "--runs", "1", "--format", "msgpack"])
+        .success()
+        .stdout(predicate::str::contains("50 synthetic todos stored as MessagePack"))
+        .stdout(predicate::str::contains("load").and(predicate::str::contains("save one change")));
+    assert!(!sandbox.dir.join("todos.json").exists());
+    sandbox.cmd(&["--help"]).success().stdout(predicate::str::contains("bench").not());
+}
+
+#[test]
+fn done_report_groups_todos_completed_in_the_range() {
+    let sandbox = Sandbox::new("report-done");
+    for title in ["Write report", "Call Sam", "Cancelled idea", "Long ago"] {
+        sandbox.cmd(&["-q", "add", title]).success();
+    }
+    for id in ["1", "2", "4"] {
+        sandbox.cmd(&["-q", "done", id]).success();
+    }
+    sandbox.cmd(&["-q", "status", "3", "cancelled"]).success();
+    let file = sandbox.dir.join("todos.json");
+    let mut todos: Vec<serde_json::Value> = serde_json::from_slice(&fs::read(&file).unwrap()).unwrap();
+    todos[0]["completed_at"] = "2026-03-02T09:00:00Z".into();
+    todos[1]["completed_at"] = "2026-03-04T17:00:00Z".into();
+    todos[1]["project"] = "work".into();
+    todos[2]["completed_at"] = "2026-03-03T10:00:00Z".into();
+    todos[3]["completed_at"] = "2026-01-15T09:00:00Z".into();
+    fs::write(&file, serde_json::to_string(&todos).unwrap()).unwrap();
+
+    sandbox
+        .cmd(&["report", "done", "--since", "2026-03-01", "--until", "2026-03-08"])
+        .success()
+        .stdout(predicate::str::contains("Completed from Sun 2026-03-01 to Sun 2026-03-08: 2"))
+        .stdout(predicate::str::contains("Mon 2026-03-02 (1)\n  #1     Write report"))
+        .stdout(predicate::str::contains("Cancelled idea").not())
+        .stdout(predicate::str::contains("Long ago").not());
+    sandbox
+        .cmd(&["report", "done", "--since", "2026-01-01", "--until", "2026-03-08", "--by", "project"])
+        .success()
+        .stdout(predicate::str::contains("work (1)\n  #2     Call Sam"))
+        .stdout(predicate::str::contains("No project (2)"));
+    sandbox.cmd(&["report", "done", "--since", "-3d"]).success().stdout(predicate::str::contains("Nothing completed"));
+}
+
+#[cfg(feature = "share")]
+#[test]
+fn share_shows_the_checklist_and_asks_before_uploading() {
+    let sandbox = Sandbox::new("share");
+    sandbox.cmd(&["-q", "add", "Buy milk", "--tag", "home", "--notes", "Oat, not cow"]).success();
+    sandbox.cmd(&["-q", "add", "Plan the surprise party"]).success();
+    sandbox.cmd(&["-q", "add", "Fix the sink", "--due", "2026-11-01"]).success();
+
+    sandbox
+        .cmd(&["share", "--ids", "1,3", "--to", "paste"])
+        .failure()
+        .stdout(predicate::str::contains("- [ ] Buy milk #home\n- [ ] Fix the sink (due 2026-11-01)"))
+        .stdout(predicate::str::contains("Oat").not().and(predicate::str::contains("surprise").not()))
+        .stderr(predicate::str::contains("pass --yes"));
+    sandbox
+        .cmd(&["import", "--from-url", "file:///etc/passwd"])
+        .failure()
+        .stderr(predicate::str::contains("Not an http or https URL"));
+}
+
+#[cfg(feature = "feeds")]
+#[test]
+fn feeds_are_kept_in_the_config_and_failed_syncs_only_warn() {
+    let sandbox = Sandbox::new("feeds");
+    fs::write(sandbox.dir.join("config.json"), r#"{"me": "Sam"}"#).unwrap();
+    sandbox.cmd(&["feed", "add", "ftp://example.com/feed.ics"]).failure().code(5);
+    sandbox
+        .cmd(&["feed", "add", "http://127.0.0.1:9/cs101.ics", "--every", "2h", "--title", "{calendar}: {summary}"])
+        .success()
+        .stdout(predicate::str::contains("synced at most every 2h"));
+    let config = sandbox.read(&sandbox.dir.join("config.json"));
+    assert!(config.contains(r#""me": "Sam""#) && config.contains(r#""every": "2h""#), "{}", config);
+    sandbox.cmd(&["feed", "list"]).success().stdout(predicate::str::contains("1. http://127.0.0.1:9/cs101.ics (every 2h)"));
+
+    // Nothing listens on port 9, so the download fails
+    sandbox
+        .cmd(&["feed", "sync"])
+        .success()
+        .stderr(predicate::str::contains("Warning: feed http://127.0.0.1:9/cs101.ics failed"));
+
+    sandbox.cmd(&["feed", "remove", "2"]).failure().code(5);
+    sandbox.cmd(&["feed", "remove", "1"]).success().stdout(predicate::str::contains("Unsubscribed"));
+    assert!(!sandbox.read(&sandbox.dir.join("config.json")).contains("feeds"));
+    sandbox.cmd(&["feed", "sync"]).success().stdout(predicate::str::contains("No feeds yet"));
+}
+
+#[test]
+fn todos_are_assigned_and_listed_by_assignee() {
+    let sandbox = Sandbox::new("assignee");
+    fs::write(sandbox.dir.join("config.json"), r#"{"me": "Sam", "display": {"assignee": true}}"#).unwrap();
+    sandbox.cmd(&["-q", "add", "Book the vet", "--assignee", "me"]).success();
+    sandbox.cmd(&["-q", "add", "Fix the sink", "--assignee", "Alex"]).success();
+    sandbox.cmd(&["-q", "add", "Water the plants"]).success();
+    sandbox.cmd(&["-q", "edit", "3", "--assignee", "alex"]).success();
+
+    sandbox
+        .cmd(&["list", "--assignee", "me"])
+        .success()
+        .stdout(predicate::str::contains("Assignee"))
+        .stdout(predicate::str::is_match(r"1 .* Sam +Book the vet").unwrap())
+        .stdout(predicate::str::contains("Fix the sink").not());
+    sandbox
+        .cmd(&["list", "--assignee", "ALEX"])
+        .success()
+        .stdout(predicate::str::contains("Fix the sink").and(predicate::str::contains("Water the plants")))
+        .stdout(predicate::str::contains("Book the vet").not());
+}
+
+#[test]
+fn diff_lists_changes_since_a_snapshot() {
+    let sandbox = Sandbox::new("diff");
+    sandbox.cmd(&["-q", "add", "Write report"]).success();
+    sandbox.cmd(&["-q", "add", "Old idea"]).success();
+    fs::copy(sandbox.dir.join("todos.json"), sandbox.dir.join("before.json")).unwrap();
+    sandbox.cmd(&["diff", "before.json"]).success().stdout(predicate::str::contains("No differences"));
+
+    sandbox.cmd(&["-q", "done", "1"]).success();
+    sandbox.cmd(&["-q", "remove", "2"]).success();
+    sandbox.cmd(&["-q", "add", "New idea"]).success();
+    sandbox
+        .cmd(&["diff", "before.json"])
+        .success()
+        .stdout(predicate::str::contains("Changes from before.json to the database:"))
+        .stdout(predicate::str::contains("+ #2    New idea"))
+        .stdout(predicate::str::contains("- #2    Old idea"))
+        .stdout(predicate::str::contains("~ #1    Write report"))
+        .stdout(predicate::str::contains("      status: todo → done"))
+        .stdout(predicate::str::contains("1 added, 1 removed, 1 changed"));
+    sandbox.cmd(&["diff", "missing.json"]).failure().stderr(predicate::str::contains("No backup at missing.json"));
+}
+
+#[test]
+fn aliases_expand_before_parsing_and_refuse_cycles() {
+    let sandbox = Sandbox::new("aliases");
+    fs::write(sandbox.dir.join("config.json"), r#"{"me": "Sam"}"#).unwrap();
+    sandbox.cmd(&["-q", "add", "Water the plants", "--priority", "high"]).success();
+    sandbox.cmd(&["-q", "add", "File taxes"]).success();
+    sandbox.cmd(&["-q", "status", "2", "in-progress"]).success();
+
+    sandbox.cmd(&["alias", "add", "wip", "list --status in-progress"]).success();
+    sandbox.cmd(&["alias", "add", "mine", "wip --sort title"]).success();
+    sandbox
+        .cmd(&["-f", "todos.json", "mine", "--no-pager"])
+        .success()
+        .stdout(predicate::str::contains("File taxes").and(predicate::str::contains("Water the plants").not()));
+    sandbox
+        .cmd(&["alias", "list"])
+        .success()
+        .stdout(predicate::str::is_match(r"mine +wip --sort title\nwip +list --status in-progress").unwrap());
+    let config = sandbox.read(&sandbox.dir.join("config.json"));
+    assert!(config.contains(r#""me": "Sam""#), "{}", config);
+
+    sandbox
+        .cmd(&["alias", "add", "wip", "mine"])
+        .failure()
+        .stderr(predicate::str::contains("Alias cycle: wip -> mine -> wip"));
+    sandbox
+        .cmd(&["alias", "add", "list", "list --all"])
+        .failure()
+        .stderr(predicate::str::contains("built-in command"));
+
+    sandbox.cmd(&["alias", "remove", "wip"]).success();
+    sandbox.cmd(&["mine"]).failure().stderr(predicate::str::contains("Unknown command 'wip'"));
+}
+
+#[test]
+fn timestamps_are_stored_in_utc_and_shown_in_local_time() {
+    let sandbox = Sandbox::new("utc");
+    fs::write(
+        sandbox.db(),
+        r#"[{"id": 1, "uuid": "0f1e2d3c", "title": "Renew passport", "completed": false,
+            "created_at": "2026-03-01T23:30:00+02:00", "due_date": "2026-03-10"}]"#,
+    )
+    .unwrap();
+
+    // Loading rewrites the old local offset in UTC
+    let utc_plus_5 = [("TZ", "XYZ-5".as_ref())];
+    sandbox
+        .cmd_env(&["show", "1"], &utc_plus_5)
+        .success()
+        .stdout(predicate::str::contains("2026-03-02 02:30"));
+    let saved = sandbox.read(&sandbox.db());
+    assert!(saved.contains(r#""created_at": "2026-03-01T21:30:00Z""#), "{}", saved);
+
+    sandbox
+        .cmd_env(&["show", "1"], &[("TZ", "XYZ+8".as_ref())])
+        .success()
+        .stdout(predicate::str::contains("2026-03-01 13:30"));
+}
+
+#[test]
+fn the_configured_locale_translates_messages_and_dates() {
+    let sandbox = Sandbox::new("locale");
+    fs::write(sandbox.dir.join("config.json"), r#"{"locale": "de"}"#).unwrap();
+    sandbox
+        .cmd(&["add", "Steuererklärung", "--due", "2031-03-09"])
+        .success()
+        .stdout(predicate::str::contains("Aufgabe #1 'Steuererklärung' hinzugefügt").and(predicate::str::contains("Fällig")));
+    sandbox
+        .cmd(&["show", "1"])
+        .success()
+        .stdout(predicate::str::contains("Fällig:").and(predicate::str::contains("09.03.2031")));
+
+    fs::write(sandbox.dir.join("config.json"), "{}").unwrap();
+    sandbox
+        .cmd_env(&["list", "--no-pager"], &[("LC_MESSAGES", "de_DE.UTF-8".as_ref())])
+        .success()
+        .stdout(predicate::str::contains("Fällig"));
+    sandbox.cmd(&["list", "--no-pager"]).success().stdout(predicate::str::contains("Due"));
+}
+
+#[cfg(unix)]
+#[test]
+fn hooks_hear_about_changes_and_pre_save_can_refuse_them() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let sandbox = Sandbox::new("hooks");
+    let hooks = sandbox.dir.join("xdg/todo-cli/hooks");
+    fs::create_dir_all(&hooks).unwrap();
+    let scripts = [
+        ("on-add", "#!/bin/sh\n{ printf '%s ' \"$TODO_EVENT\"; cat; echo; } >> log.txt\n"),
+        ("on-done", "#!/bin/sh\necho \"done $TODO_ID\" >> log.txt\nexit 1\n"),
+        ("pre-save", "#!/bin/sh\nif grep -q password; then echo 'no secrets' >&2; exit 1; fi\n"),
+    ];
+    for (name, script) in scripts {
+        let path = hooks.join(name);
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    sandbox.cmd(&["-q", "add", "Water the plants"]).success();
+    sandbox
+        .cmd(&["-q", "add", "Email the password"])
+        .failure()
+        .code(5)
+        .stderr(predicate::str::contains("The pre-save hook refused the change to #2: no secrets"));
+    sandbox
+        .cmd(&["-q", "done", "1"])
+        .success()
+        .stderr(predicate::str::contains("Warning: the on-done hook failed for #1"));
+
+    let log = sandbox.read(&sandbox.dir.join("log.txt"));
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", log);
+    assert!(lines[0].starts_with("added {") && lines[0].contains(r#""title":"Water the plants""#), "{}", log);
+    assert_eq!(lines[1], "done 1");
+    assert!(!sandbox.read(&sandbox.db()).contains("password"));
+}
+
+#[cfg(unix)]
+#[test]
+fn refused_removals_leave_nothing_in_the_trash() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let sandbox = Sandbox::new("refused-removal");
+    let hooks = sandbox.dir.join("xdg/todo-cli/hooks");
+    fs::create_dir_all(&hooks).unwrap();
+    let path = hooks.join("pre-save");
+    fs::write(&path, "#!/bin/sh\n[ \"$TODO_EVENT\" != removed ]\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+    sandbox.cmd(&["-q", "add", "Keep me"]).success();
+    sandbox.cmd(&["-q", "remove", "1"]).failure().code(5);
+    sandbox.cmd(&["list"]).success().stdout(predicate::str::contains("Keep me"));
+    sandbox.cmd(&["trash", "list"]).success().stdout(predicate::str::contains("The trash is empty."));
+}
+
+#[cfg(unix)]
+#[test]
+fn unknown_commands_run_plugins_from_path_with_the_database() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let sandbox = Sandbox::new("plugins");
+    let bin = sandbox.dir.join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let plugin = bin.join("todo-hello");
+    let script = "#!/bin/sh\necho \"hello $1 from $TODO_FILE ($TODO_COLOR, quiet=$TODO_QUIET)\"\n\"$TODO_BIN\" -q add \"$1\"\nexit 3\n";
+    fs::write(&plugin, script).unwrap();
+    fs::set_permissions(&plugin, fs::Permissions::from_mode(0o755)).unwrap();
+    let mode = bin.join("todo-mode");
+    fs::write(&mode, "#!/bin/sh\necho \"read-only=$TODO_READ_ONLY\"\n").unwrap();
+    fs::set_permissions(&mode, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = std::env::join_paths(
+        std::iter::once(bin).chain(std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default())),
+    )
+    .unwrap();
+
+    sandbox
+        .cmd_env(&["-q", "--list", "work", "hello", "world"], &[("PATH", path.as_os_str())])
+        .code(3)
+        .stdout(predicate::str::contains("hello world from todos-work.json (never, quiet=1)"))
+        .stderr(predicate::str::contains("todo-hello exited with status 3"));
+    assert!(sandbox.read(&sandbox.dir.join("todos-work.json")).contains("world"));
+
+    fs::write(sandbox.dir.join("config.json"), r#"{"read_only": true}"#).unwrap();
+    sandbox
+        .cmd_env(&["mode"], &[("PATH", path.as_os_str())])
+        .success()
+        .stdout(predicate::str::contains("read-only=1"));
+
+    sandbox
+        .cmd(&["frobnicate"])
+        .failure()
+        .stderr(predicate::str::contains("Unknown command 'frobnicate', and no todo-frobnicate plugin on PATH"));
+}
+
+#[test]
+fn verbose_lists_show_the_urgency_used_by_the_urgency_sort() {
+    let sandbox = Sandbox::new("urgency");
+    sandbox.cmd(&["-q", "add", "Renew passport", "--priority", "low", "--due", "2020-01-01"]).success();
+    sandbox.cmd(&["-q", "add", "Fix the build", "--priority", "urgent"]).success();
+    fs::write(sandbox.dir.join("config.json"), r#"{"urgency": {"priority": 20.0}}"#).unwrap();
+
+    let out = sandbox.cmd(&["-v", "list", "--sort", "urgency"]).success().get_output().stdout.clone();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("Urgency"), "{}", out);
+    assert!(out.contains(" 20.0 ") && out.contains(" 12.0 "), "{}", out);
+    assert!(out.find("Fix the build").unwrap() < out.find("Renew passport").unwrap());
+    sandbox.cmd(&["list"]).success().stdout(predicate::str::contains("Urgency").not());
+}
+
+#[test]
+fn review_steps_through_todos_not_reviewed_recently() {
+    let sandbox = Sandbox::new("review");
+    let todos = [("Call the bank", "urgent"), ("Plan the trip", "high"), ("Old receipts", "normal"), ("Broken lamp", "low")];
+    for (title, priority) in todos {
+        sandbox.cmd(&["-q", "add", title, "--priority", priority]).success();
+    }
+
+    sandbox
+        .cmd_input(&["-q", "review"], "k\nr\n2031-03-09\np\nsoon\np\nlow\nt\nhome\n\na\nd\n")
+        .success()
+        .stdout(predicate::str::contains("Reviewing 4 of 4"))
+        .stdout(predicate::str::contains("Invalid priority: soon"))
+        .stdout(predicate::str::contains("Archived 'Old receipts'"))
+        .stdout(predicate::str::contains("Removed todo #4"))
+        .stdout(predicate::str::contains("Reviewed 4 of 4 todos"));
+
+    let db = sandbox.read(&sandbox.db());
+    assert_eq!(db.matches(r#""last_reviewed": "20"#).count(), 2, "{}", db);
+    assert!(db.contains(r#""due_date": "2031-03-09""#) && db.contains(r#""home""#) && !db.contains("Old receipts"));
+    assert!(sandbox.read(&sandbox.dir.join("todos-archive.json")).contains("Old receipts"));
+
+    sandbox.cmd(&["review"]).success().stdout(predicate::str::contains("Nothing to review"));
+    sandbox
+        .cmd_input(&["-q", "review", "--all"], "s\n")
+        .success()
+        .stdout(predicate::str::contains("Reviewed 0 of 2 todos"));
+}
+
+#[test]
+fn checklists_are_ticked_off_by_number_and_counted_in_the_list() {
+    let sandbox = Sandbox::new("checklist");
+    sandbox.cmd(&["-q", "add", "Plan the trip"]).success();
+    for item in ["buy tickets", "book hotel", "renew passport"] {
+        sandbox.cmd(&["-q", "check", "add", "1", item]).success();
+    }
+    sandbox
+        .cmd(&["check", "done", "Plan the trip", "2"])
+        .success()
+        .stdout(predicate::str::contains("Ticked off item 2 of todo #1: book hotel"));
+    sandbox.cmd(&["check", "done", "1", "4"]).failure().stderr(predicate::str::contains("has 3 checklist items"));
+    sandbox.cmd(&["check", "add", "1", "  "]).failure();
+
+    sandbox.cmd(&["list"]).success().stdout(predicate::str::contains("Plan the trip ██░░░ 1/3"));
+    sandbox
+        .cmd(&["show", "1"])
+        .success()
+        .stdout(predicate::str::contains("███░░░░░░░ 33%"))
+        .stdout(predicate::str::contains("Checklist"))
+        .stdout(predicate::str::contains("1. [ ] buy tickets"))
+        .stdout(predicate::str::contains("2. [x] book hotel"));
+}
+
+#[test]
+fn ages_in_the_config_past_a_century_are_rejected() {
+    let sandbox = Sandbox::new("long-ages");
+    sandbox.cmd(&["-q", "add", "Ship it"]).success();
+    for config in [r#"{"list": {"hide_completed_after": "99999999w"}}"#, r#"{"archive": {"completed_after": "99999999w"}}"#] {
+        fs::write(sandbox.dir.join("config.json"), config).unwrap();
+        sandbox.cmd(&["list"]).code(1).stderr(predicate::str::contains("longer than 36525 days"));
+    }
+}
+
+#[test]
+fn saves_archive_todos_completed_longer_ago_than_configured() {
+    let sandbox = Sandbox::new("auto-archive");
+    for title in ["Old chore", "Recent chore", "Open chore"] {
+        sandbox.cmd(&["-q", "add", title]).success();
+    }
+    sandbox.cmd(&["-q", "done", "1"]).success();
+    sandbox.cmd(&["-q", "done", "2"]).success();
+    let file = sandbox.db();
+    let mut todos: Vec<serde_json::Value> = serde_json::from_slice(&fs::read(&file).unwrap()).unwrap();
+    todos[0]["completed_at"] = "2020-01-01T09:00:00Z".into();
+    fs::write(&file, serde_json::to_string(&todos).unwrap()).unwrap();
+    fs::write(sandbox.dir.join("config.json"), r#"{"archive": {"completed_after": "30d"}}"#).unwrap();
+
+    sandbox.cmd(&["list", "--all"]).success().stdout(predicate::str::contains("Archived").not());
+    sandbox.cmd(&["-q", "add", "New chore"]).success();
+    let titles = |path: &Path| -> Vec<String> {
+        let todos: Vec<serde_json::Value> = serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+        todos.iter().map(|t| t["title"].as_str().unwrap().to_string()).collect()
+    };
+    assert_eq!(titles(&file), ["Recent chore", "Open chore", "New chore"]);
+    assert_eq!(titles(&sandbox.dir.join("todos-archive.json")), ["Old chore"]);
+    // Archiving isn't deletion, in the journal or in stats
+    let journal = sandbox.read(&sandbox.dir.join("todos.journal.jsonl"));
+    assert!(journal.contains(r#""operation":"archived""#) && !journal.contains(r#""operation":"removed""#), "{}", journal);
+
+    sandbox.cmd(&["list"]).success().stdout(predicate::str::contains("Old chore").not());
+    sandbox
+        .cmd(&["list", "--all"])
+        .success()
+        .stdout(predicate::str::contains("Archived, kept in the list named archive (1)"))
+        .stdout(predicate::str::contains("Old chore"));
+    sandbox
+        .cmd(&["--list", "archive", "list", "--all"])
+        .success()
+        .stdout(predicate::str::contains("Old chore").count(1));
+}
@@ -0,0 +1,1262 @@
+//! Commands run against the in-memory store, and sorting invariants
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use clap::Parser;
+use std::collections::HashSet;
+use todo_cli::app::dispatch;
+use todo_cli::config::PromptConfig;
+use todo_cli::events::{diff, EventKind, EventSink, EventStore};
+use todo_cli::filter::{DueWindow, Filter};
+use todo_cli::i18n::{missing_keys, Locale};
+use todo_cli::journal::Operation;
+use todo_cli::output::{set_verbosity, Verbosity};
+use todo_cli::staging::StagedStore;
+use todo_cli::sync::{merge_copies, Side};
+use todo_cli::urgency::{score, Coefficients};
+use todo_cli::{
+    actionable_todos, clipboard_builder, daily_workload, group_todos, icon_title, parse_date_input, priority_scale,
+    render_segment, sort_todos, stale_todos, Attachment, AttachmentKind, Cli, ExternalKind, ExternalRef, Grouping,
+    JsonFileStore, MemoryStore, Priority, SortField, SmartSort, SortOrder, Status, StorageFormat, Summary, Todo,
+    TodoBuilder, TodoStore,
+};
+
+/// Parses and runs a command line against `store`
+fn run(store: &mut MemoryStore, args: &[&str]) -> anyhow::Result<()> {
+    set_verbosity(Verbosity::Quiet);
+    // Keep any config on this machine out of the way
+    std::env::set_var("TODO_CONFIG", std::env::temp_dir().join("todo-cli-test-missing-config.json"));
+    let cli = Cli::try_parse_from(std::iter::once("todo-cli").chain(args.iter().copied()))?;
+    dispatch(store, cli.command)
+}
+
+#[test]
+fn add_done_remove_journal_each_step() {
+    let mut store = MemoryStore::new();
+    run(&mut store, &["add", "Write tests", "--priority", "urgent"]).unwrap();
+    run(&mut store, &["add", "Review PR"]).unwrap();
+    assert_eq!(store.todos.len(), 2);
+    assert_eq!(store.todos[0].priority, "urgent".parse::<Priority>().unwrap());
+
+    run(&mut store, &["done", "1"]).unwrap();
+    assert!(store.todos[0].completed);
+    assert_eq!(store.todos[0].status, Status::Done);
+    assert!(store.todos[0].completed_at.is_some());
+
+    run(&mut store, &["remove", "2"]).unwrap();
+    assert_eq!(store.todos.len(), 1);
+    assert_eq!(store.trash.len(), 1);
+    assert_eq!(store.trash[0].todo.title, "Review PR");
+
+    let operations: Vec<Operation> = store.journal.iter().map(|e| e.operation).collect();
+    assert_eq!(
+        operations,
+        [Operation::Added, Operation::Added, Operation::Completed, Operation::Removed]
+    );
+}
+
+#[test]
+fn trash_restore_brings_the_todo_back() {
+    let mut store = MemoryStore::new();
+    run(&mut store, &["add", "Water plants"]).unwrap();
+    run(&mut store, &["remove", "1"]).unwrap();
+    assert!(store.todos.is_empty());
+
+    run(&mut store, &["trash", "restore", "1"]).unwrap();
+    assert_eq!(store.todos.len(), 1);
+    assert_eq!(store.todos[0].title, "Water plants");
+    assert!(store.trash.is_empty());
+
+    // Of two removed todos with the same ID, the later one comes back
+    run(&mut store, &["remove", "1"]).unwrap();
+    store.trash[0].deleted_at -= Duration::hours(1);
+    run(&mut store, &["add", "Feed the cat"]).unwrap();
+    run(&mut store, &["remove", "1"]).unwrap();
+    run(&mut store, &["trash", "restore", "1"]).unwrap();
+    assert_eq!(store.todos[0].title, "Feed the cat");
+    assert_eq!(store.trash[0].todo.title, "Water plants");
+}
+
+#[test]
+fn statuses_keep_completed_in_step() {
+    let mut store = MemoryStore::new();
+    run(&mut store, &["add", "Renew passport"]).unwrap();
+
+    run(&mut store, &["status", "1", "waiting"]).unwrap();
+    assert_eq!(store.todos[0].status, Status::Waiting);
+    assert!(!store.todos[0].completed);
+
+    run(&mut store, &["cancel", "1"]).unwrap();
+    assert!(store.todos[0].completed);
+    assert!(store.todos[0].completed_at.is_some());
+
+    run(&mut store, &["status", "1", "todo"]).unwrap();
+    assert!(!store.todos[0].completed);
+    assert!(store.todos[0].completed_at.is_none());
+}
+
+#[test]
+fn edit_sets_and_clears_icons_and_colors() {
+    let mut store = MemoryStore::new();
+    run(&mut store, &["add", "Fix the gate", "--icon", "🏠"]).unwrap();
+    run(&mut store, &["edit", "1", "--title", "Fix the garden gate", "--title-color", "bright green"]).unwrap();
+    assert_eq!(store.todos[0].title, "Fix the garden gate");
+    assert_eq!(store.todos[0].icon.as_deref(), Some("🏠"));
+    assert_eq!(store.todos[0].color.as_deref(), Some("bright green"));
+
+    let err = run(&mut store, &["edit", "1", "--title-color", "sparkly"]).unwrap_err();
+    assert_eq!(todo_cli::error::exit_code(&err), 5);
+    assert_eq!(store.todos[0].color.as_deref(), Some("bright green"));
+
+    run(&mut store, &["edit", "1", "--icon", "", "--title-color", ""]).unwrap();
+    assert_eq!(store.todos[0].icon, None);
+    assert_eq!(store.todos[0].color, None);
+}
+
+#[test]
+fn attachments_are_detected_and_not_repeated() {
+    assert_eq!(Attachment::detect("www.example.com").target, "https://www.example.com");
+    assert_eq!(Attachment::detect("mailto:me@example.com").kind, AttachmentKind::Link);
+    assert_eq!(Attachment::detect("/tmp/spec.pdf").kind, AttachmentKind::File);
+
+    let mut store = MemoryStore::new();
+    run(&mut store, &["add", "Read the spec"]).unwrap();
+    run(&mut store, &["attach", "1", "https://example.com/spec"]).unwrap();
+    run(&mut store, &["attach", "1", "https://example.com/spec"]).unwrap();
+    run(&mut store, &["attach", "1", "spec.pdf"]).unwrap();
+    let attachments = &store.todos[0].attachments;
+    assert_eq!(attachments.len(), 2);
+    assert_eq!(attachments[1].kind, AttachmentKind::File);
+    assert!(std::path::Path::new(&attachments[1].target).is_absolute());
+
+    let err = run(&mut store, &["open", "1", "3"]).unwrap_err();
+    assert_eq!(todo_cli::error::exit_code(&err), 5);
+}
+
+#[test]
+fn clipboard_text_splits_into_title_notes_and_links() {
+    let text = "\n  Review the budget  \nSee <https://example.com/budget>, and www.example.com.\nThanks!\n";
+    let todo = clipboard_builder(text, TodoBuilder::new("")).unwrap().build(1).unwrap();
+    assert_eq!(todo.title, "Review the budget");
+    assert_eq!(todo.notes.as_deref(), Some("See <https://example.com/budget>, and www.example.com.\nThanks!"));
+    let links: Vec<&str> = todo.attachments.iter().map(|a| a.target.as_str()).collect();
+    assert_eq!(links, ["https://example.com/budget", "https://www.example.com"]);
+
+    let err = clipboard_builder(" \n", TodoBuilder::new("")).unwrap_err();
+    assert_eq!(todo_cli::error::exit_code(&err), 5);
+}
+
+#[test]
+fn captured_titles_give_up_their_tokens() {
+    let tokens = todo_cli::tokens::parse("Submit report !high @work #finance due:+2d #q3 ! # for Ann").unwrap();
+    assert_eq!(tokens.title, "Submit report ! # for Ann");
+    assert_eq!(tokens.priority, Some("high".parse().unwrap()));
+    assert_eq!(tokens.project.as_deref(), Some("work"));
+    assert_eq!(tokens.tags, ["finance", "q3"]);
+    assert_eq!(tokens.due.map(|d| d.to_date()), Some(Local::now().date_naive() + Duration::days(2)));
+    assert!(todo_cli::tokens::parse("Pay rent !soonish").is_err());
+    assert!(todo_cli::tokens::parse("Pay rent due:someday").is_err());
+
+    let mut store = MemoryStore::new();
+    run(&mut store, &["capture", "Call the bank @home #admin prio:urgent due:friday"]).unwrap();
+    let todo = &store.todos[0];
+    assert_eq!((todo.title.as_str(), todo.priority.name()), ("Call the bank", "Urgent"));
+    assert_eq!((todo.project.as_deref(), &todo.tags[..]), (Some("home"), &["admin".to_string()][..]));
+    assert_eq!(todo.due_date.map(|d| d.weekday()), Some(chrono::Weekday::Fri));
+    assert!(todo.due_date.unwrap() - Local::now().date_naive() < Duration::days(7));
+}
+
+#[test]
+fn week_shortcuts_and_this_week_follow_the_first_day_of_the_week() {
+    let today = Local::now().date_naive();
+    let next_week = parse_date_input("Next Week").unwrap().to_date();
+    let end_of_week = parse_date_input("end of week").unwrap().to_date();
+    assert_eq!(next_week.weekday(), chrono::Weekday::Mon);
+    assert_eq!(end_of_week.weekday(), chrono::Weekday::Sun);
+    assert!(today < next_week && next_week - today <= Duration::days(7));
+    assert_eq!(next_week - end_of_week, Duration::days(1));
+    assert!(parse_date_input("end of month").is_err());
+
+    // Wednesday 2026-03-04, in the week of Monday 2026-03-02
+    let wednesday = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+    let due_on = |day: u32| TodoBuilder::new("Due").due_date(NaiveDate::from_ymd_opt(2026, 3, day)).build(1).unwrap();
+    let filter = Filter { due: Some(DueWindow::ThisWeek), ..Filter::default() };
+    let picked: Vec<u32> = (1..=9).filter(|&day| filter.matches_on(&due_on(day), wednesday)).collect();
+    assert_eq!(picked, [2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn todos_turn_overdue_when_their_local_due_day_ends() {
+    let today = Local::now().date_naive();
+    let mut todo = TodoBuilder::new("Renew passport").due_date(Some(today)).build(1).unwrap();
+    assert!(!todo.is_overdue_on(today));
+    assert!(todo.is_overdue_on(today + Duration::days(1)));
+
+    let deadline = todo.overdue_from().unwrap();
+    assert_eq!(deadline.date_naive(), today + Duration::days(1));
+    assert!(deadline > Local::now());
+
+    todo.set_status(Status::Done);
+    assert!(!todo.is_overdue_on(today + Duration::days(1)));
+}
+
+#[test]
+fn next_leaves_out_blocked_hidden_and_later_todos() {
+    let today = Local::now().date_naive();
+    let mut store = MemoryStore::new();
+    for title in ["Write the talk", "Book the venue", "Pick a caterer", "Print flyers", "Send invites"] {
+        run(&mut store, &["add", title]).unwrap();
+    }
+    run(&mut store, &["priority", "5", "urgent"]).unwrap();
+    run(&mut store, &["block", "3", "--on", "2"]).unwrap();
+    run(&mut store, &["hide", "4", "--until", "tomorrow"]).unwrap();
+    store.todos[0].start_date = Some(today + Duration::days(2));
+
+    let ids: Vec<u64> = actionable_todos(store.todos.clone(), today, false).iter().map(|t| t.id).collect();
+    assert_eq!(ids, [5, 2]);
+    let later = today + Duration::days(2);
+    let ids: Vec<u64> = actionable_todos(store.todos.clone(), later, false).iter().map(|t| t.id).collect();
+    assert_eq!(ids, [5, 1, 2, 4]);
+}
+
+#[test]
+fn workload_sums_estimates_by_due_day_with_overdue_work_today() {
+    let today = Local::now().date_naive();
+    let mut store = MemoryStore::new();
+    run(&mut store, &["add", "Draft the plan", "--estimate", "3h", "--due", "today"]).unwrap();
+    run(&mut store, &["add", "Late review", "--estimate", "1h30m", "--due", "2020-01-01"]).unwrap();
+    run(&mut store, &["add", "Slides", "--estimate", "2h", "--due", "tomorrow"]).unwrap();
+    run(&mut store, &["add", "Unestimated", "--due", "tomorrow"]).unwrap();
+    run(&mut store, &["add", "Far off", "--estimate", "8h", "--due", "+30d"]).unwrap();
+    run(&mut store, &["add", "Finished", "--estimate", "4h", "--due", "today"]).unwrap();
+    run(&mut store, &["done", "6"]).unwrap();
+
+    let loads = daily_workload(&store.todos, today, 7);
+    assert_eq!(loads.len(), 7);
+    assert_eq!(loads[0].estimated, Duration::minutes(270));
+    assert_eq!(loads[0].todos, [1, 2]);
+    assert_eq!(loads[1].estimated, Duration::hours(2));
+    assert!(loads[2..].iter().all(|load| load.todos.is_empty()));
+}
+
+#[test]
+fn summary_counts_open_todos_only() {
+    let today = Local::now().date_naive();
+    let urgent = Priority::highest();
+    let todos = vec![
+        TodoBuilder::new("Pay rent").due_date(Some(today)).priority(urgent).build(1).unwrap(),
+        TodoBuilder::new("Return books").due_date(Some(today - Duration::days(2))).build(2).unwrap(),
+        TodoBuilder::new("Plan trip").build(3).unwrap(),
+        {
+            let mut done = TodoBuilder::new("File taxes").due_date(Some(today)).build(4).unwrap();
+            done.set_status(Status::Done);
+            done
+        },
+    ];
+    let summary = Summary::of(&todos, today);
+    assert_eq!((summary.active, summary.due_today, summary.overdue, summary.urgent), (3, 1, 1, 1));
+    assert_eq!(summary.to_string(), "3 active, 1 due today, 1 overdue, 1 urgent");
+}
+
+#[test]
+fn prompt_segments_leave_out_zero_counts() {
+    colored::control::set_override(false);
+    let summary = Summary { active: 5, overdue: 2, done_today: 3, ..Summary::default() };
+    let config = PromptConfig::default();
+    assert_eq!(render_segment(&config, &summary).unwrap(), "✔3 ⏰2");
+    assert_eq!(render_segment(&config, &Summary::default()).unwrap(), "");
+
+    let config = PromptConfig { format: "todo: {active}/{urgent}".to_string(), ..PromptConfig::default() };
+    assert_eq!(render_segment(&config, &summary).unwrap(), "todo: 5/0");
+    let config = PromptConfig { format: "{late}".to_string(), ..PromptConfig::default() };
+    assert!(render_segment(&config, &summary).is_err());
+}
+
+#[test]
+fn groups_keep_sort_order_and_list_tagged_todos_under_each_tag() {
+    let today = Local::now().date_naive();
+    let todos = vec![
+        TodoBuilder::new("Call plumber").tags(["home", "calls"]).build(1).unwrap(),
+        TodoBuilder::new("Late report")
+            .due_date(Some(today - Duration::days(3)))
+            .project(Some("Work".into()))
+            .build(2)
+            .unwrap(),
+        TodoBuilder::new("Water plants").tags(["home"]).due_date(Some(today)).build(3).unwrap(),
+    ];
+
+    let by_tag = group_todos(&todos, Grouping::Tag, today);
+    let headings: Vec<&str> = by_tag.iter().map(|g| g.heading.as_str()).collect();
+    assert_eq!(headings, ["#calls", "#home", "No tags"]);
+    assert_eq!(by_tag[1].todos.iter().map(|t| t.id).collect::<Vec<_>>(), [1, 3]);
+
+    let by_week = group_todos(&todos, Grouping::DueWeek, today);
+    let headings: Vec<&str> = by_week.iter().map(|g| g.heading.as_str()).collect();
+    assert_eq!(headings, ["Overdue", "This week", "No due date"]);
+
+    let by_project = group_todos(&todos, Grouping::Project, today);
+    assert_eq!(by_project.last().unwrap().heading, "No project");
+    assert_eq!(by_project.iter().map(|g| g.todos.len()).sum::<usize>(), 3);
+}
+
+#[test]
+fn dry_runs_stage_changes_without_saving_them() {
+    let mut store = MemoryStore::new();
+    for title in ["Old report", "Old invoice", "New plan"] {
+        run(&mut store, &["add", title]).unwrap();
+    }
+    run(&mut store, &["remove", "2"]).unwrap();
+    let before: Vec<String> = store.todos.iter().map(|t| t.uuid.clone()).collect();
+
+    let mut staged = StagedStore::new(&mut store);
+    let cli = Cli::try_parse_from(["todo-cli", "remove", "--where", "title~old", "--dry-run"]).unwrap();
+    assert!(cli.command.dry_run());
+    dispatch(&mut staged, cli.command).unwrap();
+    let cli = Cli::try_parse_from(["todo-cli", "trash", "empty"]).unwrap();
+    dispatch(&mut staged, cli.command).unwrap();
+    let changes = staged.changes().unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!((changes[0].kind, changes[0].id), (EventKind::Removed, 1));
+    let purged: Vec<String> = staged.purged().unwrap().into_iter().map(|t| t.todo.title).collect();
+    assert_eq!(purged, ["Old invoice"]);
+
+    assert_eq!(store.todos.iter().map(|t| t.uuid.clone()).collect::<Vec<_>>(), before);
+    assert_eq!(store.trash.len(), 1);
+    assert_eq!(store.journal.len(), 4);
+}
+
+#[test]
+fn saves_stamp_changed_todos_and_stale_ones_are_found() {
+    let now = Local::now();
+    let mut store = MemoryStore::new();
+    run(&mut store, &["add", "Forgotten"]).unwrap();
+    run(&mut store, &["add", "Fresh"]).unwrap();
+    assert!(store.todos.iter().all(|t| t.modified_at.is_some()));
+    let long_ago = now - Duration::days(40);
+    store.todos[0].modified_at = Some(long_ago);
+    store.todos[1].modified_at = Some(long_ago);
+
+    run(&mut store, &["priority", "2", "high"]).unwrap();
+    assert_eq!(store.todos[0].modified_at, Some(long_ago));
+    assert!(store.todos[1].modified_at.unwrap() > long_ago);
+
+    let stale: Vec<u64> = stale_todos(&store.todos, now, Duration::days(30)).iter().map(|t| t.id).collect();
+    assert_eq!(stale, [1]);
+    run(&mut store, &["done", "1"]).unwrap();
+    assert!(stale_todos(&store.todos, now, Duration::days(30)).is_empty());
+}
+
+#[test]
+fn titles_are_checked_and_stripped_of_escapes_and_control_characters() {
+    let mut store = MemoryStore::new();
+    run(&mut store, &["add", "Pay \x1b[31mrent\x1b[0m\x1b]0;pwned\x07 today\tplease\x00"]).unwrap();
+    assert_eq!(store.todos[0].title, "Pay rent today please");
+
+    for title in [" \t ", "\x1b[2J", &"x".repeat(201)] {
+        let err = run(&mut store, &["add", title]).unwrap_err();
+        assert_eq!(todo_cli::error::exit_code(&err), 5, "{:?}", title);
+    }
+    run(&mut store, &["add", &"x".repeat(200)]).unwrap();
+
+    run(&mut store, &["edit", "1", "--title", "\x1b[1mRent\x1b[0m", "--notes", "First\r\nsecond\x1b[H"]).unwrap();
+    assert_eq!(store.todos[0].title, "Rent");
+    assert_eq!(store.todos[0].notes.as_deref(), Some("First\nsecond"));
+    assert!(run(&mut store, &["edit", "1", "--title", "\x07"]).is_err());
+    assert_eq!(store.todos.len(), 2);
+}
+
+#[test]
+fn merged_copies_keep_the_newer_value_of_each_field_and_every_todo() {
+    let mut ours = MemoryStore::new();
+    run(&mut ours, &["add", "Report"]).unwrap();
+    run(&mut ours, &["add", "Call"]).unwrap();
+    let mut theirs = MemoryStore::with_todos(ours.todos.clone());
+    run(&mut theirs, &["done", "1"]).unwrap();
+    run(&mut theirs, &["add", "Only there"]).unwrap();
+    run(&mut ours, &["edit", "2", "--title", "Call back"]).unwrap();
+    run(&mut ours, &["add", "Only here"]).unwrap();
+
+    let mut fields = Vec::new();
+    let merged = merge_copies(ours.todos.clone(), theirs.todos.clone(), |conflict| {
+        fields.push(conflict.field.clone());
+        Ok(conflict.newer())
+    })
+    .unwrap();
+    assert_eq!(fields, ["status", "title"]);
+    let summary: Vec<(u64, &str, bool)> = merged.iter().map(|t| (t.id, t.title.as_str(), t.completed)).collect();
+    assert_eq!(
+        summary,
+        [(1, "Report", true), (2, "Call back", false), (3, "Only here", false), (4, "Only there", false)]
+    );
+    assert_eq!(merged[0].status, Status::Done);
+}
+
+#[cfg(feature = "macos-integration")]
+#[test]
+fn reminders_scripts_file_todos_by_project_with_dates_and_priorities() {
+    use todo_cli::integrations::reminders::to_reminders_script;
+
+    let due = chrono::NaiveDate::from_ymd_opt(2025, 2, 28).unwrap();
+    let todos = vec![
+        TodoBuilder::new("Say \"hi\"").priority(Priority::highest()).due(due).build(1).unwrap(),
+        TodoBuilder::new("Plan").project(Some("Work".to_string())).notes("Agenda").build(2).unwrap(),
+    ];
+    let script = to_reminders_script(&todos);
+    assert!(script.starts_with("tell application \"Reminders\"\n"));
+    assert!(script.contains("make new reminder with properties {name:\"Say \\\"hi\\\"\", priority:1, due date:dueDate}"));
+    assert!(script.contains("set month of dueDate to 2\n"));
+    assert!(script.contains("    tell list \"Work\"\n        make new reminder with properties {name:\"Plan\", body:\"Agenda\", priority:5}"));
+
+    let mut store = MemoryStore::new();
+    let path = std::env::temp_dir().join("todo-cli-test-reminders.scpt");
+    std::fs::write(&path, &script).unwrap();
+    let err = run(&mut store, &["import", path.to_str().unwrap(), "--format", "reminders"]).unwrap_err();
+    assert_eq!(todo_cli::error::exit_code(&err), 5);
+}
+
+/// Returns the path of a file in `tests/fixtures`
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn microsoft_to_do_exports_import_as_json_and_csv() {
+    let mut store = MemoryStore::new();
+    run(&mut store, &["import", &fixture("mstodo.json"), "--format", "mstodo"]).unwrap();
+    let titles: Vec<&str> = store.todos.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, ["Renew passport", "Return library books", "Send the quarterly report"]);
+    let [passport, books, report] = &store.todos[..] else { unreachable!() };
+    assert_eq!(passport.priority, "high".parse::<Priority>().unwrap());
+    assert_eq!(passport.due_date, NaiveDate::from_ymd_opt(2025, 3, 1));
+    assert_eq!(passport.notes.as_deref(), Some("Photos are in the drawer"));
+    assert_eq!(passport.project, None, "the default list is no project");
+    assert!(books.completed && books.notes.is_none());
+    assert_eq!(report.project.as_deref(), Some("Work"));
+    assert_eq!(report.priority, "low".parse::<Priority>().unwrap());
+    assert!(!report.completed);
+
+    let mut store = MemoryStore::new();
+    run(&mut store, &["import", &fixture("outlook-tasks.csv"), "--format", "mstodo"]).unwrap();
+    let titles: Vec<&str> = store.todos.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, ["Book flights", "Pay rent", "Call the bank"]);
+    let [flights, rent, bank] = &store.todos[..] else { unreachable!() };
+    assert_eq!(flights.due_date, NaiveDate::from_ymd_opt(2025, 3, 14));
+    assert_eq!(flights.notes.as_deref(), Some("Window seat, if \"possible\"\nbefore noon"));
+    assert_eq!(flights.project.as_deref(), Some("Travel"));
+    assert!(rent.completed && rent.project.is_none());
+    assert!(!bank.completed && bank.due_date.is_none());
+
+    let err = run(&mut store, &["export", "--format", "mstodo"]).unwrap_err();
+    assert_eq!(todo_cli::error::exit_code(&err), 5);
+}
+
+#[test]
+fn trello_boards_import_cards_with_their_lists_labels_and_checklists() {
+    let mut store = MemoryStore::new();
+    run(&mut store, &["import", &fixture("trello.json"), "--format", "trello"]).unwrap();
+    let titles: Vec<&str> = store.todos.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, ["Get a plumber quote", "Order tiles", "Paint the hallway", "Pick the colors", "Build a shed"]);
+    let [quote, tiles, paint, colors, shed] = &store.todos[..] else { unreachable!() };
+
+    assert_eq!(quote.status, Status::Done, "a completed due date closes the card");
+    assert_eq!(tiles.status, Status::Todo);
+    assert_eq!(paint.status, Status::InProgress);
+    assert_eq!(colors.status, Status::Done);
+    assert_eq!((shed.status, shed.project.as_deref()), (Status::Todo, Some("Garden ideas")));
+    assert_eq!(paint.project, None, "status lists are no project");
+
+    assert_eq!(paint.tags, ["Weekend-job", "red"]);
+    assert!(paint.due_date.is_some_and(|due| due.format("%m").to_string() == "05"));
+    assert_eq!(paint.notes.as_deref(), Some("Light grey, two coats"));
+    let items = |todo: &Todo| todo.checklist.iter().map(|i| (i.text.clone(), i.done)).collect::<Vec<_>>();
+    assert_eq!(items(paint), [("Tape the edges".to_string(), true), ("Second coat".to_string(), false)]);
+    assert_eq!(items(tiles), [("Measure: Bathroom floor".to_string(), true), ("Shop: Compare prices".to_string(), false)]);
+    assert_eq!(paint.checklist_progress(), Some((1, 2)));
+
+    let err = run(&mut store, &["export", "--format", "trello"]).unwrap_err();
+    assert_eq!(todo_cli::error::exit_code(&err), 5);
+}
+
+#[test]
+fn calendar_events_and_to_dos_import_as_deadlines() {
+    let mut store = MemoryStore::new();
+    run(&mut store, &["import", &fixture("deadlines.ics"), "--format", "ics"]).unwrap();
+    let titles: Vec<&str> = store.todos.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, ["Homework 1 due", "Midterm exam in the main hall", "Quiz 2", "Read chapter 3"]);
+    let [homework, midterm, quiz, reading] = &store.todos[..] else { unreachable!() };
+
+    let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok();
+    assert_eq!(homework.due_date, date("2025-03-14"), "times in a named zone keep their day");
+    assert_eq!(midterm.due_date, date("2025-04-02"));
+    assert_eq!(quiz.due_date, date("2025-03-20"));
+    assert_eq!(reading.due_date, date("2025-03-10"), "to-dos are due when DUE says");
+
+    assert_eq!(homework.project.as_deref(), Some("CS101"));
+    assert_eq!(homework.tags, ["Homework", "Week-2"]);
+    assert_eq!(homework.notes.as_deref(), Some("Submit the PDF, not the source.\nLate work loses 10%."));
+    let url = Some("https://courses.example.edu/cs101/hw1".to_string());
+    let source = ExternalRef::new(ExternalKind::Calendar, "hw1@example.edu", url);
+    assert_eq!(homework.external_ref, Some(source));
+    assert_eq!((homework.status, quiz.status, reading.status), (Status::Todo, Status::Cancelled, Status::Done));
+    assert_eq!(reading.priority, "high".parse().unwrap());
+
+    // Imported again with a title template, only the new titles are added
+    let template = "{calendar}: {summary}";
+    run(&mut store, &["import", &fixture("deadlines.ics"), "--format", "ics", "--title", template]).unwrap();
+    assert_eq!(store.todos.len(), 8);
+    assert_eq!(store.todos[4].title, "CS101: Homework 1 due");
+
+    for (args, message) in [
+        (["--format", "ics", "--title", "{course}"], "Unknown placeholder '{course}'"),
+        (["--format", "markdown", "--title", "{summary}"], "only applies to --format ics"),
+    ] {
+        let err = run(&mut store, &[&["import", &fixture("deadlines.ics")], &args[..]].concat()).unwrap_err();
+        assert_eq!(todo_cli::error::exit_code(&err), 5);
+        assert!(err.to_string().contains(message), "{}", err);
+    }
+}
+
+#[test]
+fn external_refs_come_from_imports_and_old_issue_urls() {
+    let github = ExternalRef::from_url("https://github.com/owner/name/issues/12");
+    assert_eq!((github.kind, github.id.as_str()), (ExternalKind::Github, "owner/name#12"));
+    let jira = ExternalRef::from_url("https://jira.example.com/jira/browse/OPS_2-7/");
+    assert_eq!((jira.kind, jira.id.as_str()), (ExternalKind::Jira, "OPS_2-7"));
+    let link = ExternalRef::from_url("https://example.atlassian.net/browse/PROJ");
+    assert_eq!((link.kind, link.url.as_deref()), (ExternalKind::Link, Some(link.id.as_str())));
+
+    // Files from before references had kinds only kept the issue's URL
+    let mut saved = serde_json::to_value(TodoBuilder::new("Old").build(1).unwrap()).unwrap();
+    saved.as_object_mut().unwrap().remove("external_ref");
+    saved["issue_url"] = "https://github.com/owner/name/issues/12".into();
+    let todo: Todo = serde_json::from_value(saved).unwrap();
+    assert_eq!(todo.external_ref, Some(github));
+    let resaved = serde_json::to_value(&todo).unwrap();
+    assert_eq!(resaved["external_ref"]["id"], "owner/name#12");
+    assert!(resaved.get("issue_url").is_none());
+
+    let mut store = MemoryStore::new();
+    run(&mut store, &["import", &fixture("trello.json"), "--format", "trello"]).unwrap();
+    let paint = store.todos.iter().find(|t| t.title == "Paint the hallway").unwrap();
+    let card = ExternalRef::new(ExternalKind::Trello, "c-paint", Some("https://trello.com/c/Pa1nT".to_string()));
+    assert_eq!(paint.external_ref, Some(card));
+    assert_eq!(paint.external_id(ExternalKind::Trello), Some("c-paint"));
+    assert_eq!(paint.external_id(ExternalKind::Jira), None);
+
+    // Without a page or attachments there's nothing to open
+    let id = store.todos.iter().find(|t| t.title == "Order tiles").unwrap().id.to_string();
+    store.todos.iter_mut().for_each(|t| t.external_ref.iter_mut().for_each(|r| r.url = None));
+    let err = run(&mut store, &["open", &id]).unwrap_err();
+    assert_eq!(todo_cli::error::exit_code(&err), 5);
+    assert!(err.to_string().contains("no page to open"), "{}", err);
+
+    // Only web pages are kept from imports, and only they are opened
+    let local = ExternalRef::from_url("file:///etc/passwd");
+    assert_eq!((local.kind, local.url), (ExternalKind::Link, None));
+    assert_eq!(ExternalRef::new(ExternalKind::Trello, "c-1", Some("https://x.test/a b".into())).url, None);
+    let written = ExternalRef { url: Some("file:///etc/passwd".into()), ..ExternalRef::from_url("https://example.com") };
+    store.todos.iter_mut().for_each(|t| t.external_ref = Some(written.clone()));
+    let err = run(&mut store, &["open", &id]).unwrap_err();
+    assert!(err.to_string().contains("isn't an http or https page"), "{}", err);
+}
+
+#[cfg(feature = "feeds")]
+#[test]
+fn feed_syncs_follow_items_by_id_adding_updating_and_closing_todos() {
+    use todo_cli::apply_feed;
+    use todo_cli::integrations::feeds::Feed;
+
+    let calendar = std::fs::read_to_string(fixture("deadlines.ics")).unwrap();
+    let feed = Feed::new("webcal://example.edu/cs101.ics", None, None).unwrap();
+    let mut todos = vec![TodoBuilder::new("Homework 1 due").build(1).unwrap()];
+
+    // The cancelled quiz and the finished reading aren't added
+    let changes = apply_feed(&mut todos, &feed.url, feed.read(&calendar).unwrap()).unwrap();
+    assert_eq!((changes.added, changes.updated, changes.closed), (vec![1, 2], vec![], vec![]));
+    assert_eq!(todos[1].external_id(ExternalKind::Calendar), Some("hw1@example.edu"));
+    assert_eq!(todos[1].external_ref.as_ref().unwrap().feed.as_deref(), Some(feed.url.as_str()));
+
+    // Homework moved two days and renamed, the midterm dropped
+    let midterm = calendar.find("BEGIN:VEVENT\r\nUID:midterm").unwrap();
+    let quiz = calendar.find("BEGIN:VEVENT\r\nUID:quiz").unwrap();
+    let changed = format!("{}{}", &calendar[..midterm], &calendar[quiz..])
+        .replace("20250314T235900", "20250316T235900")
+        .replace("SUMMARY:Homework 1 due", "SUMMARY:Homework 1 (extended)");
+    let changes = apply_feed(&mut todos, &feed.url, feed.read(&changed).unwrap()).unwrap();
+    assert_eq!((changes.added, changes.updated, changes.closed), (vec![], vec![1], vec![2]));
+    assert_eq!(todos[1].title, "Homework 1 (extended)");
+    assert_eq!(todos[1].due_date, NaiveDate::from_ymd_opt(2025, 3, 16));
+    assert_eq!(todos[2].status, Status::Done);
+    assert_eq!(todos[0].title, "Homework 1 due", "todos not from the feed are left alone");
+
+    // Nothing new the second time; a todo finished by hand stays finished
+    todos[1].set_status(Status::Done);
+    let changes = apply_feed(&mut todos, &feed.url, feed.read(&changed).unwrap()).unwrap();
+    assert_eq!(changes, Default::default());
+    assert_eq!(todos.len(), 3);
+
+    let json = r#"{"version": "https://jsonfeed.org/version/1.1", "title": "On call", "items": [
+        {"id": 7, "title": "Primary on call", "_due": "2025-06-02", "tags": ["pager duty"]},
+        {"id": "handover", "summary": "Write the handover", "url": "https://example.com/handover"}
+    ]}"#;
+    let mut todos = Vec::new();
+    let feed = Feed::new("https://example.com/oncall.json", None, None).unwrap();
+    apply_feed(&mut todos, &feed.url, feed.read(json).unwrap()).unwrap();
+    assert_eq!(todos.iter().map(|t| t.title.as_str()).collect::<Vec<_>>(), ["Primary on call", "Write the handover"]);
+    assert_eq!((todos[0].due_date, todos[0].tags.clone()), (NaiveDate::from_ymd_opt(2025, 6, 2), vec!["pager-duty".into()]));
+    assert_eq!(todos[1].external_id(ExternalKind::Feed), Some("handover"));
+    assert_eq!(todos[1].project.as_deref(), Some("On call"));
+
+    // Not a feed, or a feed that lost all its items, closes nothing
+    assert!(feed.read(r#"{"error": "maintenance"}"#).is_err());
+    assert!(feed.read(r#"{"version": "https://jsonfeed.org/version/1.1", "title": "On call"}"#).is_err());
+    let empty = r#"{"version": "https://jsonfeed.org/version/1.1", "title": "On call", "items": []}"#;
+    assert_eq!(apply_feed(&mut todos, &feed.url, feed.read(empty).unwrap()).unwrap(), Default::default());
+    assert!(todos.iter().all(|t| !t.completed));
+
+    let titled = Feed::new("https://example.com/oncall.json", Some("{summary}".into()), None).unwrap();
+    assert!(titled.read(json).is_err(), "title templates are for calendars");
+    assert!(Feed::new("ftp://example.com/feed.ics", None, None).is_err());
+}
+
+#[cfg(feature = "jira")]
+#[test]
+fn jira_done_needs_a_todo_linked_to_a_jira_issue() {
+    let issue = ExternalRef::from_url("https://github.com/owner/name/issues/3");
+    let mut store = MemoryStore::new();
+    store.todos.push(TodoBuilder::new("Local").build(1).unwrap());
+    store.todos.push(TodoBuilder::new("Issue").external_ref(issue).build(2).unwrap());
+    for id in ["1", "2"] {
+        let err = run(&mut store, &["jira", "done", id]).unwrap_err();
+        assert_eq!(todo_cli::error::exit_code(&err), 5);
+        assert!(err.to_string().contains("not linked to a Jira issue"), "{}", err);
+    }
+    assert!(store.todos.iter().all(|t| !t.completed));
+}
+
+#[cfg(feature = "webhooks")]
+#[test]
+fn webhooks_hear_about_todos_once_they_are_overdue() {
+    use todo_cli::integrations::webhooks::{overdue_between, Payload, Webhook, WebhookEvent};
+
+    let now = Local::now();
+    let today = now.date_naive();
+    let todos = vec![
+        TodoBuilder::new("Yesterday").due(today - Duration::days(1)).build(1).unwrap(),
+        TodoBuilder::new("Last week").due(today - Duration::days(7)).build(2).unwrap(),
+        TodoBuilder::new("Today").due(today).build(3).unwrap(),
+    ];
+    let overdue: Vec<u64> = overdue_between(&todos, now - Duration::days(1), now).iter().map(|t| t.id).collect();
+    assert_eq!(overdue, [1]);
+
+    let hook: Webhook = serde_json::from_str(r#"{"url": "https://example.com", "events": ["overdue"]}"#).unwrap();
+    assert!(hook.wants(WebhookEvent::Overdue) && !hook.wants(WebhookEvent::Added));
+    let payload = serde_json::to_value(Payload::new(WebhookEvent::Overdue, &todos[0], now)).unwrap();
+    assert_eq!(payload["event"], "overdue");
+    assert_eq!(payload["text"], "Todo #1 is overdue: Yesterday");
+    assert_eq!(payload["todo"]["title"], "Yesterday");
+}
+
+#[test]
+fn missing_todos_are_errors() {
+    let mut store = MemoryStore::new();
+    let err = run(&mut store, &["done", "3"]).unwrap_err();
+    assert_eq!(todo_cli::error::exit_code(&err), 2);
+
+    // Numbers are IDs, never titles
+    store.todos.push(TodoBuilder::new("Buy 1 apple and 2 pears").build(1).unwrap());
+    store.todos[0].uuid = "abcdef00-0000-4000-8000-000000000000".to_string();
+    let err = run(&mut store, &["done", "12"]).unwrap_err();
+    assert_eq!(todo_cli::error::exit_code(&err), 2);
+    assert!(!store.todos[0].completed);
+
+    // ...nor short UUID prefixes, which a random UUID would match by chance
+    store.todos[0].uuid = "98765432-0000-4000-8000-000000000000".to_string();
+    assert!(run(&mut store, &["done", "9"]).is_err());
+    assert!(!store.todos[0].completed);
+    run(&mut store, &["done", "9876"]).unwrap();
+    assert!(store.todos[0].completed);
+}
+
+#[test]
+fn offsets_too_far_out_are_errors_not_panics() {
+    let mut store = MemoryStore::new();
+    assert!(run(&mut store, &["add", "Someday", "--remind", "99999999999999w"]).is_err());
+    assert!(run(&mut store, &["add", "Someday", "--remind", "6000w"]).is_err());
+    run(&mut store, &["add", "Someday", "--remind", "52w"]).unwrap();
+    assert_eq!(store.todos.len(), 1);
+
+    assert!(run(&mut store, &["add", "Someday", "--due", "+999999999999d"]).is_err());
+    assert!(run(&mut store, &["capture", "Someday", "due:+2000000000000000000w"]).is_err());
+    assert_eq!(store.todos.len(), 1);
+
+    for since in ["-999999999999d", "-2000000000000000000w"] {
+        assert!(run(&mut store, &["report", "done", "--since", since]).is_err(), "{}", since);
+    }
+
+    for query in ["due<=+999999999999d", "due>-2000000000000000000w"] {
+        assert!(run(&mut store, &["list", "--where", query]).is_err(), "{}", query);
+    }
+    run(&mut store, &["list", "--where", "due<=+2w"]).unwrap();
+
+    let forever = todo_cli::duration::HumanDuration(Duration::MAX);
+    let mut archiving = todo_cli::archive::ArchivingStore::new(Box::new(store), forever);
+    let mut todos = archiving.load().unwrap();
+    todos[0].set_status(Status::Done);
+    archiving.save(&todos).unwrap();
+    assert_eq!(archiving.load().unwrap().len(), 1);
+}
+
+#[test]
+fn spans_too_long_to_show_are_rejected() {
+    let mut store = MemoryStore::new();
+    run(&mut store, &["add", "Plan the launch", "--due", "+3w"]).unwrap();
+    assert!(run(&mut store, &["timeline", "--weeks", "4000000000"]).is_err());
+    run(&mut store, &["timeline", "--weeks", "520"]).unwrap();
+    assert!(run(&mut store, &["agenda", "--weeks", "4000000000"]).is_err());
+    run(&mut store, &["agenda", "--weeks", "520"]).unwrap();
+    assert!(run(&mut store, &["workload", "--days", "200000000"]).is_err());
+    run(&mut store, &["workload", "--days", "366"]).unwrap();
+    for seconds in ["0", "86401", "18446744073709551615"] {
+        assert!(run(&mut store, &["list", "--watch", seconds]).is_err(), "{}", seconds);
+    }
+}
+
+#[test]
+fn writes_the_file_system_refuses_are_read_only_errors() {
+    use std::io::{Error, ErrorKind};
+    use todo_cli::error::TodoError;
+
+    let refused = TodoError::storage("write to", "todos.json", Error::from(ErrorKind::ReadOnlyFilesystem));
+    assert!(matches!(refused, TodoError::ReadOnly { source: Some(_), .. }));
+    assert_eq!(refused.exit_code(), 3);
+    assert!(refused.to_string().contains("the file system is read-only"));
+    let unreadable = TodoError::storage("read", "todos.json", Error::from(ErrorKind::PermissionDenied));
+    assert!(matches!(unreadable, TodoError::Storage { .. }));
+}
+
+#[test]
+fn diff_reports_each_change_once() {
+    let now = Local::now();
+    let before = vec![
+        TodoBuilder::new("Keep").build(1).unwrap(),
+        TodoBuilder::new("Finish").build(2).unwrap(),
+        TodoBuilder::new("Rename").build(3).unwrap(),
+        TodoBuilder::new("Drop").build(4).unwrap(),
+    ];
+    let mut after = before[..3].to_vec();
+    after[1].set_status(Status::Done);
+    after[2].title = "Renamed".to_string();
+    after.push(TodoBuilder::new("New").build(5).unwrap());
+
+    let events = diff(&before, &after, now);
+    let kinds: Vec<(EventKind, u64)> = events.iter().map(|e| (e.kind, e.id)).collect();
+    assert_eq!(
+        kinds,
+        [(EventKind::Completed, 2), (EventKind::Edited, 3), (EventKind::Added, 5), (EventKind::Removed, 4)]
+    );
+    assert_eq!(events[1].before.as_ref().unwrap().title, "Rename");
+    assert_eq!(events[1].after.as_ref().unwrap().title, "Renamed");
+    assert!(events[2].before.is_none());
+    assert!(events[3].after.is_none());
+}
+
+#[test]
+fn event_store_writes_a_line_per_change() {
+    let path = std::env::temp_dir().join(format!("todo-cli-test-{}-events.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut store = EventStore::new(Box::new(MemoryStore::new()), EventSink::file(&path));
+
+    let todo = TodoBuilder::new("Announce me").build(1).unwrap();
+    store.save(std::slice::from_ref(&todo)).unwrap();
+    store.save(std::slice::from_ref(&todo)).unwrap();
+    store.save(&[]).unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let events: Vec<serde_json::Value> = content.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(events.len(), 2, "saving the same todos again is not a change");
+    assert_eq!(events[0]["event"], "added");
+    assert_eq!(events[1]["event"], "removed");
+    assert_eq!(events[1]["before"]["title"], "Announce me");
+}
+
+/// A small deterministic generator, so failures reproduce
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound
+    }
+}
+
+/// Builds up to 30 todos with mixed statuses, priorities, due dates, and ages
+fn random_todos(rng: &mut Lcg) -> Vec<Todo> {
+    let priorities: Vec<Priority> = priority_scale().priorities().collect();
+    let statuses = [Status::Todo, Status::InProgress, Status::Waiting, Status::Done, Status::Cancelled];
+    let today = Local::now().date_naive();
+    let count = 1 + rng.next(30);
+    (1..=count)
+        .map(|id| {
+            let mut builder = TodoBuilder::new(format!("Todo {}", id))
+                .priority(priorities[rng.next(priorities.len() as u64) as usize]);
+            if rng.next(3) > 0 {
+                builder = builder.due(today + Duration::days(rng.next(20) as i64 - 10));
+            }
+            let mut todo = builder.build(id).unwrap();
+            todo.created_at -= Duration::minutes(rng.next(10_000) as i64);
+            todo.set_status(statuses[rng.next(5) as usize]);
+            todo
+        })
+        .collect()
+}
+
+#[test]
+fn smart_sort_orders_by_status_then_overdue_then_priority() {
+    let mut rng = Lcg(42);
+    let today = Local::now().date_naive();
+    for _ in 0..200 {
+        let mut todos = random_todos(&mut rng);
+        sort_todos(&mut todos, &SortOrder::default(), &HashSet::new());
+
+        for pair in todos.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            assert!(a.status.rank() <= b.status.rank(), "{:?} before {:?}", a.status, b.status);
+            if a.status == b.status {
+                let (a_overdue, b_overdue) = (a.is_overdue_on(today), b.is_overdue_on(today));
+                assert!(a_overdue >= b_overdue, "overdue todos first");
+                if a_overdue == b_overdue {
+                    assert!(a.priority >= b.priority);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn the_priority_strategy_keeps_overdue_todos_in_priority_order() {
+    let today = Local::now().date_naive();
+    let late = TodoBuilder::new("Renew passport")
+        .priority("low".parse().unwrap())
+        .due_date(Some(today - Duration::days(14)))
+        .build(1)
+        .unwrap();
+    let pressing = TodoBuilder::new("Ship the release")
+        .priority("urgent".parse().unwrap())
+        .due_date(Some(today + Duration::days(1)))
+        .build(2)
+        .unwrap();
+
+    assert_eq!(SmartSort::OverdueFirst.compare(&late, &pressing, today), std::cmp::Ordering::Less);
+    assert_eq!(SmartSort::Priority.compare(&late, &pressing, today), std::cmp::Ordering::Greater);
+    let mut todos = vec![pressing, late];
+    sort_todos(&mut todos, &SortOrder::default(), &HashSet::new());
+    assert_eq!(todos[0].title, "Renew passport", "overdue first by default");
+}
+
+#[test]
+fn smart_sort_puts_blocked_todos_after_other_open_ones() {
+    let mut rng = Lcg(7);
+    for _ in 0..200 {
+        let mut todos = random_todos(&mut rng);
+        let blocked: HashSet<String> =
+            todos.iter().filter(|_| rng.next(3) == 0).map(|t| t.uuid.clone()).collect();
+        sort_todos(&mut todos, &SortOrder::default(), &blocked);
+
+        let open: Vec<bool> = todos.iter().filter(|t| !t.completed).map(|t| blocked.contains(&t.uuid)).collect();
+        assert!(open.windows(2).all(|w| w[0] <= w[1]), "blocked todos before unblocked ones");
+        let first_closed = todos.iter().position(|t| t.completed).unwrap_or(todos.len());
+        assert!(todos[first_closed..].iter().all(|t| t.completed));
+    }
+}
+
+#[test]
+fn every_save_records_the_fields_it_changed() {
+    let mut store = MemoryStore::new();
+    run(&mut store, &["add", "Write report", "--due", "2026-10-20"]).unwrap();
+    run(&mut store, &["add", "Review PR"]).unwrap();
+    assert!(store.history.is_empty(), "new todos have no history yet");
+
+    run(&mut store, &["edit", "1", "--due", "2026-10-27"]).unwrap();
+    run(&mut store, &["priority", "2", "high"]).unwrap();
+    run(&mut store, &["remove", "2"]).unwrap();
+    let fields: Vec<(u64, &str, String, String)> = store
+        .history
+        .iter()
+        .map(|c| (c.id, c.field.as_str(), c.old.to_string(), c.new.to_string()))
+        .collect();
+    assert_eq!(fields, [
+        (1, "due_date", "\"2026-10-20\"".to_string(), "\"2026-10-27\"".to_string()),
+        (2, "priority", "\"Normal\"".to_string(), "\"High\"".to_string()),
+    ]);
+    assert_eq!(store.history[0].uuid, store.todos[0].uuid);
+    run(&mut store, &["history", "1", "--field", "due_date"]).unwrap();
+}
+
+#[test]
+fn changes_are_credited_to_the_actor_who_saved_them() {
+    let mut alice = MemoryStore::new();
+    alice.actor = Some("alice".to_string());
+    run(&mut alice, &["add", "Book the plumber"]).unwrap();
+    run(&mut alice, &["add", "Pay the bill"]).unwrap();
+    assert_eq!(alice.todos[0].modified_by.as_deref(), Some("alice"));
+
+    let mut bob = MemoryStore::with_todos(alice.todos.clone());
+    bob.actor = Some("bob".to_string());
+    run(&mut bob, &["done", "1"]).unwrap();
+    assert_eq!(bob.todos[0].modified_by.as_deref(), Some("bob"));
+    assert_eq!(bob.todos[1].modified_by.as_deref(), Some("alice"), "untouched todos keep their author");
+    assert!(bob.history.iter().all(|c| c.by.as_deref() == Some("bob") && c.id == 1));
+    run(&mut bob, &["show", "1"]).unwrap();
+
+    let merged = merge_copies(alice.todos.clone(), bob.todos.clone(), |_| Ok(Side::Theirs)).unwrap();
+    assert!(merged[0].completed);
+    assert_eq!(merged[0].modified_by.as_deref(), Some("bob"));
+}
+
+#[test]
+fn comments_are_appended_in_order_and_kept_from_both_merged_copies() {
+    let mut ours = MemoryStore::new();
+    run(&mut ours, &["add", "Migrate the database"]).unwrap();
+    run(&mut ours, &["comment", "1", "Backed up the old one"]).unwrap();
+    assert!(run(&mut ours, &["comment", "1", " \u{7} "]).is_err(), "blank comments are refused");
+    let mut theirs = MemoryStore::with_todos(ours.todos.clone());
+    run(&mut ours, &["comment", "1", "Schema applied"]).unwrap();
+    run(&mut theirs, &["comment", "1", "Asked about downtime\non the list"]).unwrap();
+    run(&mut ours, &["show", "1"]).unwrap();
+
+    let texts = |todo: &Todo| todo.comments.iter().map(|c| c.text.clone()).collect::<Vec<_>>();
+    assert_eq!(texts(&ours.todos[0]), ["Backed up the old one", "Schema applied"]);
+    let merged = merge_copies(ours.todos.clone(), theirs.todos, |_| unreachable!("comments never conflict")).unwrap();
+    assert_eq!(texts(&merged[0]), ["Backed up the old one", "Schema applied", "Asked about downtime\non the list"]);
+}
+
+#[test]
+fn due_and_priority_sorts_keep_their_keys_in_order() {
+    let mut rng = Lcg(1234);
+    for _ in 0..200 {
+        let todos = random_todos(&mut rng);
+
+        let mut by_due = todos.clone();
+        sort_todos(&mut by_due, &SortOrder::by(SortField::Due), &HashSet::new());
+        let dated = by_due.iter().take_while(|t| t.due_date.is_some()).count();
+        assert!(by_due[dated..].iter().all(|t| t.due_date.is_none()));
+        assert!(by_due[..dated].windows(2).all(|w| w[0].due_date <= w[1].due_date));
+
+        let mut by_priority = todos.clone();
+        sort_todos(&mut by_priority, &SortOrder::by(SortField::Priority), &HashSet::new());
+        assert!(by_priority.windows(2).all(|w| w[0].priority >= w[1].priority));
+
+        let mut by_created = todos;
+        sort_todos(&mut by_created, &SortOrder::by(SortField::Created), &HashSet::new());
+        assert!(by_created.windows(2).all(|w| w[0].created_at <= w[1].created_at));
+    }
+}
+
+#[test]
+fn compound_sorts_break_ties_with_later_keys() {
+    let order: SortOrder = "priority, due:desc".parse().unwrap();
+    assert_eq!(order.keys.len(), 2);
+    assert!("due:sideways".parse::<SortOrder>().is_err());
+
+    let mut rng = Lcg(77);
+    for _ in 0..200 {
+        let mut todos = random_todos(&mut rng);
+        sort_todos(&mut todos, &order, &HashSet::new());
+        for pair in todos.windows(2) {
+            assert!(pair[0].priority >= pair[1].priority);
+            if pair[0].priority == pair[1].priority && pair[1].due_date.is_some() {
+                assert!(pair[0].due_date >= pair[1].due_date, "dated todos latest first within a priority");
+            }
+        }
+
+        let mut reversed = todos.clone();
+        sort_todos(&mut reversed, &order.clone().reversed(), &HashSet::new());
+        assert!(reversed.windows(2).all(|w| w[0].priority <= w[1].priority));
+    }
+}
+
+#[test]
+fn sorting_twice_changes_nothing() {
+    let mut rng = Lcg(99);
+    for _ in 0..100 {
+        let mut todos = random_todos(&mut rng);
+        sort_todos(&mut todos, &SortOrder::default(), &HashSet::new());
+        let once: Vec<u64> = todos.iter().map(|t| t.id).collect();
+        sort_todos(&mut todos, &SortOrder::default(), &HashSet::new());
+        assert_eq!(once, todos.iter().map(|t| t.id).collect::<Vec<_>>());
+    }
+}
+
+/// Fills in the free-text and optional fields of `todos`, some with non-ASCII text
+fn decorate(rng: &mut Lcg, todos: &mut [Todo]) {
+    let words = ["café", "naïve", "\"quoted\"", "tab\there", "日本語", "emoji 🎉", "back\\slash", "line\nbreak"];
+    let word = |rng: &mut Lcg| words[rng.next(words.len() as u64) as usize].to_string();
+    for todo in todos {
+        todo.title = format!("{} {}", todo.title, word(rng));
+        todo.tags = (0..rng.next(4)).map(|_| word(rng)).collect();
+        if rng.next(2) == 0 {
+            todo.notes = Some(word(rng));
+        }
+        if rng.next(2) == 0 {
+            todo.project = Some(word(rng));
+        }
+        todo.focused = rng.next(4) == 0;
+    }
+}
+
+#[test]
+fn saved_todos_load_back_unchanged_in_either_format() {
+    let dir = std::env::temp_dir().join(format!("todo-cli-test-{}-round-trip", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    // Saving stamps modified_at, so that's left out of the comparison
+    let fields = |todos: &[Todo]| -> Vec<serde_json::Value> {
+        let mut values: Vec<serde_json::Value> = todos.iter().map(|t| serde_json::to_value(t).unwrap()).collect();
+        for value in &mut values {
+            value.as_object_mut().unwrap().remove("modified_at");
+        }
+        values
+    };
+    let mut rng = Lcg(2024);
+    for seed in 0..100 {
+        let mut todos = random_todos(&mut rng);
+        decorate(&mut rng, &mut todos);
+        for format in [StorageFormat::Json, StorageFormat::Msgpack] {
+            let path = dir.join(format!("todos-{}-{}.json", seed, format.name()));
+            JsonFileStore::new(&path).format(format).save(&todos).unwrap();
+            assert_eq!(std::fs::read(&path).unwrap()[0] == b'[', format == StorageFormat::Json);
+            let loaded = JsonFileStore::new(&path).load().unwrap();
+            assert_eq!(fields(&loaded), fields(&todos), "seed {} as {}", seed, format.name());
+        }
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn filters_keep_exactly_the_todos_meeting_every_criterion() {
+    let today = Local::now().date_naive();
+    let priorities: Vec<Priority> = priority_scale().priorities().collect();
+    let mut rng = Lcg(31);
+    for _ in 0..200 {
+        let todos = random_todos(&mut rng);
+        let pick = |rng: &mut Lcg| today + Duration::days(rng.next(20) as i64 - 10);
+        let criteria = [
+            Filter { active_only: true, ..Filter::default() },
+            Filter { overdue: true, ..Filter::default() },
+            Filter { min_priority: Some(priorities[rng.next(priorities.len() as u64) as usize]), ..Filter::default() },
+            Filter { due_before: Some(pick(&mut rng)), ..Filter::default() },
+            Filter { due_after: Some(pick(&mut rng)), ..Filter::default() },
+            Filter { due: Some(DueWindow::Today), ..Filter::default() },
+            Filter { due: Some(DueWindow::Week), ..Filter::default() },
+        ];
+        assert!(todos.iter().all(|t| Filter::default().matches_on(t, today)));
+
+        for filter in &criteria {
+            let mut kept = todos.clone();
+            filter.apply(&mut kept);
+            let expected: Vec<u64> = todos.iter().filter(|t| filter.matches_on(t, today)).map(|t| t.id).collect();
+            assert_eq!(kept.iter().map(|t| t.id).collect::<Vec<_>>(), expected, "kept in order");
+            for todo in &kept {
+                assert!(!filter.active_only || !todo.completed);
+                assert!(!filter.overdue || todo.is_overdue_on(today));
+                assert!(filter.min_priority.is_none_or(|p| todo.priority >= p));
+                assert!(filter.due_before.is_none_or(|d| todo.due_date.is_some_and(|due| due < d)));
+                assert!(filter.due_after.is_none_or(|d| todo.due_date.is_some_and(|due| due > d)));
+                assert!(filter.due != Some(DueWindow::Today) || todo.due_date == Some(today));
+            }
+        }
+
+        for (i, a) in criteria.iter().enumerate() {
+            for b in &criteria[i + 1..] {
+                let both = Filter {
+                    active_only: a.active_only || b.active_only,
+                    overdue: a.overdue || b.overdue,
+                    min_priority: a.min_priority.or(b.min_priority),
+                    due_before: a.due_before.or(b.due_before),
+                    due_after: a.due_after.or(b.due_after),
+                    due: a.due.or(b.due),
+                    ..Filter::default()
+                };
+                for todo in &todos {
+                    let each = a.matches_on(todo, today) && b.matches_on(todo, today);
+                    assert_eq!(both.matches_on(todo, today), each, "combined criteria are a conjunction");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn urgency_adds_up_weighted_priority_due_age_and_tags() {
+    let now = Local::now();
+    let today = now.date_naive();
+    let coefficients = Coefficients::default();
+    let todo = |priority: &str, due: Option<i64>, tags: usize| {
+        let mut todo = TodoBuilder::new("Write the report")
+            .priority(priority.parse().unwrap())
+            .due_date(due.map(|days| today + Duration::days(days)))
+            .build(1)
+            .unwrap();
+        todo.tags = (0..tags).map(|i| format!("tag{}", i)).collect();
+        todo
+    };
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+    assert!(close(score(&todo("low", None, 0), now, false, &coefficients), 0.0));
+    assert!(close(score(&todo("urgent", None, 0), now, false, &coefficients), 6.0));
+    assert!(close(score(&todo("normal", None, 0), now, false, &coefficients), 2.0));
+    // Due proximity runs from 0.2 two weeks out to 1 a week overdue
+    assert!(close(score(&todo("low", Some(30), 0), now, false, &coefficients), 12.0 * 0.2));
+    assert!(close(score(&todo("low", Some(14), 0), now, false, &coefficients), 12.0 * 0.2));
+    assert!(close(score(&todo("low", Some(-7), 0), now, false, &coefficients), 12.0));
+    assert!(close(score(&todo("low", Some(-30), 0), now, false, &coefficients), 12.0));
+    assert!(close(score(&todo("low", Some(0), 0), now, false, &coefficients), 12.0 * (14.0 * 0.8 / 21.0 + 0.2)));
+    assert!(close(score(&todo("low", None, 1), now, false, &coefficients), 0.8));
+    assert!(close(score(&todo("low", None, 5), now, false, &coefficients), 1.0));
+    assert!(close(score(&todo("low", None, 0), now, true, &coefficients), -5.0));
+
+    let mut old = todo("low", None, 0);
+    old.created_at = now - Duration::days(730);
+    assert!(close(score(&old, now, false, &coefficients), 2.0), "age counts up to a year");
+    old.set_status(Status::Done);
+    assert!(close(score(&old, now, false, &coefficients), 0.0), "closed todos aren't urgent");
+
+    let custom = Coefficients { priority: 0.0, tags: 3.0, ..Coefficients::default() };
+    assert!(close(score(&todo("urgent", None, 3), now, false, &custom), 3.0));
+}
+
+#[test]
+fn urgency_sort_puts_overdue_and_unblocked_todos_first() {
+    let today = Local::now().date_naive();
+    let mut todos: Vec<Todo> = [("low", Some(-10)), ("normal", Some(1)), ("urgent", None), ("urgent", None)]
+        .iter()
+        .enumerate()
+        .map(|(i, (priority, due))| {
+            TodoBuilder::new(format!("Todo {}", i + 1))
+                .priority(priority.parse().unwrap())
+                .due_date(due.map(|days| today + Duration::days(days)))
+                .build(i as u64 + 1)
+                .unwrap()
+        })
+        .collect();
+    let blocked: HashSet<String> = [todos[3].uuid.clone()].into();
+
+    sort_todos(&mut todos, &"urgency".parse().unwrap(), &blocked);
+    assert_eq!(todos.iter().map(|t| t.id).collect::<Vec<_>>(), [1, 2, 3, 4]);
+    sort_todos(&mut todos, &"urgency:asc".parse().unwrap(), &blocked);
+    assert_eq!(todos[0].id, 4);
+}
+
+#[test]
+fn the_focus_list_is_picked_by_hand_and_filters_the_list() {
+    let mut store = MemoryStore::new();
+    for title in ["Pay rent", "Call mum", "Fix the bike"] {
+        run(&mut store, &["add", title]).unwrap();
+    }
+    run(&mut store, &["done", "2"]).unwrap();
+
+    run(&mut store, &["focus", "add", "1", "Fix the bike"]).unwrap();
+    assert!(run(&mut store, &["focus", "add", "2"]).is_err(), "completed todos can't be focused");
+    assert!(run(&mut store, &["focus", "add", "3", "9"]).is_err());
+    let focused = |store: &MemoryStore| store.todos.iter().filter(|t| t.focused).map(|t| t.id).collect::<Vec<_>>();
+    assert_eq!(focused(&store), [1, 3]);
+
+    let filter = Filter { focused: true, ..Filter::default() };
+    let mut todos = store.todos.clone();
+    filter.apply(&mut todos);
+    assert_eq!(todos.iter().map(|t| t.id).collect::<Vec<_>>(), [1, 3]);
+    run(&mut store, &["list", "--focus", "--no-pager"]).unwrap();
+    run(&mut store, &["focus", "show"]).unwrap();
+
+    run(&mut store, &["focus", "remove", "1"]).unwrap();
+    run(&mut store, &["focus", "remove", "1"]).unwrap();
+    assert_eq!(focused(&store), [3]);
+}
+
+#[test]
+fn move_before_and_after_set_the_manual_order() {
+    let mut store = MemoryStore::new();
+    for title in ["Alpha", "Bravo", "Charlie", "Delta"] {
+        run(&mut store, &["add", title]).unwrap();
+    }
+    let manual = |store: &MemoryStore| {
+        let mut todos = store.todos.clone();
+        sort_todos(&mut todos, &SortOrder::by(SortField::Manual), &HashSet::new());
+        todos.iter().map(|t| t.id).collect::<Vec<_>>()
+    };
+    assert_eq!(manual(&store), [1, 2, 3, 4], "todos never moved go by ID");
+
+    run(&mut store, &["move", "4", "--before", "1"]).unwrap();
+    run(&mut store, &["move", "Alpha", "--after", "3"]).unwrap();
+    assert_eq!(manual(&store), [4, 2, 3, 1]);
+    assert_eq!(store.todos.iter().filter(|t| t.sort_key.is_some()).count(), 2, "only moved todos get a key");
+    assert!(run(&mut store, &["move", "2", "--before", "2"]).is_err());
+    assert!(run(&mut store, &["move", "2"]).is_err(), "a list or a place is needed");
+
+    // Halving the same gap runs out of precision, and everything is renumbered
+    for i in 0..80 {
+        run(&mut store, &["move", if i % 2 == 0 { "3" } else { "2" }, "--after", "1"]).unwrap();
+    }
+    assert_eq!(manual(&store), [4, 1, 2, 3]);
+    assert!(store.todos.iter().all(|t| t.sort_key.is_some()));
+    let mut keys: Vec<f64> = store.todos.iter().map(|t| t.manual_key()).collect();
+    keys.dedup();
+    assert_eq!(keys.len(), 4);
+}
+
+#[test]
+fn pinned_todos_stay_on_top_whatever_the_sort() {
+    let mut store = MemoryStore::new();
+    run(&mut store, &["add", "Water the plants", "--priority", "low"]).unwrap();
+    run(&mut store, &["add", "File taxes", "--priority", "urgent"]).unwrap();
+    run(&mut store, &["add", "Book dentist", "--priority", "high"]).unwrap();
+    let order = |store: &MemoryStore, sort_order: &SortOrder| {
+        let mut todos = store.todos.clone();
+        sort_todos(&mut todos, sort_order, &HashSet::new());
+        todos.iter().map(|t| t.id).collect::<Vec<_>>()
+    };
+    assert_eq!(order(&store, &SortOrder::default()), [2, 3, 1]);
+
+    run(&mut store, &["pin", "Water the plants"]).unwrap();
+    run(&mut store, &["pin", "1"]).unwrap();
+    assert_eq!(order(&store, &SortOrder::default()), [1, 2, 3]);
+    assert_eq!(order(&store, &SortOrder::by(SortField::Title)), [1, 3, 2]);
+    assert_eq!(order(&store, &SortOrder::by(SortField::Priority).reversed()), [1, 3, 2]);
+    assert!(icon_title(&store.todos[0]).ends_with("Water the plants"));
+    assert!(icon_title(&store.todos[0]).starts_with(['★', '*']));
+
+    run(&mut store, &["unpin", "1"]).unwrap();
+    run(&mut store, &["unpin", "1"]).unwrap();
+    assert!(run(&mut store, &["pin", "9"]).is_err());
+    assert_eq!(order(&store, &SortOrder::default()), [2, 3, 1]);
+}
+
+#[test]
+fn progress_is_set_by_hand_unless_a_checklist_says() {
+    let mut store = MemoryStore::new();
+    run(&mut store, &["add", "Write the thesis"]).unwrap();
+    run(&mut store, &["add", "Pack for the move"]).unwrap();
+    assert_eq!(store.todos[0].progress_percent(), None);
+
+    run(&mut store, &["progress", "1", "60"]).unwrap();
+    assert_eq!(store.todos[0].progress_percent(), Some(60));
+    assert!(run(&mut store, &["progress", "1", "101"]).is_err());
+    assert_eq!(store.todos[0].progress, Some(60));
+
+    for item in ["books", "kitchen", "clothes"] {
+        run(&mut store, &["check", "add", "2", item]).unwrap();
+    }
+    run(&mut store, &["check", "done", "2", "1"]).unwrap();
+    run(&mut store, &["check", "done", "2", "3"]).unwrap();
+    assert_eq!(store.todos[1].checklist_progress(), Some((2, 3)));
+    assert_eq!(store.todos[1].progress_percent(), Some(66));
+    assert!(run(&mut store, &["progress", "2", "100"]).is_err(), "the checklist decides");
+}
+
+#[test]
+fn every_locale_translates_every_message() {
+    for locale in Locale::ALL {
+        assert_eq!(missing_keys(locale), Vec::<&str>::new(), "{:?}", locale);
+    }
+}